@@ -0,0 +1,66 @@
+//! Backend for the asdf plugin protocol, which mise also speaks. asdf runs
+//! the scripts in a plugin's `bin` directory with `ASDF_*` variables set;
+//! the scripts [`plugin_scripts`] writes hand each call to `wasmedgeup asdf`.
+
+use std::path::Path;
+use crate::{platform::OS, release::Release};
+
+/// Callbacks of the asdf plugin protocol, each run as `wasmedgeup asdf <name>`.
+const CALLBACKS: &[&str] = &["list-all", "latest-stable", "download", "install", "list-bin-paths"];
+
+/// `bin` scripts of an asdf plugin delegating to wasmedgeup, by file name.
+pub fn plugin_scripts() -> Vec<(String, String)> {
+    let mut scripts: Vec<(String, String)> = CALLBACKS
+        .iter()
+        .map(|callback| {
+            (callback.to_string(), format!("#!/usr/bin/env bash\nset -euo pipefail\nexec wasmedgeup --quiet asdf {}\n", callback))
+        })
+        .collect();
+    // asdf sources exec-env instead of running it
+    scripts.push((
+        "exec-env".to_string(),
+        "#!/usr/bin/env bash\neval \"$(wasmedgeup --quiet asdf exec-env)\"\n".to_string(),
+    ));
+    scripts
+}
+
+/// Every release version, oldest first and space separated, as `list-all`
+/// prints them. `releases` come newest first from the GitHub API.
+pub fn list_all(releases: &[Release]) -> String {
+    releases.iter().rev().map(|r| r.tag_name.as_str()).collect::<Vec<_>>().join(" ")
+}
+
+/// Newest release that is not a pre-release.
+pub fn latest_stable(releases: &[Release]) -> Option<&str> {
+    releases.iter().find(|r| !r.prerelease).map(|r| r.tag_name.as_str())
+}
+
+/// Shell lines exporting the library path of the installation at
+/// `install_path`, for asdf's `exec-env`. asdf already puts `bin` on the path.
+pub fn exec_env(install_path: &Path, os: &OS) -> String {
+    let var = os.library_path_var();
+    if var == "PATH" {
+        return String::new();
+    }
+    format!("export {var}=\"{}${{{var}:+:${var}}}\"\n", install_path.join("lib").display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_are_listed_oldest_first() {
+        let releases: Vec<Release> = serde_json::from_str(r#"[
+            {"tag_name": "0.15.0-alpha.1", "prerelease": true, "assets": []},
+            {"tag_name": "0.14.1", "assets": []},
+            {"tag_name": "0.14.0", "assets": []}
+        ]"#).unwrap();
+        assert_eq!(list_all(&releases), "0.14.0 0.14.1 0.15.0-alpha.1");
+        assert_eq!(latest_stable(&releases), Some("0.14.1"));
+
+        let env = exec_env(Path::new("/asdf/installs/wasmedge/0.14.1"), &OS::Darwin);
+        assert_eq!(env, "export DYLD_LIBRARY_PATH=\"/asdf/installs/wasmedge/0.14.1/lib${DYLD_LIBRARY_PATH:+:$DYLD_LIBRARY_PATH}\"\n");
+        assert_eq!(plugin_scripts().len(), 6);
+    }
+}
@@ -0,0 +1,178 @@
+//! Names of plugin archives published with WasmEdge releases:
+//! `WasmEdge-plugin-<name>-<version>-<platform>.tar.gz`, or `.zip` for
+//! Windows. Names contain
+//! dashes and numbers (`wasi_nn-ggml-cuda-11`), versions may carry a
+//! pre-release with its own dash (`0.15.0-alpha.1`), and platforms never
+//! contain a dash (`ubuntu20.04_x86_64`, `manylinux_2_28_aarch64`).
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use crate::platform::Platform;
+use crate::version::WasmEdgeVersion;
+
+const PLUGIN_PREFIX: &str = "WasmEdge-plugin-";
+const ARCHIVE_SUFFIXES: &[&str] = &[".tar.gz", ".zip"];
+
+/// A parsed plugin archive name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginAsset {
+    pub name: String,
+    pub version: WasmEdgeVersion,
+    pub platform: String,
+    /// `.tar.gz` or `.zip`
+    pub suffix: &'static str,
+}
+
+impl PluginAsset {
+    /// Parse a plugin archive name. `None` for everything else a release
+    /// publishes: runtime archives, installers, checksum lists and plugin
+    /// archives in other formats.
+    pub fn parse(asset: &str) -> Option<Self> {
+        let asset = asset.strip_prefix(PLUGIN_PREFIX)?;
+        let (stem, suffix) = ARCHIVE_SUFFIXES.iter().find_map(|suffix| Some((asset.strip_suffix(suffix)?, *suffix)))?;
+        let (rest, platform) = stem.rsplit_once('-')?;
+        if platform.is_empty() {
+            return None;
+        }
+        // The version starts at the first dash followed by a complete version,
+        // so numeric name parts like `cuda-11` stay in the name
+        rest.match_indices('-').filter(|&(i, _)| i > 0).find_map(|(i, _)| {
+            Some(Self {
+                name: rest[..i].to_string(),
+                version: rest[i + 1..].parse().ok()?,
+                platform: platform.to_string(),
+                suffix,
+            })
+        })
+    }
+}
+
+/// Platforms a release published archives for, as `platforms <version>`
+/// lists them.
+#[derive(Debug, Serialize)]
+pub struct ReleasePlatforms {
+    pub version: WasmEdgeVersion,
+    /// Runtime platform strings, each with the variants published besides
+    /// the regular archive (`static`, `debug symbols`)
+    pub runtime: BTreeMap<String, Vec<&'static str>>,
+    /// Platform strings of each plugin's archives
+    pub plugins: BTreeMap<String, Vec<String>>,
+    /// The `--platform` value of each platform string above that one
+    /// selects. Archives of the rest cannot be installed
+    pub selectable: BTreeMap<String, String>,
+}
+
+impl ReleasePlatforms {
+    /// Sort the `assets` of the `version` release by platform. Source
+    /// tarballs, checksum lists and archives of other versions are left out.
+    pub fn from_assets<'a>(version: &WasmEdgeVersion, assets: impl IntoIterator<Item = &'a str>) -> Self {
+        let runtime_prefix = format!("WasmEdge-{}-", version);
+        let mut runtime: BTreeMap<String, Vec<&'static str>> = BTreeMap::new();
+        let mut plugins: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for asset in assets {
+            if let Some(plugin) = PluginAsset::parse(asset) {
+                if plugin.version == *version {
+                    plugins.entry(plugin.name).or_default().push(plugin.platform);
+                }
+                continue;
+            }
+            let Some(platform) = asset
+                .strip_prefix(runtime_prefix.as_str())
+                .and_then(|rest| [".tar.gz", ".zip", ".msi"].iter().find_map(|ext| rest.strip_suffix(ext)))
+            else {
+                continue;
+            };
+            let (platform, variant) = if let Some(platform) = platform.strip_suffix("_static") {
+                (platform, Some("static"))
+            } else if let Some(platform) = platform.strip_prefix("debugsymbols_") {
+                (platform, Some("debug symbols"))
+            } else {
+                (platform, None)
+            };
+            if platform == "src" {
+                continue;
+            }
+            let variants = runtime.entry(platform.to_string()).or_default();
+            if let Some(variant) = variant.filter(|v| !variants.contains(v)) {
+                variants.push(variant);
+            }
+        }
+        plugins.values_mut().for_each(|platforms| platforms.sort());
+        let selectable = runtime
+            .keys()
+            .chain(plugins.values().flatten())
+            .filter_map(|suffix| Some((suffix.clone(), Platform::from_asset_suffix(suffix)?.shorthand())))
+            .collect();
+        Self { version: version.clone(), runtime, plugins, selectable }
+    }
+
+    /// How `platforms <version>` shows the platform string `suffix`: the
+    /// `--platform` value selecting it, or the string itself, marked.
+    pub fn display(&self, suffix: &str) -> String {
+        match self.selectable.get(suffix) {
+            Some(platform) => platform.clone(),
+            None => t!("platforms-unselectable", platform = suffix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASE_ASSETS: &str = include_str!("release_assets.txt");
+
+    #[test]
+    fn test_plugin_asset_names_of_releases() {
+        let mut out = String::new();
+        let mut release = None;
+        for line in RELEASE_ASSETS.lines().filter(|l| !l.is_empty()) {
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Ok(version) = comment.trim().parse::<WasmEdgeVersion>() {
+                    out.push_str(&format!("{}\n", version));
+                    release = Some(version);
+                }
+                continue;
+            }
+            if let Some(asset) = PluginAsset::parse(line) {
+                assert_eq!(Some(&asset.version), release.as_ref(), "{}", line);
+                let tar_gz = asset.version.plugin_asset_name(&asset.name, &asset.platform);
+                assert_eq!(tar_gz.replace(".tar.gz", asset.suffix), line);
+                out.push_str(&format!("  {} {}{}\n", asset.name, asset.platform, if asset.suffix == ".zip" { " (zip)" } else { "" }));
+            }
+        }
+        insta::assert_snapshot!("plugin_asset_names", out);
+
+        // Names that end in something platform- or version-like
+        let tricky = PluginAsset::parse("WasmEdge-plugin-foo-darwin_arm64-0.14.1-darwin_arm64.tar.gz").unwrap();
+        assert_eq!((tricky.name.as_str(), tricky.platform.as_str()), ("foo-darwin_arm64", "darwin_arm64"));
+        assert_eq!(PluginAsset::parse("WasmEdge-plugin-wasi_nn-ggml-b4381-0.14.1-ubuntu20.04_x86_64.tar.gz").unwrap().name, "wasi_nn-ggml-b4381");
+        assert_eq!(PluginAsset::parse("WasmEdge-plugin-0.14.1-ubuntu20.04_x86_64.tar.gz"), None);
+        assert_eq!(PluginAsset::parse("WasmEdge-plugin-wasi_logging-0.14.1-.tar.gz"), None);
+        let zip = PluginAsset::parse("WasmEdge-plugin-wasi_nn-ggml-0.14.1-windows_x86_64.zip").unwrap();
+        assert_eq!((zip.name.as_str(), zip.platform.as_str(), zip.suffix), ("wasi_nn-ggml", "windows_x86_64", ".zip"));
+        assert_eq!(PluginAsset::parse("WasmEdge-plugin-wasi_nn-ggml-0.14.1-windows_x86_64.msi"), None);
+    }
+
+    #[test]
+    fn test_release_platforms_from_assets() {
+        let section = RELEASE_ASSETS.split("# ").find(|s| s.starts_with("0.14.1")).unwrap();
+        let version = "0.14.1".parse().unwrap();
+        let platforms = ReleasePlatforms::from_assets(&version, section.lines().skip(1));
+        assert_eq!(
+            platforms.runtime.keys().map(String::as_str).collect::<Vec<_>>(),
+            [
+                "android_aarch64", "darwin_arm64", "darwin_x86_64", "manylinux2014_aarch64", "manylinux2014_x86_64",
+                "manylinux_2_28_aarch64", "manylinux_2_28_x86_64", "ubuntu20.04_aarch64", "ubuntu20.04_x86_64", "windows",
+            ]
+        );
+        assert_eq!(platforms.runtime["manylinux2014_x86_64"], ["debug symbols"]);
+        assert_eq!(platforms.plugins["wasi_nn-ggml-cuda-11"], ["ubuntu20.04_aarch64", "ubuntu20.04_x86_64"]);
+        assert_eq!(platforms.plugins["wasi_nn-ggml"].len(), 9);
+        assert!(platforms.plugins["wasi_nn-ggml"].contains(&"windows_x86_64".to_string()));
+        assert_eq!(platforms.selectable["ubuntu20.04_aarch64"], "ubuntu20.04/aarch64");
+        assert_eq!(platforms.selectable["darwin_arm64"], "darwin/arm64");
+        assert!(!platforms.selectable.contains_key("manylinux_2_28_x86_64"));
+        assert!(!platforms.selectable.contains_key("windows"));
+    }
+}
@@ -0,0 +1,61 @@
+//! What this wasmedgeup binary is, as recorded by `build.rs`. Printed by
+//! `--version` and `doctor`, and after errors, so bug reports name the
+//! exact build.
+
+use std::time::{Duration, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub commit: &'static str,
+    pub target: &'static str,
+    pub profile: &'static str,
+    pub features: Vec<&'static str>,
+    pub build_date: String,
+}
+
+impl BuildInfo {
+    pub fn get() -> Self {
+        let built_at = env!("WASMEDGEUP_BUILD_TIME").parse().unwrap_or(0);
+        let build_date = humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(built_at)).to_string();
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            commit: env!("WASMEDGEUP_BUILD_COMMIT"),
+            target: env!("WASMEDGEUP_BUILD_TARGET"),
+            profile: env!("WASMEDGEUP_BUILD_PROFILE"),
+            features: env!("WASMEDGEUP_BUILD_FEATURES").split(',').filter(|f| !f.is_empty()).collect(),
+            build_date: build_date[..10].to_string(),
+        }
+    }
+
+    /// One line naming the build, e.g. for the end of an error report.
+    pub fn summary(&self) -> String {
+        format!("wasmedgeup {} ({} {}, {})", self.version, self.commit, self.build_date, self.target)
+    }
+
+    /// The version with the rest of the build details on the lines after
+    /// it, for `--version`.
+    pub fn long_version(&self) -> String {
+        let features = if self.features.is_empty() { "none".to_string() } else { self.features.join(", ") };
+        format!(
+            "{}\ncommit: {}\ntarget: {}\nprofile: {}\nfeatures: {}\nbuild date: {}",
+            self.version, self.commit, self.target, self.profile, features, self.build_date
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_names_the_build() {
+        let info = BuildInfo::get();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(info.target.contains(std::env::consts::ARCH), "{}", info.target);
+        assert_eq!(info.build_date.len(), "2026-10-15".len());
+        assert!(info.summary().starts_with(&format!("wasmedgeup {} (", info.version)));
+        assert!(info.long_version().starts_with(&format!("{}\ncommit: ", info.version)));
+        assert!(info.long_version().lines().any(|line| line == format!("target: {}", info.target)));
+    }
+}
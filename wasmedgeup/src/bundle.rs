@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use tar::{Builder, Header};
+use crate::{checksum, extract::{self, ExtractLimits}, plugin::{is_compatible, PluginReceipt}, version::WasmEdgeVersion};
+
+/// Manifest at the root of a bundle.
+const MANIFEST: &str = "wasmedgeup-plugins.json";
+
+/// Directory inside a bundle holding the plugin files.
+const FILES_DIR: &str = "plugin";
+
+/// A set of installed plugins packed by `plugin export`, for installing on a
+/// machine without internet access. Receipt paths are relative to the plugin
+/// directory.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginBundle {
+    pub runtime_version: String,
+    pub platform: String,
+    pub plugins: BTreeMap<String, PluginReceipt>,
+}
+
+impl PluginBundle {
+    /// Bundle the plugins whose `receipts` record files under `plugin_dir`.
+    pub fn from_receipts<'a>(
+        plugin_dir: &Path,
+        runtime_version: &str,
+        platform: &str,
+        receipts: impl IntoIterator<Item = (&'a String, &'a PluginReceipt)>,
+    ) -> Result<Self> {
+        let mut plugins = BTreeMap::new();
+        for (name, receipt) in receipts {
+            // License files live outside the plugin directory and stay behind
+            let mut relative = PluginReceipt {
+                files: Vec::new(),
+                sha256: BTreeMap::new(),
+                licenses: Vec::new(),
+                ..receipt.clone()
+            };
+            for file in &receipt.files {
+                let path = file
+                    .strip_prefix(plugin_dir)
+                    .with_context(|| format!("{} is outside {}", file.display(), plugin_dir.display()))?;
+                if let Some(digest) = receipt.sha256.get(file) {
+                    relative.sha256.insert(path.to_path_buf(), digest.clone());
+                }
+                relative.files.push(path.to_path_buf());
+            }
+            plugins.insert(name.clone(), relative);
+        }
+        Ok(Self {
+            runtime_version: runtime_version.to_string(),
+            platform: platform.to_string(),
+            plugins,
+        })
+    }
+
+    /// Write the bundle with the plugin files from `plugin_dir` to `output`
+    /// as a gzipped tarball.
+    pub fn write(&self, plugin_dir: &Path, output: &Path) -> Result<()> {
+        let file = std::fs::File::create(output)
+            .with_context(|| format!("Failed to create {}", output.display()))?;
+        let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+
+        let manifest = serde_json::to_vec_pretty(self)?;
+        let mut header = Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, MANIFEST, manifest.as_slice())?;
+
+        for receipt in self.plugins.values() {
+            for file in &receipt.files {
+                let source = plugin_dir.join(file);
+                builder
+                    .append_path_with_name(&source, Path::new(FILES_DIR).join(file))
+                    .with_context(|| format!("Failed to add {} to the bundle", source.display()))?;
+            }
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    /// Unpack the bundle at `path` into `staging` and check every file
+    /// against its recorded digest. Returns the manifest and the directory
+    /// the plugin files were unpacked to.
+    pub fn unpack(path: &Path, staging: &Path, limits: &ExtractLimits) -> Result<(Self, PathBuf)> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        extract::unpack_tar_gz(file, staging, limits, |_| {}).with_context(|| format!("Failed to unpack {}", path.display()))?;
+
+        let manifest_path = staging.join(MANIFEST);
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("{} is not a plugin bundle: it has no {}", path.display(), MANIFEST))?;
+        let bundle: Self = serde_json::from_str(&manifest)
+            .with_context(|| format!("Failed to parse {}", MANIFEST))?;
+
+        let files = staging.join(FILES_DIR);
+        for receipt in bundle.plugins.values() {
+            for file in &receipt.files {
+                if !file.components().all(|c| matches!(c, Component::Normal(_))) {
+                    anyhow::bail!("Bundle entry {} points outside the plugin directory", file.display());
+                }
+                match receipt.sha256.get(file) {
+                    Some(expected) => checksum::verify_checksum(&files.join(file), expected)?,
+                    None if !files.join(file).is_file() => anyhow::bail!("Bundle is missing {}", file.display()),
+                    None => {}
+                }
+            }
+        }
+        Ok((bundle, files))
+    }
+
+    /// Fail unless the bundled plugins can run on `platform` with WasmEdge
+    /// `runtime_version`.
+    pub fn check_target(&self, platform: &str, runtime_version: &WasmEdgeVersion) -> Result<()> {
+        if self.platform != platform {
+            anyhow::bail!(
+                "The bundle holds plugins for {}, but this machine needs {}",
+                self.platform,
+                platform
+            );
+        }
+        if !is_compatible(runtime_version, &self.runtime_version) {
+            anyhow::bail!(
+                "The bundle holds plugins for WasmEdge {}, but WasmEdge {} is installed: the major.minor versions must match",
+                self.runtime_version,
+                runtime_version
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip_checks_digests_and_target() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let library = plugin_dir.path().join("libwasmedgePluginWasiLogging.so");
+        std::fs::write(&library, b"elf").unwrap();
+        let receipt = PluginReceipt {
+            version: "0.14.1".into(),
+            platform: "manylinux2014_x86_64".into(),
+            files: vec![library.clone()],
+            sha256: [(library.clone(), checksum::sha256_file(&library).unwrap())].into(),
+            source: None,
+            licenses: Vec::new(),
+        };
+        let name = "wasi_logging".to_string();
+        let bundle = PluginBundle::from_receipts(
+            plugin_dir.path(),
+            "0.14.1",
+            "manylinux2014_x86_64",
+            [(&name, &receipt)],
+        )
+        .unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let path = out.path().join("plugins.tar.gz");
+        bundle.write(plugin_dir.path(), &path).unwrap();
+
+        let staging = tempfile::tempdir().unwrap();
+        let (unpacked, files) = PluginBundle::unpack(&path, staging.path(), &ExtractLimits::default()).unwrap();
+        assert_eq!(unpacked.plugins[&name].files, vec![PathBuf::from("libwasmedgePluginWasiLogging.so")]);
+        assert_eq!(std::fs::read(files.join("libwasmedgePluginWasiLogging.so")).unwrap(), b"elf");
+        assert!(unpacked.check_target("manylinux2014_x86_64", &"0.14.0".parse().unwrap()).is_ok());
+        assert!(unpacked.check_target("darwin_arm64", &"0.14.1".parse().unwrap()).is_err());
+        assert!(unpacked.check_target("manylinux2014_x86_64", &"0.13.5".parse().unwrap()).is_err());
+
+        // A file changed after the digest was recorded is rejected
+        std::fs::write(&library, b"tampered").unwrap();
+        bundle.write(plugin_dir.path(), &path).unwrap();
+        let staging = tempfile::tempdir().unwrap();
+        assert!(PluginBundle::unpack(&path, staging.path(), &ExtractLimits::default()).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use log::{debug, info};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use crate::fsutil::FileSystem;
+
+/// Prefix shared by every temporary directory wasmedgeup creates, so leftovers
+/// from crashed runs can be recognised and swept.
+pub const TEMP_DIR_PREFIX: &str = "wasmedgeup-";
+
+/// Create a uniquely named staging directory under `parent`. The directory and
+/// everything in it is removed when the returned guard is dropped, including on
+/// early returns caused by errors.
+/// The name carries the process id, so [`sweep_temp_dir`] can tell the
+/// directories of running processes from leftovers.
+pub fn temp_dir_in(parent: &Path) -> Result<TempDir> {
+    std::fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create temporary directory {}", parent.display()))?;
+    tempfile::Builder::new()
+        .prefix(&format!("{}{}-", TEMP_DIR_PREFIX, std::process::id()))
+        .tempdir_in(parent)
+        .with_context(|| format!("Failed to create staging directory in {}", parent.display()))
+}
+
+/// Root of the wasmedgeup cache.
+#[cfg(not(test))]
+pub fn cache_dir() -> Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .context("Could not determine cache directory")?
+        .join("wasmedgeup"))
+}
+
+/// Tests get a cache of their own, so what they fetch never lands in, or is
+/// read from, the user's cache.
+#[cfg(test)]
+pub fn cache_dir() -> Result<PathBuf> {
+    static TEST_CACHE: std::sync::OnceLock<TempDir> = std::sync::OnceLock::new();
+    let dir = TEST_CACHE.get_or_init(|| tempfile::tempdir().expect("Failed to create the test cache directory"));
+    Ok(dir.path().join("wasmedgeup"))
+}
+
+/// Most responses [`store_metadata`] keeps; the least recently fetched go
+/// first.
+const MAX_METADATA_ENTRIES: usize = 64;
+
+fn metadata_path(url: &str) -> Result<PathBuf> {
+    let key = hex::encode(Sha256::digest(url.as_bytes()));
+    Ok(cache_dir()?.join("metadata").join(key))
+}
+
+/// Keep the latest response from `url` for [`cached_metadata`].
+pub fn store_metadata(url: &str, body: &str) {
+    let result = metadata_path(url).and_then(|path| {
+        let dir = path.parent().unwrap();
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, body)?;
+        keep_newest(dir, MAX_METADATA_ENTRIES)
+    });
+    if let Err(e) = result {
+        debug!("Could not cache response from {}: {}", url, e);
+    }
+}
+
+/// Remove all but the `keep` most recently modified files in `dir`.
+fn keep_newest(dir: &Path, keep: usize) -> Result<()> {
+    let mut files: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if files.len() <= keep {
+        return Ok(());
+    }
+    files.sort_unstable_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in files.drain(keep..) {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// The last response stored for `url` and when it was fetched.
+pub fn cached_metadata(url: &str) -> Option<(String, SystemTime)> {
+    let path = metadata_path(url).ok()?;
+    let fetched = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    Some((std::fs::read_to_string(&path).ok()?, fetched))
+}
+
+/// Remove the whole wasmedgeup cache directory.
+pub fn clean_cache(fs: &dyn FileSystem) -> Result<()> {
+    let dir = cache_dir()?;
+    remove_cache_dir(fs, &dir)
+}
+
+/// Remove the parts of the cache directory named `parts`, e.g. `metadata`,
+/// and return the directories removed.
+pub fn clean_cache_parts(fs: &dyn FileSystem, parts: &[&str]) -> Result<Vec<PathBuf>> {
+    let root = cache_dir()?;
+    parts
+        .iter()
+        .map(|part| {
+            let dir = root.join(part);
+            remove_cache_dir(fs, &dir).map(|()| dir)
+        })
+        .collect()
+}
+
+fn remove_cache_dir(fs: &dyn FileSystem, dir: &Path) -> Result<()> {
+    if dir.exists() {
+        fs.remove_dir_all(dir)
+            .with_context(|| format!("Failed to remove cache directory {}", dir.display()))?;
+        info!("Removed cache directory {}", dir.display());
+    }
+    Ok(())
+}
+
+/// Age after which a staging entry whose owner cannot be checked counts as
+/// left behind.
+const ABANDONED_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Whether the staging entry `name`, last modified at `modified`, was left
+/// behind: its process no longer runs, or where that cannot be told (older
+/// entries without a process id, or systems without a cheap process check),
+/// it has not been touched for a day.
+fn is_abandoned(name: &str, modified: Option<SystemTime>) -> bool {
+    let owner = name
+        .strip_prefix(TEMP_DIR_PREFIX)
+        .and_then(|rest| rest.split_once('-'))
+        .and_then(|(pid, _)| pid.parse::<u32>().ok());
+    match owner {
+        Some(pid) if cfg!(target_os = "linux") => !crate::lock::is_running(pid),
+        _ => modified
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= ABANDONED_AFTER),
+    }
+}
+
+/// Remove staging directories and files left in `tmpdir` by interrupted runs,
+/// keeping those of runs still going. Returns the number of entries removed.
+pub fn sweep_temp_dir(fs: &dyn FileSystem, tmpdir: &Path) -> Result<usize> {
+    let entries = match std::fs::read_dir(tmpdir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", tmpdir.display()))
+        }
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let modified = entry.metadata().and_then(|m| m.modified()).ok();
+        if !name.starts_with(TEMP_DIR_PREFIX) || !is_abandoned(&name, modified) {
+            continue;
+        }
+        let path = entry.path();
+        let result = if path.is_dir() {
+            fs.remove_dir_all(&path)
+        } else {
+            fs.remove_file(&path)
+        };
+        result.with_context(|| format!("Failed to remove {}", path.display()))?;
+        debug!("Removed leftover {}", path.display());
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_newest_drops_the_oldest_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        for (i, name) in ["old", "middle", "new"].iter().enumerate() {
+            let file = std::fs::File::create(dir.path().join(name)).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(60 * (3 - i as u64))).unwrap();
+        }
+        keep_newest(dir.path(), 2).unwrap();
+        assert!(!dir.path().join("old").exists());
+        assert!(dir.path().join("middle").exists() && dir.path().join("new").exists());
+    }
+
+    #[test]
+    fn test_sweep_only_removes_abandoned_prefixed_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let running = temp_dir_in(root.path()).unwrap();
+        std::fs::write(running.path().join("partial.tar.gz"), b"partial").unwrap();
+        std::fs::write(root.path().join("unrelated.txt"), b"keep").unwrap();
+        let fresh = root.path().join("wasmedgeup-fresh");
+        let old = root.path().join("wasmedgeup-old");
+        for dir in [&fresh, &old] {
+            std::fs::create_dir(dir).unwrap();
+        }
+        let two_days_ago = SystemTime::now() - 2 * ABANDONED_AFTER;
+        std::fs::File::open(&old).unwrap().set_modified(two_days_ago).unwrap();
+
+        assert_eq!(sweep_temp_dir(&crate::fsutil::RealFs, root.path()).unwrap(), 1);
+        assert!(!old.exists());
+        assert!(fresh.exists() && running.path().exists());
+        assert!(root.path().join("unrelated.txt").exists());
+
+        #[cfg(target_os = "linux")]
+        {
+            let crashed = root.path().join("wasmedgeup-4000000000-abc");
+            std::fs::create_dir(&crashed).unwrap();
+            assert_eq!(sweep_temp_dir(&crate::fsutil::RealFs, root.path()).unwrap(), 1);
+            assert!(!crashed.exists() && running.path().exists());
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Public key pinning for release hosts. TLS certificates are always verified
+//! against the system roots; `--pin-cert` additionally requires the leaf
+//! certificate of a host to carry one of the given public keys, so a
+//! misissued certificate or an intercepting proxy cannot serve artifacts.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Prefix of a pin, naming its hash like HPKP and curl's `--pinnedpubkey`.
+const PIN_PREFIX: &str = "sha256/";
+
+/// Accepted SPKI pins by host, from `HOST=sha256/BASE64` specs.
+#[derive(Debug, Clone, Default)]
+pub struct CertPins {
+    pins: BTreeMap<String, Vec<String>>,
+}
+
+impl CertPins {
+    /// Parse `--pin-cert` values. Repeating a host adds backup pins, and a
+    /// host of `*.example.com` covers every subdomain of `example.com`.
+    pub fn parse(specs: &[String]) -> Result<Self> {
+        let mut pins: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for spec in specs.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let invalid = || t!("cert-pin-invalid", spec = spec);
+            let (host, pin) = spec.split_once('=').with_context(invalid)?;
+            let digest = pin.trim().strip_prefix(PIN_PREFIX).with_context(invalid)?;
+            if BASE64.decode(digest).ok().map(|d| d.len()) != Some(32) {
+                anyhow::bail!(invalid());
+            }
+            let host = host.trim().to_ascii_lowercase();
+            if host.is_empty() {
+                anyhow::bail!(invalid());
+            }
+            pins.entry(host).or_default().push(pin.trim().to_string());
+        }
+        Ok(Self { pins })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    /// Pins accepted for `host`, if it is pinned.
+    fn for_host(&self, host: &str) -> Option<&[String]> {
+        let host = host.to_ascii_lowercase();
+        if let Some(pins) = self.pins.get(&host) {
+            return Some(pins);
+        }
+        self.pins.iter().find_map(|(pattern, pins)| {
+            let suffix = pattern.strip_prefix("*.")?;
+            host.strip_suffix(suffix)?.ends_with('.').then_some(pins.as_slice())
+        })
+    }
+
+    /// Fail unless the certificate that served `response` has a pinned key.
+    /// Runs for every response of a redirect chain, so a release download is
+    /// checked against the pins of `github.com` and then those of
+    /// `objects.githubusercontent.com`.
+    pub fn check(&self, response: &reqwest::Response) -> Result<()> {
+        let Some(host) = response.url().host_str() else { return Ok(()) };
+        let Some(pins) = self.for_host(host) else { return Ok(()) };
+        let certificate = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .with_context(|| t!("cert-pin-no-tls", host = host))?;
+        let actual = spki_pin(certificate)?;
+        if !pins.contains(&actual) {
+            anyhow::bail!(t!("cert-pin-mismatch", host = host, actual = actual));
+        }
+        Ok(())
+    }
+}
+
+/// `sha256/<base64>` of the SubjectPublicKeyInfo of a DER certificate.
+fn spki_pin(der: &[u8]) -> Result<String> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(der).context("Invalid peer certificate")?;
+    let key = certificate.public_key().raw;
+    Ok(format!("{}{}", PIN_PREFIX, BASE64.encode(Sha256::digest(key))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBiDCCAS+gAwIBAgIUKeFzQEf93Q5xu0kYbj8l0pJHmHAwCgYIKoZIzj0EAwIw
+GjEYMBYGA1UEAwwPcmVsZWFzZS5leGFtcGxlMB4XDTI2MTAxNTE3Mjk1OVoXDTM2
+MTAxMjE3Mjk1OVowGjEYMBYGA1UEAwwPcmVsZWFzZS5leGFtcGxlMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEdLQdEUFs+kqwefO4ftNTauSY0PbSxwZwihddfQpX
++XZb9DQ9+cttteXbx3TQZFU+8qmtfV5csVW2t7Nbap33W6NTMFEwHQYDVR0OBBYE
+FElSh/Mj6SE+cxcABCE74mJjlHkLMB8GA1UdIwQYMBaAFElSh/Mj6SE+cxcABCE7
+4mJjlHkLMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDRwAwRAIgUqk+NBlo
+z0QETQi5Wge6vCSBqgx01WMd/6oTEHh7l/gCIHNV92jsUf00yQ5S1Ac8uWBJTHpg
+qOBdI/sbVUuXuuIp
+-----END CERTIFICATE-----
+";
+    const PIN: &str = "sha256/Tlwik00eZwknOljvNC6d9+2ZgUYDkvdr8KRDUtwd/Sg=";
+
+    #[test]
+    fn test_pins_match_hosts_and_keys() {
+        let (_, pem) = x509_parser::pem::parse_x509_pem(CERT.as_bytes()).unwrap();
+        let der = pem.contents;
+        assert_eq!(spki_pin(&der).unwrap(), PIN);
+
+        let pins = CertPins::parse(&[
+            format!("GitHub.com={}", PIN),
+            "github.com=sha256/AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_string(),
+            format!("*.githubusercontent.com={}", PIN),
+        ])
+        .unwrap();
+        assert_eq!(pins.for_host("github.com").map(|p| p.len()), Some(2));
+        assert!(pins.for_host("objects.githubusercontent.com").is_some());
+        assert!(pins.for_host("githubusercontent.com").is_none());
+        assert!(pins.for_host("evilgithubusercontent.com").is_none());
+        assert!(pins.for_host("api.github.com").is_none());
+
+        for spec in ["github.com", "github.com=Tlwik00e", "github.com=sha256/dG9vIHNob3J0", &format!("={}", PIN)] {
+            assert!(CertPins::parse(&[spec.to_string()]).is_err(), "{} was accepted", spec);
+        }
+    }
+}
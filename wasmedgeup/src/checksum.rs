@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use log::debug;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+/// Read size when hashing. Large reads keep the hasher, not syscalls, the
+/// bottleneck on multi-hundred-MB archives.
+const HASH_BUFFER: usize = 1 << 20;
+
+/// Compute the hex encoded SHA-256 digest of the file at `path`.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {} for hashing", path.display()))?;
+    let len = file.metadata()?.len();
+    if len <= HASH_BUFFER as u64 {
+        hash_reader(file, len as usize)
+    } else {
+        hash_pipelined(file)
+    }
+}
+
+fn hash_reader(mut file: std::fs::File, len: usize) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; len.clamp(8192, HASH_BUFFER)];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hash while a second thread reads ahead, so disk and CPU work overlap.
+/// Two buffers circulate between the threads.
+fn hash_pipelined(mut file: std::fs::File) -> Result<String> {
+    let (full_tx, full_rx) = mpsc::sync_channel::<std::io::Result<(Vec<u8>, usize)>>(2);
+    let (empty_tx, empty_rx) = mpsc::sync_channel::<Vec<u8>>(2);
+    for _ in 0..2 {
+        empty_tx.send(vec![0u8; HASH_BUFFER]).unwrap();
+    }
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            while let Ok(mut buf) = empty_rx.recv() {
+                let read = file.read(&mut buf).map(|n| (buf, n));
+                let done = !matches!(read, Ok((_, n)) if n > 0);
+                if full_tx.send(read).is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        let mut hasher = Sha256::new();
+        loop {
+            let (buf, n) = full_rx.recv().expect("hashing reader stopped early")?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+            // The reader is gone once it has hit the end
+            let _ = empty_tx.send(buf);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    })
+}
+
+/// Digests of `paths`, hashed in parallel, in the same order.
+pub fn sha256_files(paths: &[&Path]) -> Vec<Result<String>> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()).min(paths.len().max(1));
+    let chunk = paths.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|path| sha256_file(path)).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("hashing thread panicked")).collect()
+    })
+}
+
+/// Fail unless the file at `path` has the SHA-256 digest `expected`.
+pub fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
+    debug!("Verified checksum of {}", path.display());
+    Ok(())
+}
+
+/// Expected digests of release artifacts, in `sha256sum` format:
+/// `<hex digest>  <artifact file name>` per line, `#` starts a comment.
+#[derive(Debug, Default, Clone)]
+pub struct ChecksumFile {
+    path: PathBuf,
+    entries: HashMap<String, String>,
+}
+
+impl ChecksumFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read checksum file {}", path.display()))?;
+        Self::parse(&content, path)
+    }
+
+    /// Parse checksum lines; `path` names their origin in error messages.
+    pub fn parse(content: &str, path: &Path) -> Result<Self> {
+        let mut entries = HashMap::new();
+        for (lineno, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (digest, name) = line
+                .split_once(char::is_whitespace)
+                .with_context(|| format!("{}:{}: expected `<sha256>  <file>`", path.display(), lineno + 1))?;
+            // sha256sum marks binary mode with a leading `*` on the file name
+            let name = name.trim().trim_start_matches('*');
+            entries.insert(name.to_string(), digest.to_lowercase());
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Pinned digest of `artifact_name`, if any.
+    pub fn get(&self, artifact_name: &str) -> Option<&str> {
+        self.entries.get(artifact_name).map(String::as_str)
+    }
+
+    /// Verify a downloaded artifact. Every artifact must be pinned; an
+    /// artifact missing from the file is treated as a failure.
+    pub fn verify(&self, artifact_name: &str, path: &Path) -> Result<()> {
+        let expected = self.entries.get(artifact_name).with_context(|| {
+            format!(
+                "{} has no pinned checksum in {}",
+                artifact_name,
+                self.path.display()
+            )
+        })?;
+        verify_checksum(path, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_file_verifies_pinned_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifact = dir.path().join("artifact.tar.gz");
+        std::fs::write(&artifact, b"hello").unwrap();
+
+        let sums = dir.path().join("wasmedgeup.sums");
+        std::fs::write(
+            &sums,
+            "# pinned\n2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824 *artifact.tar.gz\n",
+        )
+        .unwrap();
+
+        let pins = ChecksumFile::load(&sums).unwrap();
+        assert!(pins.verify("artifact.tar.gz", &artifact).is_ok());
+        assert!(pins.verify("other.tar.gz", &artifact).is_err());
+
+        std::fs::write(&artifact, b"tampered").unwrap();
+        assert!(pins.verify("artifact.tar.gz", &artifact).is_err());
+    }
+
+    #[test]
+    fn test_pipelined_hash_matches_buffered_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let large = dir.path().join("large.bin");
+        let content: Vec<u8> = (0..HASH_BUFFER * 2 + 123).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&large, &content).unwrap();
+
+        let expected = hex::encode(Sha256::digest(&content));
+        assert_eq!(sha256_file(&large).unwrap(), expected);
+        let small = dir.path().join("small.bin");
+        std::fs::write(&small, b"hello").unwrap();
+        let digests = sha256_files(&[&large, &small, &dir.path().join("missing")]);
+        assert_eq!(digests[0].as_ref().unwrap(), &expected);
+        assert!(digests[1].as_ref().unwrap().starts_with("2cf24dba"));
+        assert!(digests[2].is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use clap::ValueEnum;
+use std::path::PathBuf;
+
+/// Where CI jobs get wasmedgeup from.
+const INSTALL_WASMEDGEUP: &str =
+    "cargo install --locked --git https://github.com/Ytemiloluwa/Wasmedgeup.git wasmedgeup";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Provider {
+    /// GitHub Actions workflow step
+    Github,
+    /// GitLab CI job template to `extends:` from
+    Gitlab,
+    /// CircleCI job step
+    Circle,
+}
+
+impl Provider {
+    fn name(self) -> &'static str {
+        match self {
+            Provider::Github => "github",
+            Provider::Gitlab => "gitlab",
+            Provider::Circle => "circle",
+        }
+    }
+}
+
+/// What a generated pipeline installs.
+#[derive(Debug, Clone)]
+pub struct CiPlan {
+    pub version: String,
+    /// Plugin specs, `name@version`
+    pub plugins: Vec<String>,
+    /// Pin file, relative to the repository root, every download is checked against
+    pub checksum_file: Option<PathBuf>,
+}
+
+impl CiPlan {
+    /// Shell commands that install the plan with wasmedgeup.
+    fn commands(&self) -> Vec<String> {
+        let mut global = String::from("--yes");
+        if let Some(path) = &self.checksum_file {
+            global.push_str(&format!(" --checksum-file {}", path.display()));
+        }
+        let mut commands = vec![
+            INSTALL_WASMEDGEUP.to_string(),
+            format!("wasmedgeup {} install {}", global, self.version),
+        ];
+        if !self.plugins.is_empty() {
+            commands.push(format!("wasmedgeup {} plugin install {}", global, self.plugins.join(" ")));
+        }
+        commands
+    }
+
+    /// Pipeline snippet for `provider`. The installation lands in the default
+    /// `~/.wasmedge` and is put on the path for the rest of the job.
+    pub fn render(&self, provider: Provider) -> String {
+        let mut out = format!("# Generated by `wasmedgeup generate ci --provider {}`\n", provider.name());
+        let commands = self.commands();
+        match provider {
+            Provider::Github => {
+                out.push_str(&format!("- name: Install WasmEdge {}\n  run: |\n", self.version));
+                for command in commands {
+                    out.push_str(&format!("    {}\n", command));
+                }
+                out.push_str("    echo \"$HOME/.wasmedge/bin\" >> \"$GITHUB_PATH\"\n");
+                out.push_str("    echo \"LD_LIBRARY_PATH=$HOME/.wasmedge/lib${LD_LIBRARY_PATH:+:$LD_LIBRARY_PATH}\" >> \"$GITHUB_ENV\"\n");
+            }
+            Provider::Gitlab => {
+                out.push_str(".wasmedge:\n  before_script:\n");
+                for command in commands {
+                    out.push_str(&format!("    - {}\n", command));
+                }
+                out.push_str("    - source \"$HOME/.wasmedge/env\"\n");
+            }
+            Provider::Circle => {
+                out.push_str(&format!("- run:\n    name: Install WasmEdge {}\n    command: |\n", self.version));
+                for command in commands {
+                    out.push_str(&format!("      {}\n", command));
+                }
+                out.push_str("      echo 'source \"$HOME/.wasmedge/env\"' >> \"$BASH_ENV\"\n");
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ci_templates() {
+        let plans = [
+            CiPlan {
+                version: "0.14.1".into(),
+                plugins: vec!["wasi_logging@0.14.1".into(), "wasi_nn-ggml@0.14.1".into()],
+                checksum_file: Some(PathBuf::from("wasmedgeup.sums")),
+            },
+            CiPlan {
+                version: "0.13.5".into(),
+                plugins: Vec::new(),
+                checksum_file: None,
+            },
+        ];
+        let mut out = String::new();
+        for provider in [Provider::Github, Provider::Gitlab, Provider::Circle] {
+            for plan in &plans {
+                out.push_str(&plan.render(provider));
+            }
+        }
+        insta::assert_snapshot!("ci_templates", out);
+    }
+}
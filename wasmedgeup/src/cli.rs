@@ -1,60 +1,819 @@
-use clap::{Parser, Subcommand};
+use clap::builder::FalseyValueParser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use crate::buildinfo::BuildInfo;
+use crate::ci::Provider;
+use crate::complete::Shell;
+use crate::extract;
+use crate::output::ProgressMode;
+use crate::platform::Platform;
+use crate::version::{self, WasmEdgeVersion};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    /// Without a command, a first run offers to install WasmEdge
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 
     /// Enable verbose output
-    #[arg(short = 'V', long)]
+    #[arg(short = 'v', long, global = true, env = "WASMEDGEUP_VERBOSE", value_parser = FalseyValueParser::new())]
     pub verbose: bool,
 
     /// Disable progress output
-    #[arg(short, long)]
+    #[arg(short, long, global = true, env = "WASMEDGEUP_QUIET", value_parser = FalseyValueParser::new())]
     pub quiet: bool,
+
+    /// How to show progress: `bar` for people, or `json` for one event per
+    /// line on stderr (phase, bytes, totals, current file) instead of bars
+    #[arg(long, global = true, env = "WASMEDGEUP_PROGRESS", value_enum, default_value_t = ProgressMode::Bar)]
+    pub progress: ProgressMode,
+
+    /// Answer yes to every confirmation prompt
+    #[arg(short, long, global = true, env = "WASMEDGEUP_YES", value_parser = FalseyValueParser::new())]
+    pub yes: bool,
+
+    /// Print results as a single JSON document with a `warnings` array;
+    /// diagnostics still go to stderr
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Show what would change on disk without changing anything. Archives are
+    /// resolved but not downloaded
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Reproducible provisioning: require exact versions, a pinned
+    /// checksum for every artifact and verified signatures, and refuse
+    /// fallback archives, guessed URLs, cached metadata and source builds
+    #[arg(long, global = true, env = "WASMEDGEUP_STRICT", value_parser = FalseyValueParser::new())]
+    pub strict: bool,
+
+    /// Ask before batch operations that download more than this many megabytes
+    #[arg(long, global = true, env = "WASMEDGEUP_CONFIRM_ABOVE", value_name = "MB", default_value_t = 500)]
+    pub confirm_above: u64,
+
+    /// Abort extracting an archive that unpacks to more than this many megabytes
+    #[arg(long, global = true, env = "WASMEDGEUP_MAX_EXTRACT_SIZE", value_name = "MB", default_value_t = extract::DEFAULT_MAX_EXTRACT_MB)]
+    pub max_extract_size: u64,
+
+    /// Abort extracting an archive that holds more than this many files
+    #[arg(long, global = true, env = "WASMEDGEUP_MAX_EXTRACT_FILES", default_value_t = extract::DEFAULT_MAX_EXTRACT_FILES)]
+    pub max_extract_files: u64,
+
+    /// On Apple silicon, when run from an x86_64 terminal under Rosetta,
+    /// install x86_64 builds matching the terminal instead of arm64 builds
+    /// for the hardware
+    #[arg(long, global = true, env = "WASMEDGEUP_ROSETTA", value_parser = FalseyValueParser::new())]
+    pub rosetta: bool,
+
+    /// Base URL to download release artifacts from instead of https://github.com
+    #[arg(long, global = true, env = "WASMEDGEUP_MIRROR")]
+    pub mirror: Option<String>,
+
+    /// Directory to take release artifacts from instead of downloading
+    /// them, for networks that allow API access but block large downloads.
+    /// Release metadata is still fetched online
+    #[arg(long, global = true, env = "WASMEDGEUP_ARTIFACT_STORE")]
+    pub artifact_store: Option<PathBuf>,
+
+    /// Group-writable directory, e.g. on an NFS mount, caching downloaded
+    /// artifacts for every user and machine sharing it. Concurrent runs
+    /// download each artifact once and validate every read by digest
+    #[arg(long, global = true, env = "WASMEDGEUP_SHARED_CACHE")]
+    pub shared_cache: Option<PathBuf>,
+
+    /// Proxy every request goes through, e.g. `http://proxy:3128`, except to
+    /// hosts NO_PROXY lists. Without it the *_PROXY variables apply, then
+    /// the system proxy on Windows and macOS
+    #[arg(long, global = true, env = "WASMEDGEUP_PROXY", value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Require the TLS certificate of a host to carry this public key, as
+    /// `HOST=sha256/BASE64` of its SubjectPublicKeyInfo. Repeat a host for
+    /// backup pins; `*.example.com` covers its subdomains
+    #[arg(long, global = true, env = "WASMEDGEUP_PIN_CERTS", value_name = "HOST=PIN", value_delimiter = ',')]
+    pub pin_cert: Vec<String>,
+
+    /// Base URL of a server publishing zstd patches between runtime releases.
+    /// `update` fetches `<url>/<from>/<to>/<package>.zst` when the previous
+    /// archive is cached, and downloads in full otherwise
+    #[arg(long, global = true, env = "WASMEDGEUP_PATCH_SERVER")]
+    pub patch_server: Option<String>,
+
+    /// URL of a JSON document listing supported release lines, as
+    /// `{"maintained": ["0.14"], "deprecated": ["0.12"]}`. Upstream publishes
+    /// none, so versions only get a support status with one
+    #[arg(long, global = true, env = "WASMEDGEUP_SUPPORT_MANIFEST", value_name = "URL")]
+    pub support_manifest: Option<String>,
+
+    /// GitHub token used for API requests, raising the rate limit
+    #[arg(long, global = true, env = "WASMEDGEUP_GITHUB_TOKEN", hide_env_values = true)]
+    pub github_token: Option<String>,
+
+    /// Apply the settings of this profile from the config file
+    #[arg(long, global = true, env = "WASMEDGEUP_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Language for messages (defaults to LC_ALL/LC_MESSAGES/LANG)
+    #[arg(long, global = true, env = "WASMEDGEUP_LOCALE")]
+    pub locale: Option<String>,
+
+    /// File of pinned SHA-256 digests that every downloaded artifact must match
+    #[arg(long, global = true, env = "WASMEDGEUP_CHECKSUM_FILE")]
+    pub checksum_file: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Install WasmEdge runtime
-    Install {
-        /// Version to install (use 'latest' for the latest version)
+    Install(Box<InstallArgs>),
+
+    /// Download and verify release artifacts into a directory without installing them
+    Download {
+        /// Runtime version to download (use 'latest' for the latest version)
+        #[arg(value_parser = version::parse_arg)]
         version: String,
 
-        /// Installation path
-        #[arg(short, long, default_value = "~/.wasmedge")]
-        path: PathBuf,
+        /// Plugins to download for the version (can specify version with name@version)
+        #[arg(long, value_delimiter = ',')]
+        plugins: Vec<String>,
 
-        /// Temporary directory for downloads
-        #[arg(short, long, default_value = "/tmp")]
-        tmpdir: PathBuf,
+        /// Directory to save the artifacts in
+        #[arg(short, long = "output-dir", default_value = ".")]
+        output: PathBuf,
 
         /// Override OS detection
-        #[arg(short, long)]
+        #[arg(long)]
         os: Option<String>,
 
         /// Override architecture detection
-        #[arg(short, long)]
+        #[arg(long)]
         arch: Option<String>,
+
+        /// Override OS and architecture detection at once, as `<os>/<arch>` (see `wasmedgeup platforms`)
+        #[arg(long, value_parser = Platform::parse, conflicts_with_all = ["os", "arch"])]
+        platform: Option<Platform>,
+
+        /// Download the statically linked CLI build (Linux only)
+        #[arg(long = "static")]
+        static_build: bool,
+    },
+
+    /// List available WasmEdge versions and what is installed
+    List {
+        /// Installation path to compare against
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Also fetch and show the upstream checksum of each version's archive for this platform
+        #[arg(long)]
+        verify: bool,
+
+        /// Also list pre-releases such as 0.15.0-rc.1 (installed ones are
+        /// always listed)
+        #[arg(long, visible_alias = "include-prerelease")]
+        pre: bool,
+    },
+
+    /// Make a version installed with `install --versioned` the default, by
+    /// linking it into the installation path (copying on Windows)
+    Default {
+        /// Version to make the default, e.g. 0.14.1
+        version: String,
+
+        /// Installation path the versions were installed under
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Temporary directory for downloads
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
     },
 
-    /// List available WasmEdge versions
-    List,
+    /// List the platforms `--platform` takes and the release archives they
+    /// install, or with a version, the platforms that release published
+    /// runtime and plugin archives for
+    Platforms {
+        /// Release to list published platforms of (use 'latest' for the latest version)
+        #[arg(value_parser = version::parse_arg)]
+        version: Option<String>,
+    },
 
     /// Remove WasmEdge installation
     Remove {
         /// Installation path to remove from
-        #[arg(short, long)]
+        #[arg(short, long, env = "WASMEDGEUP_PATH")]
         path: PathBuf,
+
+        /// Remove files even if running processes are using them
+        #[arg(short, long)]
+        force: bool,
+
+        /// Also remove user data: installed plugins, the download cache and
+        /// the config file. Without it they are kept for a later install
+        #[arg(long)]
+        purge: bool,
     },
 
     /// Plugin management commands
     Plugin {
+        /// Installation whose plugin directory to manage
+        #[arg(short, long, global = true, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Temporary directory for downloads and source builds
+        #[arg(short, long, global = true, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+
         #[command(subcommand)]
         command: PluginCommands,
     },
+
+    /// Manage optional release components
+    Component {
+        #[command(subcommand)]
+        command: ComponentCommands,
+    },
+
+    /// Write a wasmedge-toolchain.toml pinning the runtime, plugins and
+    /// components of the project in the current directory
+    Init {
+        /// Runtime version to pin
+        #[arg(long, default_value = "latest", value_parser = version::parse_arg)]
+        version: String,
+
+        /// Plugins the project needs
+        #[arg(long, value_delimiter = ',')]
+        plugins: Vec<String>,
+
+        /// Parts of the runtime the project needs (defaults to all)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        components: Vec<RuntimePart>,
+
+        /// Install the pinned toolchain right away, like `sync`
+        #[arg(long)]
+        install: bool,
+
+        /// Replace an existing wasmedge-toolchain.toml
+        #[arg(long)]
+        force: bool,
+
+        /// Installation path the pinned version is installed under, with
+        /// `--install`
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Temporary directory for downloads
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+    },
+
+    /// Install the versions pinned by `wasmedge-toolchain.toml` and
+    /// `.wasmedge-version` files
+    Sync {
+        /// Install the pins of every member listed in the enclosing
+        /// wasmedgeup-workspace.json instead of only the nearest pin
+        #[arg(long)]
+        workspace: bool,
+
+        /// Installation path the pinned versions are installed under
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Temporary directory for downloads
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+    },
+
+    /// Run a program with the managed WasmEdge environment applied
+    Exec {
+        /// Installation path to use. The version pinned nearest to the
+        /// current directory is taken from under it, as `sync` installs it
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Installed version to run with: one installed with
+        /// `install --versioned` under the path, or the one installed there
+        #[arg(long, value_parser = version::parse_arg)]
+        version: Option<String>,
+
+        /// Also point WASMEDGE_PLUGIN_PATH at the managed plugin directory
+        #[arg(long)]
+        with_plugins: bool,
+
+        /// Program to run
+        program: String,
+
+        /// Arguments passed to the program
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Print the shell script that puts an installation on the path, for
+    /// `eval "$(wasmedgeup env)"` in dotfiles, or
+    /// `wasmedgeup env | Out-String | Invoke-Expression` in PowerShell
+    Env {
+        /// Installation path to use. The version pinned nearest to the
+        /// current directory is taken from under it, as `sync` installs it
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Shell to print the script for (defaults to PowerShell when run
+        /// from it on Windows, otherwise the shell of the `env` file)
+        #[arg(long)]
+        shell: Option<EnvShell>,
+    },
+
+    /// Serve as the backend of an asdf or mise plugin, following the asdf
+    /// plugin protocol
+    Asdf {
+        #[command(subcommand)]
+        command: AsdfCommands,
+    },
+
+    /// Upgrade the installed runtime in place to the latest release, keeping
+    /// its plugins and install options
+    Update {
+        /// Installation path
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Only report available updates of the runtime, its plugins and
+        /// wasmedgeup itself, one per line, and exit with status 10 if there
+        /// are any (0 when up to date, 1 on errors)
+        #[arg(long)]
+        check: bool,
+
+        /// Temporary directory for downloads
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+
+        /// Replace files even if running processes are using them
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Report available updates from release information fetched by earlier
+    /// commands, without going to the network, e.g. in a login hook
+    Outdated {
+        /// Installation path
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// How to report the updates
+        #[arg(long, value_enum, default_value = "lines")]
+        format: OutdatedFormat,
+    },
+
+    /// Diagnose an installation, e.g. SELinux or AppArmor blocking libwasmedge
+    Doctor {
+        /// Installation path
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+    },
+
+    /// Check whether plugins built for one release load in a runtime: both
+    /// must be of the same `major.minor` release line. Fails when they do not
+    Compat {
+        /// Runtime version
+        #[arg(value_parser = version::parse_arg)]
+        runtime: String,
+        /// Version of the release the plugin was built for
+        #[arg(value_parser = version::parse_arg)]
+        plugin: String,
+    },
+
+    /// Print the location of a managed path
+    Show {
+        /// Which path to print
+        what: ShowTarget,
+
+        /// Installation path
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+    },
+
+    /// Manage cached and temporary files
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+
+    /// Manage the wasmedgeup installation itself
+    #[command(name = "self")]
+    SelfCmd {
+        #[command(subcommand)]
+        command: SelfCommands,
+    },
+
+
+    /// Generate configuration for other tools
+    Generate {
+        #[command(subcommand)]
+        command: GenerateCommands,
+    },
+
+    /// Print a shell completion script, e.g. `source <(wasmedgeup completions bash)`
+    Completions {
+        /// Shell to generate the script for
+        shell: Shell,
+    },
+
+    /// Print completion candidates for the words typed so far (used by the
+    /// completion scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Words after the program name; the last is the one being completed
+        #[arg(raw = true)]
+        words: Vec<String>,
+    },
+}
+
+/// Arguments of `install`.
+#[derive(Args)]
+pub struct InstallArgs {
+    /// Version to install (use 'latest' for the latest version)
+    #[arg(env = "WASMEDGEUP_VERSION", value_parser = version::parse_arg)]
+    pub version: String,
+
+    /// Installation path
+    #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+    pub path: PathBuf,
+
+    /// Temporary directory for downloads
+    #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+    pub tmpdir: PathBuf,
+
+    /// Override OS detection
+    #[arg(short, long)]
+    pub os: Option<String>,
+
+    /// Override architecture detection
+    #[arg(short, long)]
+    pub arch: Option<String>,
+
+    /// Override OS and architecture detection at once, as `<os>/<arch>` (see `wasmedgeup platforms`)
+    #[arg(long, value_parser = Platform::parse, conflicts_with_all = ["os", "arch"])]
+    pub platform: Option<Platform>,
+
+    /// Replace files even if running processes are using them
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Patch binaries to find the managed libraries without LD_LIBRARY_PATH/DYLD_LIBRARY_PATH
+    #[arg(long)]
+    pub patch_rpath: bool,
+
+    /// Install the statically linked CLI build (Linux only)
+    #[arg(long = "static")]
+    pub static_build: bool,
+
+    /// Allow installing a pre-release such as 0.15.0-rc.1, and resolve
+    /// `latest` to the newest release including pre-releases
+    #[arg(long, visible_alias = "include-prerelease")]
+    pub pre: bool,
+
+    /// Install into `<path>/versions/<version>`, next to the other
+    /// versions installed this way, instead of replacing the runtime in
+    /// the installation path. Each version has its own plugin directory
+    #[arg(long)]
+    pub versioned: bool,
+
+    /// Install for every user of the machine into /opt/wasmedge, with
+    /// mode 755 unless --mode says otherwise
+    #[arg(long)]
+    pub system: bool,
+
+    /// User (name or uid) to own every installed file and directory
+    #[arg(long)]
+    pub owner: Option<String>,
+
+    /// Group (name or gid) to own every installed file and directory
+    #[arg(long)]
+    pub group: Option<String>,
+
+    /// Octal mode for installed directories and executables, e.g. 775;
+    /// other files get it without the execute bits
+    #[arg(long)]
+    pub mode: Option<String>,
+
+    /// On SELinux systems, label the installed libraries and binaries
+    /// (semanage fcontext + restorecon) so they can be loaded from this prefix
+    #[arg(long)]
+    pub selinux_relabel: bool,
+
+    /// Write no `env` file and leave the shell environment alone, e.g.
+    /// when dotfiles or Nix manage it; `env` and `exec` still work
+    #[arg(long)]
+    pub no_env: bool,
+
+    /// Mark the installed files read-only once the install succeeds;
+    /// `remove` and later installs make them writable again
+    #[arg(long)]
+    pub readonly: bool,
+
+    /// Build the runtime from the WasmEdge sources (git, cmake, ninja and a
+    /// C++ compiler required) instead of installing a release archive.
+    /// The only way to install on FreeBSD and OpenBSD
+    #[arg(long)]
+    pub build_from_source: bool,
+
+    /// Tag, branch or commit to build instead of the version's tag
+    #[arg(long, value_name = "REF", requires = "build_from_source")]
+    pub git_ref: Option<String>,
+
+    /// Plugins to build along with the runtime, e.g. `wasi_nn-ggml,wasi_logging`
+    #[arg(long, value_name = "PLUGINS", value_delimiter = ',', requires = "build_from_source")]
+    pub source_plugins: Vec<String>,
+
+    /// Extra CMake option for the source build, as NAME=VALUE; repeatable
+    #[arg(long = "cmake-define", value_name = "NAME=VALUE", requires = "build_from_source")]
+    pub cmake_defines: Vec<String>,
+
+    /// Flush the installed files to disk before reporting success, so the
+    /// install survives an abrupt power cycle
+    #[arg(long)]
+    pub fsync: bool,
+
+    /// Only install these parts of the release (defaults to all)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub components: Vec<RuntimePart>,
+
+    /// Unpack only the wasmedge CLI and the libwasmedge it loads, skipping
+    /// the other tools, headers and static libraries, for minimal images
+    #[arg(long, conflicts_with_all = ["components", "build_from_source"])]
+    pub bin_only: bool,
+
+    /// Do not warn when installing a version upstream has deprecated
+    #[arg(long)]
+    pub allow_deprecated: bool,
+
+    /// Allow installing a version older than the installed one
+    #[arg(long)]
+    pub allow_downgrade: bool,
+
+    /// Give up if downloading and unpacking the runtime takes longer than
+    /// this many seconds. Moving files into the prefix is never cut short
+    #[arg(long, value_name = "SECS", env = "WASMEDGEUP_MAX_TIME")]
+    pub max_time: Option<u64>,
+
+    /// Plugins to install once the runtime is installed, e.g.
+    /// `wasi_nn-ggml,wasi_logging`
+    #[arg(long, value_name = "PLUGINS", value_delimiter = ',', env = "WASMEDGEUP_PLUGINS")]
+    pub plugins: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ComponentCommands {
+    /// Install an optional component into an existing installation
+    Add {
+        /// Component to install
+        component: Component,
+
+        /// WasmEdge version the component belongs to
+        #[arg(long, value_parser = version::parse_arg)]
+        version: String,
+
+        /// Installation path
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Temporary directory for downloads
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Component {
+    /// Debug symbols for the runtime libraries and binaries
+    Debug,
+}
+
+/// Part of a runtime release `--components` selects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RuntimePart {
+    /// The wasmedge CLI tools
+    Bin,
+    /// The shared libraries
+    Lib,
+    /// The C API headers
+    Include,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EnvShell {
+    /// `sh` and compatible shells
+    Sh,
+    /// The Windows command prompt
+    Cmd,
+    Powershell,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ShowTarget {
+    /// Installation root
+    Home,
+    /// Directory plugins are installed into
+    PluginDir,
+    /// Cache directory
+    CacheDir,
+    /// Config file
+    Config,
+    /// Generated environment script
+    EnvFile,
+    /// Installed runtime version and its upstream support status
+    Version,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Remove cached files
+    Clean {
+        /// Parts of the cache to remove, comma separated (all of it when omitted)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        only: Vec<CachePart>,
+
+        /// Also sweep staging directories left behind by interrupted runs
+        #[arg(long)]
+        tmp: bool,
+
+        /// Temporary directory to sweep (defaults to the system temp directory)
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR")]
+        tmpdir: Option<PathBuf>,
+    },
+}
+
+/// Parts of the cache `cache clean --only` removes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CachePart {
+    /// Release listings and other API responses
+    Metadata,
+    /// Runtime archives kept as the base of patch upgrades
+    Archives,
+    /// Version and plugin names offered by shell completion
+    Completions,
+}
+
+impl CachePart {
+    /// Directory of this part under the cache directory.
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            CachePart::Metadata => "metadata",
+            CachePart::Archives => "archives",
+            CachePart::Completions => "completions",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum GenerateCommands {
+    /// Print a CI pipeline snippet that installs the same runtime and plugins
+    /// as this machine, verified against the checksum pin file
+    Ci {
+        /// CI system to generate for
+        #[arg(long, value_enum)]
+        provider: Provider,
+
+        /// Installation to copy the runtime version from
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Runtime version to install instead of the installed one
+        #[arg(long, value_parser = version::parse_arg)]
+        version: Option<String>,
+
+        /// Plugins to install instead of the installed ones (name or name@version)
+        #[arg(long, value_delimiter = ',')]
+        plugins: Vec<String>,
+
+        /// Toolchain file (`wasmedge-toolchain.toml`) to take the runtime
+        /// version and plugins from instead of the installation
+        #[arg(long, conflicts_with_all = ["version", "plugins"])]
+        lockfile: Option<PathBuf>,
+    },
+
+    /// Print an SPDX software bill of materials of the runtime and plugins
+    /// installed, listing the license and notice files of each
+    Sbom {
+        /// Installation to describe
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+    },
+
+    /// Print a Nix derivation pinning the runtime and plugin archives by URL
+    /// and SHA-256, for declarative system configurations
+    Nix {
+        /// Installation to copy the runtime version from
+        #[arg(short, long, env = "WASMEDGEUP_PATH", default_value = "~/.wasmedge")]
+        path: PathBuf,
+
+        /// Runtime version to pin instead of the installed one
+        #[arg(long, value_parser = version::parse_arg)]
+        version: Option<String>,
+
+        /// Plugins to pin (name or name@version)
+        #[arg(long, value_delimiter = ',')]
+        plugins: Vec<String>,
+
+        /// Temporary directory for archives downloaded to compute missing digests
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AsdfCommands {
+    /// Write an asdf/mise plugin whose scripts call wasmedgeup
+    Plugin {
+        /// Directory to write the plugin to
+        dir: PathBuf,
+    },
+
+    /// Print every release version, oldest first
+    ListAll,
+
+    /// Print the newest stable version
+    LatestStable,
+
+    /// Download and verify the runtime archive into ASDF_DOWNLOAD_PATH
+    Download {
+        #[arg(long, value_enum, env = "ASDF_INSTALL_TYPE", default_value = "version")]
+        install_type: AsdfInstallType,
+
+        /// Release version, or the branch, tag or commit of a ref install,
+        /// taken as written
+        #[arg(long, env = "ASDF_INSTALL_VERSION")]
+        version: String,
+
+        /// Where `install` will put the runtime
+        #[arg(long, env = "ASDF_INSTALL_PATH")]
+        path: PathBuf,
+
+        #[arg(long, env = "ASDF_DOWNLOAD_PATH")]
+        download_path: PathBuf,
+    },
+
+    /// Install into ASDF_INSTALL_PATH, from ASDF_DOWNLOAD_PATH when a
+    /// download left the archive there
+    Install {
+        #[arg(long, value_enum, env = "ASDF_INSTALL_TYPE", default_value = "version")]
+        install_type: AsdfInstallType,
+
+        /// Release version, or the branch, tag or commit of a ref install,
+        /// taken as written
+        #[arg(long, env = "ASDF_INSTALL_VERSION")]
+        version: String,
+
+        #[arg(long, env = "ASDF_INSTALL_PATH")]
+        path: PathBuf,
+
+        #[arg(long, env = "ASDF_DOWNLOAD_PATH")]
+        download_path: Option<PathBuf>,
+
+        /// Temporary directory for downloads
+        #[arg(short, long, env = "WASMEDGEUP_TMPDIR", default_value = "/tmp")]
+        tmpdir: PathBuf,
+    },
+
+    /// Print the directories holding executables
+    ListBinPaths,
+
+    /// Print shell exports of the library path for ASDF_INSTALL_PATH
+    ExecEnv {
+        #[arg(long, env = "ASDF_INSTALL_PATH")]
+        path: PathBuf,
+    },
+}
+
+/// What ASDF_INSTALL_VERSION names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutdatedFormat {
+    /// One line per outdated component, like `update --check`
+    Lines,
+    /// A single summary line, e.g. for the message of the day
+    Brief,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AsdfInstallType {
+    /// A release
+    Version,
+    /// A git tag, branch or commit, built from source
+    Ref,
+}
+
+#[derive(Subcommand)]
+pub enum SelfCommands {
+    /// Replace wasmedgeup with the latest release after checking its signature
+    Update {
+        /// Install a release that is unsigned or cannot be verified
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -63,24 +822,309 @@ pub enum PluginCommands {
     Install {
         /// Plugin names to install (can specify version with name@version)
         plugins: Vec<String>,
+
+        /// Build the plugins from the WasmEdge sources (git, cmake, ninja and
+        /// a C++ compiler required), e.g. when no published archive fits
+        #[arg(long)]
+        build_from_source: bool,
+
+        /// Optional build features, e.g. `cuda` for wasi_nn-ggml
+        #[arg(long, value_delimiter = ',', requires = "build_from_source")]
+        features: Vec<String>,
+
+        /// Tag, branch or commit to build instead of the version's tag
+        #[arg(long, value_name = "REF", requires = "build_from_source")]
+        git_ref: Option<String>,
     },
 
     /// List available plugins
-    List,
+    List {
+        /// List the installed plugins instead, along with libraries in the
+        /// plugin directory that no plugin is recorded for
+        #[arg(long)]
+        installed: bool,
+    },
 
     /// Remove plugins
     Remove {
         /// Plugin names to remove (can specify version with name@version)
+        #[arg(required_unless_present = "file")]
+        plugins: Vec<String>,
+
+        /// Remove a single file from the plugin directory, by file name or
+        /// path, e.g. a stray library no plugin is recorded for. Files
+        /// outside the plugin directory are refused
+        #[arg(long, value_name = "NAME|PATH")]
+        file: Vec<String>,
+    },
+
+    /// Record libraries in the plugin directory that no plugin is recorded
+    /// for, e.g. copied in by hand, as installed plugins
+    Adopt {
+        /// Library file names or paths in the plugin directory
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Name to record a single library under (defaults to the library
+        /// name, e.g. wasmedgePluginWasiNN for libwasmedgePluginWasiNN.so)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Freeze plugins at a version (name@version, or the installed version)
+    /// so installs and syncs do not move them. Lists pins without arguments
+    Pin {
+        plugins: Vec<String>,
+    },
+
+    /// Release plugins pinned with `plugin pin`
+    Unpin {
+        #[arg(required = true)]
+        plugins: Vec<String>,
+    },
+
+    /// Check installed plugins against their recorded digests and confirm the
+    /// runtime can load them
+    Verify {
+        /// Plugins to check (all installed plugins when omitted)
+        plugins: Vec<String>,
+    },
+
+    /// Pack installed plugins into a bundle for installing on another machine
+    /// with the same platform and runtime version
+    Export {
+        /// Plugins to export (all installed plugins when omitted)
         plugins: Vec<String>,
+
+        /// Bundle file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Install the plugins from a bundle made by `plugin export`
+    Import {
+        /// Bundle file to install from
+        bundle: PathBuf,
+    },
+
+    /// Show the plugins the installed runtime actually loads and how they
+    /// differ from what wasmedgeup installed
+    Active,
+
+    /// Inspect what installed plugins depend on
+    Deps {
+        #[command(subcommand)]
+        command: DepsCommands,
+    },
+
+    /// Inspect the plugin catalog of a release
+    Catalog {
+        #[command(subcommand)]
+        command: CatalogCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DepsCommands {
+    /// Print the shared libraries a plugin's libraries link against as a
+    /// tree, marking the ones that are missing
+    Tree {
+        /// Installed plugin to inspect
+        plugin: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CatalogCommands {
+    /// Write the resolved plugin catalog of a release as JSON
+    Export {
+        /// WasmEdge release to export the catalog of
+        #[arg(long, value_parser = version::parse_arg)]
+        version: String,
+
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
+impl InstallArgs {
+    /// `install latest` with every other argument at its default, taking
+    /// `defaults` like [`Cli::try_parse_with_defaults`]: what a first run
+    /// without a command installs.
+    pub fn latest(defaults: &[(&str, String)]) -> Result<Self, clap::Error> {
+        let command = with_defaults(Self::augment_args(clap::Command::new("install")), defaults);
+        Self::from_arg_matches(&command.try_get_matches_from(["install", "latest"])?)
+    }
+}
+
 impl Cli {
-    pub fn parse_plugin_name_version(plugin_spec: &str) -> (String, Option<String>) {
-        if let Some((name, version)) = plugin_spec.split_once('@') {
-            (name.to_string(), Some(version.to_string()))
-        } else {
-            (plugin_spec.to_string(), None)
+    /// Parse the process arguments, taking `defaults` (by `WASMEDGEUP_*`
+    /// variable, e.g. from the config file) for flags neither the command
+    /// line nor the environment sets.
+    pub fn parse_with_defaults(defaults: &[(&str, String)]) -> Self {
+        Self::try_parse_with_defaults(std::env::args_os(), defaults).unwrap_or_else(|e| e.exit())
+    }
+
+    /// [`parse_with_defaults`](Self::parse_with_defaults) of `args`.
+    pub fn try_parse_with_defaults<I, T>(args: I, defaults: &[(&str, String)]) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        // `--version` prints the build details to include in bug reports
+        let command = Self::command().long_version(BuildInfo::get().long_version());
+        let mut matches = with_defaults(command, defaults).try_get_matches_from(args)?;
+        // A prefix from WASMEDGEUP_PATH or the config file is a default for
+        // user installs, so only `--path` itself conflicts with `--system`
+        if let Some(("install", install)) = matches.subcommand() {
+            if install.get_flag("system") && install.value_source("path") == Some(ValueSource::CommandLine) {
+                return Err(Self::command().error(ErrorKind::ArgumentConflict, "the argument '--system' cannot be used with '--path <PATH>'"));
+            }
         }
+        Self::from_arg_matches_mut(&mut matches)
+    }
+
+    /// Profile `--profile` (or `WASMEDGEUP_PROFILE`) selects in `args`, found
+    /// before the profile's settings become the defaults of the other flags.
+    pub fn selected_profile<I, T>(args: I) -> Option<String>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Self::command().ignore_errors(true).try_get_matches_from(args).ok()?;
+        matches.get_one::<String>("profile").cloned()
+    }
+
+    pub fn parse_plugin_name_version(plugin_spec: &str) -> anyhow::Result<(String, Option<WasmEdgeVersion>)> {
+        match plugin_spec.split_once('@') {
+            Some((name, version)) => Ok((name.to_string(), Some(version.parse()?))),
+            None => Ok((plugin_spec.to_string(), None)),
+        }
+    }
+}
+
+/// `command` with the built-in default of every argument read from one of
+/// the `defaults` variables replaced, in its subcommands too. Clap still
+/// prefers the command line, then the environment.
+fn with_defaults(command: clap::Command, defaults: &[(&str, String)]) -> clap::Command {
+    let command = command.mut_args(|arg| {
+        let default = arg.get_env().and_then(|env| defaults.iter().find(|(name, _)| env == *name));
+        match default {
+            Some((_, value)) => arg.default_value(value.clone()).required(false),
+            None => arg,
+        }
+    });
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    subcommands.into_iter().fold(command, |command, name| command.mut_subcommand(name, |sub| with_defaults(sub, defaults)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_version_flags_print_the_version_and_the_build() {
+        let Err(short) = Cli::try_parse_with_defaults(["wasmedgeup", "-V"], &[]) else { panic!("-V parsed") };
+        assert_eq!(short.kind(), ErrorKind::DisplayVersion);
+        assert_eq!(short.to_string(), format!("wasmedgeup {}\n", env!("CARGO_PKG_VERSION")));
+
+        let Err(long) = Cli::try_parse_with_defaults(["wasmedgeup", "--version"], &[]) else { panic!("--version parsed") };
+        assert_eq!(long.kind(), ErrorKind::DisplayVersion);
+        assert!(long.to_string().starts_with(&format!("wasmedgeup {}\ncommit: ", env!("CARGO_PKG_VERSION"))), "{}", long);
+
+        let Ok(cli) = Cli::try_parse_with_defaults(["wasmedgeup", "-v", "list"], &[]) else { panic!("-v did not parse") };
+        assert!(cli.verbose);
+    }
+
+    #[test]
+    fn test_profile_is_found_anywhere_before_the_program_arguments() {
+        assert_eq!(Cli::selected_profile(["wasmedgeup", "--profile", "ci", "install"]).as_deref(), Some("ci"));
+        assert_eq!(Cli::selected_profile(["wasmedgeup", "install", "--profile=gpu-dev"]).as_deref(), Some("gpu-dev"));
+        // Required arguments may still come from the profile
+        assert_eq!(Cli::selected_profile(["wasmedgeup", "--profile", "ci", "component", "add"]).as_deref(), Some("ci"));
+        assert_eq!(Cli::selected_profile(["wasmedgeup", "exec", "--", "app", "--profile", "x"]), None);
+    }
+
+    #[test]
+    fn test_only_release_versions_drop_a_leading_v() {
+        let cli = Cli::try_parse_from(["wasmedgeup", "install", "v0.14.1"]).unwrap();
+        let Some(Commands::Install(install)) = cli.command else { panic!("not an install") };
+        assert_eq!(install.version, "0.14.1");
+
+        // asdf refs name branches and commits, which may well start with a v
+        let args = ["wasmedgeup", "asdf", "install", "--install-type", "ref", "--version", "v2-dev", "--path", "/opt/asdf/wasmedge"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        let Some(Commands::Asdf { command: AsdfCommands::Install { version, .. } }) = cli.command else { panic!("not an asdf install") };
+        assert_eq!(version, "v2-dev");
+    }
+
+    #[test]
+    fn test_defaults_apply_below_flags() {
+        let defaults = [
+            ("WASMEDGEUP_PATH", "/opt/wasmedge".to_string()),
+            ("WASMEDGEUP_VERSION", "0.13.5".to_string()),
+            ("WASMEDGEUP_YES", "true".to_string()),
+            ("WASMEDGEUP_CONFIRM_ABOVE", "100".to_string()),
+        ];
+        let cli = Cli::try_parse_with_defaults(["wasmedgeup", "install"], &defaults).unwrap();
+        assert!(cli.yes);
+        assert_eq!(cli.confirm_above, 100);
+        let Some(Commands::Install(install)) = cli.command else { panic!("not an install") };
+        let InstallArgs { version, path, .. } = *install;
+        assert_eq!(version, "0.13.5");
+        assert_eq!(path, PathBuf::from("/opt/wasmedge"));
+
+        let cli = Cli::try_parse_with_defaults(["wasmedgeup", "install", "0.14.1", "-p", "/srv/wasmedge", "--confirm-above", "5"], &defaults).unwrap();
+        assert_eq!(cli.confirm_above, 5);
+        let Some(Commands::Install(install)) = cli.command else { panic!("not an install") };
+        let InstallArgs { version, path, .. } = *install;
+        assert_eq!(version, "0.14.1");
+        assert_eq!(path, PathBuf::from("/srv/wasmedge"));
+
+        let cli = Cli::try_parse_with_defaults(["wasmedgeup", "plugin", "list"], &defaults).unwrap();
+        let Some(Commands::Plugin { path, .. }) = cli.command else { panic!("not a plugin command") };
+        assert_eq!(path, PathBuf::from("/opt/wasmedge"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_first_run_installs_latest_with_the_defaults() {
+        let defaults = [("WASMEDGEUP_PATH", "/opt/wasmedge".to_string()), ("WASMEDGEUP_VERSION", "0.13.5".to_string())];
+        let cli = Cli::try_parse_with_defaults(["wasmedgeup", "--yes"], &defaults).unwrap();
+        assert!(cli.command.is_none() && cli.yes);
+
+        let install = InstallArgs::latest(&defaults).unwrap();
+        assert_eq!(install.version, "latest");
+        assert_eq!(install.path, PathBuf::from("/opt/wasmedge"));
+        assert_eq!(install.tmpdir, PathBuf::from("/tmp"));
+        assert!(!install.system && !install.force && install.plugins.is_empty());
+    }
+
+    #[test]
+    fn test_components_are_a_comma_separated_list() {
+        let cli = Cli::try_parse_from(["wasmedgeup", "install", "0.14.1", "--components", "include,lib"]).unwrap();
+        let Some(Commands::Install(install)) = cli.command else { panic!("not an install") };
+        assert_eq!(install.components, [RuntimePart::Include, RuntimePart::Lib]);
+
+        let Err(error) = Cli::try_parse_from(["wasmedgeup", "install", "0.14.1", "--components", "docs"]) else { panic!("unknown component accepted") };
+        assert_eq!(error.kind(), ErrorKind::InvalidValue);
+        let Err(error) = Cli::try_parse_from(["wasmedgeup", "install", "0.14.1", "--components", "bin", "--bin-only"]) else { panic!("--components allowed with --bin-only") };
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_system_only_conflicts_with_a_path_on_the_command_line() {
+        let defaults = [("WASMEDGEUP_PATH", "/srv/wasmedge".to_string())];
+        let cli = Cli::try_parse_with_defaults(["wasmedgeup", "install", "0.14.1", "--system"], &defaults).unwrap();
+        let Some(Commands::Install(install)) = cli.command else { panic!("not an install") };
+        assert!(install.system);
+
+        let Err(error) = Cli::try_parse_with_defaults(["wasmedgeup", "install", "0.14.1", "--system", "-p", "/srv/wasmedge"], &defaults) else { panic!("--path allowed with --system") };
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+    }
+}
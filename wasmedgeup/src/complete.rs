@@ -0,0 +1,231 @@
+//! Dynamic shell completion. Shells call the hidden `__complete` command with
+//! the words typed so far; candidates are printed one per line. Flags and
+//! subcommands come from the clap definition, release versions and plugin
+//! names from lists cached whenever they are fetched, so completing never
+//! touches the network.
+
+use anyhow::Result;
+use clap::{Arg, ArgAction, Command, CommandFactory, ValueEnum};
+use log::debug;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use crate::{cache, cli::Cli};
+
+const VERSIONS_FILE: &str = "versions";
+const PLUGINS_FILE: &str = "plugins";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Script that wires the shell's completion up to `__complete`.
+    pub fn script(self) -> &'static str {
+        match self {
+            Shell::Bash => r#"_wasmedgeup() {
+    local IFS=$'\n'
+    COMPREPLY=($(wasmedgeup __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+}
+complete -o default -F _wasmedgeup wasmedgeup
+"#,
+            Shell::Zsh => r#"#compdef wasmedgeup
+_wasmedgeup() {
+    local -a candidates
+    candidates=("${(@f)$(wasmedgeup __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    compadd -a candidates
+}
+compdef _wasmedgeup wasmedgeup
+"#,
+            Shell::Fish => r#"complete -c wasmedgeup -f -a '(wasmedgeup __complete -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)'
+"#,
+        }
+    }
+}
+
+fn completion_dir() -> Result<PathBuf> {
+    Ok(cache::cache_dir()?.join("completions"))
+}
+
+fn cached(file: &str) -> Vec<String> {
+    completion_dir().map(|dir| cached_in(&dir, file)).unwrap_or_default()
+}
+
+fn cached_in(dir: &Path, file: &str) -> Vec<String> {
+    std::fs::read_to_string(dir.join(file))
+        .map(|content| content.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn store_in(dir: &Path, file: &str, lines: &[String]) {
+    let result = std::fs::create_dir_all(dir).and_then(|()| std::fs::write(dir.join(file), lines.join("\n")));
+    // The cache only speeds up completion; never fail a command over it
+    if let Err(e) = result {
+        debug!("Could not update completion cache {}: {}", file, e);
+    }
+}
+
+/// Cache the release versions offered when completing a version argument.
+pub fn remember_versions<'a>(versions: impl IntoIterator<Item = &'a str>) {
+    if let Ok(dir) = completion_dir() {
+        remember_versions_in(&dir, versions);
+    }
+}
+
+fn remember_versions_in<'a>(dir: &Path, versions: impl IntoIterator<Item = &'a str>) {
+    let versions: Vec<String> = versions.into_iter().map(str::to_string).collect();
+    store_in(dir, VERSIONS_FILE, &versions);
+}
+
+/// Add plugin names to those offered when completing a plugin argument.
+/// Releases publish different plugin sets, so names accumulate.
+pub fn remember_plugins<'a>(names: impl IntoIterator<Item = &'a str>) {
+    if let Ok(dir) = completion_dir() {
+        remember_plugins_in(&dir, names);
+    }
+}
+
+fn remember_plugins_in<'a>(dir: &Path, names: impl IntoIterator<Item = &'a str>) {
+    let mut all: BTreeSet<String> = cached_in(dir, PLUGINS_FILE).into_iter().collect();
+    let before = all.len();
+    all.extend(names.into_iter().map(str::to_string));
+    if all.len() != before {
+        store_in(dir, PLUGINS_FILE, &all.into_iter().collect::<Vec<_>>());
+    }
+}
+
+/// Completion candidates for the last of `words`, the arguments typed after
+/// the program name. The last word is the one being completed and may be
+/// empty.
+pub fn candidates(words: &[String]) -> Vec<String> {
+    let mut root = Cli::command();
+    root.build();
+    candidates_in(&root, words, cached(VERSIONS_FILE), cached(PLUGINS_FILE))
+}
+
+fn candidates_in(root: &Command, words: &[String], versions: Vec<String>, plugins: Vec<String>) -> Vec<String> {
+    let (current, typed) = match words.split_last() {
+        Some((current, typed)) => (current.as_str(), typed),
+        None => ("", &[][..]),
+    };
+
+    let mut command = root;
+    let mut positional = 0;
+    let mut pending: Option<&Arg> = None;
+    for word in typed {
+        if pending.take().is_some() {
+            continue;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            if !long.contains('=') {
+                pending = command
+                    .get_arguments()
+                    .find(|a| a.get_long() == Some(long))
+                    .filter(|a| a.get_action().takes_values());
+            }
+        } else if let Some(shorts) = word.strip_prefix('-').filter(|s| !s.is_empty()) {
+            // Only a lone or trailing short flag consumes the next word
+            if shorts.chars().count() == 1 {
+                let short = shorts.chars().next();
+                pending = command
+                    .get_arguments()
+                    .find(|a| a.get_short() == short)
+                    .filter(|a| a.get_action().takes_values());
+            }
+        } else if let Some(sub) = command.find_subcommand(word) {
+            command = sub;
+            positional = 0;
+        } else {
+            positional += 1;
+        }
+    }
+
+    let options: Vec<String> = if let Some(arg) = pending {
+        values_for(arg, current, &versions, &plugins)
+    } else if current.starts_with('-') {
+        command
+            .get_arguments()
+            .filter(|a| !a.is_hide_set())
+            .filter_map(|a| a.get_long())
+            .map(|long| format!("--{}", long))
+            .collect()
+    } else if command.has_subcommands() {
+        command
+            .get_subcommands()
+            .filter(|s| !s.is_hide_set())
+            .map(|s| s.get_name().to_string())
+            .collect()
+    } else {
+        let positionals: Vec<&Arg> = command.get_positionals().collect();
+        let arg = positionals.get(positional).or_else(|| {
+            positionals
+                .last()
+                .filter(|a| matches!(a.get_action(), ArgAction::Append))
+        });
+        arg.map(|a| values_for(a, current, &versions, &plugins))
+            .unwrap_or_default()
+    };
+
+    options.into_iter().filter(|o| o.starts_with(current)).collect()
+}
+
+fn values_for(arg: &Arg, current: &str, versions: &[String], plugins: &[String]) -> Vec<String> {
+    match arg.get_id().as_str() {
+        "version" => versions.to_vec(),
+        "plugins" => match current.split_once('@') {
+            Some((name, _)) => versions.iter().map(|v| format!("{}@{}", name, v)).collect(),
+            None => plugins.to_vec(),
+        },
+        _ => arg
+            .get_possible_values()
+            .into_iter()
+            .filter(|v| !v.is_hide_set())
+            .map(|v| v.get_name().to_string())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete(line: &str) -> Vec<String> {
+        let mut root = Cli::command();
+        root.build();
+        let words: Vec<String> = line.split(' ').map(str::to_string).collect();
+        candidates_in(
+            &root,
+            &words,
+            vec!["0.14.1".into(), "0.14.0".into(), "0.13.5".into()],
+            vec!["wasi_nn-ggml".into(), "wasi_crypto".into(), "wasmedge_tensorflow".into()],
+        )
+    }
+
+    #[test]
+    fn test_completes_subcommands_versions_and_plugins() {
+        assert_eq!(complete("pl"), vec!["platforms", "plugin"]);
+        assert_eq!(complete("install 0.14"), vec!["0.14.1", "0.14.0"]);
+        assert_eq!(complete("--quiet install -p /opt 0.13"), vec!["0.13.5"]);
+        assert_eq!(complete("component add debug --version 0.13"), vec!["0.13.5"]);
+        assert_eq!(complete("plugin install wasi_"), vec!["wasi_nn-ggml", "wasi_crypto"]);
+        assert_eq!(complete("plugin install wasi_crypto wasi_nn"), vec!["wasi_nn-ggml"]);
+        assert_eq!(complete("plugin remove wasi_crypto@0.13"), vec!["wasi_crypto@0.13.5"]);
+        assert_eq!(complete("show plugin"), vec!["plugin-dir"]);
+        assert!(complete("install --allow-d").contains(&"--allow-downgrade".to_string()));
+        assert!(complete("").iter().all(|c| c != "__complete"));
+    }
+
+    #[test]
+    fn test_remembered_versions_replace_and_plugins_accumulate() {
+        let dir = tempfile::tempdir().unwrap();
+        remember_versions_in(dir.path(), ["0.14.1", "0.14.0"]);
+        remember_versions_in(dir.path(), ["0.14.1"]);
+        assert_eq!(cached_in(dir.path(), VERSIONS_FILE), ["0.14.1"]);
+
+        remember_plugins_in(dir.path(), ["wasi_nn-ggml", "wasi_crypto"]);
+        remember_plugins_in(dir.path(), ["wasi_logging", "wasi_crypto"]);
+        assert_eq!(cached_in(dir.path(), PLUGINS_FILE), ["wasi_crypto", "wasi_logging", "wasi_nn-ggml"]);
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use crate::paths;
+
+/// Environment variable naming an alternative config file.
+pub const CONFIG_ENV: &str = "WASMEDGEUP_CONFIG";
+
+/// Persistent defaults, read from `<config dir>/wasmedgeup/config.toml`.
+///
+/// Settings apply with the precedence CLI flag > `WASMEDGEUP_*` environment
+/// variable > config file > built-in default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where the settings were read from, whether or not the file exists
+    #[serde(skip)]
+    pub file: PathBuf,
+    pub path: Option<PathBuf>,
+    pub tmpdir: Option<PathBuf>,
+    pub version: Option<String>,
+    pub mirror: Option<String>,
+    pub patch_server: Option<String>,
+    pub support_manifest: Option<String>,
+    pub artifact_store: Option<PathBuf>,
+    pub shared_cache: Option<PathBuf>,
+    pub proxy: Option<String>,
+    pub pinned_certs: Option<Vec<String>>,
+    pub github_token: Option<String>,
+    pub quiet: Option<bool>,
+    pub verbose: Option<bool>,
+    pub progress: Option<String>,
+    pub yes: Option<bool>,
+    pub strict: Option<bool>,
+    pub rosetta: Option<bool>,
+    pub confirm_above: Option<u64>,
+    pub max_extract_size: Option<u64>,
+    pub max_extract_files: Option<u64>,
+    /// Plugins `install` adds after the runtime
+    pub plugins: Option<Vec<String>>,
+    pub checksum_file: Option<PathBuf>,
+    /// Named sets of the settings above, e.g. `ci` or `gpu-dev`, selected
+    /// with `--profile`. A profile's settings replace the top-level ones
+    pub profiles: BTreeMap<String, Config>,
+}
+
+impl Config {
+    /// Location of the config file.
+    pub fn path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV) {
+            return Ok(PathBuf::from(path));
+        }
+        Ok(dirs::config_dir()
+            .context("Could not determine config directory")?
+            .join("wasmedgeup")
+            .join("config.toml"))
+    }
+
+    /// Load the config file, or the defaults if there is none.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let config: Self = match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Failed to parse config file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read config file {}", path.display())),
+        };
+        Ok(Self { file: path, ..config })
+    }
+
+    /// The settings of profile `name` over the top-level ones.
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let Some(profile) = self.profiles.remove(name) else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            anyhow::bail!(t!("profile-unknown", name = name, known = if known.is_empty() { "none".to_string() } else { known.join(", ") }));
+        };
+        if !profile.profiles.is_empty() {
+            anyhow::bail!(t!("profile-nested", name = name));
+        }
+        Ok(Self {
+            file: self.file,
+            path: profile.path.or(self.path),
+            tmpdir: profile.tmpdir.or(self.tmpdir),
+            version: profile.version.or(self.version),
+            mirror: profile.mirror.or(self.mirror),
+            patch_server: profile.patch_server.or(self.patch_server),
+            support_manifest: profile.support_manifest.or(self.support_manifest),
+            artifact_store: profile.artifact_store.or(self.artifact_store),
+            shared_cache: profile.shared_cache.or(self.shared_cache),
+            proxy: profile.proxy.or(self.proxy),
+            pinned_certs: profile.pinned_certs.or(self.pinned_certs),
+            github_token: profile.github_token.or(self.github_token),
+            quiet: profile.quiet.or(self.quiet),
+            verbose: profile.verbose.or(self.verbose),
+            progress: profile.progress.or(self.progress),
+            yes: profile.yes.or(self.yes),
+            strict: profile.strict.or(self.strict),
+            rosetta: profile.rosetta.or(self.rosetta),
+            confirm_above: profile.confirm_above.or(self.confirm_above),
+            max_extract_size: profile.max_extract_size.or(self.max_extract_size),
+            max_extract_files: profile.max_extract_files.or(self.max_extract_files),
+            plugins: profile.plugins.or(self.plugins),
+            checksum_file: profile.checksum_file.or(self.checksum_file),
+            profiles: self.profiles,
+        })
+    }
+
+    /// Config values by the `WASMEDGEUP_*` variable of the flag they
+    /// default, for [`Cli::parse_with_defaults`](crate::cli::Cli::parse_with_defaults)
+    /// to put between the environment and the built-in defaults.
+    ///
+    /// Relative paths in the config file are relative to its directory.
+    pub fn defaults(&self) -> Vec<(&'static str, String)> {
+        let base = self.file.parent().map(PathBuf::from).unwrap_or_default();
+        let path = |p: &Option<PathBuf>| {
+            p.as_ref().map(|p| match paths::resolve_relative_to(p, &base) {
+                Ok(resolved) => resolved.to_string_lossy().into_owned(),
+                // Left for the command to report where the path is used
+                Err(_) => p.to_string_lossy().into_owned(),
+            })
+        };
+        let flag = |b: &Option<bool>| b.map(|b| b.to_string());
+        let values = [
+            ("WASMEDGEUP_PATH", path(&self.path)),
+            ("WASMEDGEUP_TMPDIR", path(&self.tmpdir)),
+            ("WASMEDGEUP_VERSION", self.version.clone()),
+            ("WASMEDGEUP_MIRROR", self.mirror.clone()),
+            ("WASMEDGEUP_PATCH_SERVER", self.patch_server.clone()),
+            ("WASMEDGEUP_SUPPORT_MANIFEST", self.support_manifest.clone()),
+            ("WASMEDGEUP_ARTIFACT_STORE", path(&self.artifact_store)),
+            ("WASMEDGEUP_SHARED_CACHE", path(&self.shared_cache)),
+            ("WASMEDGEUP_PROXY", self.proxy.clone()),
+            ("WASMEDGEUP_PIN_CERTS", self.pinned_certs.as_ref().map(|pins| pins.join(","))),
+            ("WASMEDGEUP_GITHUB_TOKEN", self.github_token.clone()),
+            ("WASMEDGEUP_QUIET", flag(&self.quiet)),
+            ("WASMEDGEUP_VERBOSE", flag(&self.verbose)),
+            ("WASMEDGEUP_PROGRESS", self.progress.clone()),
+            ("WASMEDGEUP_YES", flag(&self.yes)),
+            ("WASMEDGEUP_STRICT", flag(&self.strict)),
+            ("WASMEDGEUP_ROSETTA", flag(&self.rosetta)),
+            ("WASMEDGEUP_CONFIRM_ABOVE", self.confirm_above.map(|mb| mb.to_string())),
+            ("WASMEDGEUP_MAX_EXTRACT_SIZE", self.max_extract_size.map(|mb| mb.to_string())),
+            ("WASMEDGEUP_MAX_EXTRACT_FILES", self.max_extract_files.map(|n| n.to_string())),
+            ("WASMEDGEUP_PLUGINS", self.plugins.as_ref().map(|plugins| plugins.join(","))),
+            ("WASMEDGEUP_CHECKSUM_FILE", path(&self.checksum_file)),
+        ];
+        values.into_iter().filter_map(|(name, value)| value.map(|value| (name, value))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_overrides_top_level_settings() {
+        let config = || -> Config { toml::from_str(r#"
+            path = "~/.wasmedge"
+            version = "0.14.0"
+            strict = true
+
+            [profiles.gpu-dev]
+            path = "~/wasmedge-gpu"
+            version = "0.14.1"
+            plugins = ["wasi_nn-ggml"]
+        "#).unwrap() };
+        let profile = config().with_profile("gpu-dev").unwrap();
+        assert_eq!(profile.path, Some(PathBuf::from("~/wasmedge-gpu")));
+        assert_eq!(profile.version.as_deref(), Some("0.14.1"));
+        assert_eq!(profile.strict, Some(true));
+        assert_eq!(profile.plugins, Some(vec!["wasi_nn-ggml".to_string()]));
+        assert!(config().with_profile("ci").unwrap_err().to_string().contains("gpu-dev"));
+    }
+
+    #[test]
+    fn test_checksum_file_is_relative_to_the_config_file() {
+        let config = Config {
+            file: PathBuf::from("/etc/wasmedgeup/config.toml"),
+            ..toml::from_str("checksum_file = \"wasmedgeup.sums\"").unwrap()
+        };
+        let defaults = config.defaults();
+        assert!(defaults.contains(&("WASMEDGEUP_CHECKSUM_FILE", "/etc/wasmedgeup/wasmedgeup.sums".to_string())));
+        assert!(Config::default().defaults().iter().all(|(name, _)| *name != "WASMEDGEUP_CHECKSUM_FILE"));
+    }
+}
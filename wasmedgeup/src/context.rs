@@ -0,0 +1,285 @@
+use anyhow::Result;
+use futures::StreamExt;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use crate::{
+    certpin::CertPins,
+    checksum::ChecksumFile,
+    cli::Cli,
+    config::Config,
+    downloader::{DownloadOptions, Downloader},
+    events::Events,
+    extract::ExtractLimits,
+    fsutil::{DryRunFs, FileSystem, RealFs},
+    installer::{Confirm, Installer},
+    output::{self, ProgressMode},
+    paths::expand_path,
+    platform::{self, Architecture, Platform},
+    plugin::PluginManager,
+    prompt,
+    proxy::ProxyConfig,
+    version::WasmEdgeVersion,
+};
+
+/// Everything the global flags decide, resolved once per run: the platform,
+/// the configured HTTP client, checksum pins and whether changes are real.
+/// Subsystems are created from here so every one of them sees the same
+/// settings. The platform, HTTP client and pins are only resolved when a
+/// command first needs them, so a problem with one of them fails just the
+/// commands that use it.
+pub struct WasmedgeupContext {
+    config: Config,
+    platform: OnceLock<Platform>,
+    downloader: OnceLock<Downloader>,
+    checksums: OnceLock<Option<ChecksumFile>>,
+    settings: Settings,
+    patch_server: Option<String>,
+    support_manifest: Option<String>,
+    fs: Arc<dyn FileSystem>,
+    dry_run: Option<Arc<DryRunFs>>,
+    assume_yes: bool,
+    strict: bool,
+    extract_limits: ExtractLimits,
+    events: Events,
+}
+
+/// The global flags the lazily resolved parts of the context are made from.
+struct Settings {
+    rosetta: bool,
+    checksum_file: Option<PathBuf>,
+    mirror: Option<String>,
+    github_token: Option<String>,
+    artifact_store: Option<PathBuf>,
+    shared_cache: Option<PathBuf>,
+    proxy: Option<String>,
+    pin_cert: Vec<String>,
+}
+
+impl WasmedgeupContext {
+    /// Context for `cli`, parsed with the defaults of `config`.
+    pub fn new(cli: &Cli, config: Config) -> Result<Self> {
+        let dry_run = cli.dry_run.then(|| Arc::new(DryRunFs::default()));
+        let fs: Arc<dyn FileSystem> = match &dry_run {
+            Some(recorder) => recorder.clone(),
+            None => Arc::new(RealFs),
+        };
+
+        Ok(Self {
+            config,
+            platform: OnceLock::new(),
+            downloader: OnceLock::new(),
+            checksums: OnceLock::new(),
+            settings: Settings {
+                rosetta: cli.rosetta,
+                checksum_file: cli.checksum_file.clone(),
+                mirror: cli.mirror.clone(),
+                github_token: cli.github_token.clone(),
+                artifact_store: cli.artifact_store.clone(),
+                shared_cache: cli.shared_cache.clone(),
+                proxy: cli.proxy.clone(),
+                pin_cert: cli.pin_cert.clone(),
+            },
+            patch_server: cli.patch_server.clone(),
+            support_manifest: cli.support_manifest.clone(),
+            fs,
+            dry_run,
+            assume_yes: cli.yes,
+            strict: cli.strict,
+            extract_limits: ExtractLimits::new(cli.max_extract_size, cli.max_extract_files),
+            events: match cli.progress {
+                ProgressMode::Json => Events::json_lines(),
+                ProgressMode::Bar => trace_events(),
+            },
+        })
+    }
+
+    /// The platform this machine installs for, detected on first use.
+    pub fn platform(&self) -> Result<&Platform> {
+        if let Some(platform) = self.platform.get() {
+            return Ok(platform);
+        }
+        let mut platform = Platform::detect()?;
+        if platform::under_rosetta() {
+            if self.settings.rosetta {
+                platform.arch = Architecture::X86_64;
+            } else {
+                log::warn!("{}", t!("rosetta-detected"));
+            }
+        }
+        Ok(self.platform.get_or_init(|| platform))
+    }
+
+    /// The HTTP client every download goes through, configured on first use.
+    pub fn downloader(&self) -> Result<&Downloader> {
+        if let Some(downloader) = self.downloader.get() {
+            return Ok(downloader);
+        }
+        let settings = &self.settings;
+        let downloader = Downloader::with_options(DownloadOptions {
+            mirror: settings.mirror.clone(),
+            github_token: settings.github_token.clone(),
+            artifact_store: settings.artifact_store.as_deref().map(expand_path).transpose()?,
+            shared_cache: settings.shared_cache.as_deref().map(expand_path).transpose()?,
+            proxy: ProxyConfig::resolve(settings.proxy.as_deref())?,
+            strict: self.strict,
+            cert_pins: CertPins::parse(&settings.pin_cert)?,
+        });
+        Ok(self.downloader.get_or_init(|| downloader))
+    }
+
+    /// Checksum pins downloads must match, loaded on first use.
+    fn checksums(&self) -> Result<Option<ChecksumFile>> {
+        if let Some(checksums) = self.checksums.get() {
+            return Ok(checksums.clone());
+        }
+        let checksums = self.settings.checksum_file.as_deref().map(|path| ChecksumFile::load(&expand_path(path)?)).transpose()?;
+        Ok(self.checksums.get_or_init(|| checksums).clone())
+    }
+
+    /// Installer for the detected platform.
+    pub fn installer(&self, install_path: PathBuf, temp_dir: PathBuf) -> Result<Installer> {
+        self.installer_for(self.platform()?.clone(), install_path, temp_dir)
+    }
+
+    /// Installer for what is already installed in `install_path`, e.g. to
+    /// remove it; it downloads and stages nothing.
+    pub fn installer_at(&self, install_path: PathBuf) -> Result<Installer> {
+        self.installer(install_path, std::env::temp_dir())
+    }
+
+    /// Installer for `platform`, e.g. from `--os`/`--arch` overrides.
+    pub fn installer_for(&self, platform: Platform, install_path: PathBuf, temp_dir: PathBuf) -> Result<Installer> {
+        self.installer_with(self.downloader()?.clone(), platform, install_path, temp_dir)
+    }
+
+    /// Installer taking release archives from `store`, e.g. where an earlier
+    /// `download` left them, instead of downloading them.
+    pub fn installer_from_store(&self, store: PathBuf, install_path: PathBuf, temp_dir: PathBuf) -> Result<Installer> {
+        self.installer_with(self.downloader()?.with_artifact_store(store), self.platform()?.clone(), install_path, temp_dir)
+    }
+
+    fn installer_with(&self, downloader: Downloader, platform: Platform, install_path: PathBuf, temp_dir: PathBuf) -> Result<Installer> {
+        Ok(Installer::with_fetcher(install_path, temp_dir, platform, downloader)
+            .with_checksums(self.checksums()?)
+            .with_patch_server(self.patch_server.clone())
+            .with_support_manifest(self.support_manifest.clone())
+            .with_fallback_confirmation(Some(self.confirm()))
+            .with_events(self.events.clone())
+            .with_strict(self.strict)
+            .with_extract_limits(self.extract_limits)
+            .with_filesystem(self.fs.clone()))
+    }
+
+    /// Plugin manager for the installation at `install_path`, with plugins
+    /// built for `runtime_version`.
+    pub fn plugin_manager(&self, install_path: PathBuf, runtime_version: WasmEdgeVersion) -> Result<PluginManager> {
+        self.plugin_manager_for(self.platform()?.clone(), install_path, runtime_version)
+    }
+
+    /// Plugin manager for the installation at `install_path`, with plugins
+    /// built for `platform` and `runtime_version`.
+    pub fn plugin_manager_for(&self, platform: Platform, install_path: PathBuf, runtime_version: WasmEdgeVersion) -> Result<PluginManager> {
+        Ok(PluginManager::with_fetcher(install_path, runtime_version, platform, self.downloader()?.clone())
+            .with_checksums(self.checksums()?)
+            .with_events(self.events.clone())
+            .with_strict(self.strict)
+            .with_extract_limits(self.extract_limits)
+            .with_filesystem(self.fs.clone()))
+    }
+
+    /// In `--strict` mode, refuse `latest`, ranges and anything else that is
+    /// not one exact release.
+    pub fn require_exact_version(&self, version: &str) -> Result<()> {
+        if self.strict && version.parse::<WasmEdgeVersion>().is_err() {
+            anyhow::bail!(t!("strict-inexact-version", version = version));
+        }
+        Ok(())
+    }
+
+    /// In `--strict` mode, refuse data that skips signature verification.
+    pub fn require_signed(&self, allow_unsigned: bool) -> Result<()> {
+        if self.strict && allow_unsigned {
+            anyhow::bail!(t!("strict-unsigned"));
+        }
+        Ok(())
+    }
+
+    /// In `--strict` mode, refuse to trust an artifact no pin or published
+    /// digest covers on first download.
+    pub fn require_checksum(&self, name: &str) -> Result<()> {
+        if self.strict {
+            anyhow::bail!(t!("strict-no-checksum", name = name));
+        }
+        Ok(())
+    }
+
+    /// Questions are answered yes with `--yes`, asked on a terminal and
+    /// answered no otherwise.
+    fn confirm(&self) -> Confirm {
+        let assume_yes = self.assume_yes;
+        Arc::new(move |question: &str| {
+            if assume_yes {
+                Ok(true)
+            } else if prompt::is_interactive() {
+                prompt::confirm(question, false)
+            } else {
+                Ok(false)
+            }
+        })
+    }
+
+    /// URL of the manifest listing supported release lines, if configured.
+    pub fn support_manifest(&self) -> Option<&str> {
+        self.support_manifest.as_deref()
+    }
+
+    pub fn fs(&self) -> &dyn FileSystem {
+        self.fs.as_ref()
+    }
+
+    /// The config file settings, with the selected profile applied.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// Print a success message. Skipped on dry runs, where it would be
+    /// misleading.
+    pub fn report(&self, message: String) {
+        if !self.is_dry_run() {
+            output::say(message);
+        }
+    }
+
+    /// On a dry run, the changes that were recorded instead of made.
+    pub fn dry_run_changes(&self) -> Option<Vec<String>> {
+        self.dry_run.as_ref().map(|recorder| recorder.operations())
+    }
+
+    /// On a dry run, print the changes that were recorded instead of made.
+    pub fn print_dry_run(&self) {
+        if let Some(recorder) = &self.dry_run {
+            println!("{}", t!("dry-run-header"));
+            for operation in recorder.operations() {
+                println!("  {}", operation);
+            }
+        }
+    }
+}
+
+/// Install events, logged at trace level.
+fn trace_events() -> Events {
+    if !log::log_enabled!(log::Level::Trace) {
+        return Events::default();
+    }
+    let (events, mut stream) = Events::channel();
+    tokio::spawn(async move {
+        while let Some(event) = stream.next().await {
+            log::trace!("{}", serde_json::to_string(&event).unwrap_or_default());
+        }
+    });
+    events
+}
@@ -0,0 +1,158 @@
+//! Patch upgrades of the runtime for slow links. A patch server publishes
+//! `zstd --patch-from` deltas between releases; applying one to the archive
+//! kept from the previous install rebuilds the new archive, which must then
+//! match the digest upstream publishes for it.
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use crate::{cache, checksum, downloader::Fetcher, release, version::WasmEdgeVersion};
+
+/// Largest window a patch may reference, as produced by `zstd --long=31`.
+const WINDOW_LOG_MAX: u32 = 31;
+
+/// Most bytes a patch may expand to when the release does not list the
+/// size of the archive it rebuilds; runtime archives are far smaller.
+const MAX_PATCHED_SIZE: u64 = 1024 * 1024 * 1024;
+
+fn asset_name(version: &str, package_name: &str) -> String {
+    format!("WasmEdge-{}-{}", version, package_name)
+}
+
+/// Directory in the cache holding the archive of the installed runtime, the
+/// base the next patch applies to.
+fn archive_dir() -> Result<PathBuf> {
+    Ok(cache::cache_dir()?.join("archives"))
+}
+
+/// URL of the patch from `from` to `to` for the archive `package_name`.
+pub fn patch_url(server: &str, from: &str, to: &str, package_name: &str) -> String {
+    format!("{}/{}/{}/{}.zst", server.trim_end_matches('/'), from, to, package_name)
+}
+
+/// Keep `archive`, the runtime archive for `version`, as the base for the
+/// next patch upgrade. Archives of earlier versions for the same package are
+/// removed.
+pub fn keep_archive(version: &str, package_name: &str, archive: &Path) -> Result<()> {
+    let dir = archive_dir()?;
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let name = asset_name(version, package_name);
+    let suffix = format!("-{}", package_name);
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name != name && file_name.ends_with(&suffix) {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    std::fs::copy(archive, dir.join(&name))
+        .with_context(|| format!("Failed to keep {} in {}", name, dir.display()))?;
+    Ok(())
+}
+
+/// Rebuild `dest` from the archive `base` and the zstd patch `patch`. A
+/// patch expanding to more than `max_size` bytes is refused before more than
+/// that is written.
+pub fn apply_patch(base: &Path, patch: &Path, dest: &Path, max_size: u64) -> Result<()> {
+    let base_file = File::open(base).with_context(|| format!("Failed to open {}", base.display()))?;
+    // The patch may reference any part of the base, but mapping it pages in
+    // only what it does, instead of reading the whole archive into memory
+    // SAFETY: the map only lives while the patch is applied, and kept
+    // archives are only written after patching is done
+    let dictionary = unsafe { memmap2::Mmap::map(&base_file) }
+        .with_context(|| format!("Failed to read {}", base.display()))?;
+    let reader = BufReader::new(
+        File::open(patch).with_context(|| format!("Failed to open {}", patch.display()))?,
+    );
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(reader, &dictionary)?;
+    decoder.window_log_max(WINDOW_LOG_MAX)?;
+    let mut out = File::create(dest)
+        .with_context(|| format!("Failed to create {}", dest.display()))?;
+    let written = io::copy(&mut decoder.take(max_size + 1), &mut out)
+        .with_context(|| format!("Failed to apply patch {}", patch.display()))?;
+    if written > max_size {
+        anyhow::bail!("Patch {} expands beyond {} bytes", patch.display(), max_size);
+    }
+    Ok(())
+}
+
+/// Build the archive `package_name` of WasmEdge `to` at `dest` by patching
+/// the archive kept from `from`. Returns `false` without fetching anything
+/// when no archive of `from` is kept. A rebuilt archive that does not match
+/// the upstream digest, or a release without digests, is an error: the
+/// caller then downloads the archive in full.
+pub async fn fetch_patched(
+    fetcher: &impl Fetcher,
+    server: &str,
+    from: &str,
+    to: &WasmEdgeVersion,
+    package_name: &str,
+    dest: &Path,
+) -> Result<bool> {
+    let base = archive_dir()?.join(asset_name(from, package_name));
+    if !base.is_file() {
+        debug!("No archive of WasmEdge {} kept at {}", from, base.display());
+        return Ok(false);
+    }
+
+    let asset = to.asset_name(package_name);
+    let release = release::fetch_release(fetcher, to).await?;
+    let max_size = release
+        .assets
+        .iter()
+        .find(|a| a.name == asset && a.size > 0)
+        .map_or(MAX_PATCHED_SIZE, |a| a.size);
+    let expected = release::fetch_release_checksums(fetcher, &release)
+        .await?
+        .and_then(|sums| sums.get(&asset).map(str::to_string))
+        .with_context(|| format!("WasmEdge {} publishes no digest for {} to check a patched archive against", to, asset))?;
+
+    let url = patch_url(server, from, &to.tag(), package_name);
+    info!("Fetching patch from {}", url);
+    let patch = dest.with_extension("zst");
+    fetcher.download_file(&url, &patch).await?;
+    apply_patch(&base, &patch, dest, max_size)?;
+    checksum::verify_checksum(dest, &expected)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_apply_patch_rebuilds_new_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let old: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut new = old.clone();
+        new[1000..1010].copy_from_slice(b"0.14.1-new");
+        new.extend_from_slice(b"appended");
+
+        let mut encoder = zstd::stream::write::Encoder::with_dictionary(Vec::new(), 19, &old).unwrap();
+        encoder.write_all(&new).unwrap();
+        let patch = encoder.finish().unwrap();
+        assert!(patch.len() < new.len() / 10);
+
+        let base = dir.path().join("old.tar.gz");
+        let patch_path = dir.path().join("new.tar.zst");
+        let dest = dir.path().join("new.tar.gz");
+        std::fs::write(&base, &old).unwrap();
+        std::fs::write(&patch_path, &patch).unwrap();
+        apply_patch(&base, &patch_path, &dest, new.len() as u64).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), new);
+
+        // A patch expanding past the expected size stops there
+        let error = apply_patch(&base, &patch_path, &dest, new.len() as u64 - 1).unwrap_err();
+        assert!(error.to_string().contains("expands beyond"));
+        assert_eq!(std::fs::metadata(&dest).unwrap().len(), new.len() as u64);
+
+        assert_eq!(
+            patch_url("https://patches.example.com/", "0.14.0", "0.14.1", "manylinux2014_x86_64.tar.gz"),
+            "https://patches.example.com/0.14.0/0.14.1/manylinux2014_x86_64.tar.gz.zst"
+        );
+    }
+}
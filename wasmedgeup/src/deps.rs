@@ -0,0 +1,347 @@
+//! Dependency trees of installed plugins for `plugin deps tree`: the shared
+//! libraries each plugin library links against, resolved the way the
+//! dynamic loader would.
+
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use crate::platform::OS;
+
+/// A node of a dependency tree.
+#[derive(Debug, Clone, Serialize)]
+pub struct DepNode {
+    pub name: String,
+    /// `plugin`, `archive`, `file` or `library`
+    pub kind: &'static str,
+    pub satisfied: bool,
+    /// Where the dependency resolved to, or why it did not
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DepNode>,
+}
+
+impl DepNode {
+    pub fn new(name: impl Into<String>, kind: &'static str, satisfied: bool) -> Self {
+        Self { name: name.into(), kind, satisfied, detail: None, children: Vec::new() }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Whether this node and everything below it resolved.
+    pub fn is_satisfied(&self) -> bool {
+        self.satisfied && self.children.iter().all(DepNode::is_satisfied)
+    }
+
+    /// Nodes below this one that did not resolve.
+    pub fn missing(&self) -> usize {
+        self.children.iter().map(|c| usize::from(!c.satisfied) + c.missing()).sum()
+    }
+
+    /// The tree as indented lines with ✓/✗ markers.
+    pub fn render(&self) -> String {
+        let mut out = format!("{} {}\n", marker(self.satisfied), self.label());
+        render_children(&self.children, "", &mut out);
+        out
+    }
+
+    fn label(&self) -> String {
+        match &self.detail {
+            Some(detail) => format!("{} ({})", self.name, detail),
+            None => self.name.clone(),
+        }
+    }
+}
+
+fn marker(satisfied: bool) -> &'static str {
+    if satisfied { "✓" } else { "✗" }
+}
+
+fn render_children(children: &[DepNode], prefix: &str, out: &mut String) {
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        out.push_str(&format!("{}{} {} {}\n", prefix, if last { "└──" } else { "├──" }, marker(child.satisfied), child.label()));
+        render_children(&child.children, &format!("{}{}", prefix, if last { "    " } else { "│   " }), out);
+    }
+}
+
+/// Shared libraries `library` links against, resolved the way the runtime
+/// would with `env` set. `None` when they cannot be read on this OS.
+///
+/// On Linux the ELF dynamic section is read directly rather than through
+/// `ldd`, which may run code from the library it inspects; only the
+/// libraries it names itself are listed.
+pub fn shared_libraries(library: &Path, os: &OS, env: &[(String, OsString)], lib_dir: &Path) -> Option<Vec<DepNode>> {
+    match os {
+        OS::Linux(_) => {
+            let data = std::fs::read(library)
+                .map_err(|e| log::warn!("Could not read {}: {}", library.display(), e))
+                .ok()?;
+            let Some(dynamic) = ElfDynamic::parse(&data) else {
+                log::warn!("{} is not a dynamically linked ELF library", library.display());
+                return None;
+            };
+            let library_path: Vec<PathBuf> = env
+                .iter()
+                .find(|(name, _)| name == os.library_path_var())
+                .map(|(_, value)| std::env::split_paths(value).collect())
+                .unwrap_or_default();
+            Some(dynamic.resolve(library, &library_path, &system_lib_dirs()))
+        }
+        OS::Darwin => {
+            let output = std::process::Command::new("otool")
+                .arg("-L")
+                .arg(library)
+                .output()
+                .map_err(|e| log::warn!("Could not run otool on {}: {}", library.display(), e))
+                .ok()?;
+            Some(parse_otool(&String::from_utf8_lossy(&output.stdout), lib_dir))
+        }
+        _ => None,
+    }
+}
+
+/// What the dynamic section of an ELF object asks of the loader.
+#[derive(Debug, Default, PartialEq)]
+pub struct ElfDynamic {
+    /// `DT_NEEDED` entries, in order
+    pub needed: Vec<String>,
+    /// `DT_RUNPATH`, or `DT_RPATH` when there is none, split on `:`
+    pub runpath: Vec<String>,
+}
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+const DT_RPATH: u64 = 15;
+const DT_RUNPATH: u64 = 29;
+
+impl ElfDynamic {
+    /// Read the dynamic section of the ELF object in `data`. `None` for
+    /// anything else, and for static or malformed objects.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != b"\x7fELF" {
+            return None;
+        }
+        let wide = match data.get(4)? {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let big_endian = *data.get(5)? == 2;
+        let bytes = |offset: usize, len: usize| -> Option<u64> {
+            let raw = data.get(offset..offset.checked_add(len)?)?;
+            let mut buf = [0u8; 8];
+            if big_endian {
+                buf[8 - len..].copy_from_slice(raw);
+                Some(u64::from_be_bytes(buf))
+            } else {
+                buf[..len].copy_from_slice(raw);
+                Some(u64::from_le_bytes(buf))
+            }
+        };
+        let word = if wide { 8 } else { 4 };
+        let at = |offset: u64| usize::try_from(offset).ok();
+
+        // Program headers: where each segment is loaded, and the dynamic one
+        let (phoff, phentsize, phnum) = if wide {
+            (bytes(0x20, 8)?, bytes(0x36, 2)?, bytes(0x38, 2)?)
+        } else {
+            (bytes(0x1c, 4)?, bytes(0x2a, 2)?, bytes(0x2c, 2)?)
+        };
+        let mut loads = Vec::new();
+        let mut dynamic = None;
+        for i in 0..phnum {
+            let header = at(phoff.checked_add(i.checked_mul(phentsize)?)?)?;
+            let p_type = bytes(header, 4)? as u32;
+            let (offset, vaddr, filesz) = if wide {
+                (bytes(header + 8, 8)?, bytes(header + 16, 8)?, bytes(header + 32, 8)?)
+            } else {
+                (bytes(header + 4, 4)?, bytes(header + 8, 4)?, bytes(header + 16, 4)?)
+            };
+            match p_type {
+                PT_LOAD => loads.push((offset, vaddr, filesz)),
+                PT_DYNAMIC => dynamic = Some((offset, filesz)),
+                _ => {}
+            }
+        }
+        let (dynamic_offset, dynamic_size) = dynamic?;
+
+        let mut entries = Vec::new();
+        let mut strtab = None;
+        let mut entry = at(dynamic_offset)?;
+        let end = entry.checked_add(at(dynamic_size)?)?;
+        while entry + 2 * word <= end {
+            let (tag, value) = (bytes(entry, word)?, bytes(entry + word, word)?);
+            match tag {
+                0 => break,
+                DT_STRTAB => strtab = Some(value),
+                DT_NEEDED | DT_RPATH | DT_RUNPATH => entries.push((tag, value)),
+                _ => {}
+            }
+            entry += 2 * word;
+        }
+
+        // The string table is given as a load address; find it in the file
+        let strtab = strtab?;
+        let (offset, vaddr, _) = loads.iter().find(|(_, vaddr, filesz)| (*vaddr..vaddr.saturating_add(*filesz)).contains(&strtab))?;
+        let strtab = at(strtab - vaddr + offset)?;
+        let string = |index: u64| -> Option<String> {
+            let start = strtab.checked_add(at(index)?)?;
+            let len = data.get(start..)?.iter().position(|&b| b == 0)?;
+            Some(String::from_utf8_lossy(&data[start..start + len]).into_owned())
+        };
+
+        let mut parsed = Self::default();
+        let mut rpath = Vec::new();
+        for (tag, value) in entries {
+            let value = string(value)?;
+            match tag {
+                DT_NEEDED => parsed.needed.push(value),
+                DT_RUNPATH => parsed.runpath.extend(value.split(':').map(str::to_string)),
+                _ => rpath.extend(value.split(':').map(str::to_string)),
+            }
+        }
+        if parsed.runpath.is_empty() {
+            parsed.runpath = rpath;
+        }
+        Some(parsed)
+    }
+
+    /// Look each needed library up like the loader: the object's run path
+    /// (with `$ORIGIN` being its directory), then `library_path`, then the
+    /// system directories.
+    pub fn resolve(&self, object: &Path, library_path: &[PathBuf], system: &[PathBuf]) -> Vec<DepNode> {
+        let origin = object.parent().unwrap_or(Path::new("."));
+        let runpath: Vec<PathBuf> = self
+            .runpath
+            .iter()
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(dir.replace("${ORIGIN}", "$ORIGIN").replace("$ORIGIN", &origin.to_string_lossy())))
+            .collect();
+        self.needed
+            .iter()
+            .map(|name| {
+                let found = if name.contains('/') {
+                    Some(PathBuf::from(name)).filter(|path| path.exists())
+                } else {
+                    runpath.iter().chain(library_path).chain(system).map(|dir| dir.join(name)).find(|path| path.exists())
+                };
+                match found {
+                    Some(path) => DepNode::new(name, "library", true).with_detail(path.display().to_string()),
+                    None => DepNode::new(name, "library", false).with_detail("not found"),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Directories the Linux loader searches after the library path: those
+/// `/etc/ld.so.conf.d` adds, then the defaults.
+fn system_lib_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir("/etc/ld.so.conf.d")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+        .flat_map(|conf| conf.lines().map(str::trim).filter(|line| line.starts_with('/')).map(PathBuf::from).collect::<Vec<_>>())
+        .collect();
+    let multiarch = format!("{}-linux-gnu", std::env::consts::ARCH);
+    for dir in ["/lib", "/usr/lib"] {
+        dirs.push(Path::new(dir).join(&multiarch));
+    }
+    dirs.extend(["/lib64", "/usr/lib64", "/lib", "/usr/lib"].map(PathBuf::from));
+    dirs
+}
+
+/// Parse `otool -L` output, whose first line names the library itself.
+/// `otool` does not resolve anything, so system paths are taken as present
+/// and `@rpath` references are looked up in `lib_dir`.
+pub fn parse_otool(output: &str, lib_dir: &Path) -> Vec<DepNode> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.trim().split(" (compatibility").next())
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            let resolved = match path.strip_prefix("@rpath/").or_else(|| path.strip_prefix("@loader_path/")) {
+                Some(rest) => lib_dir.join(rest),
+                None => Path::new(path).to_path_buf(),
+            };
+            // The dyld shared cache holds system libraries with no file on disk
+            let system = path.starts_with("/usr/lib/") || path.starts_with("/System/");
+            let name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.to_string());
+            if system || resolved.exists() {
+                DepNode::new(name, "library", true).with_detail(resolved.display().to_string())
+            } else {
+                DepNode::new(name, "library", false).with_detail("not found")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_tree_renders_with_markers() {
+        let libraries = vec![
+            DepNode::new("libggml.so", "library", false).with_detail("not found"),
+            DepNode::new("libstdc++.so.6", "library", true).with_detail("/lib/x86_64-linux-gnu/libstdc++.so.6"),
+        ];
+        let mut file = DepNode::new("libwasmedgePluginWasiNN.so", "file", true);
+        file.children = libraries;
+        let mut root = DepNode::new("wasi_nn-ggml", "plugin", true).with_detail("0.14.1");
+        root.children = vec![DepNode::new("libwasmedgePluginWasiLogging.so", "file", true), file];
+        assert!(!root.is_satisfied());
+        assert_eq!(root.missing(), 1);
+        assert_eq!(
+            root.render(),
+            "✓ wasi_nn-ggml (0.14.1)\n\
+             ├── ✓ libwasmedgePluginWasiLogging.so\n\
+             └── ✓ libwasmedgePluginWasiNN.so\n    \
+                 ├── ✗ libggml.so (not found)\n    \
+                 └── ✓ libstdc++.so.6 (/lib/x86_64-linux-gnu/libstdc++.so.6)\n"
+        );
+
+        let otool = "/p/libwasmedgePluginWasiNN.dylib:\n\
+            \t@rpath/libwasmedge.0.dylib (compatibility version 0.0.0, current version 0.0.0)\n\
+            \t/usr/lib/libc++.1.dylib (compatibility version 1.0.0, current version 1700.255.0)\n";
+        let libraries = parse_otool(otool, Path::new("/nonexistent/lib"));
+        assert_eq!(libraries.iter().map(|l| l.satisfied).collect::<Vec<_>>(), [false, true]);
+        assert_eq!(libraries[0].name, "libwasmedge.0.dylib");
+    }
+
+    #[test]
+    fn test_needed_libraries_resolve_through_the_run_path_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let (plugin_dir, lib_dir) = (dir.path().join("plugin"), dir.path().join("lib"));
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        std::fs::create_dir_all(&lib_dir).unwrap();
+        std::fs::write(plugin_dir.join("libggml.so"), "").unwrap();
+        std::fs::write(lib_dir.join("libwasmedge.so.0"), "").unwrap();
+        let dynamic = ElfDynamic {
+            needed: vec!["libggml.so".into(), "libwasmedge.so.0".into(), "libcuda.so.1".into()],
+            runpath: vec!["$ORIGIN".into()],
+        };
+
+        let libraries = dynamic.resolve(&plugin_dir.join("libwasmedgePluginWasiNN.so"), std::slice::from_ref(&lib_dir), &[]);
+        assert_eq!(libraries.iter().map(|l| l.satisfied).collect::<Vec<_>>(), [true, true, false]);
+        assert_eq!(libraries[0].detail.as_deref(), Some(plugin_dir.join("libggml.so").display().to_string().as_str()));
+        assert_eq!(libraries[1].detail.as_deref(), Some(lib_dir.join("libwasmedge.so.0").display().to_string().as_str()));
+
+        assert_eq!(ElfDynamic::parse(b"#!/bin/sh\n"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_dynamic_section_of_a_real_executable() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let dynamic = ElfDynamic::parse(&data).unwrap();
+        assert!(dynamic.needed.iter().any(|name| name.starts_with("libc.so")), "{:?}", dynamic.needed);
+    }
+}
@@ -0,0 +1,152 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use crate::{buildinfo::BuildInfo, downloader::Fetcher, installer, lsm, plugin, release::SupportStatus, shadow, support};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Warn,
+    Error,
+}
+
+/// Outcome of one `doctor` check.
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub check: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Finding {
+    fn new(check: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self { check, status, detail: detail.into() }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Error => "error",
+        };
+        write!(f, "[{}] {}: {}", status, self.check, self.detail)
+    }
+}
+
+/// Diagnose the installation at `install_path` and the system around it.
+pub fn run(install_path: &Path) -> Vec<Finding> {
+    let mut findings = vec![Finding::new("wasmedgeup", Status::Ok, BuildInfo::get().summary())];
+    match installer::installed_version(install_path) {
+        Some(version) => findings.push(Finding::new(
+            "runtime",
+            Status::Ok,
+            format!("WasmEdge {} in {}", version, install_path.display()),
+        )),
+        None => findings.push(Finding::new(
+            "runtime",
+            Status::Error,
+            format!("No WasmEdge installation in {}", install_path.display()),
+        )),
+    }
+    findings.push(check_path(install_path));
+    findings.extend(check_plugins(install_path));
+    if cfg!(target_os = "linux") {
+        findings.extend(check_lsm(install_path));
+    }
+    findings
+}
+
+/// Whether a new login shell runs the managed `wasmedge` or another one.
+fn check_path(install_path: &Path) -> Finding {
+    let Some(path) = shadow::login_path() else {
+        return Finding::new("path", Status::Warn, "PATH is not set");
+    };
+    match shadow::check(install_path, &path) {
+        Some(shadow) => Finding::new("path", Status::Warn, shadow.advice(install_path)),
+        None if shadow::on_path(&install_path.join("bin"), &path) => {
+            Finding::new("path", Status::Ok, format!("wasmedge resolves to {}", install_path.join("bin").display()))
+        }
+        None => Finding::new("path", Status::Warn, t!("path-not-on-path", bin = install_path.join("bin").display(),
+            env = install_path.join("env").display())),
+    }
+}
+
+/// Plugin libraries in the plugin directory of `install_path` that no
+/// receipt records.
+fn check_plugins(install_path: &Path) -> Vec<Finding> {
+    let receipts = match plugin::installed_plugins(install_path) {
+        Ok(receipts) => receipts,
+        Err(e) => return vec![Finding::new("plugins", Status::Error, format!("{:#}", e))],
+    };
+    let plugin_dir = plugin::plugin_dir(install_path);
+    let orphans = receipts.orphans(&plugin_dir);
+    if orphans.is_empty() {
+        return vec![Finding::new("plugins", Status::Ok, format!("{} installed", receipts.plugins.len()))];
+    }
+    let mut findings: Vec<Finding> = orphans
+        .iter()
+        .map(|path| Finding::new("plugins", Status::Warn, t!("plugin-orphan", path = path.display())))
+        .collect();
+    findings.push(Finding::new("plugins", Status::Warn, t!("plugin-orphans-hint")));
+    findings
+}
+
+/// Whether upstream still supports the installed version and whether
+/// security advisories affect it. Nothing is reported when the version or
+/// the upstream lists are unavailable.
+pub async fn check_support(fetcher: &impl Fetcher, manifest_url: Option<&str>, install_path: &Path) -> Vec<Finding> {
+    let Some(version) = installer::installed_version(install_path) else {
+        return Vec::new();
+    };
+    let support = match support::check(fetcher, manifest_url, &version).await {
+        Ok(support) => support,
+        Err(e) => return vec![Finding::new("support", Status::Warn, format!("Could not check upstream support: {:#}", e))],
+    };
+    let warnings = support.warnings();
+    if warnings.is_empty() {
+        let status = support.status.map_or("not listed in a support manifest", SupportStatus::as_str);
+        return vec![Finding::new("support", Status::Ok, format!("WasmEdge {} is {}", version, status))];
+    }
+    warnings.into_iter().map(|warning| Finding::new("support", Status::Warn, warning)).collect()
+}
+
+/// SELinux and AppArmor state, the label of the managed libraries and
+/// denials that involved WasmEdge.
+fn check_lsm(install_path: &Path) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let lib = install_path.join("lib");
+    match lsm::selinux_mode() {
+        lsm::SelinuxMode::Disabled => findings.push(Finding::new("selinux", Status::Ok, "disabled")),
+        lsm::SelinuxMode::Permissive => findings.push(Finding::new("selinux", Status::Ok, "permissive")),
+        lsm::SelinuxMode::Enforcing => {
+            let context = lsm::context_of(&lib);
+            if lsm::is_labelled_prefix(install_path) || !lib.exists() {
+                findings.push(Finding::new("selinux", Status::Ok, "enforcing"));
+            } else {
+                findings.push(Finding::new(
+                    "selinux",
+                    Status::Warn,
+                    format!(
+                        "enforcing, and {} is labelled {}; libwasmedge may be blocked from loading. Reinstall with --selinux-relabel",
+                        lib.display(),
+                        context.as_deref().unwrap_or("with an unknown context")
+                    ),
+                ));
+            }
+        }
+    }
+    let apparmor = if lsm::apparmor_enabled() { "enabled" } else { "disabled" };
+    findings.push(Finding::new("apparmor", Status::Ok, apparmor));
+
+    let denials = lsm::denials();
+    if denials.is_empty() {
+        findings.push(Finding::new("denials", Status::Ok, "none involving WasmEdge found in the audit log"));
+    }
+    for denial in denials {
+        findings.push(Finding::new("denials", Status::Error, denial));
+    }
+    findings
+}
@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use log::warn;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use crate::{checksum, downloader::Fetcher};
+
+/// Length of the gzip trailer: the CRC-32, then ISIZE, the unpacked size.
+const GZIP_TRAILER_LEN: u64 = 8;
+
+/// An artifact saved by `wasmedgeup download`.
+#[derive(Debug, Serialize)]
+pub struct Downloaded {
+    pub name: String,
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+/// Check the downloaded artifact `staged` against the digest upstream
+/// publishes for it and move it into `output_dir` as `name`. An artifact
+/// upstream publishes no digest for is kept with a warning.
+pub fn keep(staged: &Path, output_dir: &Path, name: &str, expected: Option<&str>) -> Result<Downloaded> {
+    match expected {
+        Some(expected) => checksum::verify_checksum(staged, expected)?,
+        None => warn!("{}", t!("download-unverified", name = name)),
+    }
+    let sha256 = checksum::sha256_file(staged)?;
+    let path = output_dir.join(name);
+    std::fs::rename(staged, &path)
+        .with_context(|| format!("Failed to move {} to {}", name, output_dir.display()))?;
+    Ok(Downloaded { name: name.to_string(), path, sha256 })
+}
+
+/// Size of a release archive, downloaded and unpacked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArchiveSize {
+    pub download: u64,
+    /// Bytes the archive unpacks to, when the archive records it
+    pub unpacked: Option<u64>,
+}
+
+/// Size of the archive at `url`: `known` when the release listing gives it,
+/// otherwise what the server reports. The unpacked size of a gzipped
+/// archive is read from its trailer. `None` when the server reports none.
+pub async fn archive_size(fetcher: &impl Fetcher, url: &str, known: Option<u64>) -> Result<Option<ArchiveSize>> {
+    let download = match known {
+        Some(size) => size,
+        None => match fetcher.head(url).await? {
+            Some(size) => size,
+            None => return Ok(None),
+        },
+    };
+    let unpacked = if url.ends_with(".gz") || url.ends_with(".tgz") {
+        match fetcher.tail(url, GZIP_TRAILER_LEN).await {
+            Ok(trailer) => trailer.and_then(|trailer| gzip_unpacked_size(&trailer, download)),
+            Err(e) => {
+                log::debug!("Could not read the gzip trailer of {}: {}", url, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    Ok(Some(ArchiveSize { download, unpacked }))
+}
+
+/// Unpacked size a gzip `trailer` records for a stream of `download` bytes.
+/// ISIZE holds the size modulo 4 GiB, so one smaller than the download has
+/// wrapped around and is not used.
+fn gzip_unpacked_size(trailer: &[u8], download: u64) -> Option<u64> {
+    let recorded: [u8; 4] = trailer.get(trailer.len().checked_sub(4)?..)?.try_into().ok()?;
+    Some(u64::from(u32::from_le_bytes(recorded))).filter(|&unpacked| unpacked >= download)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_moves_only_matching_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let staged = dir.path().join("staged");
+        let output = dir.path().join("artifacts");
+        std::fs::create_dir(&output).unwrap();
+        std::fs::write(&staged, b"archive").unwrap();
+        let digest = checksum::sha256_file(&staged).unwrap();
+
+        assert!(keep(&staged, &output, "a.tar.gz", Some(&"0".repeat(64))).is_err());
+        assert!(!output.join("a.tar.gz").exists());
+
+        let kept = keep(&staged, &output, "a.tar.gz", Some(&digest)).unwrap();
+        assert_eq!(kept.path, output.join("a.tar.gz"));
+        assert_eq!(kept.sha256, digest);
+        assert!(!staged.exists());
+    }
+
+    #[tokio::test]
+    async fn test_archive_size_reads_the_gzip_trailer() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0; 100_000]).unwrap();
+        let archive = encoder.finish().unwrap();
+        let url = "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/a.tar.gz";
+        let fetcher = crate::downloader::fake::FakeFetcher::default().with(url, archive.clone());
+
+        let size = archive_size(&fetcher, url, None).await.unwrap().unwrap();
+        assert_eq!(size, ArchiveSize { download: archive.len() as u64, unpacked: Some(100_000) });
+        assert_eq!(archive_size(&fetcher, url, Some(5)).await.unwrap().unwrap().download, 5);
+        // A size that wrapped around 4 GiB is not trusted
+        assert_eq!(gzip_unpacked_size(&10u32.to_le_bytes(), 1000), None);
+    }
+}
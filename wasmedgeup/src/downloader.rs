@@ -1,40 +1,351 @@
 use anyhow::{Context, Result};
 use futures_util::StreamExt;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::Client;
-use std::path::Path;
+use indicatif::ProgressStyle;
+use log::{debug, warn};
+use reqwest::header::{HeaderValue, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, LINK, LOCATION, RANGE};
+use reqwest::redirect::Policy;
+use reqwest::{Client, StatusCode};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Semaphore;
+use crate::{cache, certpin::CertPins, output, proxy::ProxyConfig, shared_cache::SharedCache};
 
+const GITHUB_URL: &str = "https://github.com";
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Times a download reconnects in a row after its connection dropped
+/// mid-transfer, before giving up.
+const MAX_RESUMES: u32 = 5;
+
+/// Wait before the first reconnect, growing with each further one.
+const RESUME_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Redirects followed per request, as many as reqwest follows by default.
+const MAX_REDIRECTS: usize = 10;
+
+/// Reconnects that may be in flight at once. A CDN hiccup tends to drop
+/// every parallel plugin download together; they resume a few at a time
+/// instead of all reconnecting at once.
+static RESUME_SLOTS: Semaphore = Semaphore::const_new(2);
+
+/// Leading bytes of the archive formats we download, by file name suffix.
+const ARCHIVE_MAGIC: &[(&str, &[u8])] = &[
+    (".tar.gz", b"\x1f\x8b"),
+    (".tgz", b"\x1f\x8b"),
+    (".zip", b"PK\x03\x04"),
+    (".zst", b"\x28\xb5\x2f\xfd"),
+];
+
+/// Download settings coming from global flags.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Base URL replacing `https://github.com` for release artifacts
+    pub mirror: Option<String>,
+    /// Token sent with GitHub API requests
+    pub github_token: Option<String>,
+    /// Directory to take artifacts from instead of downloading them;
+    /// metadata is still fetched online
+    pub artifact_store: Option<PathBuf>,
+    /// Group-writable directory caching downloaded artifacts for every user
+    /// and machine that mounts it
+    pub shared_cache: Option<PathBuf>,
+    /// How requests find their proxy
+    pub proxy: ProxyConfig,
+    /// Fail instead of serving cached metadata while GitHub is unreachable
+    pub strict: bool,
+    /// Public keys the certificates of release hosts must carry
+    pub cert_pins: CertPins,
+}
+
+/// Callback receiving the bytes downloaded so far and the total size.
+pub type Progress<'a> = dyn Fn(u64, Option<u64>) + Send + Sync + 'a;
+
+/// Network access used by [`Installer`](crate::installer::Installer) and
+/// [`PluginManager`](crate::plugin::PluginManager). [`Downloader`] is the
+/// reqwest based implementation; tests supply their own.
+pub trait Fetcher: Send + Sync {
+    /// Save the resource at `url` to `dest`.
+    fn download_file(&self, url: &str, dest: &Path) -> impl Future<Output = Result<()>> + Send;
+
+    /// Like [`download_file`](Self::download_file), calling `progress` with
+    /// the bytes received so far and the total size, when known.
+    fn download_file_with_progress(&self, url: &str, dest: &Path, progress: &Progress<'_>) -> impl Future<Output = Result<()>> + Send {
+        let _ = progress;
+        self.download_file(url, dest)
+    }
+
+    /// Fetch the resource at `url` as text.
+    fn download_text(&self, url: &str) -> impl Future<Output = Result<String>> + Send;
+
+    /// Fetch one page of a paginated API at `url`, along with the next
+    /// page its `Link` header points to.
+    fn download_page(&self, url: &str) -> impl Future<Output = Result<Page>> + Send {
+        async move {
+            Ok(Page {
+                body: self.download_text(url).await?,
+                next: None,
+            })
+        }
+    }
+
+    /// Size in bytes of the resource at `url`, if the server reports it.
+    fn head(&self, url: &str) -> impl Future<Output = Result<Option<u64>>> + Send;
+
+    /// The last `len` bytes of the resource at `url`, if the server serves
+    /// byte ranges.
+    fn tail(&self, url: &str, len: u64) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send {
+        let _ = (url, len);
+        async { Ok(None) }
+    }
+
+    /// Fetch and deserialize the JSON document at `url`.
+    fn download_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> impl Future<Output = Result<T>> + Send {
+        async move {
+            let text = self.download_text(url).await?;
+            serde_json::from_str(&text).with_context(|| format!("Invalid JSON from {}", url))
+        }
+    }
+}
+
+/// One page of a paginated API response.
+#[derive(Clone)]
+pub struct Page {
+    pub body: String,
+    /// URL of the following page, `None` on the last one.
+    pub next: Option<String>,
+}
+
+/// Target of the `rel="next"` link in a `Link` header, e.g.
+/// `<https://api.github.com/...&page=2>; rel="next", <...>; rel="last"`.
+fn next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|entry| {
+        let (target, params) = entry.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// Key the next page link of `url` is cached under, next to its body.
+fn next_page_key(url: &str) -> String {
+    format!("{} rel=next", url)
+}
+
+/// Bytes of a body [`check_body`] judges it by. Servers may send fewer in
+/// the first chunk, so downloads collect this many first.
+const BODY_CHECK_LEN: usize = 512;
+
+fn is_html(content_type: Option<&str>, head: &[u8]) -> bool {
+    if content_type.is_some_and(|ct| ct.trim_start().to_ascii_lowercase().starts_with("text/html")) {
+        return true;
+    }
+    let start = String::from_utf8_lossy(&head[..head.len().min(256)])
+        .trim_start()
+        .to_ascii_lowercase();
+    ["<!doctype html", "<html", "<head", "<body"]
+        .iter()
+        .any(|tag| start.starts_with(tag))
+}
+
+/// Reject a response that cannot be what `url` serves, judged by its content
+/// type and first bytes: an HTML page, which proxies and captive portals
+/// return with status 200, or an archive in the wrong format.
+pub fn check_body(url: &str, content_type: Option<&str>, head: &[u8]) -> Result<()> {
+    if is_html(content_type, head) {
+        anyhow::bail!(t!("received-html", url = url));
+    }
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    if let Some((suffix, magic)) = ARCHIVE_MAGIC.iter().find(|(suffix, _)| path.ends_with(suffix)) {
+        if !head.starts_with(magic) {
+            anyhow::bail!(t!("unexpected-archive", url = url, format = suffix.trim_start_matches('.')));
+        }
+    }
+    Ok(())
+}
+
+/// Format a byte count for people, e.g. `312.4 MB`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Clones share one HTTP client, and with it one connection pool: a batch of
+/// plugin installs reuses connections (and their TLS sessions) to GitHub and
+/// talks HTTP/2 where the server offers it.
+#[derive(Clone)]
 pub struct Downloader {
     client: Client,
+    options: Arc<DownloadOptions>,
+    /// Pages of paginated API responses fetched in this run, so listing the
+    /// releases again, e.g. to resolve `latest` for each step of a command,
+    /// makes no further requests.
+    pages: Arc<Mutex<HashMap<String, Page>>>,
+    /// When the oldest cached response served in place of an unreachable
+    /// release catalog was fetched
+    stale_since: Arc<Mutex<Option<SystemTime>>>,
 }
 
 impl Downloader {
+    #[cfg(test)]
     pub fn new() -> Self {
+        Self::with_options(DownloadOptions::default())
+    }
+
+    pub fn with_options(options: DownloadOptions) -> Self {
         Self {
-            client: Client::builder()
-                .user_agent("wasmedgeup")
+            client: options
+                .proxy
+                .apply(Client::builder().user_agent("wasmedgeup").pool_max_idle_per_host(8).gzip(true))
+                .tls_info(!options.cert_pins.is_empty())
+                // Pinned downloads follow redirects in `send`, checking each hop
+                .redirect(if options.cert_pins.is_empty() { Policy::default() } else { Policy::none() })
                 .build()
-                .unwrap(),
+                .expect("Failed to initialize the HTTP client"),
+            options: Arc::new(options),
+            pages: Arc::default(),
+            stale_since: Arc::default(),
+        }
+    }
+
+    /// When the oldest cached release catalog response this run fell back
+    /// to was fetched, if it fell back to any.
+    pub fn stale_since(&self) -> Option<SystemTime> {
+        *self.stale_since.lock().unwrap()
+    }
+
+    fn record_stale(&self, fetched: SystemTime) {
+        let mut stale_since = self.stale_since.lock().unwrap();
+        *stale_since = Some(stale_since.map_or(fetched, |since| since.min(fetched)));
+    }
+
+    /// The same downloader, taking artifacts from `store` instead of
+    /// downloading them.
+    pub fn with_artifact_store(&self, store: PathBuf) -> Self {
+        let mut options = (*self.options).clone();
+        options.artifact_store = Some(store);
+        Self {
+            client: self.client.clone(),
+            options: Arc::new(options),
+            pages: self.pages.clone(),
+            stale_since: self.stale_since.clone(),
+        }
+    }
+
+    /// Build a request, redirecting release downloads to the configured
+    /// mirror and authenticating GitHub API calls.
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let options = &self.options;
+        let url = match (&options.mirror, url.strip_prefix(GITHUB_URL)) {
+            (Some(mirror), Some(rest)) => format!("{}{}", mirror.trim_end_matches('/'), rest),
+            _ => url.to_string(),
+        };
+        let request = self.client.request(method, &url);
+        match &options.github_token {
+            Some(token) if url.starts_with(GITHUB_API_URL) => request.bearer_auth(token),
+            _ => request,
         }
     }
 
-    pub async fn download_file(&self, url: &str, dest: &Path) -> Result<()> {
-        println!("Downloading from: {}", url);
+    /// Send `request` and check the certificate pins of the host that
+    /// answers. With pins configured, redirects are followed here rather than
+    /// by reqwest, so every host on the way is checked and not just the last.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut request = request.build()?;
+        for _ in 0..=MAX_REDIRECTS {
+            let retry = request.try_clone();
+            let response = self.client.execute(request).await?;
+            self.options.cert_pins.check(&response)?;
+            if self.options.cert_pins.is_empty() || !response.status().is_redirection() {
+                return Ok(response);
+            }
+            let Some(location) = response.headers().get(LOCATION).and_then(|location| location.to_str().ok()) else {
+                return Ok(response);
+            };
+            let url = response.url().join(location)?;
+            let mut next = retry.context("Failed to follow redirect")?;
+            // Credentials stay with the host they were meant for
+            if url.host_str() != next.url().host_str() {
+                next.headers_mut().remove(AUTHORIZATION);
+            }
+            *next.url_mut() = url;
+            request = next;
+        }
+        anyhow::bail!("Too many redirects")
+    }
 
-        let resp = self.client
-            .get(url)
-            .send()
+    /// Copy the artifact `url` names from the artifact store to `dest`.
+    async fn copy_from_store(&self, store: &Path, url: &str, dest: &Path) -> Result<()> {
+        let source = store.join(artifact_file_name(url));
+        if !source.is_file() {
+            anyhow::bail!(t!("artifact-not-provisioned", path = source.display(), url = url));
+        }
+        output::status(t!("copying-artifact", path = source.display()));
+        tokio::fs::copy(&source, dest)
             .await
-            .context("Failed to send request")?;
+            .with_context(|| format!("Failed to copy {}", source.display()))?;
+        let mut head = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::Read::take(std::fs::File::open(dest)?, BODY_CHECK_LEN as u64), &mut head)?;
+        check_body(url, None, &head).with_context(|| format!("{} is not a valid artifact", source.display()))
+    }
+
+    /// Take the artifact `url` names from the shared cache at `dir`, first
+    /// downloading it into the cache unless another process already is, in
+    /// which case that download is waited for.
+    async fn fetch_shared(&self, dir: &Path, url: &str, dest: &Path, progress: &Progress<'_>) -> Result<()> {
+        let cache = SharedCache::new(dir)?;
+        let name = artifact_file_name(url);
+        let mut waiting = false;
+        loop {
+            // Locking before reading means a download that finishes in
+            // between is found instead of repeated
+            let lock = cache.try_lock(url)?;
+            if cache.get(url, dest, lock.as_ref())? {
+                let size = std::fs::metadata(dest)?.len();
+                progress(size, Some(size));
+                return Ok(());
+            }
+            if let Some(lock) = lock {
+                self.fetch_file(url, lock.partial(), progress).await?;
+                lock.commit()?;
+                continue;
+            }
+            if !waiting {
+                output::status(t!("shared-cache-waiting", name = name));
+                waiting = true;
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn fetch_file(&self, url: &str, dest: &Path, progress: &Progress<'_>) -> Result<()> {
+        output::status(t!("downloading", url = url));
+
+        let resp = self.send(self.get_file(url)).await.context("Failed to send request")?;
 
         if !resp.status().is_success() {
             anyhow::bail!("Failed to download file: HTTP {}", resp.status());
         }
 
+        let content_type = content_type(&resp);
         let total_size = resp.content_length().unwrap_or(0);
-        let pb = ProgressBar::new(total_size);
+        let validator = resume_validator(&resp);
+        let pb = output::progress_bar(total_size);
         pb.set_style(ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
             .unwrap()
@@ -43,30 +354,320 @@ impl Downloader {
         let mut file = File::create(dest).await.context("Failed to create file")?;
         let mut downloaded: u64 = 0;
         let mut stream = resp.bytes_stream();
+        let mut failures = 0;
+        // The start of the body, until there is enough of it to check
+        let mut head = Some(Vec::new());
 
         while let Some(chunk) = stream.next().await {
-            let chunk = chunk.context("Failed to download chunk")?;
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                // A dropped connection resumes from the bytes already
+                // written, as long as the server can tell the file is the same
+                Err(e) => {
+                    let Some(validator) = validator.as_ref().filter(|_| downloaded > 0) else {
+                        return Err(e).context("Failed to download chunk");
+                    };
+                    let resumed = loop {
+                        failures += 1;
+                        if failures > MAX_RESUMES {
+                            return Err(e).context("Failed to download chunk");
+                        }
+                        warn!("{}", t!("download-resuming", url = url, bytes = human_size(downloaded), error = e.to_string()));
+                        match self.resume(url, downloaded, validator, failures).await {
+                            Ok(resumed) => break resumed,
+                            Err(e) => debug!("Reconnecting to {} failed: {:#}", url, e),
+                        }
+                    };
+                    if resumed.status() == StatusCode::OK {
+                        // The file changed since, or the server ignores ranges
+                        file.set_len(0).await.context("Failed to write chunk")?;
+                        file.rewind().await.context("Failed to write chunk")?;
+                        downloaded = 0;
+                        head = Some(Vec::new());
+                    }
+                    stream = resumed.bytes_stream();
+                    continue;
+                }
+            };
+            failures = 0;
+            if let Some(start) = head.as_mut() {
+                start.extend_from_slice(&chunk);
+                if start.len() >= BODY_CHECK_LEN {
+                    check_body(url, content_type.as_deref(), start)?;
+                    head = None;
+                }
+            }
             file.write_all(&chunk).await.context("Failed to write chunk")?;
-            downloaded = std::cmp::min(downloaded + (chunk.len() as u64), total_size);
-            pb.set_position(downloaded);
+            downloaded += chunk.len() as u64;
+            pb.set_position(downloaded.min(total_size));
+            progress(downloaded, (total_size > 0).then_some(total_size));
+        }
+        // A body shorter than that is checked whole
+        if let Some(start) = &head {
+            check_body(url, content_type.as_deref(), start)?;
         }
+        file.flush().await.context("Failed to write chunk")?;
 
         pb.finish_with_message("Download completed");
         Ok(())
     }
 
-    pub async fn download_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
-        let response = self.client
-            .get(url)
-            .send()
+    /// Request `url` again from byte `from` on, once `attempt` reconnects
+    /// have been tried. The response is the rest of the file, or all of it
+    /// when `validator` no longer matches.
+    async fn resume(&self, url: &str, from: u64, validator: &HeaderValue, attempt: u32) -> Result<reqwest::Response> {
+        let _slot = RESUME_SLOTS.acquire().await?;
+        tokio::time::sleep(RESUME_BACKOFF * attempt).await;
+        let resp = self
+            .send(self.get_file(url).header(RANGE, format!("bytes={}-", from)).header(IF_RANGE, validator.clone()))
             .await?;
+        match resp.status() {
+            StatusCode::OK => Ok(resp),
+            StatusCode::PARTIAL_CONTENT if content_range_start(&resp) == Some(from) => Ok(resp),
+            status => anyhow::bail!("Failed to resume download: HTTP {}", status),
+        }
+    }
+
+    /// API responses and other text are requested gzip-compressed.
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::GET, url)
+    }
+
+    /// Files are requested as stored, so their length, resume offsets and
+    /// digests are those of the file itself.
+    fn get_file(&self, url: &str) -> reqwest::RequestBuilder {
+        self.get(url).header(ACCEPT_ENCODING, "identity")
+    }
+
+    async fn fetch_page(&self, url: &str) -> Result<Page> {
+        let spinner = output::spinner(t!("fetching", url = url));
+        let result = self.fetch_page_quietly(url).await;
+        spinner.finish_and_clear();
+        result
+    }
+
+    async fn fetch_page_quietly(&self, url: &str) -> Result<Page> {
+        let response = self.send(self.get(url)).await?;
 
         if !response.status().is_success() {
             anyhow::bail!("Failed to download: HTTP {}", response.status());
         }
 
-        let json = response.json().await?;
-        Ok(json)
+        let content_type = content_type(&response);
+        let next = response
+            .headers()
+            .get(LINK)
+            .and_then(|link| next_link(link.to_str().ok()?));
+        let body = response.text().await?;
+        check_body(url, content_type.as_deref(), body.as_bytes())?;
+        Ok(Page { body, next })
+    }
+
+    /// The last good response from the release catalog, and the link to the
+    /// page after it, is kept and served, with a warning, while GitHub cannot
+    /// be reached. Checksums, signatures and other text are always fetched.
+    async fn load_page(&self, url: &str) -> Result<Page> {
+        if !is_release_catalog(url) {
+            return self.fetch_page(url).await;
+        }
+        match self.fetch_page(url).await {
+            Ok(page) => {
+                cache::store_metadata(url, &page.body);
+                if let Some(next) = &page.next {
+                    cache::store_metadata(&next_page_key(url), next);
+                }
+                Ok(page)
+            }
+            Err(e) if self.options.strict => Err(e),
+            Err(e) => match cache::cached_metadata(url) {
+                Some((body, fetched)) => {
+                    warn!("{}", t!("metadata-stale", url = url, error = e,
+                        time = humantime::format_rfc3339_seconds(fetched)));
+                    self.record_stale(fetched);
+                    let next = cache::cached_metadata(&next_page_key(url)).map(|(next, _)| next);
+                    Ok(Page { body, next })
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+/// Whether `url` lists releases or looks one up by tag on the GitHub API.
+fn is_release_catalog(url: &str) -> bool {
+    let Some(path) = url.strip_prefix(GITHUB_API_URL) else {
+        return false;
+    };
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    path.ends_with("/releases") || path.rsplit_once('/').is_some_and(|(parent, _)| parent.ends_with("/releases/tags"))
+}
+
+/// Name an artifact is provisioned under in the artifact store: the last
+/// segment of its URL.
+fn artifact_file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Header value that `If-Range` can send back to make sure a resumed
+/// request continues the same file: a strong ETag, or else Last-Modified.
+fn resume_validator(response: &reqwest::Response) -> Option<HeaderValue> {
+    let headers = response.headers();
+    headers
+        .get(ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .or_else(|| headers.get(LAST_MODIFIED))
+        .cloned()
+}
+
+/// First byte of a `206 Partial Content` response, from `Content-Range`.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let range = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    range.strip_prefix("bytes ")?.split('-').next()?.trim().parse().ok()
+}
+
+fn content_type(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+impl Fetcher for Downloader {
+    async fn download_file(&self, url: &str, dest: &Path) -> Result<()> {
+        self.download_file_with_progress(url, dest, &|_, _| {}).await
+    }
+
+    async fn download_file_with_progress(&self, url: &str, dest: &Path, progress: &Progress<'_>) -> Result<()> {
+        if let Some(store) = &self.options.artifact_store {
+            self.copy_from_store(store, url, dest).await?;
+            let size = std::fs::metadata(dest)?.len();
+            progress(size, Some(size));
+            return Ok(());
+        }
+        if let Some(dir) = &self.options.shared_cache {
+            return self.fetch_shared(dir, url, dest, progress).await;
+        }
+        self.fetch_file(url, dest, progress).await
+    }
+
+    async fn download_text(&self, url: &str) -> Result<String> {
+        self.load_page(url).await.map(|page| page.body)
+    }
+
+    async fn download_page(&self, url: &str) -> Result<Page> {
+        if let Some(page) = self.pages.lock().unwrap().get(url) {
+            return Ok(page.clone());
+        }
+        let page = self.load_page(url).await?;
+        self.pages.lock().unwrap().insert(url.to_string(), page.clone());
+        Ok(page)
+    }
+
+    async fn head(&self, url: &str) -> Result<Option<u64>> {
+        if let Some(store) = &self.options.artifact_store {
+            return Ok(std::fs::metadata(store.join(artifact_file_name(url))).ok().map(|m| m.len()));
+        }
+        let spinner = output::spinner(t!("fetching", url = url));
+        let response = self.send(self.request(reqwest::Method::HEAD, url)).await;
+        spinner.finish_and_clear();
+        let response = response?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to query: HTTP {}", response.status());
+        }
+
+        // The body of a HEAD response is empty, so read the header itself
+        Ok(response
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok()?.parse().ok()))
+    }
+
+    async fn tail(&self, url: &str, len: u64) -> Result<Option<Vec<u8>>> {
+        if let Some(store) = &self.options.artifact_store {
+            let Ok(content) = std::fs::read(store.join(artifact_file_name(url))) else {
+                return Ok(None);
+            };
+            let start = content.len().saturating_sub(len as usize);
+            return Ok(Some(content[start..].to_vec()));
+        }
+        let response = self.send(self.get_file(url).header(RANGE, format!("bytes=-{}", len))).await?;
+        // A server ignoring the range would send the whole archive
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Ok(None);
+        }
+        Ok(Some(response.bytes().await?.to_vec()))
+    }
+}
+
+/// In-memory [`Fetcher`] serving canned responses by URL.
+#[cfg(test)]
+pub mod fake {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    pub struct FakeFetcher {
+        responses: HashMap<String, Vec<u8>>,
+        next_pages: HashMap<String, String>,
+        delay: Option<std::time::Duration>,
+    }
+
+    impl FakeFetcher {
+        pub fn with(mut self, url: &str, body: impl Into<Vec<u8>>) -> Self {
+            self.responses.insert(url.to_string(), body.into());
+            self
+        }
+
+        /// Link the page at `url` to the page at `next`.
+        pub fn with_next(mut self, url: &str, next: &str) -> Self {
+            self.next_pages.insert(url.to_string(), next.to_string());
+            self
+        }
+
+        /// Take `delay` over every file download, like a slow link.
+        pub fn with_delay(mut self, delay: std::time::Duration) -> Self {
+            self.delay = Some(delay);
+            self
+        }
+
+        fn body(&self, url: &str) -> Result<&[u8]> {
+            self.responses
+                .get(url)
+                .map(Vec::as_slice)
+                .with_context(|| format!("Failed to download: HTTP 404 for {}", url))
+        }
+    }
+
+    impl Fetcher for FakeFetcher {
+        async fn download_file(&self, url: &str, dest: &Path) -> Result<()> {
+            if let Some(delay) = self.delay {
+                tokio::time::sleep(delay).await;
+            }
+            Ok(tokio::fs::write(dest, self.body(url)?).await?)
+        }
+
+        async fn download_text(&self, url: &str) -> Result<String> {
+            Ok(String::from_utf8(self.body(url)?.to_vec())?)
+        }
+
+        async fn download_page(&self, url: &str) -> Result<Page> {
+            Ok(Page {
+                body: self.download_text(url).await?,
+                next: self.next_pages.get(url).cloned(),
+            })
+        }
+
+        async fn head(&self, url: &str) -> Result<Option<u64>> {
+            Ok(Some(self.body(url)?.len() as u64))
+        }
+
+        async fn tail(&self, url: &str, len: u64) -> Result<Option<Vec<u8>>> {
+            let body = self.body(url)?;
+            Ok(Some(body[body.len().saturating_sub(len as usize)..].to_vec()))
+        }
     }
 }
 
@@ -75,6 +676,181 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(312_400_000), "312.4 MB");
+        assert_eq!(human_size(2_500_000_000), "2.5 GB");
+    }
+
+    #[test]
+    fn test_files_are_requested_uncompressed() {
+        let downloader = Downloader::new();
+        let file = downloader.get_file("https://example.com/a.tar.gz").build().unwrap();
+        assert_eq!(file.headers()[ACCEPT_ENCODING], "identity");
+        let page = downloader.get("https://example.com/releases").build().unwrap();
+        assert!(!page.headers().contains_key(ACCEPT_ENCODING));
+    }
+
+    #[test]
+    fn test_only_the_release_catalog_is_served_stale() {
+        assert!(is_release_catalog("https://api.github.com/repos/WasmEdge/WasmEdge/releases?per_page=100"));
+        assert!(is_release_catalog("https://api.github.com/repositories/1/releases?per_page=100&page=2"));
+        assert!(is_release_catalog("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1"));
+        assert!(!is_release_catalog("https://api.github.com/repos/Ytemiloluwa/Wasmedgeup/releases/latest"));
+        assert!(!is_release_catalog("https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/SHA256SUM"));
+        assert!(!is_release_catalog("https://raw.githubusercontent.com/WasmEdge/WasmEdge/master/version.json"));
+    }
+
+    #[test]
+    fn test_next_link_follows_rel_next_only() {
+        let link = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/releases?per_page=100&page=4>; rel="last""#;
+        assert_eq!(next_link(link).as_deref(), Some("https://api.github.com/repositories/1/releases?per_page=100&page=2"));
+        assert_eq!(next_link(r#"<https://api.github.com/x?page=1>; rel="prev", <https://api.github.com/x?page=1>; rel="first""#), None);
+    }
+
+    #[test]
+    fn test_check_body_rejects_html_and_wrong_archives() {
+        let url = "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz";
+        assert!(check_body(url, Some("application/octet-stream"), b"\x1f\x8b\x08\x00").is_ok());
+
+        let portal = check_body(url, Some("application/octet-stream"), b"\n  <!DOCTYPE html><html>Sign in").unwrap_err();
+        assert!(portal.to_string().contains("HTML"));
+        assert!(check_body(url, Some("text/html; charset=utf-8"), b"\x1f\x8b").is_err());
+        assert!(check_body(url, None, b"PK\x03\x04").is_err());
+
+        assert!(check_body("https://example.com/plugin.zip?token=1", None, b"PK\x03\x04").is_ok());
+        assert!(check_body("https://api.github.com/repos/WasmEdge/WasmEdge/releases", Some("application/json"), b"[]").is_ok());
+        assert!(check_body("https://api.github.com/repos/WasmEdge/WasmEdge/releases", None, b"<html>").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_artifact_store_serves_artifacts_and_names_missing_files() {
+        let store = tempdir().unwrap();
+        let dest = tempdir().unwrap();
+        let downloader = Downloader::with_options(DownloadOptions {
+            artifact_store: Some(store.path().to_path_buf()),
+            ..Default::default()
+        });
+        let url = "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz";
+
+        let missing = downloader.download_file(url, &dest.path().join("a.tar.gz")).await.unwrap_err();
+        let expected = store.path().join("WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz");
+        assert!(missing.to_string().contains(&expected.display().to_string()));
+        assert_eq!(downloader.head(url).await.unwrap(), None);
+
+        std::fs::write(&expected, b"\x1f\x8b\x08\x00").unwrap();
+        downloader.download_file(url, &dest.path().join("a.tar.gz")).await.unwrap();
+        assert_eq!(std::fs::read(dest.path().join("a.tar.gz")).unwrap(), b"\x1f\x8b\x08\x00");
+        assert_eq!(downloader.head(url).await.unwrap(), Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_download_resumes_from_written_bytes() {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/plugin.bin", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let responses: [&[u8]; 2] = [
+                // Cut off after 4 of 10 bytes
+                b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\nETag: \"v1\"\r\n\r\n0123",
+                b"HTTP/1.1 206 Partial Content\r\nContent-Length: 6\r\nContent-Range: bytes 4-9/10\r\n\r\n456789",
+            ];
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0];
+                    socket.read_exact(&mut byte).await.unwrap();
+                    request.push(byte[0]);
+                }
+                requests.push(String::from_utf8(request).unwrap().to_lowercase());
+                socket.write_all(response).await.unwrap();
+            }
+            requests
+        });
+
+        let dest = tempdir().unwrap();
+        Downloader::new().download_file(&url, &dest.path().join("plugin.bin")).await.unwrap();
+        assert_eq!(std::fs::read(dest.path().join("plugin.bin")).unwrap(), b"0123456789");
+        let requests = server.await.unwrap();
+        assert!(!requests[0].contains("range:"));
+        assert!(requests[1].contains("range: bytes=4-\r\n") && requests[1].contains("if-range: \"v1\"\r\n"), "{}", requests[1]);
+    }
+
+    #[tokio::test]
+    async fn test_body_is_checked_across_chunks() {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            // Each body arrives in two writes, splitting what it is judged by
+            let bodies: [(&[u8], &[u8]); 2] = [(b"\x1f", b"\x8b\x08\x00"), (b"\n  <!DOC", b"TYPE html><html>Sign in")];
+            for (first, rest) in bodies {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0];
+                    socket.read_exact(&mut byte).await.unwrap();
+                    request.push(byte[0]);
+                }
+                let header = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", first.len() + rest.len());
+                socket.write_all(header.as_bytes()).await.unwrap();
+                socket.write_all(first).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                socket.write_all(rest).await.unwrap();
+            }
+        });
+
+        let dest = tempdir().unwrap();
+        let downloader = Downloader::new();
+        let archive = format!("http://{}/plugin.tar.gz", addr);
+        downloader.download_file(&archive, &dest.path().join("plugin.tar.gz")).await.unwrap();
+        let page = format!("http://{}/plugin.bin", addr);
+        let error = downloader.download_file(&page, &dest.path().join("plugin.bin")).await.unwrap_err();
+        assert!(error.to_string().contains("HTML"), "{:#}", error);
+    }
+
+    #[tokio::test]
+    async fn test_pins_apply_to_every_redirect_hop() {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0];
+                    socket.read_exact(&mut byte).await.unwrap();
+                    request.push(byte[0]);
+                }
+                let response = if request.starts_with(b"HEAD /start ") {
+                    format!("HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{}/file\r\nConnection: close\r\n\r\n", port)
+                } else {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 3\r\nConnection: close\r\n\r\n".to_string()
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        let pinned = |host: &str| {
+            let pin = format!("{}=sha256/{}", host, "A".repeat(43) + "=");
+            Downloader::with_options(DownloadOptions { cert_pins: CertPins::parse(&[pin]).unwrap(), ..Default::default() })
+        };
+        let start = format!("http://localhost:{}/start", port);
+
+        // Redirects are still followed from unpinned hosts
+        assert_eq!(pinned("example.com").head(&start).await.unwrap(), Some(3));
+        // A pinned host is checked even though another host serves the file
+        let error = pinned("localhost").head(&start).await.unwrap_err();
+        assert!(error.to_string().contains("localhost"), "{}", error);
+    }
+
     #[tokio::test]
     async fn test_download_file() {
         let downloader = Downloader::new();
@@ -0,0 +1,97 @@
+//! The `env` script in the prefix. wasmedgeup owns a marked block of it and
+//! only ever rewrites that block, so lines users add above or below it
+//! survive installs and upgrades, the way rustup edits shell profiles.
+
+/// Opens the managed block, after the comment marker of the script.
+const BEGIN: &str = ">>> wasmedgeup >>> managed by wasmedgeup; edits inside this block are overwritten";
+/// Closes the managed block.
+const END: &str = "<<< wasmedgeup <<<";
+
+/// New contents of the `env` script and how its managed lines changed.
+#[derive(Debug, PartialEq)]
+pub struct EnvUpdate {
+    pub content: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl EnvUpdate {
+    /// Whether the managed lines changed.
+    pub fn changed(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+/// Merge the `generated` script into the `existing` one, replacing only the
+/// managed block. The first line of `generated` (`#!/bin/sh` or `@echo
+/// off`) heads the file and the rest goes into the block, marked with
+/// `comment`. Scripts written before the block existed have no markers;
+/// their lines found in `legacy`, the scripts older versions generated, are
+/// taken as managed and every other line is kept.
+pub fn update(existing: Option<&str>, generated: &str, comment: &str, legacy: &[String]) -> EnvUpdate {
+    let (header, managed) = generated.split_once('\n').unwrap_or((generated, ""));
+    let managed: Vec<&str> = managed.lines().collect();
+    let begin = format!("{} {}", comment, BEGIN);
+    let end = format!("{} {}", comment, END);
+
+    let (before, previous, after): (Vec<&str>, Vec<&str>, Vec<&str>) = match existing {
+        None => (vec![header], Vec::new(), Vec::new()),
+        Some(existing) => {
+            let lines: Vec<&str> = existing.lines().collect();
+            let start = lines.iter().position(|line| line.trim_end() == begin);
+            let stop = lines.iter().position(|line| line.trim_end() == end);
+            match (start, stop) {
+                (Some(start), Some(stop)) if start < stop => {
+                    (lines[..start].to_vec(), lines[start + 1..stop].to_vec(), lines[stop + 1..].to_vec())
+                }
+                _ => {
+                    let known = |line: &&str| line.trim() == header || legacy.iter().any(|script| script.lines().any(|l| l == *line));
+                    let (previous, kept): (Vec<&str>, Vec<&str>) = lines.into_iter().partition(known);
+                    let previous = previous.into_iter().filter(|line| line.trim() != header).collect();
+                    (vec![header], previous, kept.into_iter().skip_while(|line| line.trim().is_empty()).collect())
+                }
+            }
+        }
+    };
+
+    let mut content = String::new();
+    for line in before.iter().chain([&begin.as_str()]).chain(&managed).chain([&end.as_str()]).chain(&after) {
+        content.push_str(line);
+        content.push('\n');
+    }
+    EnvUpdate {
+        content,
+        added: managed.iter().filter(|line| !previous.contains(line)).map(|line| line.to_string()).collect(),
+        removed: previous.iter().filter(|line| !managed.contains(line)).map(|line| line.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_managed_block_is_rewritten() {
+        let generated = "#!/bin/sh\nexport PATH=/opt/wasmedge/bin:$PATH\nexport LD_LIBRARY_PATH=/opt/wasmedge/lib:$LD_LIBRARY_PATH\n";
+        let fresh = update(None, generated, "#", &[]);
+        assert!(fresh.content.starts_with("#!/bin/sh\n# >>> wasmedgeup >>>"));
+        assert!(fresh.content.ends_with("LD_LIBRARY_PATH\n# <<< wasmedgeup <<<\n"));
+        assert_eq!(fresh.added.len(), 2);
+
+        let edited = fresh.content.replace("#!/bin/sh\n", "#!/bin/sh\nset -u\n") + "alias we=wasmedge\n";
+        let again = update(Some(&edited), generated, "#", &[]);
+        assert_eq!(again.content, edited);
+        assert!(!again.changed());
+
+        let patched = update(Some(&edited), "#!/bin/sh\nexport PATH=/opt/wasmedge/bin:$PATH\n", "#", &[]);
+        assert_eq!(patched.removed, ["export LD_LIBRARY_PATH=/opt/wasmedge/lib:$LD_LIBRARY_PATH"]);
+        assert!(patched.added.is_empty());
+        assert!(patched.content.contains("set -u\n# >>>") && patched.content.ends_with("<<<\nalias we=wasmedge\n"));
+
+        // Scripts from before the markers keep only what the user added
+        let old = format!("{}\nalias we=wasmedge\n", generated.trim_end());
+        let migrated = update(Some(&old), generated, "#", &[generated.to_string()]);
+        assert!(!migrated.changed());
+        assert_eq!(migrated.content, fresh.content.clone() + "alias we=wasmedge\n");
+    }
+}
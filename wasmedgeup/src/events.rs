@@ -0,0 +1,130 @@
+//! Progress events of runtime and plugin installs. The CLI logs them at trace
+//! level, or with `--progress json` writes them as JSON lines for front-ends
+//! (GUIs, editor extensions) that run wasmedgeup and render their own
+//! progress instead of parsing its output.
+
+use futures::Stream;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// One step of an install.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The artifact to install was chosen
+    Resolved { name: String, version: String, artifact: String },
+    /// `bytes` of the artifact at `url` have arrived
+    Downloading { url: String, bytes: u64, total: Option<u64> },
+    /// The downloaded artifact is checked against its pinned digest
+    Verifying { artifact: String },
+    /// A file is being unpacked from the artifact
+    Extracting { file: PathBuf },
+    /// The install finished
+    Done { name: String, version: String },
+    /// The install failed; no `Done` follows
+    Error { name: String, message: String },
+}
+
+/// Shortest gap between two `downloading` lines of the same download in a
+/// JSON lines stream.
+const DOWNLOAD_LINE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Sending half of an event stream. The default sends nowhere, so emitting
+/// costs nothing when nobody listens.
+#[derive(Debug, Clone, Default)]
+pub struct Events(Option<Sink>);
+
+#[derive(Debug, Clone)]
+enum Sink {
+    Channel(mpsc::UnboundedSender<Event>),
+    JsonLines(Arc<Mutex<JsonLines>>),
+}
+
+impl Events {
+    /// A sender and the stream receiving what it sends. The stream ends once
+    /// every clone of the sender is dropped.
+    pub fn channel() -> (Self, impl Stream<Item = Event>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self(Some(Sink::Channel(sender))), UnboundedReceiverStream::new(receiver))
+    }
+
+    /// Events written to stderr as one JSON object per line, for
+    /// `--progress json`. Lines are written as events happen rather than
+    /// from a task, so none is lost when the process exits.
+    pub fn json_lines() -> Self {
+        Self(Some(Sink::JsonLines(Arc::default())))
+    }
+
+    pub fn emit(&self, event: Event) {
+        match &self.0 {
+            // A receiver that went away just stops listening
+            Some(Sink::Channel(sender)) => {
+                let _ = sender.send(event);
+            }
+            Some(Sink::JsonLines(lines)) => {
+                if let Some(line) = lines.lock().unwrap().line(&event, Instant::now()) {
+                    eprintln!("{}", line);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Report how an install of `name` ended.
+    pub fn finish<T>(&self, name: &str, version: &str, result: &anyhow::Result<T>) {
+        self.emit(match result {
+            Ok(_) => Event::Done { name: name.to_string(), version: version.to_string() },
+            Err(e) => Event::Error { name: name.to_string(), message: format!("{:#}", e) },
+        });
+    }
+}
+
+/// Throttles `downloading` events, which arrive once per received chunk, to
+/// a few lines per second. Every other event gets a line.
+#[derive(Debug, Default)]
+struct JsonLines {
+    /// URL and time of the last `downloading` line
+    last_download: Option<(String, Instant)>,
+}
+
+impl JsonLines {
+    fn line(&mut self, event: &Event, now: Instant) -> Option<String> {
+        if let Event::Downloading { url, bytes, total } = event {
+            let finished = total.is_some_and(|total| *bytes >= total);
+            let recent = self
+                .last_download
+                .as_ref()
+                .is_some_and(|(last, at)| last == url && now.duration_since(*at) < DOWNLOAD_LINE_INTERVAL);
+            if recent && !finished {
+                return None;
+            }
+            self.last_download = Some((url.clone(), now));
+        }
+        serde_json::to_string(event).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines_throttle_download_progress() {
+        let mut lines = JsonLines::default();
+        let start = Instant::now();
+        let chunk = |bytes| Event::Downloading { url: "https://example.com/a.tar.gz".into(), bytes, total: Some(100) };
+
+        assert_eq!(
+            lines.line(&chunk(10), start).as_deref(),
+            Some(r#"{"event":"downloading","url":"https://example.com/a.tar.gz","bytes":10,"total":100}"#)
+        );
+        assert!(lines.line(&chunk(20), start + Duration::from_millis(50)).is_none());
+        assert!(lines.line(&Event::Verifying { artifact: "a.tar.gz".into() }, start).is_some());
+        assert!(lines.line(&chunk(30), start + DOWNLOAD_LINE_INTERVAL).is_some());
+        assert!(lines.line(&chunk(100), start + DOWNLOAD_LINE_INTERVAL).is_some());
+    }
+}
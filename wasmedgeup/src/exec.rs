@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::installer;
+use crate::platform::OS;
+
+/// Environment variables that make the installation at `install_path` the one
+/// used by child processes. Managed directories are prepended to any existing
+/// values so the rest of the caller's environment keeps working.
+pub fn managed_env(install_path: &Path, os: &OS, with_plugins: bool) -> Result<Vec<(String, OsString)>> {
+    let mut vars = vec![(
+        "PATH".to_string(),
+        prepend("PATH", install_path.join("bin"))?,
+    )];
+
+    let lib_var = os.library_path_var();
+    if lib_var != "PATH" {
+        vars.push((lib_var.to_string(), prepend(lib_var, install_path.join("lib"))?));
+    }
+
+    if with_plugins {
+        vars.push((
+            "WASMEDGE_PLUGIN_PATH".to_string(),
+            install_path.join("plugin").into_os_string(),
+        ));
+    }
+
+    Ok(vars)
+}
+
+/// Prefix of `version` under `install_path`: its side-by-side installation,
+/// or `install_path` itself when that is the version installed there.
+pub fn version_prefix(install_path: &Path, version: &str) -> Result<PathBuf> {
+    [installer::versioned_prefix(install_path, version), install_path.to_path_buf()]
+        .into_iter()
+        .find(|prefix| installer::installed_version(prefix).as_deref() == Some(version))
+        .with_context(|| t!("exec-version-not-installed", version = version, path = install_path.display()))
+}
+
+fn prepend(var: &str, dir: PathBuf) -> Result<OsString> {
+    let mut paths = vec![dir];
+    if let Some(existing) = std::env::var_os(var) {
+        paths.extend(std::env::split_paths(&existing));
+    }
+    std::env::join_paths(paths).with_context(|| format!("Invalid entry in {}", var))
+}
+
+/// Run `program` with the managed environment applied and return its exit code.
+pub fn run(install_path: &Path, os: &OS, with_plugins: bool, program: &str, args: &[String]) -> Result<i32> {
+    if !install_path.join("bin").exists() {
+        anyhow::bail!(t!("exec-not-installed", path = install_path.display()));
+    }
+
+    let status = Command::new(program)
+        .args(args)
+        .envs(managed_env(install_path, os, with_plugins)?)
+        .status()
+        .with_context(|| format!("Failed to launch {}", program))?;
+
+    // A process killed by a signal has no exit code; report it as a failure
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::LinuxDistro;
+
+    fn install(prefix: &Path, version: &str) {
+        std::fs::create_dir_all(prefix.join("bin")).unwrap();
+        std::fs::write(prefix.join("VERSION"), format!("version={}\n", version)).unwrap();
+    }
+
+    #[test]
+    fn test_a_version_selects_its_prefix() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        install(root, "0.14.1");
+        install(&installer::versioned_prefix(root, "0.13.5"), "0.13.5");
+
+        assert_eq!(version_prefix(root, "0.13.5").unwrap(), root.join("versions").join("0.13.5"));
+        assert_eq!(version_prefix(root, "0.14.1").unwrap(), root);
+        let missing = version_prefix(root, "0.12.0").unwrap_err();
+        assert!(missing.to_string().contains("--versioned"), "{}", missing);
+    }
+
+    #[test]
+    fn test_plugins_are_only_pointed_at_on_request() {
+        let prefix = Path::new("/opt/wasmedge");
+        let vars = managed_env(prefix, &OS::Linux(LinuxDistro::Generic), false).unwrap();
+        let path = &vars.iter().find(|(name, _)| name == "PATH").unwrap().1;
+        assert_eq!(std::env::split_paths(path).next().unwrap(), prefix.join("bin"));
+        assert!(vars.iter().any(|(name, _)| name == "LD_LIBRARY_PATH"));
+        assert!(!vars.iter().any(|(name, _)| name == "WASMEDGE_PLUGIN_PATH"));
+
+        let vars = managed_env(prefix, &OS::Linux(LinuxDistro::Generic), true).unwrap();
+        assert!(vars.contains(&("WASMEDGE_PLUGIN_PATH".to_string(), prefix.join("plugin").into_os_string())));
+    }
+}
@@ -0,0 +1,319 @@
+//! Unpacking of downloaded `.tar.gz` and `.zip` archives. Release, plugin and bundle
+//! archives come from the network, so every entry is checked before it is
+//! written: no absolute paths or `..`, no links pointing outside the
+//! destination, no device files or FIFOs, and no more files or bytes than
+//! [`ExtractLimits`] allow.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use tar::{Archive, EntryType};
+
+/// Default cap on the unpacked size of one archive. The largest upstream
+/// archives, CUDA builds of the wasi-nn plugins, stay well below it.
+pub const DEFAULT_MAX_EXTRACT_MB: u64 = 4096;
+
+/// Default cap on the number of entries in one archive.
+pub const DEFAULT_MAX_EXTRACT_FILES: u64 = 20_000;
+
+/// Caps on what one archive may unpack to, so a crafted archive from a
+/// compromised mirror cannot fill the disk.
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractLimits {
+    pub max_bytes: u64,
+    pub max_entries: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self { max_bytes: DEFAULT_MAX_EXTRACT_MB * 1_000_000, max_entries: DEFAULT_MAX_EXTRACT_FILES }
+    }
+}
+
+impl ExtractLimits {
+    /// Limits from `--max-extract-size` (in MB) and `--max-extract-files`.
+    pub fn new(max_mb: u64, max_entries: u64) -> Self {
+        Self { max_bytes: max_mb.saturating_mul(1_000_000), max_entries }
+    }
+}
+
+/// Unpack the gzipped tarball `archive` into `dest`, calling `on_entry` with
+/// the path of each entry before it is written. Fails on the first entry
+/// that would land outside `dest` or exceed `limits`; entries before it stay
+/// unpacked.
+pub fn unpack_tar_gz(archive: impl Read, dest: &Path, limits: &ExtractLimits, on_entry: impl FnMut(&Path)) -> Result<()> {
+    unpack_tar_gz_filtered(archive, dest, limits, |_| true, on_entry)
+}
+
+/// Like [`unpack_tar_gz`], skipping entries whose path `keep` rejects.
+/// Skipped entries are still checked, but do not count towards the size
+/// limit since nothing of them is written.
+pub fn unpack_tar_gz_filtered(
+    archive: impl Read,
+    dest: &Path,
+    limits: &ExtractLimits,
+    keep: impl Fn(&Path) -> bool,
+    mut on_entry: impl FnMut(&Path),
+) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    let root = dest.canonicalize()?;
+    let mut archive = Archive::new(GzDecoder::new(archive));
+    let (mut entries, mut bytes) = (0u64, 0u64);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        entries += 1;
+        if entries > limits.max_entries {
+            anyhow::bail!(t!("extract-too-many-files", limit = limits.max_entries));
+        }
+        if !path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+            anyhow::bail!(t!("archive-entry-outside", entry = path.display()));
+        }
+        if path.components().all(|c| c == Component::CurDir) || !keep(&path) {
+            continue;
+        }
+        // Tar stores regular files whole, so the header size is what lands on disk
+        bytes = bytes.saturating_add(entry.header().size()?);
+        if bytes > limits.max_bytes {
+            anyhow::bail!(t!("extract-too-large", limit = limits.max_bytes / 1_000_000));
+        }
+        match entry.header().entry_type() {
+            EntryType::Symlink | EntryType::Link => {
+                let target = entry.link_name()?.context("Link entry without a target")?.into_owned();
+                // Symlinks resolve from their own directory as it is on disk,
+                // through links unpacked before them; hard links from the root
+                let base = match entry.header().entry_type() {
+                    EntryType::Symlink => real_dir(&root, path.parent().unwrap_or(Path::new(""))),
+                    _ => Some(root.clone()),
+                };
+                base.and_then(|base| within(&root, &base, &target)).with_context(|| {
+                    t!("archive-link-outside", entry = path.display(), target = target.display())
+                })?;
+            }
+            // Sparse files unpack to more than their header says
+            EntryType::Char | EntryType::Block | EntryType::Fifo | EntryType::GNUSparse => {
+                anyhow::bail!(t!("archive-entry-special", entry = path.display()));
+            }
+            _ => {}
+        }
+        on_entry(&path);
+        // `unpack_in` also refuses to write through a directory that
+        // resolves outside `root`, e.g. one replaced by a symlink
+        entry.unpack_in(&root)?;
+    }
+    Ok(())
+}
+
+/// Unpack the zip `archive`, the format of Windows plugin archives, into
+/// `dest` with the checks of [`unpack_tar_gz`]. WasmEdge zip archives hold
+/// no links, so symbolic link entries are refused outright.
+pub fn unpack_zip(archive: impl Read + Seek, dest: &Path, limits: &ExtractLimits, mut on_entry: impl FnMut(&Path)) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    let root = dest.canonicalize()?;
+    let mut archive = zip::ZipArchive::new(archive)?;
+    if archive.len() as u64 > limits.max_entries {
+        anyhow::bail!(t!("extract-too-many-files", limit = limits.max_entries));
+    }
+    let mut bytes = 0u64;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let path = PathBuf::from(entry.name());
+        if !path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+            anyhow::bail!(t!("archive-entry-outside", entry = path.display()));
+        }
+        if path.components().all(|c| c == Component::CurDir) {
+            continue;
+        }
+        if entry.unix_mode().is_some_and(|mode| mode & 0o170000 == 0o120000) {
+            anyhow::bail!(t!("archive-entry-link", entry = path.display()));
+        }
+        on_entry(&path);
+        let target = root.join(&path);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            continue;
+        }
+        let parent = path.parent().unwrap_or(Path::new(""));
+        std::fs::create_dir_all(root.join(parent))?;
+        real_dir(&root, parent).with_context(|| t!("archive-entry-outside", entry = path.display()))?;
+        // Count what is written rather than the size the header claims
+        let mut file = std::fs::File::create(&target)?;
+        bytes += std::io::copy(&mut (&mut entry).take(limits.max_bytes - bytes + 1), &mut file)?;
+        if bytes > limits.max_bytes {
+            anyhow::bail!(t!("extract-too-large", limit = limits.max_bytes / 1_000_000));
+        }
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(mode & 0o777))?;
+        }
+    }
+    Ok(())
+}
+
+/// Where the directory `dir`, relative to `root`, really is: the deepest
+/// part that exists with its symlinks resolved, and the rest appended. `None`
+/// when that is outside `root`.
+fn real_dir(root: &Path, dir: &Path) -> Option<PathBuf> {
+    let full = root.join(dir);
+    let existing = full.ancestors().find(|a| a.exists())?;
+    let resolved = existing.canonicalize().ok()?.join(full.strip_prefix(existing).ok()?);
+    resolved.starts_with(root).then_some(resolved)
+}
+
+/// `target` taken from the directory `base` with `.` and `..` resolved, when
+/// it stays below `root`. `..` may only lead the target: after a component
+/// that could be a symlink it would not resolve lexically.
+fn within(root: &Path, base: &Path, target: &Path) -> Option<PathBuf> {
+    let mut resolved = base.to_path_buf();
+    let mut leading = true;
+    for component in target.components() {
+        match component {
+            Component::Normal(part) => {
+                resolved.push(part);
+                leading = false;
+            }
+            Component::CurDir => {}
+            Component::ParentDir if leading => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    resolved.starts_with(root).then_some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use tar::{Builder, Header};
+
+    /// A tarball of `entries`, written with raw names since `tar` refuses to
+    /// build the malicious ones.
+    fn archive(entries: &[(&str, EntryType, &str)]) -> Vec<u8> {
+        let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, kind, content_or_target) in entries {
+            let mut header = Header::new_gnu();
+            header.set_entry_type(*kind);
+            header.set_mode(0o644);
+            let raw = header.as_gnu_mut().unwrap();
+            raw.name[..path.len()].copy_from_slice(path.as_bytes());
+            let data = match kind {
+                EntryType::Symlink | EntryType::Link => {
+                    raw.linkname[..content_or_target.len()].copy_from_slice(content_or_target.as_bytes());
+                    &[][..]
+                }
+                _ => content_or_target.as_bytes(),
+            };
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            builder.append(&header, data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_malicious_entries_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let dest = root.path().join("staging");
+        let limits = ExtractLimits::default();
+        let unpack = |entries: &[(&str, EntryType, &str)]| unpack_tar_gz(&archive(entries)[..], &dest, &limits, |_| {});
+
+        let mut seen = Vec::new();
+        unpack_tar_gz(
+            &archive(&[
+                ("./WasmEdge/lib/libwasmedge.so.0", EntryType::Regular, "elf"),
+                ("WasmEdge/lib/libwasmedge.so", EntryType::Symlink, "libwasmedge.so.0"),
+                ("WasmEdge/bin/wasmedge", EntryType::Symlink, "../lib/libwasmedge.so.0"),
+            ])[..],
+            &dest,
+            &limits,
+            |path| seen.push(path.to_path_buf()),
+        )
+        .unwrap();
+        assert_eq!(seen.len(), 3);
+        assert_eq!(std::fs::read_to_string(dest.join("WasmEdge/lib/libwasmedge.so")).unwrap(), "elf");
+        assert!(dest.join("WasmEdge/bin/wasmedge").is_file());
+
+        for entries in [
+            &[("../evil", EntryType::Regular, "x")][..],
+            &[("WasmEdge/lib/../../../evil", EntryType::Regular, "x")],
+            &[("WasmEdge/lib/../bin/wasmedge", EntryType::Regular, "x")],
+            &[("/tmp/evil", EntryType::Regular, "x")],
+            &[("lib", EntryType::Symlink, ".."), ("lib/evil", EntryType::Regular, "x")],
+            &[("lib", EntryType::Symlink, "/"), ("lib/evil", EntryType::Regular, "x")],
+            &[("a/b", EntryType::Symlink, "../../evil")],
+            // Each link stays inside, but the second resolves through the first
+            &[("x", EntryType::Symlink, "."), ("x/q", EntryType::Symlink, "../evil")],
+            &[("d/x", EntryType::Symlink, ".."), ("d/x/q", EntryType::Symlink, "../evil")],
+            &[("d", EntryType::Directory, ""), ("d/a", EntryType::Symlink, "../d/../../evil")],
+            &[("hard", EntryType::Link, "../evil")],
+            &[("null", EntryType::Char, "")],
+        ] {
+            assert!(unpack(entries).is_err(), "{:?} was unpacked", entries);
+        }
+        assert!(!root.path().join("evil").exists());
+        assert!(!dest.join("lib").exists());
+    }
+
+    /// A zip of `entries`, with the given unix modes.
+    fn zip_archive(entries: &[(&str, u32, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, mode, content_or_target) in entries {
+            let options = zip::write::FileOptions::default().unix_permissions(*mode);
+            if mode & 0o170000 == 0o120000 {
+                writer.add_symlink(*path, *content_or_target, options).unwrap();
+            } else {
+                writer.start_file(*path, options).unwrap();
+                std::io::Write::write_all(&mut writer, content_or_target.as_bytes()).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_zip_archives_are_unpacked_with_the_same_checks() {
+        let root = tempfile::tempdir().unwrap();
+        let dest = root.path().join("staging");
+        let limits = ExtractLimits::default();
+
+        let mut seen = Vec::new();
+        let archive = zip_archive(&[("lib/wasmedge/wasmedgePluginWasiNN.dll", 0o644, "dll"), ("bin/tool.exe", 0o755, "exe")]);
+        unpack_zip(std::io::Cursor::new(archive), &dest, &limits, |path| seen.push(path.to_path_buf())).unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(std::fs::read_to_string(dest.join("lib/wasmedge/wasmedgePluginWasiNN.dll")).unwrap(), "dll");
+
+        for entries in [
+            &[("../evil", 0o644, "x")][..],
+            &[("lib/../../evil", 0o644, "x")],
+            &[("/tmp/evil", 0o644, "x")],
+            &[("lib", 0o120777, "..")],
+        ] {
+            assert!(unpack_zip(std::io::Cursor::new(zip_archive(entries)), &dest, &limits, |_| {}).is_err(), "{:?} was unpacked", entries);
+        }
+        assert!(!root.path().join("evil").exists());
+
+        let files = zip_archive(&[("a", 0o644, "0123456789"), ("b", 0o644, "0123456789")]);
+        let unpack = |limits: ExtractLimits| unpack_zip(std::io::Cursor::new(files.clone()), &dest, &limits, |_| {});
+        assert!(unpack(ExtractLimits { max_bytes: 20, max_entries: 2 }).is_ok());
+        assert!(unpack(ExtractLimits { max_bytes: 20, max_entries: 1 }).is_err());
+        assert!(unpack(ExtractLimits { max_bytes: 19, max_entries: 2 }).unwrap_err().to_string().contains("--max-extract-size"));
+    }
+
+    #[test]
+    fn test_extraction_stops_at_limits() {
+        let dest = tempfile::tempdir().unwrap();
+        let files = [("a", EntryType::Regular, "0123456789"), ("b", EntryType::Regular, "0123456789")];
+        let unpack = |limits: ExtractLimits| unpack_tar_gz(&archive(&files)[..], dest.path(), &limits, |_| {});
+
+        assert!(unpack(ExtractLimits { max_bytes: 20, max_entries: 2 }).is_ok());
+        let too_many = unpack(ExtractLimits { max_bytes: 20, max_entries: 1 }).unwrap_err();
+        assert!(too_many.to_string().contains("more than 1 files"), "{}", too_many);
+        let too_large = unpack(ExtractLimits { max_bytes: 19, max_entries: 2 }).unwrap_err();
+        assert!(too_large.to_string().contains("--max-extract-size"), "{}", too_large);
+    }
+}
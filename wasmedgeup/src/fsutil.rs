@@ -0,0 +1,554 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Suffix given to files that were still in use when we tried to replace them.
+/// They are moved aside and deleted on a later run once nothing holds them.
+const RETIRED_SUFFIX: &str = ".wasmedgeup-old";
+
+/// Convert `path` to a form that is not subject to the 260 character `MAX_PATH`
+/// limit on Windows. Other platforms get the path back unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    if cfg!(windows) && path.is_absolute() {
+        PathBuf::from(verbatim(&path.to_string_lossy()))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// The `\\?\` form of the absolute Windows path `path`. Windows takes
+/// verbatim paths as written, so forward slashes become separators first.
+fn verbatim(path: &str) -> String {
+    if path.starts_with(r"\\?\") {
+        return path.to_string();
+    }
+    let path = path.replace('/', r"\");
+    match path.strip_prefix(r"\\") {
+        Some(unc) => format!(r"\\?\UNC\{}", unc),
+        None => format!(r"\\?\{}", path),
+    }
+}
+
+/// OS errors meaning a running process holds the file: ERROR_SHARING_VIOLATION
+/// and ERROR_LOCK_VIOLATION on Windows, ETXTBSY elsewhere. ERROR_ACCESS_DENIED
+/// is left out: it is what a missing permission looks like too.
+#[cfg(windows)]
+const IN_USE_ERRORS: &[i32] = &[32, 33];
+#[cfg(unix)]
+const IN_USE_ERRORS: &[i32] = &[libc::ETXTBSY];
+#[cfg(not(any(windows, unix)))]
+const IN_USE_ERRORS: &[i32] = &[];
+
+/// Whether `err` means the file is held open by a running process.
+pub fn is_in_use(err: &io::Error) -> bool {
+    err.raw_os_error().is_some_and(|code| IN_USE_ERRORS.contains(&code))
+}
+
+fn in_use_error(err: io::Error, path: &Path) -> anyhow::Error {
+    if is_in_use(&err) {
+        anyhow::anyhow!(
+            "{} is in use. Close any running wasmedge processes and try again",
+            path.display()
+        )
+    } else {
+        anyhow::Error::new(err).context(format!("Failed to modify {}", path.display()))
+    }
+}
+
+/// Move `src` to `dst`, replacing `dst` if it exists. If `dst` is locked by a
+/// running process it is renamed aside (which Windows permits for running
+/// executables) and scheduled for deletion by [`remove_retired`].
+pub fn replace(src: &Path, dst: &Path) -> Result<()> {
+    let (src, dst) = (long_path(src), long_path(dst));
+    match std::fs::rename(&src, &dst) {
+        Ok(()) => Ok(()),
+        Err(e) if is_in_use(&e) && dst.exists() => {
+            let retired = retired_name(&dst);
+            debug!("{} is busy, moving it aside to {}", dst.display(), retired.display());
+            std::fs::rename(&dst, &retired).map_err(|e| in_use_error(e, &dst))?;
+            std::fs::rename(&src, &dst).map_err(|e| in_use_error(e, &dst))?;
+            Ok(())
+        }
+        Err(e) => Err(in_use_error(e, &dst)),
+    }
+}
+
+/// Most threads moving files at once; renames are metadata operations, so
+/// more mostly contend on the directory lock.
+const MAX_MOVE_WORKERS: usize = 8;
+
+fn is_missing_or_empty(dir: &Path) -> bool {
+    match std::fs::read_dir(long_path(dir)) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(e) => e.kind() == io::ErrorKind::NotFound,
+    }
+}
+
+/// Create the directories under `dst` mirroring `src` and list the file
+/// moves that fill them.
+fn plan_moves(fs: &dyn FileSystem, src: &Path, dst: &Path, moves: &mut Vec<(PathBuf, PathBuf)>) -> Result<()> {
+    fs.create_dir_all(dst)?;
+    let entries = std::fs::read_dir(long_path(src))
+        .with_context(|| format!("Failed to read {}", src.display()))?;
+    for entry in entries {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            plan_moves(fs, &entry.path(), &target, moves)?;
+        } else {
+            moves.push((entry.path(), target));
+        }
+    }
+    Ok(())
+}
+
+/// Perform `moves`, spread over several threads unless only recording them.
+fn rename_all(fs: &dyn FileSystem, moves: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_MOVE_WORKERS)
+        .min(moves.len());
+    if workers <= 1 || fs.is_dry_run() {
+        return moves.iter().try_for_each(|(src, dst)| fs.rename(src, dst));
+    }
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = moves
+            .chunks(moves.len().div_ceil(workers))
+            .map(|chunk| scope.spawn(move || chunk.iter().try_for_each(|(src, dst)| fs.rename(src, dst))))
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().map_err(|_| anyhow::anyhow!("A file move thread panicked"))?)
+    })
+}
+
+/// Move every file under `src` into the same relative location under `dst`,
+/// creating directories as needed and replacing existing files. A missing or
+/// empty `dst` is replaced by `src` in one rename where the filesystem
+/// allows; otherwise the files move in parallel. `src` is read directly;
+/// only changes under `dst` go through `fs`.
+pub fn move_tree(fs: &dyn FileSystem, src: &Path, dst: &Path) -> Result<()> {
+    if !fs.is_dry_run() && is_missing_or_empty(dst) {
+        if let Some(parent) = dst.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        match fs.rename(src, dst) {
+            Ok(()) => return Ok(()),
+            Err(e) => debug!("Moving files one by one, {} cannot be renamed: {:#}", src.display(), e),
+        }
+    }
+    let mut moves = Vec::new();
+    plan_moves(fs, src, dst, &mut moves)?;
+    rename_all(fs, &moves)
+}
+
+/// Flush every file and directory under `path` to disk, so an install
+/// survives a power cut right after it reports success. Symbolic links are
+/// covered by flushing the directory holding them.
+pub fn sync_tree(path: &Path) -> Result<()> {
+    let metadata = match std::fs::symlink_metadata(long_path(path)) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(long_path(path))? {
+            sync_tree(&entry?.path())?;
+        }
+        // Windows cannot open directories as files to flush them
+        if cfg!(windows) {
+            return Ok(());
+        }
+    } else if !metadata.is_file() {
+        return Ok(());
+    }
+    std::fs::File::open(long_path(path))
+        .and_then(|file| file.sync_all())
+        .with_context(|| format!("Failed to flush {} to disk", path.display()))
+}
+
+/// Make every file and, on Unix, every directory under `path` read-only, so
+/// nothing can be added, removed or renamed either, or make them writable
+/// again. Symbolic links are left alone. A missing `path` is skipped.
+pub fn set_tree_readonly(fs: &dyn FileSystem, path: &Path, readonly: bool) -> Result<()> {
+    let metadata = match std::fs::symlink_metadata(long_path(path)) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    if metadata.is_dir() {
+        // Windows ignores the read-only attribute of directories
+        let protect = cfg!(unix);
+        if protect && !readonly {
+            fs.set_readonly(path, false)?;
+        }
+        for entry in std::fs::read_dir(long_path(path))? {
+            set_tree_readonly(fs, &entry?.path(), readonly)?;
+        }
+        if protect && readonly {
+            fs.set_readonly(path, true)?;
+        }
+    } else if metadata.is_file() {
+        fs.set_readonly(path, readonly)?;
+    }
+    Ok(())
+}
+
+/// Paths relative to `root` of the files under `root/dir`.
+pub fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(root.join(dir))? {
+        let entry = entry?;
+        let relative = dir.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &relative, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Remove a directory tree, reporting locked files with an actionable error.
+pub fn remove_dir_all(path: &Path) -> Result<()> {
+    std::fs::remove_dir_all(long_path(path)).map_err(|e| in_use_error(e, path))
+}
+
+/// Delete files previously moved aside by [`replace`]. Files that are still
+/// locked are left for the next run.
+pub fn remove_retired(fs: &dyn FileSystem, dir: &Path) -> Result<()> {
+    let entries = match std::fs::read_dir(long_path(dir)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            remove_retired(fs, &path)?;
+        } else if entry.file_name().to_string_lossy().ends_with(RETIRED_SUFFIX) {
+            if let Err(e) = fs.remove_file(&path) {
+                warn!("Could not remove {} yet: {}", path.display(), e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delete the copies of the file at `path` that [`replace`] moved aside, and
+/// nothing else in its directory.
+pub fn remove_retired_copies(fs: &dyn FileSystem, path: &Path) -> Result<()> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else { return Ok(()) };
+    let prefix = format!("{}.", name.to_string_lossy());
+    let entries = match std::fs::read_dir(long_path(dir)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let pid = file_name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(RETIRED_SUFFIX));
+        if pid.is_some_and(|pid| !pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit())) {
+            if let Err(e) = fs.remove_file(&entry.path()) {
+                warn!("Could not remove {} yet: {}", entry.path().display(), e);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn retired_name(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}{}", std::process::id(), RETIRED_SUFFIX));
+    path.with_file_name(name)
+}
+
+/// Changes to installation prefixes and the plugin directory. The installer
+/// and plugin manager make every change through this, so it can be swapped
+/// for [`DryRunFs`] to preview an operation. Scratch files in staging
+/// directories are written directly.
+pub trait FileSystem: Send + Sync {
+    fn create_dir_all(&self, path: &Path) -> Result<()>;
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()>;
+
+    /// Move `src` to `dst`, replacing `dst` if it exists.
+    fn rename(&self, src: &Path, dst: &Path) -> Result<()>;
+
+    fn remove_file(&self, path: &Path) -> Result<()>;
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()>;
+
+    /// Make `link` point at `target`, which is resolved against the link's
+    /// directory when relative: a symlink, or on Windows, where symlinks to
+    /// files need extra privileges, a copy of a file.
+    fn link(&self, target: &Path, link: &Path) -> Result<()>;
+
+    /// Mark `path` executable. A no-op where permissions have no such bit.
+    fn set_executable(&self, path: &Path) -> Result<()>;
+
+    /// Make the file at `path` read-only, or writable again.
+    fn set_readonly(&self, path: &Path, readonly: bool) -> Result<()>;
+
+    /// Change the owning user and/or group of `path`. Unix only.
+    fn set_owner(&self, path: &Path, uid: Option<u32>, gid: Option<u32>) -> Result<()>;
+
+    /// Set the permission bits of `path`. Unix only.
+    fn set_mode(&self, path: &Path, mode: u32) -> Result<()>;
+
+    /// Whether changes are only recorded, not made.
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+
+    /// Note a step a dry run skips entirely, e.g. a source build, among the
+    /// changes it would make.
+    fn skip(&self, _operation: String) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The real filesystem.
+#[derive(Debug, Default)]
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        std::fs::create_dir_all(long_path(path))
+            .with_context(|| format!("Failed to create {}", path.display()))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        std::fs::write(long_path(path), contents).map_err(|e| in_use_error(e, path))
+    }
+
+    fn rename(&self, src: &Path, dst: &Path) -> Result<()> {
+        replace(src, dst)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        std::fs::remove_file(long_path(path)).map_err(|e| in_use_error(e, path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()> {
+        remove_dir_all(path)
+    }
+
+    fn link(&self, target: &Path, link: &Path) -> Result<()> {
+        #[cfg(unix)]
+        let linked = std::os::unix::fs::symlink(target, link);
+        // A relative target is resolved against the link's directory, as a
+        // symlink's would be
+        #[cfg(windows)]
+        let linked = {
+            let source = link.parent().unwrap_or(Path::new("")).join(target);
+            if source.is_dir() {
+                std::os::windows::fs::symlink_dir(target, link)
+            } else {
+                std::fs::copy(long_path(&source), long_path(link)).map(drop)
+            }
+        };
+        linked.with_context(|| format!("Failed to link {} to {}", link.display(), target.display()))
+    }
+
+    fn set_executable(&self, path: &Path) -> Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(path, perms)?;
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+
+    fn set_readonly(&self, path: &Path, readonly: bool) -> Result<()> {
+        let mut perms = std::fs::metadata(long_path(path))
+            .with_context(|| format!("Failed to read permissions of {}", path.display()))?
+            .permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // Only the write bits change; restore the owner's when unsetting
+            let mode = perms.mode();
+            perms.set_mode(if readonly { mode & !0o222 } else { mode | 0o200 });
+        }
+        #[cfg(not(unix))]
+        perms.set_readonly(readonly);
+        std::fs::set_permissions(long_path(path), perms)
+            .with_context(|| format!("Failed to change permissions of {}", path.display()))
+    }
+
+    fn set_owner(&self, path: &Path, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+        #[cfg(unix)]
+        return std::os::unix::fs::chown(path, uid, gid)
+            .with_context(|| format!("Failed to change the owner of {}", path.display()));
+        #[cfg(not(unix))]
+        {
+            let _ = (uid, gid);
+            anyhow::bail!("Cannot change the owner of {}: only supported on Unix", path.display())
+        }
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                .with_context(|| format!("Failed to change permissions of {}", path.display()))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = mode;
+            anyhow::bail!("Cannot set the mode of {}: only supported on Unix", path.display())
+        }
+    }
+}
+
+/// Records the changes it is asked to make instead of making them.
+#[derive(Debug, Default)]
+pub struct DryRunFs {
+    operations: Mutex<Vec<String>>,
+}
+
+impl DryRunFs {
+    /// Recorded changes, in order, as human readable lines.
+    pub fn operations(&self) -> Vec<String> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    fn record(&self, operation: String) -> Result<()> {
+        self.operations.lock().unwrap().push(operation);
+        Ok(())
+    }
+}
+
+impl FileSystem for DryRunFs {
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        let operation = format!("create directory {}", path.display());
+        if path.is_dir() || self.operations.lock().unwrap().contains(&operation) {
+            return Ok(());
+        }
+        self.record(operation)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.record(format!("write {} ({} bytes)", path.display(), contents.len()))
+    }
+
+    fn rename(&self, src: &Path, dst: &Path) -> Result<()> {
+        let verb = if dst.exists() { "replace" } else { "add" };
+        self.record(format!("{} {} (from {})", verb, dst.display(), src.display()))
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        self.record(format!("remove {}", path.display()))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()> {
+        self.record(format!("remove directory {}", path.display()))
+    }
+
+    fn link(&self, target: &Path, link: &Path) -> Result<()> {
+        self.record(format!("link {} to {}", link.display(), target.display()))
+    }
+
+    fn set_executable(&self, path: &Path) -> Result<()> {
+        self.record(format!("make {} executable", path.display()))
+    }
+
+    fn set_readonly(&self, path: &Path, readonly: bool) -> Result<()> {
+        let state = if readonly { "read-only" } else { "writable" };
+        self.record(format!("make {} {}", path.display(), state))
+    }
+
+    fn set_owner(&self, path: &Path, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+        let id = |id: Option<u32>| id.map_or("-".to_string(), |id| id.to_string());
+        self.record(format!("set owner of {} to {}:{}", path.display(), id(uid), id(gid)))
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> Result<()> {
+        self.record(format!("set mode of {} to {:o}", path.display(), mode))
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+
+    fn skip(&self, operation: String) -> Result<()> {
+        self.record(operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_paths_are_verbatim_on_windows() {
+        assert_eq!(verbatim(r"C:\Users\me\.wasmedge"), r"\\?\C:\Users\me\.wasmedge");
+        assert_eq!(verbatim("C:/Users/me/.wasmedge/lib"), r"\\?\C:\Users\me\.wasmedge\lib");
+        assert_eq!(verbatim(r"\\server\share\wasmedge"), r"\\?\UNC\server\share\wasmedge");
+        assert_eq!(verbatim(r"\\?\C:\wasmedge"), r"\\?\C:\wasmedge");
+        assert_eq!(long_path(Path::new("relative/lib")), Path::new("relative/lib"));
+        if !cfg!(windows) {
+            assert_eq!(long_path(Path::new("/opt/wasmedge")), Path::new("/opt/wasmedge"));
+        }
+    }
+
+    #[test]
+    fn test_only_busy_files_count_as_in_use() {
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(!is_in_use(&denied));
+        #[cfg(windows)]
+        {
+            assert!(!is_in_use(&io::Error::from_raw_os_error(5)));
+            assert!(is_in_use(&io::Error::from_raw_os_error(32)));
+        }
+        #[cfg(unix)]
+        assert!(!is_in_use(&io::Error::from_raw_os_error(libc::EACCES)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_writing_a_running_executable_is_in_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let sleep = dir.path().join("sleep");
+        std::fs::copy("/bin/sleep", &sleep).unwrap();
+        let mut child = std::process::Command::new(&sleep).arg("30").spawn().unwrap();
+        let busy = std::fs::OpenOptions::new().write(true).open(&sleep);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let err = busy.unwrap_err();
+        assert!(is_in_use(&err), "{}", err);
+        assert!(in_use_error(err, &sleep).to_string().contains("Close any running wasmedge processes"));
+    }
+
+    #[test]
+    fn test_move_tree_renames_or_merges() {
+        let root = tempfile::tempdir().unwrap();
+        let tree = |name: &str, files: &[&str]| {
+            let dir = root.path().join(name);
+            for file in files {
+                std::fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+                std::fs::write(dir.join(file), name).unwrap();
+            }
+            dir
+        };
+
+        let fresh = root.path().join("fresh");
+        move_tree(&RealFs, &tree("a", &["x.h", "sub/y.h"]), &fresh).unwrap();
+        assert!(fresh.join("sub").join("y.h").is_file());
+        assert!(!root.path().join("a").exists());
+
+        let existing = tree("existing", &["keep.h", "x.h"]);
+        move_tree(&RealFs, &tree("b", &["x.h", "sub/y.h", "z.h"]), &existing).unwrap();
+        assert_eq!(std::fs::read_to_string(existing.join("x.h")).unwrap(), "b");
+        assert_eq!(std::fs::read_to_string(existing.join("keep.h")).unwrap(), "existing");
+        assert!(existing.join("sub").join("y.h").is_file());
+        sync_tree(&existing).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Bundled message catalogs. To add a locale, drop `<lang>.ftl` next to
+/// `en.ftl` and register it here; missing keys fall back to English.
+const CATALOGS: &[(&str, &str)] = &[("en", include_str!("locales/en.ftl"))];
+
+const FALLBACK_LOCALE: &str = "en";
+
+type Bundle = FluentBundle<FluentResource>;
+
+struct Messages {
+    selected: Option<Bundle>,
+    fallback: Bundle,
+}
+
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+impl Messages {
+    /// Messages of the catalog for `lang`, falling back to English.
+    fn new(lang: &str) -> Self {
+        let selected = (lang != FALLBACK_LOCALE).then(|| catalog(lang)).flatten().map(|source| bundle(lang, source));
+        Self { selected, fallback: bundle(FALLBACK_LOCALE, catalog(FALLBACK_LOCALE).unwrap_or_default()) }
+    }
+
+    fn format(&self, key: &str, args: &FluentArgs) -> String {
+        self.selected
+            .iter()
+            .chain([&self.fallback])
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(key)?.value()?;
+                Some(bundle.format_pattern(pattern, Some(args), &mut Vec::new()).into_owned())
+            })
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+fn bundle(lang: &str, source: &str) -> Bundle {
+    let mut bundle = FluentBundle::new_concurrent(vec![lang.parse::<LanguageIdentifier>().unwrap_or_default()]);
+    // Isolation marks only show up as stray characters in a terminal
+    bundle.set_use_isolating(false);
+    // Entries that fail to parse are left out and fall back; the tests make
+    // sure the bundled catalogs have none
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+fn catalog(locale: &str) -> Option<&'static str> {
+    CATALOGS
+        .iter()
+        .find(|(lang, _)| *lang == locale)
+        .map(|(_, source)| *source)
+}
+
+/// Language of a locale like `de_DE.UTF-8`: `de`.
+fn language(locale: &str) -> String {
+    locale.split(['_', '-', '.']).next().filter(|lang| !lang.is_empty()).unwrap_or(FALLBACK_LOCALE).to_lowercase()
+}
+
+/// Pick the catalog for `requested` (from `--locale`) or, failing that, from
+/// LC_ALL/LC_MESSAGES/LANG. Values like `de_DE.UTF-8` select `de`.
+pub fn init(requested: Option<&str>) {
+    let env_locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()));
+    let locale = requested
+        .map(str::to_string)
+        .or(env_locale)
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+    let _ = MESSAGES.set(Messages::new(&language(&locale)));
+}
+
+/// An argument as Fluent sees it. Whole numbers are passed as numbers, so
+/// messages can pick a plural form with `{ $count -> [one] ... }`.
+fn argument(value: &str) -> FluentValue<'_> {
+    let whole = !value.is_empty() && value.len() <= 15 && value.bytes().all(|b| b.is_ascii_digit());
+    match value.parse::<f64>() {
+        Ok(number) if whole && (value == "0" || !value.starts_with('0')) => FluentValue::from(number),
+        _ => FluentValue::from(value),
+    }
+}
+
+/// Look up `key` and format it with `args` for its `{ $name }` placeables.
+pub fn message(key: &str, args: &[(&str, String)]) -> String {
+    let messages = MESSAGES.get_or_init(|| Messages::new(FALLBACK_LOCALE));
+    let args: FluentArgs = args.iter().map(|(name, value)| (*name, argument(value))).collect();
+    messages.format(key, &args)
+}
+
+/// Translate a message: `t!("install-success", version = v)`.
+macro_rules! t {
+    ($key:expr) => {
+        crate::i18n::message($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        crate::i18n::message($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fluent_bundle::FluentMessage;
+
+    /// Message ids of `source`, which must parse without errors.
+    fn ids(source: &str) -> Vec<&str> {
+        if let Err((_, errors)) = FluentResource::try_new(source.to_string()) {
+            panic!("{:?}", errors);
+        }
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| Some(line.split_once('=')?.0.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_catalog_parses_and_has_the_english_keys() {
+        let english = ids(catalog(FALLBACK_LOCALE).unwrap());
+        assert!(english.len() > 100);
+        let fallback = Messages::new(FALLBACK_LOCALE).fallback;
+        for key in &english {
+            assert!(fallback.get_message(key).as_ref().and_then(FluentMessage::value).is_some(), "{} has no value", key);
+        }
+        for (lang, source) in CATALOGS {
+            let messages = ids(source);
+            for key in &english {
+                assert!(messages.contains(key), "{} is missing {}", lang, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_counts_select_plural_forms() {
+        let messages = Messages::new(FALLBACK_LOCALE);
+        let format = |count: &str| {
+            let args: FluentArgs = [("count", argument(count)), ("path", argument("/tmp"))].into_iter().collect();
+            messages.format("cache-swept", &args)
+        };
+        assert_eq!(format("1"), "Removed 1 leftover temporary entry from /tmp");
+        assert_eq!(format("3"), "Removed 3 leftover temporary entries from /tmp");
+        assert_eq!(format("0"), "Removed 0 leftover temporary entries from /tmp");
+        assert_eq!(message("install-success", &[("version", "0.14.1".into())]), "Successfully installed WasmEdge 0.14.1");
+        assert_eq!(message("install-success", &[("version", "007".into())]), "Successfully installed WasmEdge 007");
+    }
+
+    #[test]
+    fn test_missing_messages_fall_back_to_english() {
+        let messages = Messages {
+            selected: Some(bundle("de", "install-success = WasmEdge { $version } installiert\n")),
+            fallback: bundle(FALLBACK_LOCALE, catalog(FALLBACK_LOCALE).unwrap()),
+        };
+        let args: FluentArgs = [("version", argument("0.14.1")), ("name", argument("wasi_nn"))].into_iter().collect();
+        assert_eq!(messages.format("install-success", &args), "WasmEdge 0.14.1 installiert");
+        assert_eq!(messages.format("plugin-install-success", &args), "Successfully installed plugin wasi_nn");
+        assert_eq!(messages.format("no-such-message", &args), "no-such-message");
+
+        assert_eq!(language("de_DE.UTF-8"), "de");
+        assert_eq!(language("C.UTF-8"), "c");
+        assert!(Messages::new(&language("xx_YY")).selected.is_none());
+    }
+}
@@ -1,161 +1,2043 @@
 use anyhow::{Context, Result};
-use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
-use flate2::read::GzDecoder;
-use tar::Archive;
 use crate::{
-    downloader::Downloader,
-    platform::{Platform, OS},
+    cache,
+    checksum::{self, ChecksumFile},
+    delta,
+    download::{self, ArchiveSize, Downloaded},
+    downloader::{Downloader, Fetcher},
+    envfile,
+    events::{Event, Events},
+    extract::{self, ExtractLimits},
+    fsutil::{self, FileSystem, RealFs},
+    licenses::{self, LICENSES_DIR},
+    lsm,
+    manifest::{Manifest, MANIFEST_FILE},
+    output,
+    ownership::Ownership,
+    platform::{Architecture, Platform, OS},
+    plugin,
+    process,
+    release::{self, SupportStatus},
+    rpath,
+    shutdown,
+    source::SourceBuild,
+    version::WasmEdgeVersion,
 };
 
 const WASMEDGE_GITHUB_REPO: &str = "WasmEdge/WasmEdge";
 
-pub struct Installer {
+/// Parts of a runtime release that can be installed independently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuntimeComponent {
+    /// The wasmedge CLI tools
+    Bin,
+    /// The shared libraries
+    Lib,
+    /// The C API headers
+    Include,
+}
+
+impl RuntimeComponent {
+    pub const ALL: &'static [RuntimeComponent] = &[Self::Bin, Self::Lib, Self::Include];
+
+    /// The component a manifest records as `name`, e.g. `bin`.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| format!("{:?}", c).eq_ignore_ascii_case(name))
+    }
+}
+
+/// Download URL of the release asset `asset_name` published for `version`.
+pub fn release_download_url(version: &WasmEdgeVersion, asset_name: &str) -> String {
+    format!(
+        "https://github.com/{}/releases/download/{}/{}",
+        WASMEDGE_GITHUB_REPO, version, asset_name
+    )
+}
+
+/// Version an installation records in its manifest, or in the `VERSION`
+/// file of installs that predate manifests.
+pub fn installed_version(install_path: &Path) -> Option<String> {
+    if let Ok(Some(manifest)) = Manifest::read(install_path) {
+        return Some(manifest.version);
+    }
+    let content = std::fs::read_to_string(install_path.join("VERSION")).ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("version="))
+        .map(|v| v.trim().to_string())
+}
+
+/// Directory in the prefix holding versions installed side by side, one
+/// prefix of its own per version.
+pub const VERSIONS_DIR: &str = "versions";
+
+/// Prefix of `version` installed side by side under `install_path`.
+pub fn versioned_prefix(install_path: &Path, version: &str) -> PathBuf {
+    install_path.join(VERSIONS_DIR).join(version)
+}
+
+/// Versions installed side by side under `install_path`, oldest first, with
+/// their prefixes.
+pub fn installed_versions(install_path: &Path) -> Vec<(WasmEdgeVersion, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(install_path.join(VERSIONS_DIR)) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<(WasmEdgeVersion, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|prefix| Some((installed_version(&prefix)?.parse().ok()?, prefix)))
+        .collect();
+    versions.sort();
+    versions
+}
+
+/// Side-by-side version `wasmedgeup default` linked into `install_path`,
+/// if the runtime there is such a link rather than an install of its own.
+pub fn default_version(install_path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(install_path.join("VERSION")).ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("default="))
+        .map(|v| v.trim().to_string())
+}
+
+/// Target of the link at `file` in a prefix to the same file of the version
+/// in `source_dir`, relative to the link's directory: `bin/wasmedge` links
+/// to `../versions/0.14.1/bin/wasmedge`.
+fn relative_target(source_dir: &Path, file: &Path) -> PathBuf {
+    let depth = file.parent().map_or(0, |parent| parent.components().count());
+    let mut target: PathBuf = std::iter::repeat_n("..", depth).collect();
+    target.push(source_dir);
+    target.push(file);
+    target
+}
+
+/// Prefix whose runtime `wasmedgeup default` linked to the side-by-side
+/// version installed at `prefix`, if it is the linked one.
+pub fn linked_from(prefix: &Path) -> Option<PathBuf> {
+    let versions = prefix.parent()?;
+    if versions.file_name()? != VERSIONS_DIR {
+        return None;
+    }
+    let root = versions.parent()?;
+    (versioned_prefix(root, &default_version(root)?) == prefix).then(|| root.to_path_buf())
+}
+
+/// Whether the installation at `install_path` was made read-only by
+/// `install --readonly`.
+pub fn is_readonly(install_path: &Path) -> bool {
+    has_receipt_line(install_path, "readonly=true")
+}
+
+/// Whether the installation at `install_path` has binaries patched to find
+/// its libraries, by `install --patch-rpath`.
+pub fn is_rpath_patched(install_path: &Path) -> bool {
+    has_receipt_line(install_path, "rpath=patched")
+}
+
+/// Whether `install --no-env` left the environment of the installation at
+/// `install_path` to the user.
+pub fn is_env_unmanaged(install_path: &Path) -> bool {
+    has_receipt_line(install_path, "env=false")
+}
+
+/// Owner, group and mode the installation at `install_path` was installed
+/// with, e.g. by `install --system`.
+pub fn installed_ownership(install_path: &Path) -> Option<Ownership> {
+    let id = |key| receipt_value(install_path, key).and_then(|v| v.parse().ok());
+    let ownership = Ownership {
+        uid: id("owner"),
+        gid: id("group"),
+        mode: receipt_value(install_path, "mode").and_then(|mode| u32::from_str_radix(&mode, 8).ok()),
+    };
+    (ownership != Ownership::default()).then_some(ownership)
+}
+
+fn has_receipt_line(install_path: &Path, wanted: &str) -> bool {
+    std::fs::read_to_string(install_path.join("VERSION"))
+        .is_ok_and(|content| content.lines().any(|line| line.trim() == wanted))
+}
+
+/// Value of the `key=value` line for `key` in the `VERSION` receipt of the
+/// installation at `install_path`.
+fn receipt_value(install_path: &Path, key: &str) -> Option<String> {
+    let content = std::fs::read_to_string(install_path.join("VERSION")).ok()?;
+    content.lines().find_map(|line| line.trim().strip_prefix(key)?.strip_prefix('=').map(str::to_string))
+}
+
+/// Directory in the prefix holding user snippets for the `env` script, e.g.
+/// exporting `WASMEDGE_PLUGIN_PATH`. Like plugins, it survives upgrades and
+/// `remove`.
+pub const ENV_SNIPPET_DIR: &str = "env.d";
+
+/// Shell the `env` script is written for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptShell {
+    /// `sh` and compatible shells
+    Posix,
+    /// The Windows command prompt
+    Cmd,
+    PowerShell,
+}
+
+impl ScriptShell {
+    /// The shell of the `env` file on `os`.
+    pub fn for_os(os: &OS) -> Self {
+        if *os == OS::Windows { ScriptShell::Cmd } else { ScriptShell::Posix }
+    }
+
+    /// The shell `wasmedgeup` was most likely run from on `os`: PowerShell
+    /// on Windows when it looks like the parent, otherwise that of the
+    /// `env` file.
+    pub fn detect(os: &OS) -> Self {
+        let var = |name| std::env::var(name).ok();
+        if *os == OS::Windows && is_powershell(var("PSModulePath").as_deref(), var("USERPROFILE").as_deref()) {
+            ScriptShell::PowerShell
+        } else {
+            Self::for_os(os)
+        }
+    }
+
+    fn comment(self) -> &'static str {
+        if self == ScriptShell::Cmd { "rem" } else { "#" }
+    }
+
+    /// Extensions of the `env.d` snippets for this shell.
+    fn snippet_extensions(self) -> &'static [&'static str] {
+        match self {
+            ScriptShell::Posix => &["sh"],
+            ScriptShell::Cmd => &["bat", "cmd"],
+            ScriptShell::PowerShell => &["ps1"],
+        }
+    }
+}
+
+/// Whether `PSModulePath` is that of a PowerShell session. Windows sets it
+/// for every process, but only PowerShell adds the module directory in the
+/// user's profile when it starts.
+fn is_powershell(ps_module_path: Option<&str>, user_profile: Option<&str>) -> bool {
+    let (Some(paths), Some(profile)) = (ps_module_path, user_profile) else {
+        return false;
+    };
+    let profile = profile.trim_end_matches('\\').to_lowercase();
+    !profile.is_empty() && paths.split(';').any(|path| path.to_lowercase().starts_with(&format!("{}\\", profile)))
+}
+
+/// Shell script that puts the installation at `install_path` on the path,
+/// followed by the snippets in its `env.d`. Binaries with a patched rpath
+/// need no library path.
+pub fn env_script(install_path: &Path, os: &OS, patch_rpath: bool) -> String {
+    shell_env_script(install_path, os, ScriptShell::for_os(os), patch_rpath)
+}
+
+/// [`env_script`] for `shell` rather than the shell of the `env` file.
+pub fn shell_env_script(install_path: &Path, os: &OS, shell: ScriptShell, patch_rpath: bool) -> String {
+    let mut content = String::new();
+    let bin = install_path.join("bin");
+    let lib = install_path.join("lib");
+    let library_path = match os {
+        OS::Linux(_) | OS::FreeBsd | OS::OpenBsd => Some("LD_LIBRARY_PATH"),
+        OS::Darwin => Some("DYLD_LIBRARY_PATH"),
+        OS::Windows => None,
+    }
+    .filter(|_| !patch_rpath);
+
+    match shell {
+        ScriptShell::Posix => {
+            content.push_str("#!/bin/sh\n");
+            content.push_str(&format!("export PATH={}:$PATH\n", bin.display()));
+            if let Some(var) = library_path {
+                content.push_str(&format!("export {var}={}:${var}\n", lib.display()));
+            }
+        }
+        ScriptShell::Cmd => {
+            content.push_str("@echo off\n");
+            content.push_str(&format!("set PATH={};%PATH%\n", bin.display()));
+        }
+        ScriptShell::PowerShell => {
+            // For `wasmedgeup env | Out-String | Invoke-Expression`
+            let separator = if *os == OS::Windows { ';' } else { ':' };
+            content.push_str(&format!("$env:PATH = \"{}{}\" + $env:PATH\n", bin.display(), separator));
+            if let Some(var) = library_path {
+                content.push_str(&format!("$env:{var} = \"{}{}\" + $env:{var}\n", lib.display(), separator));
+            }
+        }
+    }
+
+    let comment = shell.comment();
+    for (name, snippet) in env_snippets(install_path, shell) {
+        content.push_str(&format!("\n{} {}/{}\n{}", comment, ENV_SNIPPET_DIR, name, snippet));
+        if !snippet.ends_with('\n') {
+            content.push('\n');
+        }
+    }
+
+    content
+}
+
+/// Comment marker of the `env` script on `os`.
+fn script_comment(os: &OS) -> &'static str {
+    ScriptShell::for_os(os).comment()
+}
+
+/// Snippets in the `env.d` of `install_path` for `shell`, by file name:
+/// `*.sh` for the POSIX script, `*.bat` or `*.cmd` for the command prompt
+/// and `*.ps1` for PowerShell. Other files, such as editor backups, are
+/// ignored.
+fn env_snippets(install_path: &Path, shell: ScriptShell) -> Vec<(String, String)> {
+    let extensions = shell.snippet_extensions();
+    let Ok(entries) = std::fs::read_dir(install_path.join(ENV_SNIPPET_DIR)) else {
+        return Vec::new();
+    };
+    let mut snippets: Vec<(String, String)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().and_then(|e| e.to_str()).is_some_and(|e| extensions.contains(&e)))
+        .filter_map(|path| match std::fs::read_to_string(&path) {
+            Ok(snippet) => Some((path.file_name()?.to_string_lossy().into_owned(), snippet)),
+            Err(e) => {
+                log::warn!("{}", t!("env-snippet-unreadable", path = path.display(), error = e.to_string()));
+                None
+            }
+        })
+        .collect();
+    snippets.sort();
+    snippets
+}
+
+/// Whether moving from `installed` to `requested` goes back to an older
+/// release. Versions that do not parse as semver are never a downgrade.
+pub fn is_downgrade(installed: &str, requested: &str) -> bool {
+    let parse = |v: &str| v.parse::<WasmEdgeVersion>().ok();
+    match (parse(installed), parse(requested)) {
+        (Some(installed), Some(requested)) => requested < installed,
+        _ => false,
+    }
+}
+
+/// `lines` indented for listing under a message, one per line.
+fn indented(lines: &[impl std::fmt::Display]) -> String {
+    lines.iter().map(|line| format!("  {}", line)).collect::<Vec<_>>().join("\n")
+}
+
+/// Whether the manifest entry `file` names a path inside the prefix.
+fn is_inside_prefix(file: &str) -> bool {
+    !file.is_empty() && Path::new(file).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Asks the user a yes/no question, e.g. whether to accept a fallback archive.
+pub type Confirm = Arc<dyn Fn(&str) -> Result<bool> + Send + Sync>;
+
+/// A runtime built or unpacked into staging, ready to be moved into place.
+struct StagedRuntime {
+    /// Tree laid out like a release archive
+    tree: PathBuf,
+    /// The archive or build it came from
+    source: String,
+    /// Digests of what was fetched
+    checksums: BTreeMap<String, String>,
+    /// Directory holding the plugins a source build built along with the
+    /// runtime, and the commit it built
+    built_plugins: Option<(PathBuf, String)>,
+}
+
+pub struct Installer<F: Fetcher = Downloader> {
     install_path: PathBuf,
     temp_dir: PathBuf,
     platform: Platform,
-    downloader: Downloader,
+    downloader: F,
+    patch_rpath: bool,
+    static_build: bool,
+    components: Vec<RuntimeComponent>,
+    bin_only: bool,
+    checksums: Option<ChecksumFile>,
+    patch_server: Option<String>,
+    patch_base: Option<String>,
+    support_manifest: Option<String>,
+    readonly: bool,
+    no_env: bool,
+    ownership: Option<Ownership>,
+    selinux_relabel: bool,
+    fsync: bool,
+    source_build: Option<SourceBuild>,
+    recorded_version: Option<String>,
+    max_time: Option<Duration>,
+    confirm_fallback: Option<Confirm>,
+    events: Events,
+    strict: bool,
+    extract_limits: ExtractLimits,
+    fs: Arc<dyn FileSystem>,
 }
 
+#[cfg(test)]
 impl Installer {
     pub fn new(install_path: PathBuf, temp_dir: PathBuf, platform: Platform) -> Self {
+        Self::with_fetcher(install_path, temp_dir, platform, Downloader::new())
+    }
+}
+
+impl<F: Fetcher> Installer<F> {
+    /// Create an installer that downloads through `fetcher` instead of the
+    /// default HTTP client.
+    pub fn with_fetcher(install_path: PathBuf, temp_dir: PathBuf, platform: Platform, fetcher: F) -> Self {
         Self {
             install_path,
             temp_dir,
             platform,
-            downloader: Downloader::new(),
+            downloader: fetcher,
+            patch_rpath: false,
+            static_build: false,
+            components: RuntimeComponent::ALL.to_vec(),
+            bin_only: false,
+            checksums: None,
+            patch_server: None,
+            patch_base: None,
+            support_manifest: None,
+            readonly: false,
+            no_env: false,
+            ownership: None,
+            selinux_relabel: false,
+            fsync: false,
+            source_build: None,
+            recorded_version: None,
+            max_time: None,
+            confirm_fallback: None,
+            events: Events::default(),
+            strict: false,
+            extract_limits: ExtractLimits::default(),
+            fs: Arc::new(RealFs),
+        }
+    }
+
+    /// Install into the side-by-side prefix of `version` under the
+    /// installation path (`versions/<version>`) instead of into the
+    /// installation path itself, leaving other versions in place.
+    pub fn with_versioned_layout(mut self, version: Option<&WasmEdgeVersion>) -> Self {
+        if let Some(version) = version {
+            self.install_path = versioned_prefix(&self.install_path, &version.tag());
+        }
+        self
+    }
+
+    /// Install with the options the runtime already in the installation
+    /// path was installed with: its platform, components, read-only files,
+    /// env file handling, rpath patching, static build, ownership, SELinux
+    /// relabeling, fsync and `--bin-only`. For upgrades in place.
+    pub fn with_installed_options(mut self) -> Self {
+        let prefix = self.install_path.clone();
+        if let Some(platform) = receipt_value(&prefix, "platform").and_then(|p| Platform::parse(&p).ok()) {
+            self.platform = platform;
+        }
+        let components = Manifest::read(&prefix)
+            .ok()
+            .flatten()
+            .map(|m| m.components)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|c| RuntimeComponent::from_name(c))
+            .collect();
+        self.with_components(components)
+            .with_readonly(is_readonly(&prefix))
+            .with_no_env(is_env_unmanaged(&prefix))
+            .with_rpath_patching(is_rpath_patched(&prefix))
+            .with_bin_only(has_receipt_line(&prefix, "bin-only=true"))
+            .with_static_build(has_receipt_line(&prefix, "static=true"))
+            .with_ownership(installed_ownership(&prefix))
+            .with_selinux_relabel(has_receipt_line(&prefix, "selinux-relabel=true"))
+            .with_fsync(has_receipt_line(&prefix, "fsync=true"))
+    }
+
+    /// Prefix the runtime is installed into.
+    pub fn install_path(&self) -> &Path {
+        &self.install_path
+    }
+
+    /// Patch installed binaries to find the managed libraries on their own
+    /// instead of exporting a library search path in the env file.
+    pub fn with_rpath_patching(mut self, enabled: bool) -> Self {
+        self.patch_rpath = enabled;
+        self
+    }
+
+    /// Prefer the statically linked CLI archive, failing if the release has none.
+    pub fn with_static_build(mut self, enabled: bool) -> Self {
+        self.static_build = enabled;
+        self
+    }
+
+    /// Restrict the install to a subset of the release, e.g. only the headers
+    /// for projects that vendor their own libwasmedge.
+    pub fn with_components(mut self, components: Vec<RuntimeComponent>) -> Self {
+        if !components.is_empty() {
+            self.components = components;
+        }
+        self
+    }
+
+    /// Unpack only the `wasmedge` CLI and the libwasmedge it loads, skipping
+    /// the other tools, headers and static libraries, for minimal images.
+    pub fn with_bin_only(mut self, enabled: bool) -> Self {
+        self.bin_only = enabled;
+        if enabled {
+            self.components = vec![RuntimeComponent::Bin, RuntimeComponent::Lib];
         }
+        self
     }
 
-    pub async fn install_runtime(&self, version: &str) -> Result<()> {
+    /// Require every downloaded artifact to match a pinned digest.
+    pub fn with_checksums(mut self, checksums: Option<ChecksumFile>) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// Keep each installed runtime archive as the base of patch upgrades
+    /// with deltas from `server`.
+    pub fn with_patch_server(mut self, server: Option<String>) -> Self {
+        self.patch_server = server;
+        self
+    }
+
+    /// Upgrade from `from` by patching its kept archive, when a patch server
+    /// is configured. Only `update` does; other installs download in full.
+    pub fn with_patch_base(mut self, from: Option<&WasmEdgeVersion>) -> Self {
+        self.patch_base = from.map(WasmEdgeVersion::tag);
+        self
+    }
+
+    /// Check versions against the support manifest at `url`.
+    pub fn with_support_manifest(mut self, url: Option<String>) -> Self {
+        self.support_manifest = url;
+        self
+    }
+
+    /// Mark the installed files read-only once the install succeeds, e.g. to
+    /// protect a provisioned build image.
+    pub fn with_readonly(mut self, enabled: bool) -> Self {
+        self.readonly = enabled;
+        self
+    }
+
+    /// Write no `env` file, for users who manage their environment in
+    /// dotfiles or Nix. `wasmedgeup env` and `exec` still work on demand.
+    pub fn with_no_env(mut self, enabled: bool) -> Self {
+        self.no_env = enabled;
+        self
+    }
+
+    /// Give every installed file and directory this owner, group and mode
+    /// instead of leaving them to the invoking user's umask.
+    pub fn with_ownership(mut self, ownership: Option<Ownership>) -> Self {
+        self.ownership = ownership;
+        self
+    }
+
+    /// Label the installed files for SELinux when it is enforcing.
+    pub fn with_selinux_relabel(mut self, enabled: bool) -> Self {
+        self.selinux_relabel = enabled;
+        self
+    }
+
+    /// Build the runtime from source instead of downloading a release
+    /// archive, e.g. for patched builds or on the BSDs, which have no
+    /// official binaries.
+    pub fn with_source_build(mut self, build: Option<SourceBuild>) -> Self {
+        self.source_build = build;
+        self
+    }
+
+    /// Record the installation as `version` rather than the release it was
+    /// resolved to, e.g. the branch or commit of an asdf `ref:` install.
+    pub fn with_recorded_version(mut self, version: Option<String>) -> Self {
+        self.recorded_version = version;
+        self
+    }
+
+    /// Flush the installed files to disk before reporting success, for hosts
+    /// that may lose power right after provisioning.
+    pub fn with_fsync(mut self, enabled: bool) -> Self {
+        self.fsync = enabled;
+        self
+    }
+
+    /// Give up if fetching and unpacking the runtime takes longer than
+    /// `max_time`. Once files are being moved into the prefix the install
+    /// runs to completion.
+    pub fn with_max_time(mut self, max_time: Option<Duration>) -> Self {
+        self.max_time = max_time;
+        self
+    }
+
+    /// Ask through `confirm` before falling back to an archive that only
+    /// runs under emulation. Without it such fallbacks are refused.
+    pub fn with_fallback_confirmation(mut self, confirm: Option<Confirm>) -> Self {
+        self.confirm_fallback = confirm;
+        self
+    }
+
+    /// Make changes to the installation through `fs`, e.g. a [`DryRunFs`](fsutil::DryRunFs).
+    pub fn with_filesystem(mut self, fs: Arc<dyn FileSystem>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Send progress [`Event`]s of `install_runtime` to `events`.
+    pub fn with_events(mut self, events: Events) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Refuse artifacts without a pinned digest and fallbacks to
+    /// other archives than the platform's own.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Abort unpacking archives that exceed `limits`.
+    pub fn with_extract_limits(mut self, limits: ExtractLimits) -> Self {
+        self.extract_limits = limits;
+        self
+    }
+
+    /// Check a downloaded artifact against the pins, if any.
+    fn verify_pinned(&self, artifact_name: &str, path: &Path) -> Result<()> {
+        self.events.emit(Event::Verifying { artifact: artifact_name.to_string() });
+        self.require_pin(artifact_name)?;
+        match &self.checksums {
+            Some(pins) => pins.verify(artifact_name, path),
+            None => Ok(()),
+        }
+    }
+
+    /// In `--strict` mode, refuse an artifact the pin file does not cover.
+    fn require_pin(&self, artifact_name: &str) -> Result<()> {
+        let pinned = self.checksums.as_ref().is_some_and(|pins| pins.get(artifact_name).is_some());
+        if self.strict && !pinned {
+            anyhow::bail!(t!("strict-no-checksum", name = artifact_name));
+        }
+        Ok(())
+    }
+
+    /// Files an install puts in the prefix, outside the plugin directory.
+    fn installed_paths(&self) -> Vec<PathBuf> {
+        ["bin", "lib", "include", "debug", "env", "VERSION", MANIFEST_FILE]
+            .iter()
+            .map(|name| self.install_path.join(name))
+            .collect()
+    }
+
+    /// Make the installed files read-only, or writable again.
+    fn set_readonly(&self, readonly: bool) -> Result<()> {
+        for path in self.installed_paths() {
+            fsutil::set_tree_readonly(self.fs.as_ref(), &path, readonly)?;
+        }
+        Ok(())
+    }
+
+    fn has(&self, component: RuntimeComponent) -> bool {
+        self.components.contains(&component)
+    }
+
+    /// Refuse to touch an installation that running processes still use,
+    /// unless `force` is set, in which case only warn.
+    pub fn ensure_not_in_use(&self, force: bool) -> Result<()> {
+        if !self.install_path.exists() {
+            return Ok(());
+        }
+        let processes = process::find_using(&self.install_path);
+        if processes.is_empty() {
+            return Ok(());
+        }
+
+        let list = indented(&processes);
+        if force {
+            log::warn!("{}", t!("install-in-use-forced", path = self.install_path.display(), processes = list));
+            Ok(())
+        } else {
+            anyhow::bail!(t!("install-in-use", path = self.install_path.display(), processes = list))
+        }
+    }
+
+    pub async fn install_runtime(&self, version: &WasmEdgeVersion) -> Result<()> {
+        let result = self.install_runtime_files(version).await;
+        self.events.finish("wasmedge", &version.tag(), &result);
+        result
+    }
+
+    /// Build or download and unpack the runtime into `staging`.
+    async fn stage_runtime(&self, version: &WasmEdgeVersion, staging: &Path) -> Result<StagedRuntime> {
+        if let Some(build) = &self.source_build {
+            if self.strict {
+                anyhow::bail!(t!("strict-source-build"));
+            }
+            self.events.emit(Event::Resolved {
+                name: "wasmedge".into(),
+                version: version.to_string(),
+                artifact: format!("source:{}", build.git_ref()),
+            });
+            let built = build.build(staging).await?;
+            // Plugins built along go to the plugin directory rather than
+            // along with the libraries into `lib/wasmedge`
+            let plugins = staging.join("plugins");
+            if built.plugin_dir().exists() {
+                std::fs::rename(built.plugin_dir(), &plugins)?;
+            }
+            return Ok(StagedRuntime {
+                source: format!("source:{}@{}", build.git_ref(), built.commit),
+                tree: built.prefix,
+                checksums: BTreeMap::new(),
+                built_plugins: Some((plugins, built.commit)),
+            });
+        }
+        if !self.platform.os.has_release_binaries() {
+            anyhow::bail!(t!("no-release-binaries", os = self.platform.os));
+        }
+        // Download WasmEdge release
+        let package_name = self.resolve_package_name(version).await?;
+        let asset_name = version.asset_name(&package_name);
+        self.events.emit(Event::Resolved {
+            name: "wasmedge".into(),
+            version: version.to_string(),
+            artifact: asset_name.clone(),
+        });
+        let archive_path = staging.join(format!("wasmedge-{}.tar.gz", version));
+        self.download_runtime_archive(version, &package_name, &archive_path).await?;
+        self.verify_pinned(&asset_name, &archive_path)?;
+        let archive_sha256 = checksum::sha256_file(&archive_path)?;
+        if self.patch_server.is_some() && !self.fs.is_dry_run() {
+            if let Err(e) = delta::keep_archive(&version.tag(), &package_name, &archive_path) {
+                log::debug!("Could not keep {} for patch upgrades: {:#}", asset_name, e);
+            }
+        }
+        let extracted_dir = self.extract_archive(&archive_path, staging)?;
+        Ok(StagedRuntime {
+            tree: extracted_dir,
+            source: asset_name.clone(),
+            checksums: BTreeMap::from([(asset_name, archive_sha256)]),
+            built_plugins: None,
+        })
+    }
+
+    async fn install_runtime_files(&self, version: &WasmEdgeVersion) -> Result<()> {
         // Create necessary directories
-        fs::create_dir_all(&self.install_path).await?;
+        self.fs.create_dir_all(&self.install_path)?;
         fs::create_dir_all(&self.temp_dir).await?;
 
         // Prepare paths for installation
-        let bin_dir = self.install_path.join("bin");
-        let lib_dir = self.install_path.join("lib");
-        let include_dir = self.install_path.join("include");
-        let plugin_dir = self.install_path.join("plugin");
+        if self.has(RuntimeComponent::Bin) {
+            self.fs.create_dir_all(&self.install_path.join("bin"))?;
+            self.fs.create_dir_all(&self.install_path.join("plugin"))?;
+        }
+        if self.has(RuntimeComponent::Lib) {
+            self.fs.create_dir_all(&self.install_path.join("lib"))?;
+        }
+        if self.has(RuntimeComponent::Include) {
+            self.fs.create_dir_all(&self.install_path.join("include"))?;
+        }
 
-        fs::create_dir_all(&bin_dir).await?;
-        fs::create_dir_all(&lib_dir).await?;
-        fs::create_dir_all(&include_dir).await?;
-        fs::create_dir_all(&plugin_dir).await?;
+        // A read-only installation has to be writable again to be replaced
+        if is_readonly(&self.install_path) {
+            self.set_readonly(false)?;
+        }
 
-        // Download WasmEdge release
-        let package_name = self.platform.get_release_package_name(version);
-        let download_url = format!(
-            "https://github.com/{}/releases/download/{}/WasmEdge-{}-{}",
-            WASMEDGE_GITHUB_REPO, version, version, package_name
-        );
+        // Files replaced while in use by an earlier upgrade can go now
+        fsutil::remove_retired(self.fs.as_ref(), &self.install_path)?;
+
+        // A build takes long and changes nothing outside staging, so a dry
+        // run only says it would happen
+        if let Some(build) = self.source_build.as_ref().filter(|_| self.fs.is_dry_run()) {
+            return self.fs.skip(format!("build WasmEdge {} from source into {}", build.git_ref(), self.install_path.display()));
+        }
+        // Nor is the archive downloaded just to list the files it holds
+        if self.fs.is_dry_run() {
+            if !self.platform.os.has_release_binaries() {
+                anyhow::bail!(t!("no-release-binaries", os = self.platform.os));
+            }
+            let package_name = self.resolve_package_name(version).await?;
+            let asset_name = version.asset_name(&package_name);
+            self.require_pin(&asset_name)?;
+            self.events.emit(Event::Resolved {
+                name: "wasmedge".into(),
+                version: version.to_string(),
+                artifact: asset_name.clone(),
+            });
+            let url = release_download_url(version, &asset_name);
+            return self.fs.skip(format!("download {} and install it into {}", url, self.install_path.display()));
+        }
 
-        println!("Downloading from: {}", download_url);
+        // All intermediate state lives in a uniquely named staging directory
+        // that is removed when `staging` is dropped, even if a step fails.
+        let staging = cache::temp_dir_in(&self.temp_dir)?;
+        let staged = self.stage_runtime(version, staging.path());
+        let StagedRuntime { tree, source, checksums, built_plugins } = match self.max_time {
+            // Dropping the timed out future leaves the staging directory to
+            // be removed; nothing has touched the prefix yet
+            Some(max_time) => tokio::time::timeout(max_time, staged)
+                .await
+                .map_err(|_| anyhow::anyhow!(t!("install-timed-out", secs = max_time.as_secs())))??,
+            None => staged.await?,
+        };
 
-        let archive_path = self.temp_dir.join(format!("wasmedge-{}.tar.gz", version));
-        self.downloader.download_file(&download_url, &archive_path).await?;
+        // From here on files land in the prefix; an interrupt waits for this
+        // to finish rather than leave a mix of old and new files behind
+        let _commit = shutdown::commit_section().await;
+        let license_files = licenses::gather(self.fs.as_ref(), &tree, &self.install_path.join(LICENSES_DIR).join("wasmedge"))?;
+        let license_files: Vec<String> = license_files
+            .iter()
+            .filter_map(|path| path.strip_prefix(&self.install_path).ok())
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+            .collect();
+        let mut files = self.tree_files(&tree)?;
+        files.extend(license_files.iter().cloned());
+        files.sort();
+        self.remove_replaced_files(&files)?;
+        self.install_tree(&tree)?;
+        if let (Some(build), Some((plugins, commit))) = (&self.source_build, &built_plugins) {
+            plugin::install_built_plugins(self.fs.as_ref(), &self.install_path, &self.platform, build, plugins, commit)?;
+        }
 
-        // Extract archive
-        self.extract_archive(&archive_path).await?;
+        if self.patch_rpath && self.has(RuntimeComponent::Bin) && !self.fs.is_dry_run() {
+            rpath::patch(&self.install_path, &self.platform.os)?;
+        }
 
         // Set up environment variables
-        self.setup_environment().await?;
+        if self.has(RuntimeComponent::Bin) && !self.no_env {
+            self.setup_environment()?;
+        }
 
-        // Cleanup
-        fs::remove_file(archive_path).await?;
+        // Record which release the files came from, so embedders can match
+        // vendored libraries against the installed headers
+        let components = self
+            .components
+            .iter()
+            .map(|c| format!("{:?}", c).to_lowercase())
+            .collect::<Vec<_>>()
+            .join(",");
+        let recorded_version = self.recorded_version.clone().unwrap_or_else(|| version.tag());
+        let mut receipt = format!("version={}\ncomponents={}\nplatform={}\n", recorded_version, components, self.platform.shorthand());
+        if self.readonly {
+            receipt.push_str("readonly=true\n");
+        }
+        if self.no_env {
+            receipt.push_str("env=false\n");
+        }
+        if self.patch_rpath {
+            receipt.push_str("rpath=patched\n");
+        }
+        if self.bin_only {
+            receipt.push_str("bin-only=true\n");
+        }
+        if self.static_build {
+            receipt.push_str("static=true\n");
+        }
+        if let Some(ownership) = &self.ownership {
+            if let Some(uid) = ownership.uid {
+                receipt.push_str(&format!("owner={}\n", uid));
+            }
+            if let Some(gid) = ownership.gid {
+                receipt.push_str(&format!("group={}\n", gid));
+            }
+            if let Some(mode) = ownership.mode {
+                receipt.push_str(&format!("mode={:o}\n", mode));
+            }
+        }
+        if self.selinux_relabel {
+            receipt.push_str("selinux-relabel=true\n");
+        }
+        if self.fsync {
+            receipt.push_str("fsync=true\n");
+        }
+        self.fs.write(&self.install_path.join("VERSION"), receipt.as_bytes())?;
+        let manifest = Manifest {
+            version: recorded_version,
+            os: self.platform.os.to_string().to_lowercase(),
+            arch: self.platform.arch.to_string(),
+            archive: source,
+            components: components.split(',').map(str::to_string).collect(),
+            installed_at: humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string(),
+            wasmedgeup_version: env!("CARGO_PKG_VERSION").to_string(),
+            checksums,
+            files,
+            licenses: license_files,
+        };
+        self.fs.write(&self.install_path.join(MANIFEST_FILE), manifest.to_toml()?.as_bytes())?;
+        if let Some(ownership) = &self.ownership {
+            ownership.apply(self.fs.as_ref(), &self.install_path)?;
+        }
+        if matches!(self.platform.os, OS::Linux(_)) && !self.fs.is_dry_run() {
+            self.check_selinux()?;
+        }
+        if self.readonly {
+            self.set_readonly(true)?;
+        }
+        if self.fsync && !self.fs.is_dry_run() {
+            fsutil::sync_tree(&self.install_path)?;
+        }
 
         Ok(())
     }
 
-    async fn extract_archive(&self, archive_path: &Path) -> Result<()> {
-        let file_content = fs::read(archive_path).await.context("Failed to read archive file")?;
-        let gz = GzDecoder::new(&file_content[..]);
-        let mut archive = Archive::new(gz);
-        
-        // Extract to temp directory first
-        archive.unpack(&self.temp_dir).context("Failed to extract archive")?;
+    /// URL of the archive an install of `version` downloads, and its digest
+    /// from the pin file or the release, when known.
+    pub async fn resolve_runtime_archive(&self, version: &WasmEdgeVersion) -> Result<(String, Option<String>)> {
+        if !self.platform.os.has_release_binaries() {
+            anyhow::bail!(t!("no-release-binaries", os = self.platform.os));
+        }
+        let package_name = self.resolve_package_name(version).await?;
+        let asset_name = version.asset_name(&package_name);
+        let pinned = self.checksums.as_ref().and_then(|pins| pins.get(&asset_name));
+        let sha256 = match pinned {
+            Some(sha256) => Some(sha256.to_string()),
+            None => {
+                let release = release::fetch_release(&self.downloader, version).await?;
+                release::fetch_release_checksums(&self.downloader, &release)
+                    .await?
+                    .and_then(|sums| sums.get(&asset_name).map(str::to_string))
+            }
+        };
+        Ok((release_download_url(version, &asset_name), sha256))
+    }
 
-        // Move files to their proper locations
-        let extracted_dir = self.temp_dir.join(format!("WasmEdge-{}-{}", self.platform.os, self.platform.arch));
-        
-        println!("Extracting to: {}", extracted_dir.display());
+    /// Download the runtime archive for `version` into `output_dir` without
+    /// installing it, verified against the published digest and any pins.
+    pub async fn download_runtime(&self, version: &WasmEdgeVersion, output_dir: &Path) -> Result<Downloaded> {
+        if !self.platform.os.has_release_binaries() {
+            anyhow::bail!(t!("no-release-binaries", os = self.platform.os));
+        }
+        let package_name = self.resolve_package_name(version).await?;
+        let asset_name = version.asset_name(&package_name);
+        let release = release::fetch_release(&self.downloader, version).await?;
+        let expected = release::fetch_release_checksums(&self.downloader, &release)
+            .await?
+            .and_then(|sums| sums.get(&asset_name).map(str::to_string));
+
+        let staging = cache::temp_dir_in(output_dir)?;
+        let staged = staging.path().join(&asset_name);
+        self.downloader.download_file(&release_download_url(version, &asset_name), &staged).await?;
+        self.verify_pinned(&asset_name, &staged)?;
+        download::keep(&staged, output_dir, &asset_name, expected.as_deref())
+    }
 
-        // Move bin files
-        if let Ok(mut entries) = fs::read_dir(extracted_dir.join("bin")).await {
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                let target = self.install_path.join("bin").join(entry.file_name());
-                fs::rename(entry.path(), target).await?;
+    /// Relabel the installation if asked to, or warn when an enforcing
+    /// SELinux policy is likely to block libraries in this prefix.
+    fn check_selinux(&self) -> Result<()> {
+        if lsm::selinux_mode() != lsm::SelinuxMode::Enforcing {
+            if self.selinux_relabel {
+                log::info!("SELinux is not enforcing, skipping relabelling");
             }
+            return Ok(());
+        }
+        if self.selinux_relabel {
+            lsm::relabel(&self.install_path)?;
+        } else if !lsm::is_labelled_prefix(&self.install_path) {
+            log::warn!("{}", t!("selinux-unlabelled", path = self.install_path.display()));
+        }
+        Ok(())
+    }
+
+    /// Download the runtime archive `package_name` of `version` to `dest`.
+    /// With a patch server, an upgrade first tries to rebuild the archive
+    /// from the one kept for the installed version, falling back to the full
+    /// download if that fails.
+    async fn download_runtime_archive(&self, version: &WasmEdgeVersion, package_name: &str, dest: &Path) -> Result<()> {
+        let from = self.patch_base.as_ref().filter(|from| **from != version.tag());
+        if let (Some(server), Some(from)) = (&self.patch_server, from) {
+            match delta::fetch_patched(&self.downloader, server, from, version, package_name, dest).await {
+                Ok(true) => {
+                    log::info!("{}", t!("patch-applied", from = from, version = version));
+                    return Ok(());
+                }
+                Ok(false) => {}
+                Err(e) => log::warn!("{}", t!("patch-fallback", from = from, version = version, error = format!("{:#}", e))),
+            }
+        }
+
+        let url = release_download_url(version, &version.asset_name(package_name));
+        let progress = |bytes, total| self.events.emit(Event::Downloading { url: url.clone(), bytes, total });
+        self.downloader.download_file_with_progress(&url, dest, &progress).await
+    }
+
+    /// Size of the runtime archive installing `version` would download, if
+    /// the server reports it.
+    pub async fn download_size(&self, version: &WasmEdgeVersion) -> Result<Option<ArchiveSize>> {
+        let package_name = self.resolve_package_name(version).await?;
+        let url = release_download_url(version, &version.asset_name(&package_name));
+        download::archive_size(&self.downloader, &url, None).await
+    }
+
+    /// Warn when upstream lists `version` as deprecated, unless `allow` is set.
+    /// Failing to fetch the manifest never blocks an install.
+    pub async fn warn_if_deprecated(&self, version: &WasmEdgeVersion, allow: bool) {
+        if allow {
+            return;
+        }
+        if let Some(warning) = self.deprecation_warning(version).await {
+            log::warn!("{}", warning);
+        }
+    }
+
+    /// The warning for installing `version`, if the support manifest lists
+    /// it as deprecated.
+    async fn deprecation_warning(&self, version: &WasmEdgeVersion) -> Option<String> {
+        match release::fetch_version_manifest(&self.downloader, self.support_manifest.as_deref()).await {
+            Ok(manifest) if manifest.status(&version.tag()) == Some(SupportStatus::Deprecated) => {
+                Some(t!("install-deprecated", version = version, maintained = manifest.maintained.join(", ")))
+            }
+            Ok(_) => None,
+            Err(e) => {
+                log::debug!("Could not check deprecation status: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Install the debug symbol archive published for `version` into
+    /// `<install_path>/debug`, returning that directory.
+    pub async fn install_debug_symbols(&self, version: &WasmEdgeVersion) -> Result<PathBuf> {
+        let release = release::fetch_release(&self.downloader, version).await?;
+        let package_name = self.platform.get_release_package_name(&version.tag());
+        let platform_key = package_name.trim_end_matches(".tar.gz");
+        let asset = release
+            .find_component_asset(&version.tag(), "debugsymbols", platform_key)
+            .with_context(|| t!("debug-symbols-unpublished", version = version, os = self.platform.os, arch = self.platform.arch))?;
+
+        let debug_dir = self.install_path.join("debug");
+        if self.fs.is_dry_run() {
+            self.require_pin(&asset.name)?;
+            self.fs.skip(format!("download {} and install it into {}", asset.browser_download_url, debug_dir.display()))?;
+            return Ok(debug_dir);
+        }
+
+        let staging = cache::temp_dir_in(&self.temp_dir)?;
+        let archive_path = staging.path().join(&asset.name);
+        self.downloader.download_file(&asset.browser_download_url, &archive_path).await?;
+        self.verify_pinned(&asset.name, &archive_path)?;
+
+        let unpacked = staging.path().join("unpacked");
+        let file = std::fs::File::open(&archive_path).context("Failed to open debug symbol archive")?;
+        extract::unpack_tar_gz(file, &unpacked, &self.extract_limits, |_| {}).context("Failed to extract debug symbol archive")?;
+
+        // Archives carry a single top-level directory named after the asset
+        let mut root = unpacked.clone();
+        let mut entries = std::fs::read_dir(&unpacked)?.flatten().collect::<Vec<_>>();
+        if entries.len() == 1 && entries[0].path().is_dir() {
+            root = entries.remove(0).path();
         }
 
-        // Move lib files
+        fsutil::move_tree(self.fs.as_ref(), &root, &debug_dir)?;
+        if is_readonly(&self.install_path) {
+            fsutil::set_tree_readonly(self.fs.as_ref(), &debug_dir, true)?;
+        }
+        Ok(debug_dir)
+    }
+
+    /// Archive to install `version` from: the static build if asked for,
+    /// otherwise the first of the platform's candidates the release
+    /// publishes. Falling back is reported, and needs confirmation when the
+    /// archive only runs under emulation.
+    async fn resolve_package_name(&self, version: &WasmEdgeVersion) -> Result<String> {
+        if self.static_build {
+            return self.resolve_static_package_name(version).await;
+        }
+        let candidates = self.platform.release_package_candidates(&version.tag());
+        // Only some releases publish 32-bit ARM archives, so always check
+        if candidates.len() == 1 && self.platform.arch != Architecture::Armv7 {
+            return Ok(candidates[0].package.clone());
+        }
+        let release = match release::fetch_release(&self.downloader, version).await {
+            Ok(release) => release,
+            Err(e) => {
+                log::debug!("Could not list the archives of {}, assuming {} exists: {:#}", version, candidates[0].package, e);
+                return Ok(candidates[0].package.clone());
+            }
+        };
+        let available = release.runtime_asset_names(&version.tag());
+        let preferred = &candidates[0].package;
+        for candidate in &candidates {
+            if !available.contains(&version.asset_name(&candidate.package).as_str()) {
+                log::debug!("WasmEdge {} publishes no {} archive", version, candidate.package);
+                continue;
+            }
+            if candidate.package != *preferred {
+                if self.strict {
+                    anyhow::bail!(t!("strict-fallback", version = version, preferred = preferred, fallback = &candidate.package));
+                }
+                if candidate.needs_confirmation {
+                    let question = t!("package-fallback-confirm", version = version, preferred = preferred, fallback = &candidate.package);
+                    let accepted = match &self.confirm_fallback {
+                        Some(confirm) => confirm(&question)?,
+                        None => false,
+                    };
+                    if !accepted {
+                        anyhow::bail!(t!("package-fallback-declined", version = version, preferred = preferred, fallback = &candidate.package));
+                    }
+                }
+                output::status(t!("package-fallback", version = version, preferred = preferred, fallback = &candidate.package));
+            }
+            return Ok(candidate.package.clone());
+        }
+        if self.platform.arch == Architecture::Armv7 {
+            return Err(self.armv7_unavailable(version).await);
+        }
+        anyhow::bail!(t!("package-unpublished", version = version, os = self.platform.os, arch = self.platform.arch, available = indented(&available)))
+    }
+
+    /// Error for a release without 32-bit ARM archives, naming the newest
+    /// release that has them.
+    async fn armv7_unavailable(&self, version: &WasmEdgeVersion) -> anyhow::Error {
+        let suffix = format!("_{}.tar.gz", self.platform.arch);
+        let last = release::fetch_releases(&self.downloader)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|r| r.runtime_asset_names(&r.tag_name).iter().any(|name| name.ends_with(&suffix)))
+            .map(|r| r.tag_name);
+        match last {
+            Some(last) => anyhow::anyhow!(t!("armv7-unavailable", version = version, last = last)),
+            None => anyhow::anyhow!(t!("armv7-never-published", version = version)),
+        }
+    }
+
+    async fn resolve_static_package_name(&self, version: &WasmEdgeVersion) -> Result<String> {
+        let release = release::fetch_release(&self.downloader, version).await?;
+        let available = release.runtime_asset_names(&version.tag());
+        let prefix = version.asset_name("");
+        let packages: Vec<&str> = available.iter().filter_map(|name| name.strip_prefix(prefix.as_str())).collect();
+
+        if let Some(package_name) = self.platform.find_static_package(&packages) {
+            return Ok(package_name.to_string());
+        }
+
+        anyhow::bail!(t!("static-package-unpublished", version = version, os = self.platform.os, arch = self.platform.arch, available = indented(&available)))
+    }
+
+    /// Unpack the release archive into `staging`, returning the directory
+    /// holding its `bin`, `lib` and `include`.
+    fn extract_archive(&self, archive_path: &Path, staging: &Path) -> Result<PathBuf> {
+        let file = std::fs::File::open(archive_path).context("Failed to read archive file")?;
+        let keep = |path: &Path| !self.bin_only || is_bin_only_entry(path) || licenses::is_license(path);
+        extract::unpack_tar_gz_filtered(file, staging, &self.extract_limits, keep, |path| {
+            self.events.emit(Event::Extracting { file: path.to_path_buf() })
+        })
+        .context("Failed to extract archive")?;
+
+        let extracted_dir = staging.join(format!("WasmEdge-{}-{}", self.platform.os, self.platform.arch));
+        output::status(t!("extracting", path = extracted_dir.display()));
+        Ok(extracted_dir)
+    }
+
+    /// Move the selected components of a release tree into the prefix,
+    /// returning the moved files relative to the prefix.
+    /// Component directories of `extracted_dir` to install, with where
+    /// they go in the prefix.
+    fn tree_moves(&self, extracted_dir: &Path) -> Vec<(PathBuf, &'static str)> {
         let lib_source = if extracted_dir.join("lib64").exists() {
             extracted_dir.join("lib64")
         } else {
             extracted_dir.join("lib")
         };
+        [
+            (RuntimeComponent::Bin, extracted_dir.join("bin"), "bin"),
+            (RuntimeComponent::Lib, lib_source, "lib"),
+            (RuntimeComponent::Include, extracted_dir.join("include"), "include"),
+        ]
+        .into_iter()
+        .filter(|(component, source, _)| self.has(*component) && source.exists())
+        .map(|(_, source, dest)| (source, dest))
+        .collect()
+    }
+
+    /// Files [`install_tree`](Self::install_tree) puts in the prefix,
+    /// relative to it with `/` separators.
+    fn tree_files(&self, extracted_dir: &Path) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        for (source, dest) in self.tree_moves(extracted_dir) {
+            let mut found = Vec::new();
+            fsutil::collect_files(&source, Path::new(""), &mut found)?;
+            files.extend(found.iter().map(|f| format!("{}/{}", dest, f.to_string_lossy().replace('\\', "/"))));
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    fn install_tree(&self, extracted_dir: &Path) -> Result<()> {
+        for (source, dest) in self.tree_moves(extracted_dir) {
+            fsutil::move_tree(self.fs.as_ref(), &source, &self.install_path.join(dest))?;
+        }
+        Ok(())
+    }
+
+    /// Delete the files the replaced install recorded that `files`, the new
+    /// install's, leaves out, e.g. a library named for the old release.
+    /// Moving the new tree in never deletes anything, and the new manifest
+    /// no longer names them for `remove`.
+    fn remove_replaced_files(&self, files: &[String]) -> Result<()> {
+        let Some(previous) = Manifest::read(&self.install_path)? else {
+            return Ok(());
+        };
+        let mut dirs = std::collections::BTreeSet::new();
+        for file in previous.files.iter().filter(|file| !files.contains(file)) {
+            // The manifest is only trusted to name files inside the prefix
+            if !is_inside_prefix(file) {
+                log::warn!("{}", t!("install-manifest-unsafe", file = file, manifest = self.install_path.join(MANIFEST_FILE).display()));
+                continue;
+            }
+            let path = self.install_path.join(file);
+            if path.symlink_metadata().is_ok() {
+                self.fs.remove_file(&path)?;
+            }
+            dirs.extend(Path::new(file).ancestors().skip(1).filter(|dir| dir.components().count() > 1).map(Path::to_path_buf));
+        }
+        // Drop directories the removed files leave empty, deepest first
+        let mut dirs: Vec<PathBuf> = dirs.into_iter().collect();
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+        for dir in dirs.into_iter().map(|dir| self.install_path.join(dir)) {
+            if std::fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_none()) {
+                self.fs.remove_dir_all(&dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Put this installation on the path in its `env` file.
+    fn setup_environment(&self) -> Result<()> {
+        self.write_env_file(&self.env_script())
+    }
 
-        if let Ok(mut entries) = fs::read_dir(lib_source).await {
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                let target = self.install_path.join("lib").join(entry.file_name());
-                fs::rename(entry.path(), target).await?;
+    /// Write `script` as the managed block of the `env` file, keeping the
+    /// lines users added around it, and report how the block changed.
+    fn write_env_file(&self, script: &str) -> Result<()> {
+        let env_file = self.install_path.join("env");
+        let existing = std::fs::read_to_string(&env_file).ok();
+        let legacy = [false, true].map(|patch_rpath| env_script(&self.install_path, &self.platform.os, patch_rpath));
+        let update = envfile::update(existing.as_deref(), script, script_comment(&self.platform.os), &legacy);
+        if existing.as_deref() == Some(update.content.as_str()) {
+            log::debug!("{} is up to date", env_file.display());
+            return Ok(());
+        }
+        self.fs.write(&env_file, update.content.as_bytes())?;
+        self.fs.set_executable(&env_file)?;
+        if existing.is_some() && update.changed() {
+            output::status(t!("env-file-updated", path = env_file.display()));
+            for line in &update.removed {
+                output::status(format!("  - {}", line));
+            }
+            for line in &update.added {
+                output::status(format!("  + {}", line));
             }
         }
+        Ok(())
+    }
+
+    /// Contents of the `env` file that puts this installation on the path.
+    fn env_script(&self) -> String {
+        env_script(&self.install_path, &self.platform.os, self.patch_rpath)
+    }
+
+    /// Make the side-by-side install of `version` the runtime of the
+    /// installation path: link its files and its plugin directory into the
+    /// prefix (copies of the files on Windows), replacing the version linked
+    /// before, and record which version is linked. The links are relative,
+    /// so the prefix can move. An install of its own in the prefix, or
+    /// plugins left from one, are refused.
+    pub async fn set_default(&self, version: &WasmEdgeVersion) -> Result<()> {
+        let source = versioned_prefix(&self.install_path, &version.tag());
+        let manifest = match Manifest::read(&source)? {
+            Some(manifest) if manifest.version.parse::<WasmEdgeVersion>().ok().as_ref() == Some(version) => manifest,
+            _ => anyhow::bail!(t!("default-not-installed", version = version)),
+        };
+        if installed_version(&self.install_path).is_some() && default_version(&self.install_path).is_none() {
+            anyhow::bail!(t!("default-own-install", path = self.install_path.display()));
+        }
+        let plugin_link = self.install_path.join("plugin");
+        let own_plugins = plugin_link
+            .symlink_metadata()
+            .is_ok_and(|m| m.is_dir() && std::fs::read_dir(&plugin_link).is_ok_and(|mut entries| entries.next().is_some()));
+        if own_plugins {
+            anyhow::bail!(t!("default-own-plugins", dir = plugin_link.display()));
+        }
 
-        // Move include files
-        if let Ok(mut entries) = fs::read_dir(extracted_dir.join("include")).await {
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                let target = self.install_path.join("include").join(entry.file_name());
-                fs::rename(entry.path(), target).await?;
+        let _commit = shutdown::commit_section().await;
+        if default_version(&self.install_path).is_some() {
+            self.remove_runtime().await?;
+        }
+        let source_dir = Path::new(VERSIONS_DIR).join(version.tag());
+        for file in &manifest.files {
+            let link = self.install_path.join(file);
+            if let Some(parent) = link.parent() {
+                self.fs.create_dir_all(parent)?;
             }
+            self.fs.link(&relative_target(&source_dir, Path::new(file)), &link)?;
+        }
+        self.fs.create_dir_all(&source.join("plugin"))?;
+        match plugin_link.symlink_metadata() {
+            Ok(metadata) if metadata.is_symlink() => self.fs.remove_file(&plugin_link)?,
+            Ok(_) => self.fs.remove_dir_all(&plugin_link)?,
+            Err(_) => {}
+        }
+        self.fs.link(&relative_target(&source_dir, Path::new("plugin")), &plugin_link)?;
+        if manifest.components.iter().any(|c| c == "bin") && !is_env_unmanaged(&source) {
+            self.write_env_file(&env_script(&self.install_path, &self.platform.os, is_rpath_patched(&source)))?;
         }
 
+        // The receipt is copied rather than linked, so writes to it stay in
+        // the prefix. The links follow the permissions of their targets.
+        let receipt = std::fs::read_to_string(source.join("VERSION"))
+            .with_context(|| format!("Failed to read {}", source.join("VERSION").display()))?;
+        let mut receipt: String = receipt
+            .lines()
+            .filter(|line| line.trim() != "readonly=true")
+            .map(|line| format!("{}\n", line))
+            .collect();
+        receipt.push_str(&format!("default={}\n", version.tag()));
+        self.fs.write(&self.install_path.join("VERSION"), receipt.as_bytes())?;
+        self.fs.write(&self.install_path.join(MANIFEST_FILE), manifest.to_toml()?.as_bytes())?;
         Ok(())
     }
 
-    async fn setup_environment(&self) -> Result<()> {
-        let env_file = self.install_path.join("env");
-        let mut content = String::new();
+    /// Remove the runtime files the install recorded in its manifest, and
+    /// the `env`, `VERSION` and manifest files, keeping plugins and anything
+    /// else in the prefix. Installs that predate the file list lose their
+    /// component directories instead.
+    pub async fn remove_runtime(&self) -> Result<()> {
+        if !self.install_path.exists() {
+            return Ok(());
+        }
+        if is_readonly(&self.install_path) {
+            self.set_readonly(false)?;
+        }
+        let recorded = Manifest::read(&self.install_path)?.map(|m| m.files).unwrap_or_default();
+        // The manifest is only trusted to name files inside the prefix
+        if let Some(file) = recorded.iter().find(|file| !is_inside_prefix(file)) {
+            anyhow::bail!(t!("remove-manifest-unsafe", file = file, manifest = self.install_path.join(MANIFEST_FILE).display()));
+        }
+        if recorded.is_empty() {
+            log::debug!("No file list recorded in {}, removing the runtime directories", self.install_path.display());
+            for path in self.installed_paths() {
+                if path.is_dir() {
+                    self.fs.remove_dir_all(&path)?;
+                } else if path.exists() {
+                    self.fs.remove_file(&path)?;
+                }
+            }
+            return Ok(());
+        }
 
-        // Add environment variables based on OS
-        match self.platform.os {
-            OS::Linux(_) => {
-                content.push_str("#!/bin/sh\n");
-                content.push_str(&format!("export PATH={}:$PATH\n", self.install_path.join("bin").display()));
-                content.push_str(&format!("export LD_LIBRARY_PATH={}:$LD_LIBRARY_PATH\n", self.install_path.join("lib").display()));
+        for file in &recorded {
+            let path = self.install_path.join(file);
+            if path.symlink_metadata().is_ok() {
+                self.fs.remove_file(&path)?;
             }
-            OS::Darwin => {
-                content.push_str("#!/bin/sh\n");
-                content.push_str(&format!("export PATH={}:$PATH\n", self.install_path.join("bin").display()));
-                content.push_str(&format!("export DYLD_LIBRARY_PATH={}:$DYLD_LIBRARY_PATH\n", self.install_path.join("lib").display()));
+        }
+        // The plugin directory of a linked version is a link to its own
+        let plugin_link = self.install_path.join("plugin");
+        if default_version(&self.install_path).is_some() && plugin_link.symlink_metadata().is_ok_and(|m| m.is_symlink()) {
+            self.fs.remove_file(&plugin_link)?;
+        }
+        // Debug symbols come from `component add debug`, outside the file list
+        let debug = self.install_path.join("debug");
+        if debug.is_dir() {
+            self.fs.remove_dir_all(&debug)?;
+        }
+        for name in ["env", "VERSION", MANIFEST_FILE] {
+            let path = self.install_path.join(name);
+            if path.exists() {
+                self.fs.remove_file(&path)?;
             }
-            OS::Windows => {
-                // For Windows, the system PATH will need to be modified.
-                // This will be handled differently in a real implementation
-                content.push_str("@echo off\n");
-                content.push_str(&format!("set PATH={};%PATH%\n", self.install_path.join("bin").display()));
+        }
+        // Drop the directories the removed files leave empty
+        // and the ones the install created up front
+        let dirs: std::collections::BTreeSet<&Path> = recorded
+            .iter()
+            .flat_map(|file| Path::new(file).ancestors().skip(1))
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .chain(["bin", "lib", "include", "plugin"].map(Path::new))
+            .collect();
+        let mut dirs: Vec<&Path> = dirs.into_iter().collect();
+        // Deepest first, so parents are empty by the time they are checked
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+        for dir in dirs.into_iter().map(|dir| self.install_path.join(dir)).chain([self.install_path.clone()]) {
+            if std::fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_none()) {
+                self.fs.remove_dir_all(&dir)?;
             }
         }
+        Ok(())
+    }
+}
 
-        fs::write(env_file, content).await?;
+/// Whether a release archive entry belongs to a `--bin-only` install: the
+/// `wasmedge` CLI and the shared libwasmedge it loads (`wasmedge.dll` sits
+/// in `bin` on Windows).
+fn is_bin_only_entry(path: &Path) -> bool {
+    let parts: Vec<&str> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect();
+    match parts.as_slice() {
+        [_, "bin", name] => matches!(*name, "wasmedge" | "wasmedge.exe" | "wasmedge.dll"),
+        [_, "lib" | "lib64", name] => name.starts_with("libwasmedge") && !name.ends_with(".a"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::fake::FakeFetcher;
+    use crate::fsutil::DryRunFs;
+    use crate::platform::LinuxDistro;
+
+    fn platforms() -> Vec<Platform> {
+        let mut platforms = Vec::new();
+        for os in [OS::Linux(LinuxDistro::Ubuntu), OS::Linux(LinuxDistro::Generic), OS::Darwin, OS::Windows] {
+            for arch in [Architecture::X86_64, Architecture::Aarch64, Architecture::Armv7] {
+                platforms.extend(Platform::checked(os.clone(), arch).ok());
+            }
+        }
+        platforms
+    }
 
-        // Make the env file executable on Unix systems
+    /// A fetcher serving a minimal 0.14.1 runtime archive for `platform`.
+    fn fake_release(platform: &Platform) -> FakeFetcher {
+        let package = platform.get_release_package_name("0.14.1");
+        FakeFetcher::default()
+            .with(&release_download_url(&"0.14.1".parse().unwrap(), &format!("WasmEdge-0.14.1-{}", package)), fake_archive(platform))
+    }
+
+    /// A minimal runtime archive for `platform`.
+    fn fake_archive(platform: &Platform) -> Vec<u8> {
+        archive_of(platform, &[("bin/wasmedge", "#!/bin/sh\n"), ("lib/libwasmedge.so", "elf"), ("LICENSE", "Apache-2.0\n")])
+    }
+
+    /// A runtime archive for `platform` holding `files`.
+    fn archive_of(platform: &Platform, files: &[(&str, &str)]) -> Vec<u8> {
+        let root = format!("WasmEdge-{}-{}", platform.os, platform.arch);
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+        for (path, body) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o755);
+            archive.append_data(&mut header, format!("{}/{}", root, path), body.as_bytes()).unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_install_records_changes_without_making_them() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        // Nothing is served, so a download would fail the install
+        let fetcher = FakeFetcher::default();
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let recorder = Arc::new(DryRunFs::default());
+        let (events, stream) = Events::channel();
+        Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fetcher)
+            .with_filesystem(recorder.clone())
+            .with_events(events)
+            .install_runtime(&"0.14.1".parse().unwrap())
+            .await
+            .unwrap();
+        let events: Vec<Event> = futures::StreamExt::collect(stream).await;
+        let artifact = "WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz".to_string();
+        assert_eq!(events, [
+            Event::Resolved { name: "wasmedge".into(), version: "0.14.1".into(), artifact: artifact.clone() },
+            Event::Done { name: "wasmedge".into(), version: "0.14.1".into() },
+        ]);
+
+        let unpinned = Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), FakeFetcher::default())
+            .with_filesystem(Arc::new(DryRunFs::default()))
+            .with_strict(true)
+            .install_runtime(&"0.14.1".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert!(unpinned.to_string().contains("--strict needs a checksum"), "{}", unpinned);
+
+        let operations = recorder.operations();
+        assert!(operations.contains(&format!("download {} and install it into {}", release_download_url(&"0.14.1".parse().unwrap(), &artifact), prefix.display())));
+        assert!(!operations.iter().any(|operation| operation.starts_with("write ")));
+        assert!(!prefix.exists());
+    }
+
+    #[test]
+    fn test_bin_only_keeps_the_cli_and_its_library() {
+        let kept: Vec<&str> = [
+            "WasmEdge-0.14.1-Linux/",
+            "WasmEdge-0.14.1-Linux/bin/",
+            "WasmEdge-0.14.1-Linux/bin/wasmedge",
+            "WasmEdge-0.14.1-Linux/bin/wasmedgec",
+            "./WasmEdge-0.14.1-Linux/lib64/libwasmedge.so.0.1.0",
+            "WasmEdge-0.14.1-Linux/lib64/libwasmedge.so",
+            "WasmEdge-0.14.1-Linux/lib64/libwasmedge.a",
+            "WasmEdge-0.14.1-Linux/include/wasmedge/wasmedge.h",
+            "WasmEdge-0.14.1-Darwin/lib/libwasmedge.0.dylib",
+            "WasmEdge-0.14.1-Windows/bin/wasmedge.dll",
+            "WasmEdge-0.14.1-Windows/lib/wasmedge.lib",
+        ]
+        .into_iter()
+        .filter(|path| is_bin_only_entry(Path::new(path)))
+        .collect();
+        assert_eq!(kept, [
+            "WasmEdge-0.14.1-Linux/bin/wasmedge",
+            "./WasmEdge-0.14.1-Linux/lib64/libwasmedge.so.0.1.0",
+            "WasmEdge-0.14.1-Linux/lib64/libwasmedge.so",
+            "WasmEdge-0.14.1-Darwin/lib/libwasmedge.0.dylib",
+            "WasmEdge-0.14.1-Windows/bin/wasmedge.dll",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_archive_falls_back_along_candidates() {
+        let release = |names: &[&str]| {
+            let assets: Vec<String> = names
+                .iter()
+                .map(|name| format!(r#"{{"name":"WasmEdge-0.9.0-{}","browser_download_url":""}}"#, name))
+                .collect();
+            format!(r#"{{"tag_name":"0.9.0","assets":[{}]}}"#, assets.join(","))
+        };
+        let release_url = "https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.9.0";
+        let scratch = tempfile::tempdir().unwrap();
+        let installer = |platform: Platform, fetcher: FakeFetcher| {
+            Installer::with_fetcher(scratch.path().join("wasmedge"), scratch.path().join("tmp"), platform, fetcher)
+        };
+
+        let ubuntu = Platform::new(OS::Linux(LinuxDistro::Ubuntu), Architecture::X86_64);
+        let fetcher = || FakeFetcher::default().with(release_url, release(&["manylinux2014_x86_64.tar.gz"]));
+        let package = installer(ubuntu.clone(), fetcher()).resolve_package_name(&"0.9.0".parse().unwrap()).await.unwrap();
+        assert_eq!(package, "manylinux2014_x86_64.tar.gz");
+        let strict = installer(ubuntu, fetcher()).with_strict(true).resolve_package_name(&"0.9.0".parse().unwrap()).await.unwrap_err();
+        assert!(strict.to_string().contains("--strict"), "{}", strict);
+
+        let apple = Platform::new(OS::Darwin, Architecture::Aarch64);
+        let fetcher = || FakeFetcher::default().with(release_url, release(&["darwin_x86_64.tar.gz"]));
+        let refused = installer(apple.clone(), fetcher()).resolve_package_name(&"0.9.0".parse().unwrap()).await.unwrap_err();
+        assert!(refused.to_string().contains("darwin_x86_64.tar.gz"));
+        let rosetta = installer(apple, fetcher())
+            .with_fallback_confirmation(Some(Arc::new(|_: &str| Ok(true))))
+            .resolve_package_name(&"0.9.0".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(rosetta, "darwin_x86_64.tar.gz");
+
+        let pi = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::Armv7);
+        let releases = format!("[{}, {}]", release(&["manylinux2014_x86_64.tar.gz"]), release(&["manylinux2014_armv7l.tar.gz"]).replace("0.9.0", "0.8.2"));
+        let fetcher = FakeFetcher::default()
+            .with(release_url, release(&["manylinux2014_x86_64.tar.gz"]))
+            .with("https://api.github.com/repos/WasmEdge/WasmEdge/releases?per_page=100", releases);
+        let unavailable = installer(pi, fetcher).resolve_package_name(&"0.9.0".parse().unwrap()).await.unwrap_err();
+        assert!(unavailable.to_string().contains("since WasmEdge 0.8.2"), "{}", unavailable);
+
+        let bsd = Platform::new(OS::FreeBsd, Architecture::X86_64);
+        let no_binaries = installer(bsd, FakeFetcher::default()).install_runtime(&"0.9.0".parse().unwrap()).await.unwrap_err();
+        assert!(no_binaries.to_string().contains("--build-from-source"), "{}", no_binaries);
+    }
+
+    #[test]
+    fn test_downgrades_compare_by_semver_precedence() {
+        for (installed, requested, downgrade) in [
+            ("0.14.1", "0.13.5", true),
+            ("0.14.1", "0.14.0", true),
+            ("0.14.1", "0.14.1-rc.1", true),
+            ("0.14.1", "0.14.1", false),
+            ("0.13.5", "0.14.1", false),
+            ("0.15.0-alpha.1", "0.15.0", false),
+            ("0.14.10", "0.14.9", true),
+            ("v0.14.1", "0.14.0", true),
+            // Names that are not versions never count as a downgrade
+            ("master", "0.13.5", false),
+            ("0.14.1", "latest", false),
+        ] {
+            assert_eq!(is_downgrade(installed, requested), downgrade, "{} -> {}", installed, requested);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_deprecated_versions_are_warned_about() {
+        let manifest_url = "https://example.com/wasmedge-support.json";
+        let manifest = r#"{"maintained": ["0.14", "0.13"], "deprecated": ["0.12", "0.11.2"]}"#;
+        let scratch = tempfile::tempdir().unwrap();
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let installer = Installer::with_fetcher(scratch.path().join("wasmedge"), scratch.path().join("tmp"), platform.clone(), FakeFetcher::default().with(manifest_url, manifest))
+            .with_support_manifest(Some(manifest_url.to_string()));
+        let deprecated = installer.deprecation_warning(&"0.12.1".parse().unwrap()).await.unwrap();
+        assert!(deprecated.contains("WasmEdge 0.12.1 is deprecated") && deprecated.contains("0.14, 0.13"), "{}", deprecated);
+        for (version, warned) in [("0.11.2", true), ("0.11.1", false), ("0.14.1", false)] {
+            assert_eq!(installer.deprecation_warning(&version.parse().unwrap()).await.is_some(), warned, "{}", version);
+        }
+
+        // Without a manifest, or when it cannot be fetched, nothing is known
+        let unreachable = Installer::with_fetcher(scratch.path().join("wasmedge"), scratch.path().join("tmp"), platform.clone(), FakeFetcher::default())
+            .with_support_manifest(Some(manifest_url.to_string()));
+        assert_eq!(unreachable.deprecation_warning(&"0.12.1".parse().unwrap()).await, None);
+        let unset = Installer::with_fetcher(scratch.path().join("wasmedge"), scratch.path().join("tmp"), platform, FakeFetcher::default());
+        assert_eq!(unset.deprecation_warning(&"0.12.1".parse().unwrap()).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_debug_symbols_unpack_into_the_debug_directory() {
+        let asset = "WasmEdge-0.14.1-debugsymbols_manylinux2014_x86_64.tar.gz";
+        let url = release_download_url(&"0.14.1".parse().unwrap(), asset);
+        let release = serde_json::json!({ "tag_name": "0.14.1", "assets": [{ "name": asset, "browser_download_url": url }] });
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        archive.append_data(&mut header, "WasmEdge-0.14.1-Linux/lib/libwasmedge.so.debug", &b"dwarf"[..]).unwrap();
+        let archive = archive.into_inner().unwrap().finish().unwrap();
+        let fetcher = || {
+            FakeFetcher::default()
+                .with("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1", release.to_string())
+                .with(&url, archive.clone())
+        };
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = |platform: Platform| Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform, fetcher());
+
+        let linux = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let debug_dir = installer(linux).install_debug_symbols(&"0.14.1".parse().unwrap()).await.unwrap();
+        assert_eq!(debug_dir, prefix.join("debug"));
+        assert_eq!(std::fs::read_to_string(debug_dir.join("lib").join("libwasmedge.so.debug")).unwrap(), "dwarf");
+
+        let mac = Platform::new(OS::Darwin, Architecture::Arm64);
+        let missing = installer(mac).install_debug_symbols(&"0.14.1".parse().unwrap()).await.unwrap_err();
+        assert!(missing.to_string().contains("No debug symbols are published"), "{}", missing);
+    }
+
+    #[tokio::test]
+    async fn test_static_build_is_taken_from_the_published_archives() {
+        let release = r#"{"tag_name":"0.14.1","assets":[
+            {"name":"WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz","browser_download_url":""},
+            {"name":"WasmEdge-0.14.1-manylinux_2_28_x86_64_static.tar.gz","browser_download_url":""},
+            {"name":"WasmEdge-0.14.1-manylinux_2_28_aarch64_static.tar.gz","browser_download_url":""}
+        ]}"#;
+        let fetcher = || FakeFetcher::default().with("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1", release);
+        let scratch = tempfile::tempdir().unwrap();
+        let installer = |platform: Platform| {
+            Installer::with_fetcher(scratch.path().join("wasmedge"), scratch.path().join("tmp"), platform, fetcher()).with_static_build(true)
+        };
+
+        let linux = Platform::new(OS::Linux(LinuxDistro::Ubuntu), Architecture::X86_64);
+        let package = installer(linux).resolve_package_name(&"0.14.1".parse().unwrap()).await.unwrap();
+        assert_eq!(package, "manylinux_2_28_x86_64_static.tar.gz");
+        let mac = Platform::new(OS::Darwin, Architecture::Arm64);
+        let missing = installer(mac).resolve_package_name(&"0.14.1".parse().unwrap()).await.unwrap_err();
+        assert!(missing.to_string().contains("No static build of WasmEdge 0.14.1"), "{}", missing);
+        // One archive per indented line
+        assert!(missing.to_string().contains("Available archives:\n  WasmEdge-0.14.1-"), "{}", missing);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_readonly_install_can_be_replaced_and_removed() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = |readonly| {
+            Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform))
+                .with_readonly(readonly)
+        };
+        let is_file_readonly = |path: PathBuf| std::fs::metadata(path).unwrap().permissions().readonly();
+
+        installer(true).install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        assert!(is_readonly(&prefix));
+        assert!(is_file_readonly(prefix.join("bin").join("wasmedge")));
+        assert!(is_file_readonly(prefix.join("VERSION")));
+        assert!(is_file_readonly(prefix.join("bin")));
+        assert!(is_file_readonly(prefix.join("lib")));
+        let manifest = Manifest::read(&prefix).unwrap().unwrap();
+        assert_eq!((manifest.version.as_str(), manifest.os.as_str()), ("0.14.1", "linux"));
+        assert!(manifest.checksums.contains_key("WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz"));
+
+        installer(false).install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        assert!(!is_readonly(&prefix));
+        assert!(!is_file_readonly(prefix.join("lib").join("libwasmedge.so")));
+        assert!(!is_file_readonly(prefix.join("lib")));
+
+        installer(true).install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        installer(false).remove_runtime().await.unwrap();
+        assert!(!prefix.exists());
+    }
+
+    #[tokio::test]
+    async fn test_no_env_install_writes_no_env_file_but_records_it() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform))
+            .with_no_env(true)
+            .install_runtime(&"0.14.1".parse().unwrap())
+            .await
+            .unwrap();
+
+        assert!(prefix.join("bin").join("wasmedge").exists());
+        assert!(!prefix.join("env").exists());
+        assert!(is_env_unmanaged(&prefix) && !is_rpath_patched(&prefix));
+        assert!(env_script(&prefix, &platform.os, false).contains("LD_LIBRARY_PATH"));
+        assert!(!env_script(&prefix, &platform.os, true).contains("LD_LIBRARY_PATH"));
+    }
+
+    #[tokio::test]
+    async fn test_only_upgrades_from_a_patch_base_fetch_patches() {
+        use std::io::Write;
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let package = platform.get_release_package_name("0.14.1");
+        let asset = format!("WasmEdge-0.14.1-{}", package);
+        let archive = fake_archive(&platform);
+        let base = b"the 0.14.0 archive".to_vec();
+        let mut encoder = zstd::stream::write::Encoder::with_dictionary(Vec::new(), 3, &base).unwrap();
+        encoder.write_all(&archive).unwrap();
+        let patch = encoder.finish().unwrap();
+        let sums_url = "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/SHA256SUM";
+        let release = serde_json::json!({ "tag_name": "0.14.1", "assets": [
+            { "name": &asset, "browser_download_url": release_download_url(&"0.14.1".parse().unwrap(), &asset), "size": archive.len() },
+            { "name": "SHA256SUM", "browser_download_url": sums_url, "size": 100 },
+        ] });
+        let sums = format!("{}  {}\n", hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&archive)), asset);
+        let archives = cache::cache_dir().unwrap().join("archives");
+        std::fs::create_dir_all(&archives).unwrap();
+        std::fs::write(archives.join(format!("WasmEdge-0.14.0-{}", package)), &base).unwrap();
+
+        // Only the patch is served, so a full download fails
+        let fetcher = || FakeFetcher::default()
+            .with("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1", release.to_string())
+            .with(sums_url, sums.clone())
+            .with(&delta::patch_url("https://patches.example.com", "0.14.0", "0.14.1", &package), patch.clone());
+        let scratch = tempfile::tempdir().unwrap();
+        let installer = |from: Option<&WasmEdgeVersion>| {
+            Installer::with_fetcher(scratch.path().join("wasmedge"), scratch.path().join("tmp"), platform.clone(), fetcher())
+                .with_patch_server(Some("https://patches.example.com".into()))
+                .with_patch_base(from)
+        };
+        assert!(installer(None).install_runtime(&"0.14.1".parse().unwrap()).await.is_err());
+        installer(Some(&"0.14.0".parse().unwrap())).install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        assert!(scratch.path().join("wasmedge").join("bin").join("wasmedge").exists());
+    }
+
+    #[tokio::test]
+    async fn test_recorded_version_replaces_the_resolved_release() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform))
+            .with_recorded_version(Some("master".into()))
+            .install_runtime(&"0.14.1".parse().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(installed_version(&prefix).as_deref(), Some("master"));
+        assert!(std::fs::read_to_string(prefix.join("VERSION")).unwrap().starts_with("version=master\n"));
+    }
+
+    #[test]
+    fn test_env_snippets_are_appended_in_name_order() {
+        let prefix = tempfile::tempdir().unwrap();
+        let snippets = prefix.path().join(ENV_SNIPPET_DIR);
+        std::fs::create_dir_all(&snippets).unwrap();
+        std::fs::write(snippets.join("20-alias.sh"), "alias we=wasmedge").unwrap();
+        std::fs::write(snippets.join("10-plugins.sh"), "export WASMEDGE_PLUGIN_PATH=/srv/plugins\n").unwrap();
+        std::fs::write(snippets.join("10-plugins.sh~"), "export BROKEN=1\n").unwrap();
+        std::fs::write(snippets.join("path.bat"), "set WASMEDGE_PLUGIN_PATH=C:\\plugins\n").unwrap();
+
+        let script = env_script(prefix.path(), &OS::Linux(LinuxDistro::Generic), true);
+        assert!(script.ends_with(
+            "\n# env.d/10-plugins.sh\nexport WASMEDGE_PLUGIN_PATH=/srv/plugins\n\n# env.d/20-alias.sh\nalias we=wasmedge\n"
+        ), "{}", script);
+        assert!(!script.contains("BROKEN") && !script.contains("C:"));
+        assert!(env_script(prefix.path(), &OS::Windows, false).ends_with("rem env.d/path.bat\nset WASMEDGE_PLUGIN_PATH=C:\\plugins\n"));
+
+        std::fs::write(snippets.join("path.ps1"), "$env:WASMEDGE_PLUGIN_PATH = \"C:\\plugins\"\n").unwrap();
+        let script = shell_env_script(prefix.path(), &OS::Windows, ScriptShell::PowerShell, false);
+        assert!(script.ends_with("\n# env.d/path.ps1\n$env:WASMEDGE_PLUGIN_PATH = \"C:\\plugins\"\n"), "{}", script);
+        assert!(!script.contains("set ") && !script.contains("export "));
+    }
+
+    #[test]
+    fn test_powershell_env_script() {
+        let prefix = Path::new("/opt/wasmedge");
+        assert_eq!(
+            shell_env_script(prefix, &OS::Windows, ScriptShell::PowerShell, false),
+            format!("$env:PATH = \"{};\" + $env:PATH\n", prefix.join("bin").display())
+        );
+        assert_eq!(
+            shell_env_script(prefix, &OS::Linux(LinuxDistro::Generic), ScriptShell::PowerShell, false),
+            "$env:PATH = \"/opt/wasmedge/bin:\" + $env:PATH\n$env:LD_LIBRARY_PATH = \"/opt/wasmedge/lib:\" + $env:LD_LIBRARY_PATH\n"
+        );
+        assert!(shell_env_script(prefix, &OS::Windows, ScriptShell::Posix, false).starts_with("#!/bin/sh\nexport PATH="));
+    }
+
+    #[test]
+    fn test_powershell_is_told_apart_from_the_command_prompt() {
+        let system = r"C:\Program Files\WindowsPowerShell\Modules;C:\WINDOWS\system32\WindowsPowerShell\v1.0\Modules";
+        let session = format!(r"C:\Users\Ada\Documents\WindowsPowerShell\Modules;{}", system);
+        assert!(is_powershell(Some(&session), Some(r"C:\Users\Ada")));
+        assert!(is_powershell(Some(&session.to_uppercase()), Some(r"C:\Users\ada\")));
+        assert!(!is_powershell(Some(system), Some(r"C:\Users\Ada")));
+        assert!(!is_powershell(Some(&session), Some("")));
+        assert!(!is_powershell(None, Some(r"C:\Users\Ada")));
+        assert_eq!(ScriptShell::detect(&OS::Linux(LinuxDistro::Generic)), ScriptShell::Posix);
+    }
+
+    #[tokio::test]
+    async fn test_remove_deletes_recorded_files_and_keeps_user_data() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform));
+        installer.install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        let manifest = Manifest::read(&prefix).unwrap().unwrap();
+        assert_eq!(manifest.files, ["bin/wasmedge", "lib/libwasmedge.so", "licenses/wasmedge/LICENSE"]);
+        assert_eq!(manifest.licenses, ["licenses/wasmedge/LICENSE"]);
+        assert_eq!(std::fs::read_to_string(prefix.join("licenses").join("wasmedge").join("LICENSE")).unwrap(), "Apache-2.0\n");
+
+        std::fs::create_dir_all(prefix.join("plugin")).unwrap();
+        std::fs::write(prefix.join("plugin").join("libwasmedgePluginWasiLogging.so"), "elf").unwrap();
+        std::fs::write(prefix.join("lib").join("libmine.so"), "user file").unwrap();
+        installer.remove_runtime().await.unwrap();
+
+        assert!(!prefix.join("bin").exists() && !prefix.join("VERSION").exists() && !prefix.join(MANIFEST_FILE).exists());
+        assert!(!prefix.join("licenses").exists());
+        assert!(!prefix.join("lib").join("libwasmedge.so").exists());
+        assert!(prefix.join("lib").join("libmine.so").exists());
+        assert!(prefix.join("plugin").join("libwasmedgePluginWasiLogging.so").exists());
+
+        // A manifest naming files outside the prefix is refused as a whole
+        installer.install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        let outside = scratch.path().join("outside");
+        std::fs::write(&outside, "keep").unwrap();
+        for escape in ["../outside", "/etc/passwd", "bin/../../outside"] {
+            let mut manifest = Manifest::read(&prefix).unwrap().unwrap();
+            manifest.files = vec!["bin/wasmedge".to_string(), escape.to_string()];
+            std::fs::write(prefix.join(MANIFEST_FILE), manifest.to_toml().unwrap()).unwrap();
+            assert!(installer.remove_runtime().await.is_err(), "{}", escape);
+            assert!(outside.exists() && prefix.join("bin").join("wasmedge").exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_removes_files_only_the_old_release_shipped() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let package = platform.get_release_package_name("0.14.1");
+        let url = |version: &str| release_download_url(&version.parse().unwrap(), &format!("WasmEdge-{}-{}", version, package));
+        let old = archive_of(&platform, &[
+            ("bin/wasmedge", "0.14.0"),
+            ("lib/libwasmedge.so.0.0.3", "0.14.0"),
+            ("include/wasmedge/wasmedge.h", "0.14.0"),
+            ("include/wasmedge/legacy/enum.h", "0.14.0"),
+            ("LICENSE", "Apache-2.0\n"),
+        ]);
+        let new = archive_of(&platform, &[
+            ("bin/wasmedge", "0.14.1"),
+            ("lib/libwasmedge.so.0.1.0", "0.14.1"),
+            ("include/wasmedge/wasmedge.h", "0.14.1"),
+            ("LICENSE", "Apache-2.0\n"),
+        ]);
+        let fetcher = || FakeFetcher::default().with(&url("0.14.0"), old.clone()).with(&url("0.14.1"), new.clone());
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = || Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fetcher());
+
+        installer().install_runtime(&"0.14.0".parse().unwrap()).await.unwrap();
+        std::fs::write(prefix.join("lib").join("libmine.so"), "user file").unwrap();
+        installer().install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+
+        let manifest = Manifest::read(&prefix).unwrap().unwrap();
+        assert_eq!(manifest.version, "0.14.1");
+        assert_eq!(manifest.files, [
+            "bin/wasmedge",
+            "include/wasmedge/wasmedge.h",
+            "lib/libwasmedge.so.0.1.0",
+            "licenses/wasmedge/LICENSE",
+        ]);
+        for file in &manifest.files {
+            assert!(prefix.join(file).exists(), "{} is missing", file);
+        }
+        assert_eq!(std::fs::read_to_string(prefix.join("bin").join("wasmedge")).unwrap(), "0.14.1");
+        assert!(!prefix.join("lib").join("libwasmedge.so.0.0.3").exists());
+        assert!(!prefix.join("include").join("wasmedge").join("legacy").exists());
+        assert!(prefix.join("lib").join("libmine.so").exists());
+
+        // Everything the upgrade left is recorded, so remove leaves nothing
+        std::fs::remove_file(prefix.join("lib").join("libmine.so")).unwrap();
+        installer().remove_runtime().await.unwrap();
+        assert!(!prefix.exists());
+    }
+
+    #[tokio::test]
+    async fn test_max_time_only_limits_fetching_and_unpacking() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = |max_time: u64| {
+            let fetcher = fake_release(&platform).with_delay(Duration::from_millis(200));
+            Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fetcher)
+                .with_max_time(Some(Duration::from_millis(max_time)))
+        };
+
+        let error = installer(10).install_runtime(&"0.14.1".parse().unwrap()).await.unwrap_err();
+        assert!(error.to_string().contains("--max-time"), "{}", error);
+        assert!(!prefix.join("bin").join("wasmedge").exists() && !prefix.join(MANIFEST_FILE).exists());
+
+        installer(60_000).install_runtime(&"0.14.1".parse().unwrap()).await.unwrap();
+        assert!(prefix.join("bin").join("wasmedge").exists());
+    }
+
+    #[tokio::test]
+    async fn test_versioned_install_leaves_the_default_in_place() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        std::fs::create_dir_all(&prefix).unwrap();
+        std::fs::write(prefix.join("VERSION"), "version=0.13.5\n").unwrap();
+
+        let version: WasmEdgeVersion = "0.14.1".parse().unwrap();
+        let installer = Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform))
+            .with_versioned_layout(Some(&version));
+        assert_eq!(installer.install_path(), prefix.join("versions").join("0.14.1"));
+        installer.install_runtime(&version).await.unwrap();
+
+        assert_eq!(installed_version(&prefix).as_deref(), Some("0.13.5"));
+        assert!(!prefix.join("bin").exists());
+        assert_eq!(installed_versions(&prefix), vec![(version, prefix.join("versions").join("0.14.1"))]);
+        assert!(prefix.join("versions").join("0.14.1").join("bin").join("wasmedge").exists());
+    }
+
+    #[tokio::test]
+    async fn test_default_links_a_versioned_install_into_the_prefix() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = || Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform));
+        let newer: WasmEdgeVersion = "0.14.1".parse().unwrap();
+        let older: WasmEdgeVersion = "0.13.5".parse().unwrap();
+        installer().with_versioned_layout(Some(&newer)).install_runtime(&newer).await.unwrap();
+
+        let missing = installer().set_default(&older).await.unwrap_err();
+        assert!(missing.to_string().contains("--versioned"), "{}", missing);
+        installer().set_default(&newer).await.unwrap();
+        assert_eq!(installed_version(&prefix).as_deref(), Some("0.14.1"));
+        assert_eq!(default_version(&prefix).as_deref(), Some("0.14.1"));
+        assert_eq!(linked_from(&versioned_prefix(&prefix, "0.14.1")), Some(prefix.clone()));
+        assert_eq!(linked_from(&versioned_prefix(&prefix, "0.13.5")), None);
+        assert_eq!(linked_from(&prefix), None);
+        assert_eq!(crate::plugin::plugin_dir(&prefix), versioned_prefix(&prefix, "0.14.1").join("plugin"));
+        assert!(prefix.join("env").exists());
         #[cfg(unix)]
         {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&self.install_path.join("env")).await?.permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&self.install_path.join("env"), perms).await?;
+            assert_eq!(std::fs::read_link(prefix.join("bin").join("wasmedge")).unwrap(), Path::new("../versions/0.14.1/bin/wasmedge"));
+            assert_eq!(std::fs::read_link(prefix.join("plugin")).unwrap(), Path::new("versions/0.14.1/plugin"));
+            assert!(prefix.join("bin").join("wasmedge").exists());
         }
 
-        Ok(())
+        // A second version that only has the CLI
+        let older_prefix = versioned_prefix(&prefix, "0.13.5");
+        std::fs::create_dir_all(older_prefix.join("bin")).unwrap();
+        std::fs::write(older_prefix.join("bin").join("wasmedge"), "#!/bin/sh\n").unwrap();
+        std::fs::write(older_prefix.join("VERSION"), "version=0.13.5\nenv=false\n").unwrap();
+        let mut manifest = Manifest::read(&versioned_prefix(&prefix, "0.14.1")).unwrap().unwrap();
+        manifest.version = "0.13.5".to_string();
+        manifest.files = vec!["bin/wasmedge".to_string()];
+        manifest.licenses = Vec::new();
+        std::fs::write(older_prefix.join(MANIFEST_FILE), manifest.to_toml().unwrap()).unwrap();
+
+        installer().set_default(&older).await.unwrap();
+        assert_eq!(default_version(&prefix).as_deref(), Some("0.13.5"));
+        assert!(is_env_unmanaged(&prefix) && !prefix.join("env").exists());
+        assert!(!prefix.join("lib").exists());
+        assert_eq!(std::fs::read_to_string(prefix.join("bin").join("wasmedge")).unwrap(), "#!/bin/sh\n");
+
+        installer().remove_runtime().await.unwrap();
+        assert!(!prefix.join("bin").exists() && default_version(&prefix).is_none());
+        assert!(prefix.join("plugin").symlink_metadata().is_err());
+        assert_eq!(installed_versions(&prefix).len(), 2);
+
+        std::fs::create_dir_all(prefix.join("plugin")).unwrap();
+        std::fs::write(prefix.join("plugin").join("libwasmedgePluginWasiLogging.so"), "").unwrap();
+        let plugins = installer().set_default(&newer).await.unwrap_err();
+        assert!(plugins.to_string().contains("plugin remove"), "{}", plugins);
+        std::fs::remove_dir_all(prefix.join("plugin")).unwrap();
+
+        std::fs::write(prefix.join("VERSION"), "version=0.14.0\n").unwrap();
+        let own = installer().set_default(&newer).await.unwrap_err();
+        assert!(own.to_string().contains("installed directly"), "{}", own);
     }
 
-    pub async fn remove_runtime(&self) -> Result<()> {
-        // Remove all files and directories
-        if self.install_path.exists() {
-            fs::remove_dir_all(&self.install_path).await?;
+    #[tokio::test]
+    async fn test_installed_options_carry_over_to_upgrades() {
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let installer = || Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), platform.clone(), fake_release(&platform));
+        let mode = Ownership { uid: None, gid: None, mode: Some(0o750) };
+        installer()
+            .with_no_env(true)
+            .with_bin_only(true)
+            .with_fsync(true)
+            .with_ownership(Some(mode.clone()))
+            .install_runtime(&"0.14.1".parse().unwrap())
+            .await
+            .unwrap();
+
+        let detected = Platform::new(OS::Darwin, Architecture::Aarch64);
+        let upgrade = Installer::with_fetcher(prefix.clone(), scratch.path().join("tmp"), detected, fake_release(&platform)).with_installed_options();
+        assert_eq!(upgrade.components, [RuntimeComponent::Bin, RuntimeComponent::Lib]);
+        assert!(upgrade.no_env && upgrade.bin_only && !upgrade.readonly && !upgrade.patch_rpath);
+        assert!(upgrade.fsync && !upgrade.static_build && !upgrade.selinux_relabel);
+        assert_eq!(upgrade.ownership, Some(mode));
+        assert_eq!(upgrade.platform.shorthand(), platform.shorthand());
+        let fresh = Installer::new(scratch.path().join("none"), scratch.path().join("tmp"), platform.clone()).with_installed_options();
+        assert_eq!(fresh.components, RuntimeComponent::ALL);
+        assert!(!fresh.no_env && !fresh.bin_only && !fresh.fsync);
+        assert_eq!(fresh.ownership, None);
+    }
+
+    #[test]
+    fn test_release_asset_urls() {
+        let mut out = String::new();
+        for version in ["0.13.5", "0.14.1", "0.15.0-alpha.1"] {
+            let release: WasmEdgeVersion = version.parse().unwrap();
+            for platform in platforms() {
+                let package = platform.get_release_package_name(version);
+                out.push_str(&format!("{} {} {}\n", version, platform.os, platform.arch));
+                out.push_str(&format!("  {}\n", release_download_url(&release, &release.asset_name(&package))));
+                for fallback in platform.release_package_candidates(version).iter().skip(1) {
+                    let confirm = if fallback.needs_confirmation { " (confirm)" } else { "" };
+                    out.push_str(&format!("  fallback: WasmEdge-{}-{}{}\n", version, fallback.package, confirm));
+                }
+            }
         }
-        Ok(())
+        insta::assert_snapshot!("release_asset_urls", out);
+
+        // Linux arm64 archives are published as aarch64, never as arm64
+        let release: WasmEdgeVersion = "0.14.1".parse().unwrap();
+        for (os, package) in [(LinuxDistro::Generic, "manylinux2014_aarch64.tar.gz"), (LinuxDistro::Ubuntu, "ubuntu20.04_aarch64.tar.gz")] {
+            let platform = Platform::new(OS::Linux(os), Architecture::Aarch64);
+            assert_eq!(platform.get_release_package_name("0.14.1"), package);
+            assert_eq!(
+                release_download_url(&release, &release.asset_name(package)),
+                format!("https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/WasmEdge-0.14.1-{}", package)
+            );
+        }
+    }
+
+    #[test]
+    fn test_env_scripts() {
+        let mut out = String::new();
+        for platform in platforms() {
+            for patch_rpath in [false, true] {
+                let installer = Installer::new(PathBuf::from("/opt/wasmedge"), PathBuf::from("/tmp"), platform.clone())
+                    .with_rpath_patching(patch_rpath);
+                out.push_str(&format!("== {} {} patch_rpath={}\n", platform.os, platform.arch, patch_rpath));
+                // Paths joined on Windows use `\`; keep one snapshot for every host
+                out.push_str(&installer.env_script().replace('\\', "/"));
+            }
+        }
+        insta::assert_snapshot!("env_scripts", out);
     }
-} 
\ No newline at end of file
+}
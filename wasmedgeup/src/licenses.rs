@@ -0,0 +1,92 @@
+//! License and notice files shipped in release and plugin archives, gathered
+//! into `<prefix>/licenses/<component>/` so they can be reviewed in one place
+//! and shipped along with images that bake the runtime in.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use crate::fsutil::{self, FileSystem};
+
+/// Directory in the prefix holding the gathered license files, one
+/// subdirectory per component (`wasmedge`, or the plugin name).
+pub const LICENSES_DIR: &str = "licenses";
+
+/// Whether `path` names a license or notice file, e.g. `LICENSE`,
+/// `LICENSE-APACHE`, `NOTICE.txt` or `COPYING`.
+pub fn is_license(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_uppercase();
+    ["LICENSE", "LICENCE", "NOTICE", "COPYING"].iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// Move the license files anywhere in the unpacked archive at `tree` into
+/// `dest`, returning their new paths, sorted. Files of the same name from
+/// different directories are told apart by their directory, e.g.
+/// `lib-LICENSE`, and by a number should that name be taken as well.
+pub fn gather(fs: &dyn FileSystem, tree: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    fsutil::collect_files(tree, Path::new(""), &mut files)?;
+    files.retain(|file| is_license(file));
+    // Shallowest first, so a top-level LICENSE keeps its plain name
+    files.sort_by_key(|file| (file.components().count(), file.clone()));
+
+    let mut gathered: Vec<PathBuf> = Vec::new();
+    for file in files {
+        let name = file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let mut target = dest.join(&name);
+        if gathered.contains(&target) {
+            let parents: Vec<String> = file
+                .parent()
+                .into_iter()
+                .flat_map(Path::components)
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let renamed = format!("{}-{}", parents.join("-"), name);
+            target = dest.join(&renamed);
+            // `a-b/LICENSE` and `a/b/LICENSE` both become `a-b-LICENSE`
+            let mut n = 2;
+            while gathered.contains(&target) {
+                target = dest.join(format!("{}-{}", renamed, n));
+                n += 1;
+            }
+        }
+        fs.create_dir_all(dest)?;
+        fs.rename(&tree.join(&file), &target)?;
+        gathered.push(target);
+    }
+    gathered.sort();
+    Ok(gathered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsutil::RealFs;
+
+    #[test]
+    fn test_gather_moves_license_files_out_of_the_tree() {
+        let root = tempfile::tempdir().unwrap();
+        let tree = root.path().join("tree");
+        for file in ["LICENSE", "lib/LICENSE", "lib/libwasmedge.so", "include/NOTICE.txt", "bin/wasmedge"] {
+            std::fs::create_dir_all(tree.join(file).parent().unwrap()).unwrap();
+            std::fs::write(tree.join(file), file).unwrap();
+        }
+
+        let dest = root.path().join("licenses").join("wasmedge");
+        let gathered = gather(&RealFs, &tree, &dest).unwrap();
+        assert_eq!(gathered, ["LICENSE", "NOTICE.txt", "lib-LICENSE"].map(|name| dest.join(name)));
+        assert_eq!(std::fs::read_to_string(dest.join("lib-LICENSE")).unwrap(), "lib/LICENSE");
+        assert!(!tree.join("LICENSE").exists() && tree.join("lib").join("libwasmedge.so").exists());
+        assert!(gather(&RealFs, &tree, &dest).unwrap().is_empty());
+        assert!(!is_license(Path::new("lib/libLicenseCheck.so")));
+
+        // A renamed file never replaces one gathered before it
+        for file in ["LICENSE", "a-b/LICENSE", "a/b/LICENSE"] {
+            std::fs::create_dir_all(tree.join(file).parent().unwrap()).unwrap();
+            std::fs::write(tree.join(file), file).unwrap();
+        }
+        let dest = root.path().join("licenses").join("plugin");
+        let gathered = gather(&RealFs, &tree, &dest).unwrap();
+        assert_eq!(gathered, ["LICENSE", "a-b-LICENSE", "a-b-LICENSE-2"].map(|name| dest.join(name)));
+        assert_eq!(std::fs::read_to_string(dest.join("a-b-LICENSE")).unwrap(), "a-b/LICENSE");
+        assert_eq!(std::fs::read_to_string(dest.join("a-b-LICENSE-2")).unwrap(), "a/b/LICENSE");
+    }
+}
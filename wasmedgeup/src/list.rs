@@ -0,0 +1,248 @@
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::warn;
+use serde::Serialize;
+use std::path::Path;
+use std::time::SystemTime;
+use crate::{
+    downloader::Fetcher,
+    installer,
+    output,
+    platform::Platform,
+    release::{self, Release, SupportStatus, VersionManifest},
+    version::WasmEdgeVersion,
+};
+
+/// Upper bound on checksum lists fetched at once, to stay clear of GitHub's
+/// abuse rate limits.
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
+/// One row of the unified `list` view.
+#[derive(Debug, Serialize)]
+pub struct VersionRow {
+    pub version: String,
+    pub channel: &'static str,
+    pub installed: bool,
+    pub default: bool,
+    pub latest: bool,
+    /// `None` when no support manifest lists the version
+    pub status: Option<SupportStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Fetch the upstream digest of this platform's runtime archive for every
+/// release, a few at a time.
+async fn fetch_digests(downloader: &impl Fetcher, releases: &[Release], platform: &Platform) -> Vec<Option<String>> {
+    let pb = output::progress_bar(releases.len() as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} Fetching checksums [{bar:40.cyan/blue}] {pos}/{len}")
+        .unwrap()
+        .progress_chars("#>-"));
+
+    // Built up front: mapping borrowed releases inside the stream hits a
+    // higher-ranked lifetime error once the command runs in a spawned task
+    let fetches: Vec<_> = releases
+        .iter()
+        .map(|r| fetch_digest(downloader, r, platform, &pb))
+        .collect();
+    let digests = stream::iter(fetches)
+        .buffered(MAX_CONCURRENT_FETCHES)
+        .collect()
+        .await;
+
+    pb.finish_and_clear();
+    digests
+}
+
+async fn fetch_digest(downloader: &impl Fetcher, release: &Release, platform: &Platform, pb: &ProgressBar) -> Option<String> {
+    let asset_name = format!(
+        "WasmEdge-{}-{}",
+        release.tag_name,
+        platform.get_release_package_name(&release.tag_name)
+    );
+    let digest = match release::fetch_release_checksums(downloader, release).await {
+        Ok(checksums) => checksums.and_then(|c| c.get(&asset_name).map(str::to_string)),
+        Err(e) => {
+            warn!("Could not fetch checksums for {}: {}", release.tag_name, e);
+            None
+        }
+    };
+    pb.inc(1);
+    digest
+}
+
+/// Sort `releases` newest version first. GitHub lists them by publication
+/// date, which puts patch releases of older lines above newer releases.
+/// Tags that are not versions go last.
+fn sort_newest_first(releases: &mut [Release]) {
+    releases.sort_by_cached_key(|r| std::cmp::Reverse(r.tag_name.parse::<WasmEdgeVersion>().ok()));
+}
+
+/// Upstream releases merged with what is installed at `install_path`, with
+/// their status from the support manifest at `manifest_url`, if any.
+/// Pre-releases are left out unless installed or `include_prerelease` is
+/// set. With `verify`, also fetch the upstream digest of each release's
+/// archive for `platform`.
+pub async fn list_versions(
+    downloader: &impl Fetcher,
+    manifest_url: Option<&str>,
+    install_path: &Path,
+    platform: &Platform,
+    verify: bool,
+    include_prerelease: bool,
+) -> Result<Vec<VersionRow>> {
+    let mut releases = release::fetch_releases(downloader).await?;
+    sort_newest_first(&mut releases);
+    let manifest = release::fetch_version_manifest(downloader, manifest_url)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not fetch version support status: {}", e);
+            VersionManifest::default()
+        });
+    let installed = installer::installed_version(install_path);
+    let side_by_side: Vec<String> = installer::installed_versions(install_path)
+        .into_iter()
+        .map(|(version, _)| version.tag())
+        .collect();
+    let is_prerelease = |r: &Release| r.prerelease || r.tag_name.parse::<WasmEdgeVersion>().is_ok_and(|v| v.is_prerelease());
+    let is_installed = |r: &Release| installed.as_deref() == Some(r.tag_name.as_str()) || side_by_side.contains(&r.tag_name);
+    releases.retain(|r| include_prerelease || !is_prerelease(r) || is_installed(r));
+    let mut digests = if verify {
+        fetch_digests(downloader, &releases, platform).await
+    } else {
+        Vec::new()
+    };
+    digests.resize(releases.len(), None);
+    let latest = releases.iter().find(|r| !is_prerelease(r)).map(|r| r.tag_name.as_str());
+
+    let rows = releases
+        .iter()
+        .zip(digests)
+        .map(|(r, sha256)| {
+            let version = r.tag_name.clone();
+            // Versions installed side by side are not the default one, which
+            // is the one in the prefix itself
+            let is_default = installed.as_deref() == Some(version.as_str());
+            VersionRow {
+                channel: if is_prerelease(r) { "pre-release" } else { "stable" },
+                installed: is_default || side_by_side.contains(&version),
+                default: is_default,
+                latest: latest == Some(version.as_str()),
+                status: manifest.status(&version),
+                sha256,
+                version,
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+/// Lines of the `list` table. The installed version in the prefix itself
+/// is the default one; versions installed side by side are only installed.
+pub fn version_lines(rows: &[VersionRow], verify: bool) -> Vec<String> {
+    let mut lines = vec![format!("{:<16} {:<12} {:<10} STATUS", "VERSION", "CHANNEL", "INSTALLED")];
+    for row in rows {
+        let installed = match (row.default, row.installed) {
+            (true, _) => "default",
+            (false, true) => "yes",
+            (false, false) => "",
+        };
+        let marker = if row.latest { " <- latest" } else { "" };
+        let status = row.status.map_or("-", SupportStatus::as_str);
+        lines.push(format!("{:<16} {:<12} {:<10} {}{}", row.version, row.channel, installed, status, marker));
+        if verify {
+            lines.push(format!("    sha256: {}", row.sha256.as_deref().unwrap_or("(not published)")));
+        }
+    }
+    lines
+}
+
+/// Says how old the listed releases are when they came from the cache,
+/// fetched at `fetched`.
+pub fn stale_note(fetched: SystemTime) -> String {
+    t!("list-stale", time = humantime::format_rfc3339_seconds(fetched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_releases_sort_by_version_not_publication() {
+        let mut releases: Vec<Release> = serde_json::from_str(r#"[
+            {"tag_name": "0.13.5", "assets": []},
+            {"tag_name": "0.15.0-alpha.1", "assets": []},
+            {"tag_name": "nightly", "assets": []},
+            {"tag_name": "0.14.1", "assets": []}
+        ]"#).unwrap();
+        sort_newest_first(&mut releases);
+        let tags: Vec<_> = releases.iter().map(|r| r.tag_name.as_str()).collect();
+        assert_eq!(tags, ["0.15.0-alpha.1", "0.14.1", "0.13.5", "nightly"]);
+    }
+
+    #[tokio::test]
+    async fn test_pre_releases_are_listed_on_request_or_when_installed() {
+        let fetcher = crate::downloader::fake::FakeFetcher::default().with(
+            "https://api.github.com/repos/WasmEdge/WasmEdge/releases?per_page=100",
+            r#"[{"tag_name": "0.15.0-rc.1", "prerelease": true, "assets": []}, {"tag_name": "0.15.0-alpha.1", "assets": []},
+                {"tag_name": "0.14.1", "assets": []}]"#,
+        );
+        let prefix = tempfile::tempdir().unwrap();
+        let platform = Platform::new(crate::platform::OS::Darwin, crate::platform::Architecture::Aarch64);
+        let versions = |rows: Vec<VersionRow>| rows.into_iter().map(|r| r.version).collect::<Vec<_>>();
+
+        let stable = list_versions(&fetcher, None, prefix.path(), &platform, false, false).await.unwrap();
+        assert_eq!(versions(stable), ["0.14.1"]);
+        let all = list_versions(&fetcher, None, prefix.path(), &platform, false, true).await.unwrap();
+        assert_eq!(versions(all), ["0.15.0-rc.1", "0.15.0-alpha.1", "0.14.1"]);
+
+        std::fs::write(prefix.path().join("VERSION"), "version=0.15.0-alpha.1\n").unwrap();
+        let installed = list_versions(&fetcher, None, prefix.path(), &platform, false, false).await.unwrap();
+        assert_eq!(versions(installed), ["0.15.0-alpha.1", "0.14.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_status_comes_from_the_configured_manifest_only() {
+        let manifest = "https://example.com/support.json";
+        let fetcher = crate::downloader::fake::FakeFetcher::default()
+            .with(
+                "https://api.github.com/repos/WasmEdge/WasmEdge/releases?per_page=100",
+                r#"[{"tag_name": "0.14.1", "assets": []}, {"tag_name": "0.13.5", "assets": []}, {"tag_name": "0.11.2", "assets": []}]"#,
+            )
+            .with(manifest, r#"{"maintained": ["0.14"], "deprecated": ["0.13"]}"#);
+        let prefix = tempfile::tempdir().unwrap();
+        let platform = Platform::new(crate::platform::OS::Darwin, crate::platform::Architecture::Aarch64);
+        let statuses = |rows: Vec<VersionRow>| rows.into_iter().map(|r| r.status).collect::<Vec<_>>();
+
+        let listed = list_versions(&fetcher, Some(manifest), prefix.path(), &platform, false, false).await.unwrap();
+        assert_eq!(statuses(listed), [Some(SupportStatus::Maintained), Some(SupportStatus::Deprecated), None]);
+        let unlisted = list_versions(&fetcher, None, prefix.path(), &platform, false, false).await.unwrap();
+        assert_eq!(statuses(unlisted), [None, None, None]);
+    }
+
+    #[test]
+    fn test_version_lines_mark_the_default_release() {
+        let row = |version: &str, installed, default| VersionRow {
+            version: version.to_string(),
+            channel: "stable",
+            installed,
+            default,
+            latest: version == "0.14.1",
+            status: None,
+            sha256: None,
+        };
+        let rows = [row("0.14.1", true, false), row("0.13.5", true, true), row("0.12.1", false, false)];
+
+        let lines = version_lines(&rows, false);
+        assert_eq!(lines[0].split_whitespace().collect::<Vec<_>>(), ["VERSION", "CHANNEL", "INSTALLED", "STATUS"]);
+        assert_eq!(lines[1].split_whitespace().collect::<Vec<_>>(), ["0.14.1", "stable", "yes", "-", "<-", "latest"]);
+        assert_eq!(lines[2].split_whitespace().collect::<Vec<_>>(), ["0.13.5", "stable", "default", "-"]);
+        assert_eq!(lines[3].split_whitespace().collect::<Vec<_>>(), ["0.12.1", "stable", "-"]);
+        assert_eq!(lines.len(), 4);
+
+        let fetched = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert!(stale_note(fetched).contains("2023-11-14T22:13:20Z"));
+    }
+}
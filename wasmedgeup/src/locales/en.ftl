@@ -0,0 +1,252 @@
+# English messages, in Fluent syntax: placeables are { $name }, and counts
+# select plural forms with { $count -> [one] ... *[other] ... }.
+
+downloading = Downloading from: { $url }
+extracting = Extracting to: { $path }
+archive-entry-outside = Archive entry { $entry } points outside the directory it is extracted to
+archive-link-outside = Archive link { $entry } -> { $target } points outside the directory it is extracted to
+archive-entry-special = Archive entry { $entry } is a device, FIFO or sparse file, which WasmEdge archives never contain
+archive-entry-link = Archive entry { $entry } is a symbolic link, which WasmEdge zip archives never contain
+extract-too-many-files = Archive holds more than { $limit } files; pass a higher --max-extract-files if it is genuine
+extract-too-large = Archive unpacks to more than { $limit } MB; pass a higher --max-extract-size if it is genuine
+
+install-success = Successfully installed WasmEdge { $version }
+install-versioned = Installed side by side in { $path }; run it with `wasmedgeup exec --path { $path } wasmedge` or source { $path }/env
+default-success = WasmEdge { $version } is now the default in { $path }
+default-not-installed = WasmEdge { $version } is not installed side by side. Install it first with `wasmedgeup install { $version } --versioned`
+default-own-install = The runtime in { $path } was installed directly, not with --versioned. Remove it first with `wasmedgeup remove`, or keep it as the default
+exec-version-not-installed = WasmEdge { $version } is not installed in { $path }. Install it first with `wasmedgeup install { $version } --versioned`
+remove-success = Successfully removed WasmEdge from { $path }
+remove-purge-success = Removed WasmEdge from { $path } along with plugins, cached downloads and the config file
+debug-symbols-installed = Installed debug symbols to { $path }
+debug-symbols-unpublished = No debug symbols are published for WasmEdge { $version } on { $os } { $arch }
+exec-not-installed = No WasmEdge installation found at { $path }. Run `wasmedgeup install` first
+install-in-use =
+    { $path } is in use by running processes:
+    { $processes }
+    Stop them first or pass --force
+install-in-use-forced =
+    Continuing although { $path } is in use by:
+    { $processes }
+install-deprecated = WasmEdge { $version } is deprecated upstream and no longer maintained. Maintained versions: { $maintained }. Pass --allow-deprecated to silence this warning
+package-unpublished =
+    No WasmEdge { $version } archive is published for { $os } { $arch }. Available archives:
+    { $available }
+static-package-unpublished =
+    No static build of WasmEdge { $version } is published for { $os } { $arch }. Available archives:
+    { $available }
+
+plugin-install-success = Successfully installed plugin { $name }
+plugin-remove-success = Successfully removed plugin { $name }
+plugin-file-removed = Removed { $path }
+plugin-file-untracked = { $name } is no longer recorded as installed
+plugin-file-missing = No plugin file { $path }
+plugin-file-outside = Refusing to remove { $path }, which is outside the plugin directory { $dir }
+plugin-file-directory = Refusing to remove { $path }, which is a directory; pass the files in it
+plugin-adopt-success = Recorded { $path } as plugin { $name } { $version }
+plugin-adopt-tracked = { $path } is already recorded as part of plugin { $name }
+plugin-adopt-exists = A plugin named { $name } is already installed; pass --name to record the library under another name
+plugin-adopt-not-library = { $path } is not a plugin library
+plugin-adopt-name-many = --name names a single library; adopt the libraries one at a time
+plugin-installed-none = No plugins are installed
+plugin-orphan = { $path } (not recorded as installed)
+plugin-orphans-hint = Libraries not recorded as installed were found in the plugin directory. Track them with `wasmedgeup plugin adopt <file>`, or delete them with `wasmedgeup plugin remove --file <file>`
+plugin-pin-success = Pinned plugin { $name } to { $version }
+plugin-unpin-success = Unpinned plugin { $name } (was { $version })
+plugin-pins-none = No plugins are pinned
+plugin-not-installed = Plugin { $name } is not installed
+plugin-remove-not-found = No matching plugin files found for { $name }
+plugin-remove-version-not-found = No matching plugin files found for { $name } { $version }
+plugin-unknown = Unknown plugin '{ $name }' for WasmEdge { $version }. Available plugins: { $available }
+plugin-platform-unpublished = Plugin '{ $name }' is not published for { $os } { $arch }. Available platforms: { $platforms }
+plugin-incompatible-runtime = Plugin '{ $name }' { $version } is not compatible with WasmEdge { $runtime }: { $reason }
+plugin-catalog-unavailable = Could not fetch the plugin catalog, guessing the download URL: { $error }
+plugin-archive-unpublished = No archive of plugin '{ $name }' is published for { $version }
+plugin-archive-no-library = The plugin archive contains no plugin library
+plugin-build-no-library = The build of { $name } installed no plugin library
+plugin-download-failed = Failed to download plugin: { $error }
+plugin-extract-failed = Failed to extract plugin: { $error }
+plugin-install-failed = Failed to install plugin '{ $name }'. The plugin may not be available for your platform ({ $os } { $arch }) or the specified version. Available plugins for your platform can be found at: https://github.com/WasmEdge/WasmEdge/releases/tag/{ $version }
+plugin-reinstall-one-failed = Could not reinstall plugin { $name }: { $error }
+plugin-pinned = Plugin { $name } is pinned to { $version }, not { $requested }. Run `wasmedgeup plugin unpin { $name }` first
+plugin-pin-needs-version = Plugin { $name } is not installed; pin it with a version, e.g. { $name }@0.14.1
+plugin-not-pinned = Plugin { $name } is not pinned
+plugin-list-header = Available plugins:
+platforms-runtime = WasmEdge { $version } runtime archives:
+platforms-plugins = Plugin archives:
+platforms-unselectable = { $platform } (no --platform value installs it)
+plugin-incompatible = { $name } { $version } [Not compatible with { $os } { $arch }]
+plugin-catalog-written = Wrote { $count ->
+        [one] { $count } catalog entry
+       *[other] { $count } catalog entries
+    } to { $path }
+
+prompt-select-many = Enter numbers to select (e.g. 1,3 or 2-4), 'all', or nothing to cancel:
+prompt-invalid-selection = Please enter numbers between 1 and { $max }.
+plugin-picker-title = Compatible plugins:
+plugin-picker-empty = No plugins are published for { $os } { $arch }.
+plugin-install-no-args = No plugins given. Pass plugin names, or run on a terminal to pick from a list.
+
+replace-confirm = { $path } already contains WasmEdge { $installed }. Replace it with { $version }?
+replace-refused = { $path } already contains WasmEdge { $installed }. Pass --yes or --force to replace it with { $version }
+install-cancelled = Installation cancelled
+downgrade-refused = Refusing to downgrade { $path } from WasmEdge { $installed } to { $version }. Pass --allow-downgrade to proceed
+plugins-reinstall-failed = These plugins could not be reinstalled for WasmEdge { $version }, so their old builds were kept, which it will not load: { $plugins }. Install them again with `wasmedgeup plugin install`
+plugins-kept-pinned = Pinned plugins were kept although WasmEdge { $version } will not load them: { $plugins }. Unpin them to move them to the new runtime
+install-timed-out = Installation did not finish within { $secs } seconds (--max-time); partial downloads were removed
+install-locked = { $path } is being modified by another wasmedgeup process (pid { $pid }). Wait for it to finish, or delete { $lock } if that process is gone
+interrupted = Interrupted; unfinished changes were rolled back
+dry-run-header = Dry run, nothing was changed. These changes would be made:
+cache-cleaned = Cleaned cache directory { $path }
+cache-swept = Removed { $count ->
+        [one] { $count } leftover temporary entry
+       *[other] { $count } leftover temporary entries
+    } from { $path }
+plugin-verify-ok = { $name }: ok
+plugin-verify-failed = { $name }: broken
+plugin-verify-summary = { $failed } of { $total ->
+        [one] { $total } plugin
+       *[other] { $total } plugins
+    } failed verification
+plugin-verify-missing = { $path } is missing
+plugin-verify-modified = { $path } was modified after installation
+plugin-verify-skew = built for WasmEdge { $version }, but WasmEdge { $runtime } is installed
+plugin-verify-load-error = failed to load: { $error }
+plugin-verify-not-loaded = not loaded by the runtime
+plugin-verify-no-runtime = Skipping the load check, could not run { $path }: { $error }
+plugin-active-no-runtime = No WasmEdge runtime to query in { $path }
+plugin-active-managed = active
+plugin-active-unmanaged = active, not installed by wasmedgeup (loaded from another directory)
+plugin-active-skew = version skew: built for WasmEdge { $version }
+plugin-active-missing-dependency = not loaded, missing dependency: { $error }
+plugin-active-load-error = not loaded: { $error }
+plugin-active-not-loaded-skew = not loaded, built for WasmEdge { $version }
+plugin-active-not-searched = not loaded, the runtime does not search { $path }
+plugin-active-none = The runtime loads no plugins and wasmedgeup installed none
+plugin-deps-missing = { $name } has { $count ->
+        [one] { $count } unresolved dependency
+       *[other] { $count } unresolved dependencies
+    }; install the missing libraries, or add their directory to the library path
+profile-unknown = No profile { $name } in the config file (profiles: { $known })
+profile-nested = Profile { $name } defines profiles of its own, which is not supported
+path-shadowed = `wasmedge` runs { $binary } from the PATH entry { $entry }, not the managed install. Move { $bin } ahead of { $entry }: source { $env } at the end of your shell profile, after anything that adds { $entry }, or remove the other copy
+path-shadowed-missing = `wasmedge` runs { $binary } from the PATH entry { $entry }, and { $bin } is not on PATH in a new login shell. Source { $env } from your shell profile, or remove the other copy
+path-not-on-path = { $bin } is not on PATH in a new login shell. Source { $env } from your shell profile
+version-invalid = { $version } is not a WasmEdge version like 0.14.1: { $error }
+download-preview = Installing { $count ->
+        [one] { $count } archive
+       *[other] { $count } archives
+    }: { $download } to download, { $disk } of disk space needed
+download-preview-unknown = Not counted in full, as the server does not report their size: { $names }
+download-confirm = Download { $download }?
+download-confirm-refused = This would download { $download }, more than --confirm-above { $limit } MB. Pass --yes to proceed
+outdated-brief-installed = WasmEdge { $version } installed
+outdated-brief-available = { $version } available
+outdated-brief-current = up to date
+outdated-brief-plugin = 1 plugin outdated
+outdated-brief-plugins = { $count ->
+        [one] { $count } plugin
+       *[other] { $count } plugins
+    } outdated
+outdated-current = WasmEdge { $version } and its plugins are up to date
+outdated-unknown = No release information cached yet; run `wasmedgeup update --check` to look for updates
+update-current = WasmEdge { $version } is the latest release
+update-success = Updated WasmEdge { $installed } to { $version }
+update-check-current = WasmEdge { $version }, its plugins and wasmedgeup are up to date
+update-check-plugins-failed = Could not check installed plugins for updates: { $error }
+update-check-self-failed = Could not check wasmedgeup for updates: { $error }
+self-update-current = wasmedgeup { $version } is the latest release
+self-update-success = Updated wasmedgeup from { $from } to { $to }
+self-update-unsigned = wasmedgeup { $version } is not signed. Pass --allow-unsigned to install it anyway
+self-update-no-key = This build of wasmedgeup has no pinned publisher key to verify { $version } with. Pass --allow-unsigned to install it anyway
+self-update-dry-run = Would update wasmedgeup from { $from } to { $to } with { $asset }
+metadata-stale = Could not reach { $url } ({ $error }); using cached data, stale as of { $time }
+list-stale = (releases as cached at { $time }, GitHub could not be reached)
+generate-ci-no-runtime = No WasmEdge runtime is installed in { $path }. Pass --version to choose one
+generate-ci-unpinned = No checksum pin file is in use, so the pipeline will not verify downloads. Pass --checksum-file or set checksum_file in the config file
+sync-not-found = No { $file } found in this directory or its parents
+workspace-member-outside = Workspace member { $member } in { $file } must be a path inside the workspace, without `..`
+sync-installed = Installed WasmEdge { $version } to { $path }
+init-exists = { $path } already exists. Pass --force to replace it
+init-written = Wrote { $path } pinning WasmEdge { $version }
+init-commit-hint = Commit { $file } so everyone working on the project, and CI, syncs the same toolchain: git add { $file }
+pin-not-installed = WasmEdge { $version } pinned by { $pin } is not installed under { $path }. Run `wasmedgeup sync` first
+received-html = Received an HTML page instead of the requested file from { $url }. A proxy or captive portal is probably intercepting downloads; check your proxy settings or --mirror
+unexpected-archive = The download from { $url } is not a { $format } archive. A proxy or mirror may be serving a different file
+plugin-export-success = Exported { $count ->
+        [one] { $count } plugin
+       *[other] { $count } plugins
+    } to { $path }
+plugin-import-success = Imported plugins: { $plugins }
+plugin-bundle-no-runtime = No WasmEdge runtime is installed in { $path }. Plugin bundles are made for and checked against the installed runtime
+patch-applied = Upgraded from { $from } to { $version } with a patch instead of a full download
+patch-fallback = Could not patch WasmEdge { $from } to { $version }, downloading it in full: { $error }
+selinux-unlabelled = SELinux is enforcing and may block loading libwasmedge from { $path }. Run the install again with --selinux-relabel, or check `wasmedgeup doctor`
+plugins-reinstalled = Reinstalled plugins for WasmEdge { $version }: { $plugins }
+plugins-unpublished = These plugins are not published for WasmEdge { $version } and were removed: { $plugins }
+plugins-reinstall-error = Installed WasmEdge { $version }, but could not reinstall the installed plugins for it
+artifact-not-provisioned = { $path } is missing from the artifact store. Provision it from { $url }
+copying-artifact = Copying from artifact store: { $path }
+cert-pin-invalid = Invalid certificate pin { $spec }; expected HOST=sha256/BASE64 of the public key
+cert-pin-mismatch = The certificate of { $host } does not carry a pinned public key (it has { $actual }); the connection may be intercepted
+cert-pin-no-tls = { $host } is pinned but was not reached over TLS
+download-resuming = Connection to { $url } dropped after { $bytes } ({ $error }); resuming
+latest-resolved = Latest stable release is { $version }
+latest-pre-resolved = Latest release, including pre-releases, is { $version }
+install-prerelease-refused = WasmEdge { $version } is a pre-release; pass --pre to install it
+latest-not-found = No stable WasmEdge release found upstream
+shared-cache-waiting = Waiting for another process to download { $name } into the shared cache
+shared-cache-corrupt = Dropping { $path } from the shared cache: { $error }
+shared-cache-stale-lock = Removing stale shared cache lock { $path }
+fetching = Fetching { $url }
+download-unverified = WasmEdge publishes no digest for { $name }; it was downloaded without verification
+package-fallback = WasmEdge { $version } publishes no { $preferred } archive; using { $fallback } instead
+package-fallback-confirm = WasmEdge { $version } publishes no { $preferred } archive. Install { $fallback } to run under Rosetta 2?
+package-fallback-declined = WasmEdge { $version } publishes no { $preferred } archive. The { $fallback } archive runs under Rosetta 2; pass --yes or confirm interactively to use it
+rosetta-detected = wasmedgeup runs under Rosetta on Apple silicon, so this terminal is x86_64 while the hardware is arm64. Using arm64 builds; pass --rosetta to use x86_64 builds for this terminal instead
+env-file-updated = Updated the managed block of { $path }:
+env-snippet-unreadable = Skipping env snippet { $path }: { $error }
+onboarding-welcome = Welcome to wasmedgeup! No WasmEdge runtime is installed yet. With the defaults, wasmedgeup will:
+onboarding-platform = - install the build for { $os } { $arch } (detected; choose another with --os and --arch)
+onboarding-version = - install the latest release (choose another with `wasmedgeup install <version>`)
+onboarding-location = - put the runtime, libraries and headers in { $path } (choose another with --path)
+onboarding-path = - write { $env }, which adds { $bin } to PATH once your shell profile sources it
+onboarding-confirm = Install now?
+onboarding-declined = Nothing was installed. Run `wasmedgeup install latest` when ready, or `wasmedgeup --help` for every command
+plugin-line-mismatch = WasmEdge { $runtime } only loads plugins built for its own major.minor release line (see `wasmedgeup compat { $runtime } { $plugin }`)
+compat-mismatch = Plugins built for { $plugin } do not load in WasmEdge { $runtime }
+compat-ok = Plugins built for { $plugin } load in WasmEdge { $runtime }
+armv7-unavailable = WasmEdge { $version } publishes no armv7 (32-bit ARM) archive; none have been published since WasmEdge { $last }. Install { $last } or older, or use a 64-bit OS
+armv7-never-published = WasmEdge { $version } publishes no armv7 (32-bit ARM) archive, and no release that does was found. Use a 64-bit OS to install WasmEdge
+no-release-binaries = WasmEdge publishes no official binaries for { $os }. Run `wasmedgeup install <version> --build-from-source` to build the runtime from source instead
+source-tools-missing = Building WasmEdge from source needs tools that were not found: { $tools }
+source-unknown-plugin = wasmedgeup does not know how to build the plugin { $name } from source; enable it with `--cmake-define` on a runtime source build instead
+source-unknown-feature = Unknown build feature { $feature } for the selected plugins. Known features: { $known }
+strict-inexact-version = --strict needs an exact release version, not { $version }
+strict-no-checksum = --strict needs a checksum for { $name }: pin it in the checksum file (--checksum-file)
+strict-fallback = --strict refuses the { $fallback } archive of WasmEdge { $version } in place of { $preferred }
+strict-guessed-url = --strict refuses to guess the download URL of plugin { $name } without the release listing
+strict-unsigned = --strict does not allow --allow-unsigned
+strict-source-build = --strict does not allow source builds, which have no checksum to verify
+support-eol = Installed WasmEdge { $version } is end-of-life upstream; upgrade to { $upgrade }
+support-eol-no-upgrade = Installed WasmEdge { $version } is end-of-life upstream
+support-advisory = WasmEdge { $version } is affected by { $id }: { $summary } (patched in { $patched })
+generate-nix-unsupported = Nix has no WasmEdge release archives for { $os }
+sbom-not-installed = No runtime installed by wasmedgeup at { $path }; install one to describe it
+generate-nix-prefetch = No published digest for { $name }; downloading it to compute one
+asdf-plugin-written = Wrote an asdf/mise plugin to { $path }
+path-no-home = Could not determine the home directory
+path-unknown-user = Unknown user ~{ $user }: no home directory found
+show-not-installed = No WasmEdge installation in { $path }
+show-env-unmanaged = { $path } was installed with --no-env and has no env file. Use `wasmedgeup env` to get its environment on demand
+releases-truncated = Stopped after { $pages ->
+        [one] { $pages } page
+       *[other] { $pages } pages
+    } of releases ({ $count ->
+        [one] { $count } release
+       *[other] { $count } releases
+    }); older releases are not listed
+remove-linked-default = { $path } is the default version of { $root }; make another version the default with `wasmedgeup default`, or remove { $root }, first
+remove-manifest-unsafe = { $manifest } names { $file }, which is not a path inside the installation; refusing to remove anything
+install-manifest-unsafe = { $manifest } names { $file }, which is not a path inside the installation; leaving it alone
+default-own-plugins = { $dir } holds plugins of an earlier installation; remove them with `wasmedgeup plugin remove` first, since each version linked by `default` brings its own
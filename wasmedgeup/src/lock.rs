@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Lock file created inside an installation prefix while it is being modified.
+pub const LOCK_FILE: &str = ".wasmedgeup.lock";
+
+/// Exclusive claim on an installation prefix. Only one wasmedgeup process may
+/// install into or remove a prefix at a time; the lock is released on drop,
+/// including when an interrupted command is unwound.
+#[derive(Debug)]
+pub struct InstallLock {
+    path: PathBuf,
+}
+
+impl InstallLock {
+    /// Take the lock for `install_path`, replacing a lock left behind by a
+    /// process that no longer runs.
+    pub fn acquire(install_path: &Path) -> Result<Self> {
+        std::fs::create_dir_all(install_path)
+            .with_context(|| format!("Failed to create {}", install_path.display()))?;
+        let path = install_path.join(LOCK_FILE);
+
+        for _ in 0..2 {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id())?;
+                    debug!("Acquired {}", path.display());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let owner = std::fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|s| s.trim().parse::<u32>().ok());
+                    match owner {
+                        Some(pid) if is_running(pid) => {
+                            anyhow::bail!(t!("install-locked", path = install_path.display(),
+                                pid = pid, lock = path.display()));
+                        }
+                        _ => {
+                            warn!("Removing stale lock {}", path.display());
+                            std::fs::remove_file(&path)
+                                .with_context(|| format!("Failed to remove {}", path.display()))?;
+                        }
+                    }
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+                }
+            }
+        }
+        anyhow::bail!("Could not acquire {}", path.display())
+    }
+}
+
+impl Drop for InstallLock {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            debug!("Could not remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Whether a process with `pid` exists. Platforms where this cannot be checked
+/// cheaply assume it does, so a lock is never stolen from a live process.
+pub fn is_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Path::new("/proc").join(pid.to_string()).exists()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive_and_released_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = InstallLock::acquire(dir.path()).unwrap();
+        assert!(InstallLock::acquire(dir.path()).is_err());
+        drop(lock);
+        assert!(!dir.path().join(LOCK_FILE).exists());
+
+        // A lock whose owner is gone is taken over
+        #[cfg(target_os = "linux")]
+        {
+            std::fs::write(dir.path().join(LOCK_FILE), "4294967295").unwrap();
+            assert!(InstallLock::acquire(dir.path()).is_ok());
+        }
+    }
+}
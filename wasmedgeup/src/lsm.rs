@@ -0,0 +1,206 @@
+//! SELinux and AppArmor, which can stop `libwasmedge` from loading out of a
+//! prefix their policy does not label for libraries, such as a home
+//! directory.
+
+use anyhow::{Context, Result};
+use log::debug;
+use std::path::Path;
+use std::process::Command;
+
+/// Where audit daemons write AVC and AppArmor denials.
+const AUDIT_LOG: &str = "/var/log/audit/audit.log";
+
+/// SELinux type each installed directory is labelled with by `relabel`.
+const FILE_CONTEXTS: &[(&str, &str)] = &[("lib", "lib_t"), ("bin", "bin_t")];
+
+/// SELinux types programs may load shared libraries from.
+const LIBRARY_TYPES: &[&str] = &["lib_t", "textrel_shlib_t", "ld_so_t"];
+
+/// SELinux mode, as reported by the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelinuxMode {
+    Disabled,
+    Permissive,
+    Enforcing,
+}
+
+pub fn selinux_mode() -> SelinuxMode {
+    match std::fs::read_to_string("/sys/fs/selinux/enforce") {
+        Ok(value) if value.trim() == "1" => SelinuxMode::Enforcing,
+        Ok(_) => SelinuxMode::Permissive,
+        Err(_) => SelinuxMode::Disabled,
+    }
+}
+
+pub fn apparmor_enabled() -> bool {
+    std::fs::read_to_string("/sys/module/apparmor/parameters/enabled").is_ok_and(|v| v.trim() == "Y")
+}
+
+/// Whether the `lib` directory of `install_path` is labelled with a type
+/// libraries load from: as it is on disk, or before it exists, as the
+/// loaded policy would label it.
+pub fn is_labelled_prefix(install_path: &Path) -> bool {
+    let lib = install_path.join("lib");
+    let context = if lib.exists() { context_of(&lib) } else { libselinux::matchpathcon(&lib) };
+    context.as_deref().and_then(context_type).is_some_and(|kind| LIBRARY_TYPES.contains(&kind))
+}
+
+/// SELinux context of `path`, e.g. `unconfined_u:object_r:user_home_t:s0`.
+pub fn context_of(path: &Path) -> Option<String> {
+    libselinux::getfilecon(path)
+}
+
+/// Type of an SELinux context, e.g. `user_home_t`.
+fn context_type(context: &str) -> Option<&str> {
+    context.split(':').nth(2)
+}
+
+/// The few libselinux calls needed, loaded when first used so that
+/// wasmedgeup neither links against libselinux nor needs it installed.
+/// Without it, nothing has a context.
+#[cfg(target_os = "linux")]
+mod libselinux {
+    use std::ffi::{c_char, c_int, CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::sync::OnceLock;
+
+    type GetFileCon = unsafe extern "C" fn(*const c_char, *mut *mut c_char) -> c_int;
+    type MatchPathCon = unsafe extern "C" fn(*const c_char, libc::mode_t, *mut *mut c_char) -> c_int;
+    type FreeCon = unsafe extern "C" fn(*mut c_char);
+
+    struct Library {
+        getfilecon: GetFileCon,
+        matchpathcon: MatchPathCon,
+        freecon: FreeCon,
+    }
+
+    fn library() -> Option<&'static Library> {
+        static LIBRARY: OnceLock<Option<Library>> = OnceLock::new();
+        LIBRARY
+            .get_or_init(|| {
+                // SAFETY: the handle is never closed, so the symbols stay
+                // valid, and each is cast to its signature in selinux.h.
+                unsafe {
+                    let handle = libc::dlopen(c"libselinux.so.1".as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+                    if handle.is_null() {
+                        return None;
+                    }
+                    let symbol = |name: &CStr| Some(libc::dlsym(handle, name.as_ptr())).filter(|s| !s.is_null());
+                    Some(Library {
+                        getfilecon: std::mem::transmute::<*mut libc::c_void, GetFileCon>(symbol(c"getfilecon")?),
+                        matchpathcon: std::mem::transmute::<*mut libc::c_void, MatchPathCon>(symbol(c"matchpathcon")?),
+                        freecon: std::mem::transmute::<*mut libc::c_void, FreeCon>(symbol(c"freecon")?),
+                    })
+                }
+            })
+            .as_ref()
+    }
+
+    /// Run a libselinux lookup that allocates a context, and take it.
+    fn take(lookup: impl FnOnce(*mut *mut c_char) -> c_int) -> Option<String> {
+        let library = library()?;
+        let mut context = std::ptr::null_mut();
+        if lookup(&mut context) != 0 || context.is_null() {
+            return None;
+        }
+        // SAFETY: on success the lookup stored a C string for freecon
+        let value = unsafe { CStr::from_ptr(context) }.to_string_lossy().into_owned();
+        unsafe { (library.freecon)(context) };
+        Some(value)
+    }
+
+    /// Context `path` is labelled with.
+    pub fn getfilecon(path: &Path) -> Option<String> {
+        let library = library()?;
+        let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: `path` is a C string and `context` a valid out pointer
+        take(|context| unsafe { (library.getfilecon)(path.as_ptr(), context) })
+    }
+
+    /// Context the loaded policy gives the directory `path`.
+    pub fn matchpathcon(path: &Path) -> Option<String> {
+        let library = library()?;
+        let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: as for getfilecon
+        take(|context| unsafe { (library.matchpathcon)(path.as_ptr(), libc::S_IFDIR, context) })
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod libselinux {
+    use std::path::Path;
+
+    pub fn getfilecon(_path: &Path) -> Option<String> {
+        None
+    }
+
+    pub fn matchpathcon(_path: &Path) -> Option<String> {
+        None
+    }
+}
+
+fn run(tool: &str, args: &[&str]) -> Result<std::process::Output> {
+    debug!("Running {} {}", tool, args.join(" "));
+    Command::new(tool)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}. Install policycoreutils or omit --selinux-relabel", tool))
+}
+
+/// Register library and executable file contexts for `install_path` with
+/// `semanage`, so they survive a relabel, and apply them with `restorecon`.
+pub fn relabel(install_path: &Path) -> Result<()> {
+    for (dir, context) in FILE_CONTEXTS {
+        let spec = format!("{}(/.*)?", install_path.join(dir).display());
+        let output = run("semanage", &["fcontext", "-a", "-t", context, &spec])?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && !stderr.contains("already defined") {
+            anyhow::bail!("semanage failed to add a file context for {}: {}", spec, stderr.trim());
+        }
+    }
+    let path = install_path.to_string_lossy();
+    let output = run("restorecon", &["-R", &path])?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "restorecon failed on {}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Whether an audit record is an SELinux or AppArmor denial involving WasmEdge.
+fn is_wasmedge_denial(line: &str) -> bool {
+    (line.contains("avc:  denied") || line.contains("apparmor=\"DENIED\"")) && line.contains("wasmedge")
+}
+
+/// Recent denials involving WasmEdge files, from `ausearch` when available
+/// and the audit log otherwise. Reading either usually requires root; no
+/// access yields no denials.
+pub fn denials() -> Vec<String> {
+    let log = match Command::new("ausearch").args(["-m", "AVC,APPARMOR_DENIED", "-ts", "recent", "--raw"]).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        _ => std::fs::read_to_string(AUDIT_LOG).unwrap_or_default(),
+    };
+    log.lines().filter(|line| is_wasmedge_denial(line)).map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denials_are_matched_for_wasmedge_only() {
+        let avc = r#"type=AVC msg=audit(1700000000.123:456): avc:  denied  { execute } for  pid=4242 comm="wasmedge" path="/home/ci/.wasmedge/lib/libwasmedge.so.0.1.0" scontext=unconfined_u:unconfined_r:unconfined_t:s0 tcontext=unconfined_u:object_r:user_home_t:s0 tclass=file permissive=0"#;
+        let apparmor = r#"type=AVC msg=audit(1700000001.000:457): apparmor="DENIED" operation="file_mmap" profile="/usr/bin/app" name="/opt/wasmedge/lib/libwasmedge.so" pid=77 comm="app""#;
+        let other = r#"type=AVC msg=audit(1700000002.000:458): avc:  denied  { read } for  pid=1 comm="httpd" path="/srv/www""#;
+        assert!(is_wasmedge_denial(avc));
+        assert!(is_wasmedge_denial(apparmor));
+        assert!(!is_wasmedge_denial(other));
+        assert_eq!(context_type("unconfined_u:object_r:user_home_t:s0"), Some("user_home_t"));
+        assert_eq!(context_type("system_u:object_r:lib_t:s0:c0.c1023"), Some("lib_t"));
+        assert_eq!(context_type("unlabeled"), None);
+    }
+}
@@ -1,131 +1,1458 @@
+#[macro_use]
+mod i18n;
+
+mod asdf;
+mod asset;
+mod buildinfo;
+mod bundle;
+mod cache;
+mod certpin;
+mod checksum;
+mod ci;
 mod cli;
+mod complete;
+mod config;
+mod context;
+mod delta;
+mod deps;
+mod doctor;
+mod download;
 mod downloader;
+mod envfile;
+mod events;
+mod exec;
+mod extract;
+mod fsutil;
 mod installer;
+mod list;
+mod licenses;
+mod lock;
+mod lsm;
+mod manifest;
+mod nix;
+mod onboarding;
+mod output;
+mod ownership;
+mod paths;
+mod pin;
 mod platform;
 mod plugin;
+mod process;
+mod prompt;
+mod proxy;
+mod release;
+mod rpath;
+mod sbom;
+mod self_update;
+mod shadow;
+mod shared_cache;
+mod shutdown;
+mod source;
+mod support;
+mod updates;
+mod version;
 
-use anyhow::Result;
-use clap::Parser;
-use cli::{Cli, Commands, PluginCommands};
-use installer::Installer;
+use anyhow::{Context, Result};
+use asset::ReleasePlatforms;
+use clap::CommandFactory;
+use cli::{AsdfCommands, AsdfInstallType, CacheCommands, CatalogCommands, Cli, Commands, Component, ComponentCommands, DepsCommands, EnvShell, GenerateCommands, InstallArgs, OutdatedFormat, PluginCommands, RuntimePart, SelfCommands, ShowTarget};
+use buildinfo::BuildInfo;
+use context::WasmedgeupContext;
+use paths::expand_path;
+use download::ArchiveSize;
+use downloader::{human_size, Fetcher};
+use installer::{RuntimeComponent, ScriptShell};
+use onboarding::Onboarding;
+use ownership::Ownership;
 use platform::{Architecture, OS, Platform};
 use plugin::PluginManager;
-use std::path::PathBuf;
+use source::SourceBuild;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use version::WasmEdgeVersion;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
-
-    // Setup logging based on verbosity
-    if cli.verbose {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
-    } else if !cli.quiet {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
-    } else {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+async fn main() -> Result<ExitCode, Reported> {
+    // Config values sit between the WASMEDGEUP_* variables and the built-in defaults
+    let mut config = config::Config::load()?;
+    if let Some(profile) = Cli::selected_profile(std::env::args_os()) {
+        config = config.with_profile(&profile)?;
     }
+    let defaults = config.defaults();
+    let mut cli = Cli::parse_with_defaults(&defaults);
+    i18n::init(cli.locale.as_deref());
 
+    // Completion runs on every keypress: no logging, network or signal setup
     match &cli.command {
-        Commands::Install { version, path, tmpdir, os, arch } => {
-            let platform = match (os, arch) {
-                (Some(os_str), Some(arch_str)) => {
-                    Platform::new(
-                        OS::from_str(os_str)?,
-                        Architecture::from_str(arch_str)?,
-                    )
-                }
-                _ => Platform::detect()?,
-            };
+        Some(Commands::Complete { words }) => {
+            for candidate in complete::candidates(words) {
+                println!("{}", candidate);
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Commands::Completions { shell }) => {
+            if cli.json {
+                output::print_json(serde_json::json!({ "script": shell.script() }))?;
+            } else {
+                print!("{}", shell.script());
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ => {}
+    }
 
-            let install_path = expand_path(path)?;
-            let temp_dir = expand_path(tmpdir)?;
+    output::init(cli.verbose, cli.quiet, cli.json, cli.progress);
+
+    // Without a command, a first run becomes `install latest` with the
+    // global flags it was given
+    let command = match cli.command.take() {
+        Some(command) => command,
+        None => match onboarding::run(&Platform::detect()?, cli.yes)? {
+            Onboarding::Installed => {
+                Cli::command().print_help()?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            Onboarding::Proceed => Commands::Install(Box::new(InstallArgs::latest(&defaults)?)),
+            Onboarding::Declined => return Ok(ExitCode::SUCCESS),
+        },
+    };
+
+    // `exec` leaves signals to the program it runs
+    if matches!(command, Commands::Exec { .. }) {
+        return Ok(run(cli, command, config).await?);
+    }
+
+    // The command runs as its own task so an interrupt can stop it while it
+    // is blocked on the network and still unwind its staging directories
+    let mut command = tokio::spawn(run(cli, command, config));
+    tokio::select! {
+        result = &mut command => {
+            Ok(result??)
+        }
+        _ = shutdown::interrupted() => {
+            shutdown::cancel(command).await;
+            eprintln!("{}", t!("interrupted"));
+            Ok(ExitCode::from(shutdown::INTERRUPTED_EXIT_CODE))
+        }
+    }
+}
+
+/// An error as `main` reports it: the error chain, then the build it
+/// happened in, for bug reports.
+struct Reported(anyhow::Error);
 
-            let installer = Installer::new(install_path, temp_dir, platform.clone());
-            
-            let version = if version == "latest" {
-                "0.14.1".to_string()
+impl<E: Into<anyhow::Error>> From<E> for Reported {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+impl std::fmt::Debug for Reported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}\n\n{}", self.0, BuildInfo::get().summary())
+    }
+}
+
+async fn run(cli: Cli, command: Commands, config: config::Config) -> Result<ExitCode> {
+    let ctx = WasmedgeupContext::new(&cli, config)?;
+    let Outcome { result, status } = dispatch(&cli, &command, &ctx).await?;
+    if matches!(command, Commands::Plugin { .. } | Commands::Component { .. } | Commands::List { .. }) {
+        support_hint(&ctx);
+    }
+
+    // The program `exec` ran owns stdout
+    if matches!(command, Commands::Exec { .. }) {
+        return Ok(status);
+    }
+    if output::is_json() {
+        match ctx.dry_run_changes() {
+            Some(changes) => output::print_json(serde_json::json!({ "changes": changes }))?,
+            None => output::print_json(result)?,
+        }
+    } else {
+        ctx.print_dry_run();
+    }
+    Ok(status)
+}
+
+/// What a command leaves behind: its result for `--json`, and the status
+/// the process exits with, returned to `main` so destructors still run.
+struct Outcome {
+    result: serde_json::Value,
+    status: ExitCode,
+}
+
+impl From<serde_json::Value> for Outcome {
+    fn from(result: serde_json::Value) -> Self {
+        Self { result, status: ExitCode::SUCCESS }
+    }
+}
+
+/// Run the selected command. Commands with a result to report return it for
+/// `--json` output, and print it themselves otherwise.
+async fn dispatch(cli: &Cli, command: &Commands, ctx: &WasmedgeupContext) -> Result<Outcome> {
+    let mut result = serde_json::Value::Null;
+    match command {
+        Commands::Install(install) => {
+            let InstallArgs { version, path, tmpdir, os, arch, platform, force, patch_rpath, static_build, pre, versioned, system, owner, group, mode, selinux_relabel, readonly, no_env, fsync, build_from_source, git_ref, source_plugins, cmake_defines, components, bin_only, allow_deprecated, allow_downgrade, max_time, plugins } = install.as_ref();
+            let platform = target_platform(ctx, platform, os, arch)?;
+            ctx.require_exact_version(version)?;
+
+            let install_path = if *system {
+                PathBuf::from(ownership::SYSTEM_PREFIX)
             } else {
-                version.clone()
+                expand_path(path)?
+            };
+            let temp_dir = expand_path(tmpdir)?;
+            let mode = match mode {
+                None if *system => Some(format!("{:o}", ownership::SYSTEM_MODE)),
+                mode => mode.clone(),
             };
+            let ownership = Ownership::resolve(owner.as_deref(), group.as_deref(), mode.as_deref())?;
 
+            let version = install_version(ctx.downloader()?, version, *pre).await?;
+            let source_build = build_from_source.then(|| {
+                SourceBuild::new(git_ref.clone().unwrap_or_else(|| version.tag()))
+                    .with_plugins(source_plugins.clone())
+                    .with_defines(cmake_defines.clone())
+            });
+
+            let installer = ctx.installer_for(platform.clone(), install_path.clone(), temp_dir.clone())?
+                .with_ownership(ownership)
+                .with_rpath_patching(*patch_rpath)
+                .with_static_build(*static_build)
+                .with_readonly(*readonly)
+                .with_no_env(*no_env)
+                .with_fsync(*fsync)
+                .with_source_build(source_build)
+                .with_selinux_relabel(*selinux_relabel)
+                .with_components(runtime_components(components))
+                .with_bin_only(*bin_only)
+                .with_max_time(max_time.map(Duration::from_secs))
+                .with_versioned_layout(versioned.then_some(&version));
+            let install_path = installer.install_path().to_path_buf();
+
+            let installed = installer::installed_version(&install_path);
+            let downgrade = installed
+                .as_deref()
+                .is_some_and(|installed| installer::is_downgrade(installed, &version.tag()));
+            if downgrade && !*allow_downgrade {
+                anyhow::bail!(t!("downgrade-refused", path = install_path.display(),
+                    installed = installed.unwrap_or_default(), version = version));
+            }
+
+            if !confirm_replace(&install_path, &version.tag(), cli.yes || *force || cli.dry_run)? {
+                output::status(t!("install-cancelled"));
+                return Ok(result.into());
+            }
+            installer.warn_if_deprecated(&version, *allow_deprecated).await;
+
+            // Plugins go into the prefix just installed, e.g. a profile's or a
+            // versioned one, and match the platform of its runtime
+            let plugin_manager = ctx.plugin_manager_for(platform, install_path.clone(), version.clone())?.with_temp_dir(temp_dir.clone());
+            if !plugins.is_empty() && !*build_from_source {
+                let runtime = installer.download_size(&version).await.unwrap_or_else(|e| {
+                    log::debug!("Could not get the size of the runtime archive: {}", e);
+                    None
+                });
+                if !confirm_plugin_downloads(cli, &plugin_manager, plugins, vec![("wasmedge".to_string(), runtime)]).await? {
+                    output::status(t!("install-cancelled"));
+                    return Ok(result.into());
+                }
+            }
+            let _lock = lock_prefix(cli, &install_path)?;
+            installer.ensure_not_in_use(*force)?;
             installer.install_runtime(&version).await?;
-            println!("Successfully installed WasmEdge {}", version);
+            ctx.report(t!("install-success", version = version));
+            if *versioned {
+                output::status(t!("install-versioned", path = install_path.display()));
+            } else if !cli.dry_run {
+                // Only `doctor` waits for a login shell; the current PATH
+                // is what this terminal resolves
+                if let Some(shadow) = std::env::var_os("PATH").and_then(|path| shadow::check(&install_path, &path)) {
+                    log::warn!("{}", shadow.advice(&install_path));
+                }
+            }
+
+            // Plugins built for another major.minor will not load in this runtime
+            if installed.as_deref().is_some_and(|installed| !plugin::is_compatible(&version, installed)) {
+                let outcome = reinstall_plugins(ctx, &install_path, &version, &temp_dir).await?;
+                result = serde_json::json!({ "version": version, "plugins": outcome });
+            }
+
+            if !plugins.is_empty() {
+                for plugin_spec in plugins {
+                    let (name, plugin_version) = Cli::parse_plugin_name_version(plugin_spec)?;
+                    plugin_manager.install_plugin(&name, plugin_version).await?;
+                    ctx.report(t!("plugin-install-success", name = name));
+                }
+            }
         }
 
-        Commands::List => {
-            // Implement version listing
-            println!("Available versions:");
-            println!("0.14.1 <- latest");
-            println!("0.14.0");
-            println!("0.13.5");
+        Commands::Download { version, plugins, output, os, arch, platform, static_build } => {
+            let platform = target_platform(ctx, platform, os, arch)?;
+            ctx.require_exact_version(version)?;
+            let version = release_version(ctx, version).await?;
+            let output_dir = expand_path(output)?;
+            std::fs::create_dir_all(&output_dir)?;
+
+            let mut downloaded = vec![ctx
+                .installer_for(platform.clone(), PathBuf::new(), output_dir.clone())?
+                .with_static_build(*static_build)
+                .download_runtime(&version, &output_dir)
+                .await?];
+            let plugin_manager = ctx.plugin_manager_for(platform, onboarding::default_install_path()?, version.clone())?;
+            for plugin_spec in plugins {
+                let (name, plugin_version) = Cli::parse_plugin_name_version(plugin_spec)?;
+                downloaded.push(plugin_manager.download_plugin(&name, plugin_version, &output_dir).await?);
+            }
+            for artifact in &downloaded {
+                output::say(format!("{}  {}", artifact.sha256, artifact.path.display()));
+            }
+            result = serde_json::to_value(&downloaded)?;
         }
 
-        Commands::Remove { path } => {
+        Commands::List { path, verify, pre } => {
+            let downloader = ctx.downloader()?;
+            let rows = list::list_versions(downloader, ctx.support_manifest(), &expand_path(path)?, ctx.platform()?, *verify, *pre).await?;
+            if output::is_json() {
+                result = serde_json::to_value(rows)?;
+            } else {
+                for line in list::version_lines(&rows, *verify) {
+                    output::say(line);
+                }
+                // A note rather than a row, so scripts reading the table need not skip it
+                if let Some(fetched) = downloader.stale_since() {
+                    output::status(list::stale_note(fetched));
+                }
+            }
+        }
+
+        Commands::Default { version, path, tmpdir } => {
             let install_path = expand_path(path)?;
-            let platform = Platform::detect()?;
-            let installer = Installer::new(
-                install_path.clone(),
-                PathBuf::from("/tmp"),
-                platform,
-            );
+            let version: WasmEdgeVersion = version.parse()?;
+            let installer = ctx.installer(install_path.clone(), expand_path(tmpdir)?)?;
+            let _lock = lock_prefix(cli, &install_path)?;
+            // Plugins stay with their version, so nothing is reinstalled
+            installer.set_default(&version).await?;
+            ctx.report(t!("default-success", version = version, path = install_path.display()));
+            result = serde_json::json!({ "version": version, "path": install_path });
+        }
 
+        Commands::Platforms { version: Some(version) } => {
+            let version = release_version(ctx, version).await?;
+            let release = release::fetch_release(ctx.downloader()?, &version).await?;
+            let platforms = ReleasePlatforms::from_assets(&version, release.assets.iter().map(|a| a.name.as_str()));
+            if output::is_json() {
+                return Ok(serde_json::to_value(platforms)?.into());
+            }
+            output::say(t!("platforms-runtime", version = version));
+            for (platform, variants) in &platforms.runtime {
+                if variants.is_empty() {
+                    output::say(format!("  {}", platforms.display(platform)));
+                } else {
+                    output::say(format!("  {} ({})", platforms.display(platform), variants.join(", ")));
+                }
+            }
+            output::say(t!("platforms-plugins"));
+            for (plugin, suffixes) in &platforms.plugins {
+                let shown: Vec<String> = suffixes.iter().map(|suffix| platforms.display(suffix)).collect();
+                output::say(format!("  {}: {}", plugin, shown.join(", ")));
+            }
+        }
+
+        Commands::Platforms { version: None } => {
+            let current = ctx.platform()?;
+            let detected = |platform: &Platform| platform.os == current.os && platform.arch == current.arch;
+            if output::is_json() {
+                let rows = Platform::known()
+                    .iter()
+                    .map(|platform| serde_json::json!({
+                        "platform": platform.shorthand(),
+                        "os": platform.os.to_string(),
+                        "arch": platform.arch.to_string(),
+                        "assets": platform.asset_suffix(),
+                        "detected": detected(platform),
+                    }))
+                    .collect();
+                return Ok(serde_json::Value::Array(rows).into());
+            }
+            output::say(format!("{:<22} {:<8} {:<8} ASSETS", "PLATFORM", "OS", "ARCH"));
+            for platform in Platform::known() {
+                let marker = if detected(&platform) { " <- detected" } else { "" };
+                output::say(format!("{:<22} {:<8} {:<8} {}{}", platform.shorthand(), platform.os.to_string(), platform.arch.to_string(),
+                    platform.asset_suffix(), marker));
+            }
+        }
+
+        Commands::Doctor { path } => {
+            let install_path = expand_path(path)?;
+            let mut findings = doctor::run(&install_path);
+            findings.extend(doctor::check_support(ctx.downloader()?, ctx.support_manifest(), &install_path).await);
+            if output::is_json() {
+                result = serde_json::to_value(&findings)?;
+            } else {
+                for finding in &findings {
+                    println!("{}", finding);
+                }
+            }
+        }
+
+        Commands::Remove { path, force, purge } => {
+            let install_path = expand_path(path)?;
+            let installer = ctx.installer_at(install_path.clone())?;
+            if let Some(root) = installer::linked_from(&install_path) {
+                anyhow::bail!(t!("remove-linked-default", path = install_path.display(), root = root.display()));
+            }
+
+            let lock = lock_prefix(cli, &install_path)?;
+            installer.ensure_not_in_use(*force)?;
+            // The plugins of the prefix being removed, found while its
+            // default link still points at them
+            let plugins = if *purge {
+                let runtime_version = installed_or_latest(ctx, &install_path).await?;
+                ctx.plugin_manager(install_path.clone(), runtime_version)?.remove_all()?
+            } else {
+                Vec::new()
+            };
             installer.remove_runtime().await?;
-            println!("Successfully removed WasmEdge from {}", install_path.display());
+            if *purge {
+                cache::clean_cache(ctx.fs())?;
+                let config = &ctx.config().file;
+                if config.exists() {
+                    ctx.fs().remove_file(config)?;
+                }
+                result = serde_json::json!({ "path": install_path, "purged": true, "plugins": plugins });
+            }
+            drop(lock);
+            // Only succeeds once nothing else is left in the prefix
+            if !cli.dry_run && std::fs::remove_dir(&install_path).is_ok() {
+                log::debug!("Removed empty {}", install_path.display());
+            }
+            ctx.report(t!(if *purge { "remove-purge-success" } else { "remove-success" }, path = install_path.display()));
         }
 
-        Commands::Plugin { command } => {
-            let platform = Platform::detect()?;
+        Commands::Plugin { path, tmpdir, command } => {
+            let install_path = expand_path(path)?;
+            let platform = ctx.platform()?;
             log::debug!("Detected platform: {} {}", platform.os, platform.arch);
-            
-            let plugin_manager = PluginManager::new("0.14.1".to_string(), platform.clone());
+
+            // Listing installed plugins needs no runtime version at all
+            if let PluginCommands::List { installed: true } = command {
+                return list_installed_plugins(&install_path).map(Outcome::from);
+            }
+            // Installed plugins must match the runtime they load into; removing
+            // and adopting only touch the plugin directory. With a runtime
+            // installed, these work offline without looking up the latest release
+            let installed = matches!(command, PluginCommands::Install { .. } | PluginCommands::Remove { .. }
+                | PluginCommands::Adopt { .. });
+            let latest = if matches!(command, PluginCommands::Export { .. } | PluginCommands::Import { .. }) {
+                // Bundles are made for and checked against the installed runtime
+                installed_runtime(&install_path)?
+            } else if installed {
+                installed_or_latest(ctx, &install_path).await?
+            } else {
+                release_version(ctx, "latest").await?
+            };
+            let plugin_manager = ctx.plugin_manager(install_path.clone(), latest.clone())?.with_temp_dir(expand_path(tmpdir)?);
 
             match command {
-                PluginCommands::Install { plugins } => {
-                    for plugin_spec in plugins {
-                        let (name, version) = Cli::parse_plugin_name_version(plugin_spec);
+                PluginCommands::Install { plugins, build_from_source, features, git_ref } => {
+                    let plugins = if plugins.is_empty() {
+                        pick_plugins(&plugin_manager, platform).await?
+                    } else {
+                        plugins.clone()
+                    };
+                    if plugins.len() > 1 && !*build_from_source && !confirm_plugin_downloads(cli, &plugin_manager, &plugins, Vec::new()).await? {
+                        output::status(t!("install-cancelled"));
+                        return Ok(result.into());
+                    }
+                    for plugin_spec in &plugins {
+                        let (name, version) = Cli::parse_plugin_name_version(plugin_spec)?;
                         log::debug!("Installing plugin {} version {:?}", name, version);
-                        plugin_manager.install_plugin(&name, version).await?;
-                        println!("Successfully installed plugin {}", name);
+                        if *build_from_source {
+                            let git_ref = git_ref.clone().or(version.map(|v| v.tag())).unwrap_or_else(|| latest.tag());
+                            let build = SourceBuild::new(git_ref).with_features(features.clone());
+                            plugin_manager.build_plugin(&name, build).await?;
+                        } else {
+                            plugin_manager.install_plugin(&name, version).await?;
+                        }
+                        ctx.report(t!("plugin-install-success", name = name));
                     }
                 }
 
-                PluginCommands::List => {
+                PluginCommands::List { .. } => {
                     log::debug!("Listing available plugins for platform {} {}", platform.os, platform.arch);
                     let plugins = plugin_manager.list_available_plugins().await?;
-                    println!("Available plugins:");
+                    if output::is_json() {
+                        let plugins: Vec<_> = plugins
+                            .into_iter()
+                            .map(|(name, version, compatible)| {
+                                serde_json::json!({ "name": name, "version": version, "compatible": compatible })
+                            })
+                            .collect();
+                        return Ok(serde_json::Value::Array(plugins).into());
+                    }
+                    println!("{}", t!("plugin-list-header"));
                     for (name, version, is_compatible) in plugins {
                         if is_compatible {
                             println!("{} {}", name, version);
                         } else {
-                            println!("{} {} [Not compatible with {} {}]", 
-                                name, version, platform.os, platform.arch);
+                            println!("{}", t!("plugin-incompatible", name = name, version = version,
+                                os = platform.os, arch = platform.arch));
                         }
                     }
                 }
 
-                PluginCommands::Remove { plugins } => {
+                PluginCommands::Remove { plugins, file } => {
+                    let _lock = lock_prefix(cli, &install_path)?;
                     for plugin_spec in plugins {
-                        let (name, version) = Cli::parse_plugin_name_version(plugin_spec);
+                        let (name, version) = Cli::parse_plugin_name_version(plugin_spec)?;
                         log::debug!("Removing plugin {} version {:?}", name, version);
                         plugin_manager.remove_plugin(&name, version)?;
-                        println!("Successfully removed plugin {}", name);
+                        ctx.report(t!("plugin-remove-success", name = name));
+                    }
+                    for file in file {
+                        let (path, owners) = plugin_manager.remove_plugin_file(file)?;
+                        ctx.report(t!("plugin-file-removed", path = path.display()));
+                        for name in owners {
+                            ctx.report(t!("plugin-file-untracked", name = name));
+                        }
+                    }
+                }
+
+                PluginCommands::Adopt { files, name } => {
+                    if name.is_some() && files.len() > 1 {
+                        anyhow::bail!(t!("plugin-adopt-name-many"));
+                    }
+                    // Adopted libraries are recorded as built for the installed runtime
+                    if installer::installed_version(&install_path).is_none() {
+                        anyhow::bail!(t!("show-not-installed", path = install_path.display()));
+                    }
+                    for file in files {
+                        let (name, path) = plugin_manager.adopt_plugin_file(file, name.as_deref())?;
+                        ctx.report(t!("plugin-adopt-success", path = path.display(), name = name, version = latest));
+                    }
+                }
+
+                PluginCommands::Pin { plugins } if plugins.is_empty() => {
+                    let pins = plugin::installed_plugins(&install_path)?.pins;
+                    if output::is_json() {
+                        return Ok(serde_json::to_value(pins)?.into());
+                    }
+                    if pins.is_empty() {
+                        output::status(t!("plugin-pins-none"));
+                    }
+                    for (name, version) in pins {
+                        output::say(format!("{}@{}", name, version));
+                    }
+                }
+
+                PluginCommands::Pin { plugins } => {
+                    for plugin_spec in plugins {
+                        let (name, version) = Cli::parse_plugin_name_version(plugin_spec)?;
+                        let version = plugin_manager.pin_plugin(&name, version)?;
+                        ctx.report(t!("plugin-pin-success", name = name, version = version));
+                    }
+                }
+
+                PluginCommands::Unpin { plugins } => {
+                    for name in plugins {
+                        let version = plugin_manager.unpin_plugin(name)?;
+                        ctx.report(t!("plugin-unpin-success", name = name, version = version));
+                    }
+                }
+
+                PluginCommands::Verify { plugins } => {
+                    let manager = installed_plugin_manager(ctx, &install_path).await?;
+                    let checks = manager.verify(&install_path, plugins)?;
+                    let failed = checks.iter().filter(|c| !c.problems.is_empty()).count();
+                    if output::is_json() {
+                        result = serde_json::to_value(&checks)?;
+                    } else {
+                        for check in &checks {
+                            if check.problems.is_empty() {
+                                println!("{}", t!("plugin-verify-ok", name = check.name));
+                            } else {
+                                println!("{}", t!("plugin-verify-failed", name = check.name));
+                                for problem in &check.problems {
+                                    println!("  {}", problem);
+                                }
+                            }
+                        }
+                    }
+                    if failed > 0 {
+                        // The command fails, but scripts still get the details
+                        if output::is_json() {
+                            output::print_json(&result)?;
+                        }
+                        anyhow::bail!(t!("plugin-verify-summary", failed = failed, total = checks.len()));
+                    }
+                }
+
+                PluginCommands::Export { plugins, output } => {
+                    let path = expand_path(output)?;
+                    let count = plugin_manager.export(plugins, &path)?;
+                    output::say(t!("plugin-export-success", count = count, path = path.display()));
+                    result = serde_json::json!({ "path": path, "plugins": count });
+                }
+
+                PluginCommands::Import { bundle } => {
+                    let _lock = lock_prefix(cli, &install_path)?;
+                    let imported = plugin_manager.import(&expand_path(bundle)?)?;
+                    ctx.report(t!("plugin-import-success", plugins = imported.join(", ")));
+                    result = serde_json::to_value(imported)?;
+                }
+
+                PluginCommands::Active => {
+                    let manager = installed_plugin_manager(ctx, &install_path).await?;
+                    let rows = manager.active(&install_path)?;
+                    if output::is_json() {
+                        return Ok(serde_json::to_value(rows)?.into());
+                    }
+                    if rows.is_empty() {
+                        println!("{}", t!("plugin-active-none"));
+                    }
+                    for row in rows {
+                        println!("{:<24} {:<10} {}", row.name, row.version.as_deref().unwrap_or("-"), row.status);
+                    }
+                }
+
+                PluginCommands::Deps { command: DepsCommands::Tree { plugin } } => {
+                    let manager = installed_plugin_manager(ctx, &install_path).await?;
+                    let tree = manager.deps_tree(&install_path, plugin)?;
+                    if output::is_json() {
+                        return Ok(serde_json::to_value(&tree)?.into());
+                    }
+                    print!("{}", tree.render());
+                    if !tree.is_satisfied() {
+                        output::status(t!("plugin-deps-missing", name = tree.name, count = tree.missing()));
+                    }
+                }
+
+                PluginCommands::Catalog { command: CatalogCommands::Export { version, output } } => {
+                    let catalog = ctx
+                        .plugin_manager(onboarding::default_install_path()?, release_version(ctx, version).await?)?
+                        .catalog()
+                        .await?;
+                    let json = serde_json::to_string_pretty(&catalog)?;
+                    match output {
+                        Some(path) => {
+                            std::fs::write(path, json)?;
+                            output::say(t!("plugin-catalog-written", count = catalog.len(), path = path.display()));
+                        }
+                        None if output::is_json() => result = serde_json::to_value(&catalog)?,
+                        None => output::say(json),
                     }
                 }
             }
         }
+
+        Commands::Component { command } => match command {
+            ComponentCommands::Add { component, version, path, tmpdir } => {
+                ctx.require_exact_version(version)?;
+                let install_path = expand_path(path)?;
+                let installer = ctx.installer(install_path.clone(), expand_path(tmpdir)?)?;
+                let _lock = lock_prefix(cli, &install_path)?;
+                let version = release_version(ctx, version).await?;
+                match component {
+                    Component::Debug => {
+                        let debug_dir = installer.install_debug_symbols(&version).await?;
+                        ctx.report(t!("debug-symbols-installed", path = debug_dir.display()));
+                        result = serde_json::json!({ "component": "debug", "version": version, "path": debug_dir });
+                    }
+                }
+            }
+        },
+
+        Commands::Init { version, plugins, components, install, force, path: install_root, tmpdir } => {
+            let dir = std::env::current_dir()?;
+            let path = dir.join(pin::TOOLCHAIN_FILE);
+            ctx.require_exact_version(version)?;
+            if path.exists() && !*force {
+                anyhow::bail!(t!("init-exists", path = path.display()));
+            }
+            let version = match version.as_str() {
+                "latest" => release_version(ctx, version).await?.to_string(),
+                version => version.to_string(),
+            };
+            let mut plugins = plugins.clone();
+            plugins.sort();
+            plugins.dedup();
+            let toolchain = pin::Toolchain { version, plugins, components: runtime_components(components) };
+            ctx.fs().write(&path, toolchain.to_toml()?.as_bytes())?;
+            ctx.report(t!("init-written", path = path.display(), version = &toolchain.version));
+            output::status(t!("init-commit-hint", file = pin::TOOLCHAIN_FILE));
+            result = serde_json::json!({ "path": path, "version": &toolchain.version, "plugins": &toolchain.plugins });
+            if *install {
+                let pin = pin::Pin {
+                    path,
+                    version: toolchain.version,
+                    plugins: toolchain.plugins,
+                    components: toolchain.components,
+                };
+                result["installed"] = serde_json::Value::Array(sync_pins(cli, ctx, &[pin], &expand_path(install_root)?, &expand_path(tmpdir)?).await?);
+            }
+        }
+
+        Commands::Sync { workspace, path, tmpdir } => {
+            let cwd = std::env::current_dir()?;
+            let pins = if *workspace {
+                let (root, members) = pin::find_workspace(&cwd)?
+                    .ok_or_else(|| anyhow::anyhow!(t!("sync-not-found", file = pin::WORKSPACE_FILE)))?;
+                pin::workspace_pins(&root, &members)?
+            } else {
+                pin::nearest_pin(&cwd)?.into_iter().collect()
+            };
+            if pins.is_empty() {
+                anyhow::bail!(t!("sync-not-found", file = format!("{} or {}", pin::TOOLCHAIN_FILE, pin::PIN_FILE)));
+            }
+
+            result = serde_json::Value::Array(sync_pins(cli, ctx, &pins, &expand_path(path)?, &expand_path(tmpdir)?).await?);
+        }
+
+        Commands::Exec { path, version, with_plugins, program, args } => {
+            let install_root = expand_path(path)?;
+            let install_path = match version {
+                Some(version) => exec::version_prefix(&install_root, version)?,
+                None => pinned_prefix(&install_root, &std::env::current_dir()?)?,
+            };
+            let code = exec::run(&install_path, &ctx.platform()?.os, *with_plugins, program, args)?;
+            // Codes beyond what a process can exit with, such as Windows
+            // NTSTATUS values, still report a failure
+            let status = u8::try_from(code).map_or(ExitCode::FAILURE, ExitCode::from);
+            return Ok(Outcome { result, status });
+        }
+
+        Commands::Env { path, shell } => {
+            let install_path = pinned_prefix(&expand_path(path)?, &std::env::current_dir()?)?;
+            if installer::installed_version(&install_path).is_none() {
+                anyhow::bail!(t!("show-not-installed", path = install_path.display()));
+            }
+            let os = &ctx.platform()?.os;
+            let shell = match shell {
+                Some(EnvShell::Sh) => ScriptShell::Posix,
+                Some(EnvShell::Cmd) => ScriptShell::Cmd,
+                Some(EnvShell::Powershell) => ScriptShell::PowerShell,
+                None => ScriptShell::detect(os),
+            };
+            let script = installer::shell_env_script(&install_path, os, shell, installer::is_rpath_patched(&install_path));
+            output::say(script.trim_end().to_string());
+            return Ok(serde_json::json!({ "path": install_path, "script": script }).into());
+        }
+
+        Commands::Asdf { command } => match command {
+            AsdfCommands::Plugin { dir } => {
+                let bin = expand_path(dir)?.join("bin");
+                ctx.fs().create_dir_all(&bin)?;
+                for (name, script) in asdf::plugin_scripts() {
+                    ctx.fs().write(&bin.join(&name), script.as_bytes())?;
+                    ctx.fs().set_executable(&bin.join(&name))?;
+                }
+                ctx.report(t!("asdf-plugin-written", path = dir.display()));
+            }
+            AsdfCommands::ListAll => {
+                let releases = release::fetch_releases(ctx.downloader()?).await?;
+                let versions = asdf::list_all(&releases);
+                output::say(versions.clone());
+                result = serde_json::json!({ "versions": versions.split(' ').collect::<Vec<_>>() });
+            }
+            AsdfCommands::LatestStable => {
+                let releases = release::fetch_releases(ctx.downloader()?).await?;
+                let version = asdf::latest_stable(&releases);
+                if let Some(version) = version {
+                    output::say(version.to_string());
+                }
+                result = serde_json::json!({ "version": version });
+            }
+            AsdfCommands::Download { install_type, version, path, download_path } => {
+                // Refs are built from source by `install`; there is nothing to fetch
+                if *install_type == AsdfInstallType::Version {
+                    ctx.require_exact_version(version)?;
+                    let installer = ctx.installer(path.clone(), download_path.clone())?;
+                    installer.download_runtime(&release_version(ctx, version).await?, download_path).await?;
+                }
+            }
+            AsdfCommands::Install { install_type, version, path, download_path, tmpdir } => {
+                let temp_dir = expand_path(tmpdir)?;
+                let downloaded = download_path.as_ref().filter(|dir| dir.read_dir().is_ok_and(|mut d| d.next().is_some()));
+                let installer = match downloaded {
+                    Some(dir) => ctx.installer_from_store(dir.clone(), path.clone(), temp_dir)?,
+                    None => ctx.installer(path.clone(), temp_dir)?,
+                };
+                // asdf owns the environment of its installs
+                let installer = installer.with_no_env(true);
+                match install_type {
+                    AsdfInstallType::Version => {
+                        ctx.require_exact_version(version)?;
+                        installer.install_runtime(&release_version(ctx, version).await?).await?;
+                    }
+                    AsdfInstallType::Ref => {
+                        // Refs that are not release tags build against the
+                        // latest release, but are recorded as the ref asdf
+                        // asked for
+                        let release = match version.parse() {
+                            Ok(release) => release,
+                            Err(_) => release_version(ctx, "latest").await?,
+                        };
+                        installer
+                            .with_source_build(Some(SourceBuild::new(version.clone())))
+                            .with_recorded_version(Some(version.clone()))
+                            .install_runtime(&release)
+                            .await?;
+                    }
+                }
+            }
+            AsdfCommands::ListBinPaths => {
+                output::say("bin".to_string());
+                result = serde_json::json!({ "paths": ["bin"] });
+            }
+            AsdfCommands::ExecEnv { path } => {
+                let script = asdf::exec_env(path, &ctx.platform()?.os);
+                if !script.is_empty() {
+                    output::say(script.trim_end().to_string());
+                }
+                result = serde_json::json!({ "script": script });
+            }
+        },
+
+        Commands::Compat { runtime, plugin } => {
+            let compatible = runtime.parse().is_ok_and(|runtime| plugin::is_compatible(&runtime, plugin));
+            result = serde_json::json!({ "runtime": runtime, "plugin": plugin, "compatible": compatible });
+            if !compatible {
+                if output::is_json() {
+                    output::print_json(&result)?;
+                }
+                anyhow::bail!(t!("compat-mismatch", runtime = runtime, plugin = plugin));
+            }
+            output::say(t!("compat-ok", runtime = runtime, plugin = plugin));
+        }
+
+        Commands::Show { what, path } => {
+            let install_path = expand_path(path)?;
+            let shown = match what {
+                ShowTarget::Home => install_path,
+                ShowTarget::PluginDir => plugin::plugin_dir(&install_path),
+                ShowTarget::CacheDir => cache::cache_dir()?,
+                ShowTarget::Config => ctx.config().file.clone(),
+                ShowTarget::EnvFile if installer::is_env_unmanaged(&install_path) && !install_path.join("env").exists() => {
+                    anyhow::bail!(t!("show-env-unmanaged", path = install_path.display()));
+                }
+                ShowTarget::EnvFile => install_path.join("env"),
+                ShowTarget::Version => {
+                    let version = installer::installed_version(&install_path)
+                        .with_context(|| t!("show-not-installed", path = install_path.display()))?;
+                    let support = support::check(ctx.downloader()?, ctx.support_manifest(), &version).await;
+                    let support = support.unwrap_or_else(|e| {
+                        log::debug!("Could not check upstream support: {:#}", e);
+                        support::Support::unknown(&version)
+                    });
+                    for warning in support.warnings() {
+                        log::warn!("{}", warning);
+                    }
+                    match support.status {
+                        Some(status) => output::say(format!("{} ({})", version, status)),
+                        None => output::say(version),
+                    }
+                    return Ok(serde_json::to_value(&support)?.into());
+                }
+            };
+            if output::is_json() {
+                result = serde_json::json!({ "path": shown });
+            } else {
+                println!("{}", shown.display());
+            }
+        }
+
+        Commands::Cache { command } => match command {
+            CacheCommands::Clean { only, tmp, tmpdir } => {
+                if only.is_empty() {
+                    cache::clean_cache(ctx.fs())?;
+                    ctx.report(t!("cache-cleaned", path = cache::cache_dir()?.display()));
+                } else {
+                    let parts: Vec<&str> = only.iter().map(|part| part.dir_name()).collect();
+                    for dir in cache::clean_cache_parts(ctx.fs(), &parts)? {
+                        ctx.report(t!("cache-cleaned", path = dir.display()));
+                    }
+                }
+
+                if *tmp {
+                    let tmpdir = match tmpdir {
+                        Some(dir) => expand_path(dir)?,
+                        None => std::env::temp_dir(),
+                    };
+                    let removed = cache::sweep_temp_dir(ctx.fs(), &tmpdir)?;
+                    ctx.report(t!("cache-swept", count = removed, path = tmpdir.display()));
+                }
+            }
+        },
+
+        Commands::Generate { command } => match command {
+            GenerateCommands::Ci { provider, path, version, plugins, lockfile } => {
+                let (version, plugins) = match lockfile {
+                    Some(lockfile) => {
+                        let content = std::fs::read_to_string(lockfile)
+                            .with_context(|| format!("Failed to read {}", lockfile.display()))?;
+                        let toolchain = pin::Toolchain::parse(&content)
+                            .with_context(|| format!("Failed to parse {}", lockfile.display()))?;
+                        (toolchain.version, toolchain.plugins)
+                    }
+                    None => generate_inputs(path, version, plugins)?,
+                };
+                let checksum_file = cli.checksum_file.clone();
+                if checksum_file.is_none() {
+                    log::warn!("{}", t!("generate-ci-unpinned"));
+                }
+
+                let snippet = ci::CiPlan { version, plugins, checksum_file }.render(*provider);
+                if output::is_json() {
+                    result = serde_json::json!({ "snippet": snippet });
+                } else {
+                    print!("{}", snippet);
+                }
+            }
+
+            GenerateCommands::Sbom { path } => {
+                let path = expand_path(path)?;
+                let created = humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string();
+                let document = sbom::document(&path, &created)?;
+                output::say(serde_json::to_string_pretty(&document)?);
+                result = document;
+            }
+
+            GenerateCommands::Nix { path, version, plugins, tmpdir } => {
+                let (version, plugins) = generate_inputs(path, version, plugins)?;
+                ctx.require_exact_version(&version)?;
+                let version = release_version(ctx, &version).await?;
+                let system = nix::nix_system(ctx.platform()?)?;
+                let install_path = expand_path(path)?;
+                let tmpdir = expand_path(tmpdir)?;
+
+                let installer = ctx.installer(install_path.clone(), tmpdir.clone())?;
+                let (url, sha256) = installer.resolve_runtime_archive(&version).await?;
+                let sha256 = match sha256 {
+                    Some(sha256) => sha256,
+                    None => prefetch(ctx, &url, &tmpdir).await?,
+                };
+                let runtime = nix::NixArtifact { name: "wasmedge".to_string(), url, sha256 };
+
+                let plugin_manager = ctx.plugin_manager(install_path, version.clone())?;
+                let mut artifacts = Vec::new();
+                for spec in &plugins {
+                    let (name, plugin_version) = Cli::parse_plugin_name_version(spec)?;
+                    let entry = plugin_manager.resolve_archive(&name, plugin_version).await?;
+                    let sha256 = match entry.sha256 {
+                        Some(sha256) => sha256,
+                        None => prefetch(ctx, &entry.url, &tmpdir).await?,
+                    };
+                    artifacts.push(nix::NixArtifact { name: entry.name, url: entry.url, sha256 });
+                }
+
+                let derivation = nix::NixPlan { version: version.tag(), system, runtime, plugins: artifacts }.render();
+                if output::is_json() {
+                    result = serde_json::json!({ "derivation": derivation });
+                } else {
+                    output::say(derivation.trim_end().to_string());
+                }
+            }
+        },
+
+        Commands::Update { path, check: false, tmpdir, force } => {
+            let install_path = expand_path(path)?;
+            let installed: WasmEdgeVersion = installer::installed_version(&install_path)
+                .with_context(|| t!("show-not-installed", path = install_path.display()))?
+                .parse()?;
+            let latest = release::latest_version(ctx.downloader()?).await?;
+            if latest <= installed {
+                ctx.report(t!("update-current", version = installed));
+                return Ok(serde_json::json!({ "version": installed, "updated": false }).into());
+            }
+
+            // A version linked by `default` is upgraded side by side and linked
+            let linked = installer::default_version(&install_path).is_some();
+            let temp_dir = expand_path(tmpdir)?;
+            let installer = ctx.installer(install_path.clone(), temp_dir.clone())?
+                .with_installed_options()
+                .with_patch_base(Some(&installed))
+                .with_versioned_layout(linked.then_some(&latest));
+            let _lock = lock_prefix(cli, &install_path)?;
+            installer.ensure_not_in_use(*force)?;
+            installer.install_runtime(&latest).await?;
+            if linked {
+                ctx.installer(install_path.clone(), temp_dir.clone())?.set_default(&latest).await?;
+            }
+            ctx.report(t!("update-success", installed = installed, version = latest));
+            result = serde_json::json!({ "version": latest, "previous": installed, "updated": true });
+            if !plugin::is_compatible(&latest, &installed.tag()) {
+                let outcome = reinstall_plugins(ctx, &install_path, &latest, &temp_dir).await?;
+                result["plugins"] = serde_json::to_value(outcome)?;
+            }
+        }
+
+        Commands::Update { path, check: true, .. } => {
+            let install_path = expand_path(path)?;
+            let installed: WasmEdgeVersion = installer::installed_version(&install_path)
+                .with_context(|| t!("show-not-installed", path = install_path.display()))?
+                .parse()?;
+            let latest = release::latest_version(ctx.downloader()?).await?;
+            let mut updates = updates::Updates {
+                runtime: updates::outdated("wasmedge", &installed, &latest),
+                ..Default::default()
+            };
+            // A check that fails leaves the others to report, then fails the run
+            let mut failed = false;
+            let receipts = plugin::installed_plugins(&install_path)?;
+            if !receipts.plugins.is_empty() {
+                match ctx.plugin_manager(install_path.clone(), installed.clone())?.catalog().await {
+                    Ok(catalog) => {
+                        updates.plugins = updates::outdated_plugins(&receipts, &installed, &catalog, &ctx.platform()?.asset_suffix());
+                    }
+                    Err(e) => {
+                        log::warn!("{}", t!("update-check-plugins-failed", error = format!("{:#}", e)));
+                        failed = true;
+                    }
+                }
+            }
+            match self_update::check(ctx.downloader()?).await {
+                Ok(outdated) => updates.wasmedgeup = outdated,
+                Err(e) => {
+                    log::warn!("{}", t!("update-check-self-failed", error = format!("{:#}", e)));
+                    failed = true;
+                }
+            }
+
+            if updates.is_empty() && !failed {
+                output::say(t!("update-check-current", version = installed));
+            }
+            for outdated in updates.all() {
+                output::say(outdated.to_string());
+            }
+            let status = match (failed, updates.is_empty()) {
+                (true, _) => ExitCode::FAILURE,
+                (false, true) => ExitCode::SUCCESS,
+                (false, false) => ExitCode::from(updates::UPDATES_AVAILABLE_EXIT_CODE),
+            };
+            return Ok(Outcome { result: serde_json::to_value(&updates)?, status });
+        }
+
+        Commands::Outdated { path, format } => {
+            let install_path = expand_path(path)?;
+            let installed: WasmEdgeVersion = installer::installed_version(&install_path)
+                .with_context(|| t!("show-not-installed", path = install_path.display()))?
+                .parse()?;
+            let updates = updates::cached(&install_path, &installed, &ctx.platform()?.asset_suffix())?;
+            match (format, &updates) {
+                (OutdatedFormat::Brief, updates) => output::say(updates::brief(&installed, updates.as_ref())),
+                (OutdatedFormat::Lines, None) => output::status(t!("outdated-unknown")),
+                (OutdatedFormat::Lines, Some(updates)) if updates.is_empty() => {
+                    output::say(t!("outdated-current", version = installed));
+                }
+                (OutdatedFormat::Lines, Some(updates)) => {
+                    for outdated in updates.all() {
+                        output::say(outdated.to_string());
+                    }
+                }
+            }
+            result = serde_json::to_value(&updates)?;
+        }
+
+        Commands::SelfCmd { command } => match command {
+            SelfCommands::Update { allow_unsigned } => {
+                ctx.require_signed(*allow_unsigned)?;
+                self_update::update(ctx.downloader()?, ctx.fs(), *allow_unsigned).await?;
+            }
+        },
+
+        Commands::Completions { .. } | Commands::Complete { .. } => unreachable!("completion is answered in main"),
+    }
+
+    Ok(result.into())
+}
+
+/// Reinstall the recorded plugins for `version` after the runtime changed
+/// `major.minor`, since builds for another one will not load, and say what
+/// happened to each.
+async fn reinstall_plugins(ctx: &WasmedgeupContext, install_path: &Path, version: &WasmEdgeVersion, tmpdir: &Path) -> Result<plugin::Reinstalled> {
+    let outcome = ctx
+        .plugin_manager(install_path.to_path_buf(), version.clone())?
+        .with_temp_dir(tmpdir.to_path_buf())
+        .reinstall_all()
+        .await
+        .with_context(|| t!("plugins-reinstall-error", version = version))?;
+    if !outcome.reinstalled.is_empty() {
+        log::info!("{}", t!("plugins-reinstalled", version = version, plugins = outcome.reinstalled.join(", ")));
+    }
+    if !outcome.removed.is_empty() {
+        log::warn!("{}", t!("plugins-unpublished", version = version, plugins = outcome.removed.join(", ")));
+    }
+    if !outcome.failed.is_empty() {
+        log::warn!("{}", t!("plugins-reinstall-failed", version = version, plugins = outcome.failed.join(", ")));
+    }
+    if !outcome.pinned.is_empty() {
+        log::warn!("{}", t!("plugins-kept-pinned", version = version, plugins = outcome.pinned.join(", ")));
+    }
+    Ok(outcome)
+}
+
+/// Print the plugins recorded in the installation at `install_path` and the
+/// libraries in its plugin directory that no receipt names.
+fn list_installed_plugins(install_path: &Path) -> Result<serde_json::Value> {
+    let plugin_dir = plugin::plugin_dir(install_path);
+    let receipts = plugin::installed_plugins(install_path)?;
+    let orphans = receipts.orphans(&plugin_dir);
+    if output::is_json() {
+        let mut rows: Vec<_> = receipts
+            .plugins
+            .iter()
+            .map(|(name, receipt)| serde_json::json!({ "name": name, "version": receipt.version, "tracked": true }))
+            .collect();
+        rows.extend(orphans.iter().map(|path| serde_json::json!({ "path": path, "tracked": false })));
+        return Ok(serde_json::Value::Array(rows));
     }
+    if receipts.plugins.is_empty() && orphans.is_empty() {
+        output::status(t!("plugin-installed-none"));
+    }
+    for (name, receipt) in &receipts.plugins {
+        output::say(format!("{} {}", name, receipt.version));
+    }
+    for path in &orphans {
+        output::say(t!("plugin-orphan", path = path.display()));
+    }
+    if !orphans.is_empty() {
+        output::status(t!("plugin-orphans-hint"));
+    }
+    Ok(serde_json::Value::Null)
+}
+
+/// Plugin manager for the plugin directory of the installation at
+/// `install_path`, with plugins built for the runtime installed there.
+async fn installed_plugin_manager(ctx: &WasmedgeupContext, install_path: &Path) -> Result<PluginManager> {
+    let runtime_version = installed_or_latest(ctx, install_path).await?;
+    ctx.plugin_manager(install_path.to_path_buf(), runtime_version)
+}
+
+/// Version of the runtime installed at `install_path`, for commands that
+/// must match it rather than fall back to the latest release.
+fn installed_runtime(install_path: &Path) -> Result<WasmEdgeVersion> {
+    let version = installer::installed_version(install_path)
+        .ok_or_else(|| anyhow::anyhow!(t!("plugin-bundle-no-runtime", path = install_path.display())))?;
+    version.parse()
+}
+
+/// Install every version `pins` require side by side, with the components
+/// and plugins the pins of each version name.
+async fn sync_pins(cli: &Cli, ctx: &WasmedgeupContext, pins: &[pin::Pin], install_root: &Path, tmpdir: &Path) -> Result<Vec<serde_json::Value>> {
+    let mut synced = Vec::new();
+    for pin in pins {
+        ctx.require_exact_version(&pin.version)
+            .with_context(|| format!("Invalid version pinned by {}", pin.path.display()))?;
+    }
+    for version in pin::required_versions(pins) {
+        let pinned: Vec<&pin::Pin> = pins.iter().filter(|pin| pin.version == version).collect();
+        // Any pin asking for every component gets them all
+        let mut components = Vec::new();
+        if pinned.iter().all(|pin| !pin.components.is_empty()) {
+            for component in pinned.iter().flat_map(|pin| &pin.components) {
+                if !components.contains(component) {
+                    components.push(*component);
+                }
+            }
+        }
+        let plugins: std::collections::BTreeSet<&String> = pinned.iter().flat_map(|pin| &pin.plugins).collect();
+        let release: WasmEdgeVersion = version.parse()?;
+
+        let prefix = pin::version_prefix(install_root, &version);
+        if installer::installed_version(&prefix).as_deref() == Some(version.as_str()) {
+            log::info!("WasmEdge {} is already installed in {}", version, prefix.display());
+        } else {
+            let _lock = lock_prefix(cli, &prefix)?;
+            ctx.installer(prefix.clone(), tmpdir.to_path_buf())?
+                .with_components(components)
+                .install_runtime(&release)
+                .await?;
+            ctx.report(t!("sync-installed", version = version, path = prefix.display()));
+        }
+
+        // Each pinned version loads the plugins in its own prefix
+        let installed = plugin::installed_plugins(&prefix)?;
+        let plugin_manager = ctx.plugin_manager(prefix.clone(), release.clone())?.with_temp_dir(tmpdir.to_path_buf());
+        for name in &plugins {
+            let current = installed.find(name).and_then(|published| installed.plugins.get(&published));
+            let up_to_date = |receipt: &plugin::PluginReceipt| match installed.pinned(name) {
+                Some(pinned) => receipt.version == *pinned,
+                None => plugin::is_compatible(&release, &receipt.version),
+            };
+            if current.is_some_and(up_to_date) {
+                log::info!("Plugin {} is already installed", name);
+                continue;
+            }
+            plugin_manager.install_plugin(name, None).await?;
+            ctx.report(t!("plugin-install-success", name = name));
+        }
+        synced.push(serde_json::json!({ "version": version, "path": prefix, "plugins": plugins }));
+    }
+    Ok(synced)
+}
+
+/// Runtime version and plugin specs for `generate`: the given ones, or those
+/// of the installation at `path`.
+fn generate_inputs(path: &Path, version: &Option<String>, plugins: &[String]) -> Result<(String, Vec<String>)> {
+    let install_path = expand_path(path)?;
+    let version = match version {
+        Some(version) => version.clone(),
+        None => installer::installed_version(&install_path).ok_or_else(|| {
+            anyhow::anyhow!(t!("generate-ci-no-runtime", path = install_path.display()))
+        })?,
+    };
+    let plugins = if plugins.is_empty() {
+        plugin::installed_plugins(&install_path)?
+            .plugins
+            .into_iter()
+            .map(|(name, receipt)| format!("{}@{}", name, receipt.version))
+            .collect()
+    } else {
+        plugins.to_vec()
+    };
+    Ok((version, plugins))
+}
+
+/// Digest of the archive at `url`, downloaded for lack of a published one.
+/// Refused in `--strict` mode, where it would only trust the download.
+async fn prefetch(ctx: &WasmedgeupContext, url: &str, tmpdir: &Path) -> Result<String> {
+    let name = url.rsplit('/').next().unwrap_or(url);
+    ctx.require_checksum(name)?;
+    log::info!("{}", t!("generate-nix-prefetch", name = name));
+    let staging = cache::temp_dir_in(tmpdir)?;
+    let staged = staging.path().join(name);
+    ctx.downloader()?.download_file(url, &staged).await?;
+    checksum::sha256_file(&staged)
+}
+
+/// Warn about an end-of-life default installation, from what earlier checks
+/// cached, so the hint costs no network round trip.
+fn support_hint(ctx: &WasmedgeupContext) {
+    let Some(version) = onboarding::default_install_path().ok().and_then(|path| installer::installed_version(&path)) else {
+        return;
+    };
+    if let Some(support) = support::check_cached(ctx.support_manifest(), &version) {
+        for warning in support.warnings() {
+            log::warn!("{}", warning);
+        }
+    }
+}
+
+/// Prefix of the version pinned for `dir`: where `sync` installs it under
+/// `install_root`, or `install_root` itself when that holds the version.
+/// `install_root` when nothing is pinned.
+fn pinned_prefix(install_root: &Path, dir: &Path) -> Result<PathBuf> {
+    let Some(pin) = pin::nearest_pin(dir)? else {
+        return Ok(install_root.to_path_buf());
+    };
+    [pin::version_prefix(install_root, &pin.version), install_root.to_path_buf()]
+        .into_iter()
+        .find(|prefix| installer::installed_version(prefix).as_deref() == Some(pin.version.as_str()))
+        .with_context(|| t!("pin-not-installed", version = pin.version, pin = pin.path.display(), path = install_root.display()))
+}
+
+/// The parts of the release `--components` names.
+fn runtime_components(parts: &[RuntimePart]) -> Vec<RuntimeComponent> {
+    parts
+        .iter()
+        .map(|part| match part {
+            RuntimePart::Bin => RuntimeComponent::Bin,
+            RuntimePart::Lib => RuntimeComponent::Lib,
+            RuntimePart::Include => RuntimeComponent::Include,
+        })
+        .collect()
+}
+
+/// Lock `install_path` against concurrent changes. A dry run changes nothing
+/// and takes no lock.
+fn lock_prefix(cli: &Cli, install_path: &Path) -> Result<Option<lock::InstallLock>> {
+    if cli.dry_run {
+        return Ok(None);
+    }
+    lock::InstallLock::acquire(install_path).map(Some)
+}
+
+/// The platform named by `--platform`, or by `--os` and `--arch`, or the
+/// detected one unless both are given.
+fn target_platform(ctx: &WasmedgeupContext, platform: &Option<Platform>, os: &Option<String>, arch: &Option<String>) -> Result<Platform> {
+    match (platform, os, arch) {
+        (Some(platform), _, _) => Ok(platform.clone()),
+        (None, Some(os), Some(arch)) => Ok(Platform::checked(OS::from_str(os)?, Architecture::from_str(arch)?)?),
+        _ => Ok(ctx.platform()?.clone()),
+    }
+}
 
-    Ok(())
+/// The release `version` names for `install`: with `pre`, `latest` is the
+/// newest release including pre-releases, and without it pre-releases are
+/// refused.
+async fn install_version(fetcher: &impl Fetcher, version: &str, pre: bool) -> Result<WasmEdgeVersion> {
+    let version = match version {
+        "latest" => return latest_release(fetcher, pre).await,
+        version => version.parse::<WasmEdgeVersion>()?,
+    };
+    if version.is_prerelease() && !pre {
+        anyhow::bail!(t!("install-prerelease-refused", version = version));
+    }
+    Ok(version)
 }
 
-fn expand_path(path: &PathBuf) -> Result<PathBuf> {
-    let path_str = path.to_string_lossy();
-    let expanded = if path_str.starts_with('~') {
-        dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            .join(path_str.strip_prefix("~/").unwrap_or(&path_str))
+/// The release `version` names, with `latest` resolved to the newest
+/// stable release upstream.
+async fn release_version(ctx: &WasmedgeupContext, version: &str) -> Result<WasmEdgeVersion> {
+    match version {
+        "latest" => latest_release(ctx.downloader()?, false).await,
+        version => version.parse(),
+    }
+}
+
+/// The newest release upstream, including pre-releases with `pre`.
+async fn latest_release(fetcher: &impl Fetcher, pre: bool) -> Result<WasmEdgeVersion> {
+    if pre {
+        let latest = release::latest_prerelease(fetcher).await?;
+        output::status(t!("latest-pre-resolved", version = latest));
+        Ok(latest)
     } else {
-        path.clone()
+        let latest = release::latest_version(fetcher).await?;
+        output::status(t!("latest-resolved", version = latest));
+        Ok(latest)
+    }
+}
+
+/// Version of the runtime installed in `install_path`, or the latest release
+/// when there is none.
+async fn installed_or_latest(ctx: &WasmedgeupContext, install_path: &Path) -> Result<WasmEdgeVersion> {
+    release_version(ctx, installer::installed_version(install_path).as_deref().unwrap_or("latest")).await
+}
+
+/// Ask before replacing an installation of a different version. Without a
+/// terminal, replacing requires `assume_yes`.
+fn confirm_replace(install_path: &Path, version: &str, assume_yes: bool) -> Result<bool> {
+    let Some(installed) = installer::installed_version(install_path) else {
+        return Ok(true);
     };
-    Ok(expanded)
+    if installed == version || assume_yes {
+        return Ok(true);
+    }
+    let path = install_path.display();
+    if !prompt::is_interactive() {
+        anyhow::bail!(t!("replace-refused", path = path, installed = installed, version = version));
+    }
+    prompt::confirm(&t!("replace-confirm", path = path, installed = installed, version = version), false)
+}
+
+/// Interactively choose plugins to install from the compatible catalog.
+async fn pick_plugins(plugin_manager: &PluginManager, platform: &Platform) -> Result<Vec<String>> {
+    if !prompt::is_interactive() {
+        anyhow::bail!(t!("plugin-install-no-args"));
+    }
+
+    let candidates = plugin_manager.compatible_plugins().await?;
+    if candidates.is_empty() {
+        anyhow::bail!(t!("plugin-picker-empty", os = platform.os, arch = platform.arch));
+    }
+    let items: Vec<String> = candidates
+        .iter()
+        .map(|e| {
+            format!(
+                "{:<32} {:>8.1} MB  {}",
+                e.name,
+                e.size as f64 / 1_000_000.0,
+                plugin::describe_plugin(&e.name)
+            )
+        })
+        .collect();
+
+    let selection = prompt::select_many(&t!("plugin-picker-title"), &items)?;
+    Ok(selection.into_iter().map(|i| candidates[i].name.clone()).collect())
+}
+
+/// Print what installing the plugins `specs`, after the archives already
+/// in `sizes` (e.g. the runtime's), will download and unpack and, above
+/// `--confirm-above`, ask before starting.
+async fn confirm_plugin_downloads(cli: &Cli, plugin_manager: &PluginManager, specs: &[String], mut sizes: Vec<(String, Option<ArchiveSize>)>) -> Result<bool> {
+    let plugins = specs.iter().map(|spec| Cli::parse_plugin_name_version(spec)).collect::<Result<Vec<_>>>()?;
+    let plugin_sizes = plugin_manager.download_sizes(&plugins).await?;
+    sizes.extend(plugins.into_iter().map(|(name, _)| name).zip(plugin_sizes));
+    confirm_downloads(cli, &sizes)
+}
+
+/// Print what installing the archives of `sizes`, by name, will download
+/// and unpack and, above `--confirm-above`, ask before starting.
+fn confirm_downloads(cli: &Cli, sizes: &[(String, Option<ArchiveSize>)]) -> Result<bool> {
+    let mut total = 0;
+    let mut disk = 0;
+    let mut unknown = Vec::new();
+    for (name, size) in sizes {
+        match size {
+            // The archive is staged next to what it unpacks to
+            Some(ArchiveSize { download, unpacked: Some(unpacked) }) => {
+                total += download;
+                disk += download + unpacked;
+            }
+            Some(ArchiveSize { download, unpacked: None }) => {
+                total += download;
+                disk += download;
+                unknown.push(name.as_str());
+            }
+            None => unknown.push(name.as_str()),
+        }
+    }
+
+    let download = human_size(total);
+    output::status(t!("download-preview", count = sizes.len(), download = download, disk = human_size(disk)));
+    if !unknown.is_empty() {
+        output::status(t!("download-preview-unknown", names = unknown.join(", ")));
+    }
+
+    let limit = cli.confirm_above.saturating_mul(1_000_000);
+    if total <= limit || cli.yes {
+        return Ok(true);
+    }
+    if !prompt::is_interactive() {
+        anyhow::bail!(t!("download-confirm-refused", download = download, limit = cli.confirm_above));
+    }
+    prompt::confirm(&t!("download-confirm", download = download), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use downloader::fake::FakeFetcher;
+
+    fn releases() -> FakeFetcher {
+        FakeFetcher::default().with(
+            "https://api.github.com/repos/WasmEdge/WasmEdge/releases?per_page=100",
+            r#"[
+                {"tag_name": "0.15.0-rc.1", "prerelease": true, "assets": []},
+                {"tag_name": "0.14.1", "prerelease": false, "assets": []}
+            ]"#,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_install_takes_pre_releases_only_with_pre() {
+        let fetcher = releases();
+        let refused = install_version(&fetcher, "0.15.0-rc.1", false).await.unwrap_err();
+        assert!(refused.to_string().contains("--pre"), "{}", refused);
+        assert_eq!(install_version(&fetcher, "0.15.0-rc.1", true).await.unwrap().to_string(), "0.15.0-rc.1");
+        assert_eq!(install_version(&fetcher, "0.14.1", false).await.unwrap().to_string(), "0.14.1");
+    }
+
+    #[test]
+    fn test_components_select_the_same_parts_in_order() {
+        let parts = [RuntimePart::Include, RuntimePart::Bin, RuntimePart::Lib];
+        assert_eq!(runtime_components(&parts), [RuntimeComponent::Include, RuntimeComponent::Bin, RuntimeComponent::Lib]);
+        assert!(runtime_components(&[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_latest_includes_pre_releases_only_with_pre() {
+        let fetcher = releases();
+        assert_eq!(install_version(&fetcher, "latest", false).await.unwrap().to_string(), "0.14.1");
+        assert_eq!(install_version(&fetcher, "latest", true).await.unwrap().to_string(), "0.15.0-rc.1");
+    }
 }
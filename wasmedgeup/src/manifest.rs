@@ -0,0 +1,101 @@
+//! `manifest.toml`, the record of what an install put in its prefix, for
+//! build tooling and wasmedgeup itself to read instead of guessing from
+//! directory names.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the manifest in an installation prefix.
+pub const MANIFEST_FILE: &str = "manifest.toml";
+
+/// Format version, bumped when a field changes meaning.
+const SCHEMA: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    /// Release archive the files came from
+    pub archive: String,
+    pub components: Vec<String>,
+    /// RFC 3339 time of the install
+    pub installed_at: String,
+    /// Version of wasmedgeup that made the install
+    pub wasmedgeup_version: String,
+    /// SHA-256 of each artifact installed, by artifact name
+    pub checksums: BTreeMap<String, String>,
+    /// Files the install put in the prefix, relative to it with `/`
+    /// separators. Empty in manifests written before files were recorded
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// The license and notice files among `files`, gathered from the
+    /// release archive into `licenses/wasmedge`
+    #[serde(default)]
+    pub licenses: Vec<String>,
+}
+
+impl Manifest {
+    pub fn to_toml(&self) -> Result<String> {
+        let body = toml::to_string(self).with_context(|| format!("Failed to serialize {}", MANIFEST_FILE))?;
+        Ok(format!("# Written by wasmedgeup when installing this prefix\nschema = {}\n{}", SCHEMA, body))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut table: toml::Table = toml::from_str(content).with_context(|| format!("Invalid {}", MANIFEST_FILE))?;
+        let schema = match table.remove("schema") {
+            Some(toml::Value::Integer(schema)) => schema,
+            Some(_) => anyhow::bail!("{} has no valid `schema`", MANIFEST_FILE),
+            None => 0,
+        };
+        if schema > i64::from(SCHEMA) {
+            anyhow::bail!("{} uses schema {}; upgrade wasmedgeup to read it", MANIFEST_FILE, schema);
+        }
+        table.try_into().with_context(|| format!("Invalid {}", MANIFEST_FILE))
+    }
+
+    /// Manifest of the installation at `install_path`, if it has one.
+    pub fn read(install_path: &Path) -> Result<Option<Self>> {
+        let path = install_path.join(MANIFEST_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))
+                .map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_round_trips() {
+        let manifest = Manifest {
+            version: "0.14.1".into(),
+            os: "linux".into(),
+            arch: "x86_64".into(),
+            archive: "WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz".into(),
+            components: vec!["bin".into(), "lib".into()],
+            installed_at: "2026-10-15T12:00:00Z".into(),
+            wasmedgeup_version: "0.1.0".into(),
+            checksums: BTreeMap::from([("WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz".into(), "ab".repeat(32))]),
+            files: vec!["bin/wasmedge".into(), "lib/libwasmedge.so.0".into(), "licenses/wasmedge/LICENSE".into()],
+            licenses: vec!["licenses/wasmedge/LICENSE".into()],
+        };
+        let toml = manifest.to_toml().unwrap();
+        assert!(toml.contains("components = [\"bin\", \"lib\"]\n"));
+        assert_eq!(Manifest::parse(&toml).unwrap(), manifest);
+        assert!(Manifest::parse(&toml.replace("schema = 1", "schema = 2")).is_err());
+        let before_files = toml.lines().filter(|l| !l.starts_with("files") && !l.starts_with("licenses")).collect::<Vec<_>>().join("\n");
+        let before_files = Manifest::parse(&before_files).unwrap();
+        assert!(before_files.files.is_empty() && before_files.licenses.is_empty());
+        // Any valid TOML reads, not just the layout written here
+        let edited = Manifest::parse(&toml.replace("version = \"0.14.1\"", "version=\"0.14.1\"  # edited")).unwrap();
+        assert_eq!(edited.version, "0.14.1");
+    }
+}
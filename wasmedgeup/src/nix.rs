@@ -0,0 +1,107 @@
+use anyhow::Result;
+use crate::platform::{Architecture, Platform, OS};
+
+/// A release archive pinned by URL and digest.
+#[derive(Debug, Clone)]
+pub struct NixArtifact {
+    pub name: String,
+    pub url: String,
+    /// Hex encoded SHA-256
+    pub sha256: String,
+}
+
+/// What a generated derivation installs.
+#[derive(Debug, Clone)]
+pub struct NixPlan {
+    pub version: String,
+    /// Nix system the archives are built for, e.g. `x86_64-linux`
+    pub system: String,
+    pub runtime: NixArtifact,
+    pub plugins: Vec<NixArtifact>,
+}
+
+/// Nix system name of `platform`. Nix only packages Linux and macOS builds.
+pub fn nix_system(platform: &Platform) -> Result<String> {
+    let arch = match platform.arch {
+        Architecture::X86_64 => "x86_64",
+        Architecture::Aarch64 | Architecture::Arm64 => "aarch64",
+        Architecture::Armv7 => "armv7l",
+    };
+    let os = match platform.os {
+        OS::Linux(_) => "linux",
+        OS::Darwin => "darwin",
+        ref os => anyhow::bail!(t!("generate-nix-unsupported", os = os)),
+    };
+    Ok(format!("{}-{}", arch, os))
+}
+
+fn fetchurl(artifact: &NixArtifact, indent: &str) -> String {
+    format!(
+        "fetchurl {{\n{indent}  url = \"{}\";\n{indent}  sha256 = \"{}\";\n{indent}}}",
+        artifact.url, artifact.sha256
+    )
+}
+
+impl NixPlan {
+    /// A `callPackage`-able derivation. Release archives unpack to `bin`,
+    /// `lib` or `lib64` and `include`; plugins go to `plugin`, which the
+    /// runtime searches next to its library directory.
+    pub fn render(&self) -> String {
+        let mut out = String::from("# Generated by `wasmedgeup generate nix`\n");
+        out.push_str("{ lib, stdenv, fetchurl, autoPatchelfHook, zlib }:\n\n");
+        out.push_str("stdenv.mkDerivation {\n");
+        out.push_str("  pname = \"wasmedge\";\n");
+        out.push_str(&format!("  version = \"{}\";\n\n", self.version));
+        out.push_str(&format!("  src = {};\n\n", fetchurl(&self.runtime, "  ")));
+        if self.plugins.is_empty() {
+            out.push_str("  plugins = [ ];\n\n");
+        } else {
+            out.push_str("  plugins = [\n");
+            for plugin in &self.plugins {
+                out.push_str(&format!("    # {}\n    ({})\n", plugin.name, fetchurl(plugin, "    ")));
+            }
+            out.push_str("  ];\n\n");
+        }
+        out.push_str("  nativeBuildInputs = lib.optionals stdenv.isLinux [ autoPatchelfHook ];\n");
+        out.push_str("  buildInputs = lib.optionals stdenv.isLinux [ stdenv.cc.cc.lib zlib ];\n\n");
+        out.push_str("  installPhase = ''\n");
+        out.push_str("    mkdir -p $out/plugin\n");
+        out.push_str("    cp -r bin include $out/\n");
+        out.push_str("    if [ -d lib64 ]; then cp -r lib64 $out/lib; else cp -r lib $out/lib; fi\n");
+        out.push_str("    for plugin in $plugins; do\n");
+        out.push_str("      tar -xzf $plugin -C $out/plugin\n");
+        out.push_str("    done\n");
+        out.push_str("  '';\n\n");
+        out.push_str(&format!("  meta.platforms = [ \"{}\" ];\n", self.system));
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::LinuxDistro;
+
+    #[test]
+    fn test_nix_derivation() {
+        let artifact = |name: &str, file: &str, sha256: &str| NixArtifact {
+            name: name.to_string(),
+            url: format!("https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/{}", file),
+            sha256: sha256.repeat(64),
+        };
+        let platform = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        let plan = NixPlan {
+            version: "0.14.1".into(),
+            system: nix_system(&platform).unwrap(),
+            runtime: artifact("wasmedge", "WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz", "a"),
+            plugins: vec![artifact("wasi_crypto", "WasmEdge-plugin-wasi_crypto-0.14.1-manylinux2014_x86_64.tar.gz", "b")],
+        };
+        let without_plugins = NixPlan { plugins: Vec::new(), ..plan.clone() };
+        insta::assert_snapshot!("nix_derivation", format!("{}{}", plan.render(), without_plugins.render()));
+
+        let windows = Platform::new(OS::Windows, Architecture::X86_64);
+        assert!(nix_system(&windows).is_err());
+        assert_eq!(nix_system(&Platform::new(OS::Darwin, Architecture::Arm64)).unwrap(), "aarch64-darwin");
+    }
+}
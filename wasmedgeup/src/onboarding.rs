@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use crate::{installer, output, paths::expand_path, platform::Platform, prompt};
+
+/// Prefix installs go to when neither `--path` nor `WASMEDGEUP_PATH` is set.
+const DEFAULT_PATH: &str = "~/.wasmedge";
+
+/// What running without a command offers to do.
+pub enum Onboarding {
+    /// A runtime is installed already; show the usual help instead
+    Installed,
+    /// Install with the defaults that were shown
+    Proceed,
+    /// The user declined, or could not be asked
+    Declined,
+}
+
+/// Where a first install goes.
+pub fn default_install_path() -> Result<PathBuf> {
+    let path = std::env::var_os("WASMEDGEUP_PATH").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(DEFAULT_PATH));
+    expand_path(&path)
+}
+
+/// The summary shown before a first install.
+fn summary(platform: &Platform, install_path: &Path) -> Vec<String> {
+    let env_file = install_path.join("env");
+    vec![
+        t!("onboarding-welcome"),
+        t!("onboarding-platform", os = platform.os, arch = platform.arch),
+        t!("onboarding-version"),
+        t!("onboarding-location", path = install_path.display()),
+        t!("onboarding-path", bin = install_path.join("bin").display(), env = env_file.display()),
+    ]
+}
+
+/// Guide a first run: when nothing is installed in the default prefix,
+/// explain what `install latest` would do there and offer to do it.
+pub fn run(platform: &Platform, assume_yes: bool) -> Result<Onboarding> {
+    let install_path = default_install_path()?;
+    if installer::installed_version(&install_path).is_some() {
+        return Ok(Onboarding::Installed);
+    }
+    for line in summary(platform, &install_path) {
+        output::status(line);
+    }
+    if assume_yes || (prompt::is_interactive() && prompt::confirm(&t!("onboarding-confirm"), true)?) {
+        return Ok(Onboarding::Proceed);
+    }
+    output::status(t!("onboarding-declined"));
+    Ok(Onboarding::Declined)
+}
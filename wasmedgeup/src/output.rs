@@ -0,0 +1,143 @@
+//! Stdout carries command results only. Log records, progress and other
+//! diagnostics go to stderr; with `--json`, warnings are instead collected
+//! into the `warnings` array of the single JSON document a run prints.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressStyle, WeakProgressBar};
+use log::{Level, Log, Metadata, Record};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+/// Whether progress bars and spinners are hidden.
+static QUIET: AtomicBool = AtomicBool::new(false);
+static PROGRESS_JSON: AtomicBool = AtomicBool::new(false);
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// The progress bar shown last, which spinners give way to.
+static BAR: Mutex<Option<WeakProgressBar>> = Mutex::new(None);
+
+/// Passes records to env_logger, except warnings and errors in `--json`
+/// mode, which are kept for [`print_json`].
+struct Logger {
+    inner: env_logger::Logger,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if is_json() && record.level() <= Level::Warn && self.inner.matches(record) {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        } else {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// How download and install progress is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ProgressMode {
+    /// Progress bars, spinners and status lines for people
+    #[default]
+    Bar,
+    /// One JSON event per line on stderr, for programs driving wasmedgeup
+    Json,
+}
+
+/// Set up logging for the verbosity flags and select the output mode.
+pub fn init(verbose: bool, quiet: bool, json: bool, progress: ProgressMode) {
+    JSON.store(json, Ordering::Relaxed);
+    let progress_json = progress == ProgressMode::Json;
+    PROGRESS_JSON.store(progress_json, Ordering::Relaxed);
+    // JSON events replace the bars
+    QUIET.store(quiet || progress_json, Ordering::Relaxed);
+    let level = if verbose {
+        "debug"
+    } else if quiet {
+        "warn"
+    } else {
+        "info"
+    };
+    let inner = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level)).build();
+    log::set_max_level(inner.filter());
+    if let Err(e) = log::set_boxed_logger(Box::new(Logger { inner })) {
+        eprintln!("Could not set up logging: {}", e);
+    }
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Narrate progress, such as a download starting. Always on stderr, and
+/// left out with `--progress json`, where events narrate instead.
+pub fn status(message: String) {
+    if !PROGRESS_JSON.load(Ordering::Relaxed) {
+        eprintln!("{}", message);
+    }
+}
+
+/// Print a line of a human readable result. `--json` runs report results
+/// through [`print_json`] instead.
+pub fn say(message: String) {
+    if !is_json() {
+        println!("{}", message);
+    }
+}
+
+/// A progress bar of `len` steps on stderr, hidden with `--quiet`. indicatif
+/// already hides it when stderr is not a terminal.
+pub fn progress_bar(len: u64) -> ProgressBar {
+    let pb = if QUIET.load(Ordering::Relaxed) {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(len)
+    };
+    *BAR.lock().unwrap() = Some(pb.downgrade());
+    pb
+}
+
+/// A spinner showing `message` while a request too small for a progress bar
+/// runs, so a stalled request does not look like a hung CLI. Hidden with
+/// `--quiet`, when stderr is not a terminal, and while a progress bar is
+/// showing, e.g. during `list --verify`'s batch of checksum requests.
+pub fn spinner(message: String) -> ProgressBar {
+    let bar_showing = BAR
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(WeakProgressBar::upgrade)
+        .is_some_and(|pb| !pb.is_finished() && !pb.is_hidden());
+    if QUIET.load(Ordering::Relaxed) || bar_showing {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner()
+        .template("{spinner:.green} {msg} [{elapsed}]")
+        .unwrap());
+    pb.set_message(message);
+    pb.enable_steady_tick(Duration::from_millis(100));
+    pb
+}
+
+#[derive(Serialize)]
+struct Document<T> {
+    result: T,
+    warnings: Vec<String>,
+}
+
+/// Print the result document of a `--json` run with the warnings logged so far.
+pub fn print_json(result: impl Serialize) -> Result<()> {
+    let warnings = std::mem::take(&mut *WARNINGS.lock().unwrap());
+    println!("{}", serde_json::to_string_pretty(&Document { result, warnings })?);
+    Ok(())
+}
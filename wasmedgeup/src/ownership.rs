@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use crate::fsutil::{long_path, FileSystem};
+
+/// Prefix `install --system` uses, shared by every user of the machine.
+pub const SYSTEM_PREFIX: &str = "/opt/wasmedge";
+
+/// Mode of a system install when `--mode` is not given, so the result does
+/// not depend on the umask of whoever ran the install.
+pub const SYSTEM_MODE: u32 = 0o755;
+
+/// Owner, group and mode applied to every file and directory of an
+/// installation. `mode` applies as given to directories and executables;
+/// other files get it without the execute bits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ownership {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub mode: Option<u32>,
+}
+
+impl Ownership {
+    /// Resolve user and group names or numeric ids and an octal mode such as
+    /// `755`. Returns `None` when nothing is set.
+    pub fn resolve(owner: Option<&str>, group: Option<&str>, mode: Option<&str>) -> Result<Option<Self>> {
+        if owner.is_none() && group.is_none() && mode.is_none() {
+            return Ok(None);
+        }
+        if cfg!(not(unix)) {
+            anyhow::bail!("--owner, --group and --mode are only supported on Unix");
+        }
+        let mode = mode
+            .map(|mode| {
+                u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o7777)
+                    .with_context(|| format!("Invalid mode {}: expected octal permissions such as 755", mode))
+            })
+            .transpose()?;
+        Ok(Some(Self {
+            uid: owner.map(lookup_user).transpose()?,
+            gid: group.map(lookup_group).transpose()?,
+            mode,
+        }))
+    }
+
+    /// Apply to `root` and everything below it. Symbolic links are left alone.
+    pub fn apply(&self, fs: &dyn FileSystem, root: &Path) -> Result<()> {
+        let metadata = match std::fs::symlink_metadata(long_path(root)) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", root.display())),
+        };
+        if metadata.file_type().is_symlink() {
+            return Ok(());
+        }
+        if self.uid.is_some() || self.gid.is_some() {
+            fs.set_owner(root, self.uid, self.gid)?;
+        }
+        if let Some(mode) = self.mode {
+            fs.set_mode(root, if metadata.is_dir() || is_executable(&metadata) { mode } else { mode & !0o111 })?;
+        }
+        if metadata.is_dir() {
+            for entry in std::fs::read_dir(long_path(root))? {
+                self.apply(fs, &entry?.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn lookup_user(name: &str) -> Result<u32> {
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+    nix::unistd::User::from_name(name)
+        .with_context(|| format!("Failed to look up user {}", name))?
+        .map(|user| user.uid.as_raw())
+        .with_context(|| format!("No such user: {}", name))
+}
+
+#[cfg(unix)]
+fn lookup_group(name: &str) -> Result<u32> {
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+    nix::unistd::Group::from_name(name)
+        .with_context(|| format!("Failed to look up group {}", name))?
+        .map(|group| group.gid.as_raw())
+        .with_context(|| format!("No such group: {}", name))
+}
+
+#[cfg(not(unix))]
+fn lookup_user(name: &str) -> Result<u32> {
+    anyhow::bail!("No such user: {}", name)
+}
+
+#[cfg(not(unix))]
+fn lookup_group(name: &str) -> Result<u32> {
+    anyhow::bail!("No such group: {}", name)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::fsutil::DryRunFs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_mode_drops_execute_bits_from_plain_files() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("bin")).unwrap();
+        std::fs::write(root.path().join("bin").join("wasmedge"), "").unwrap();
+        std::fs::write(root.path().join("VERSION"), "").unwrap();
+        let tool = root.path().join("bin").join("wasmedge");
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o700)).unwrap();
+
+        let ownership = Ownership::resolve(Some("0"), Some("0"), Some("0755")).unwrap().unwrap();
+        assert_eq!(ownership, Ownership { uid: Some(0), gid: Some(0), mode: Some(0o755) });
+        assert!(Ownership::resolve(None, None, Some("u+rwx")).is_err());
+        assert!(Ownership::resolve(None, None, None).unwrap().is_none());
+        assert_eq!(Ownership::resolve(Some("root"), None, None).unwrap().unwrap().uid, Some(0));
+        assert!(Ownership::resolve(Some("no-such-user-wasmedgeup"), None, None).is_err());
+
+        let recorder = DryRunFs::default();
+        Ownership { uid: None, gid: None, mode: Some(0o755) }.apply(&recorder, root.path()).unwrap();
+        let operations = recorder.operations();
+        assert!(operations.contains(&format!("set mode of {} to 755", root.path().join("bin").join("wasmedge").display())));
+        assert!(operations.contains(&format!("set mode of {} to 644", root.path().join("VERSION").display())));
+    }
+}
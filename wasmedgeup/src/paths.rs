@@ -0,0 +1,125 @@
+//! Resolution of user supplied paths (`--path`, `--tmpdir`, config values):
+//! a leading `~` or `~user`, `${VAR}` references, and paths relative to the
+//! config file.
+
+use anyhow::{Context, Result};
+use log::debug;
+use std::path::{Path, PathBuf};
+
+/// Expand a leading `~` or `~user` and `${VAR}` environment variable
+/// references in `path`.
+pub fn expand_path(path: &Path) -> Result<PathBuf> {
+    expand_path_with(path, &|name| std::env::var(name).ok())
+}
+
+/// [`expand_path`], looking variables up with `var`.
+fn expand_path_with(path: &Path, var: &dyn Fn(&str) -> Option<String>) -> Result<PathBuf> {
+    let Some(raw) = path.to_str() else {
+        // Not UTF-8, so nothing in it to expand
+        return Ok(path.to_path_buf());
+    };
+    let expanded = expand_home(&expand_vars(raw, var))?;
+    if expanded != path {
+        debug!("Resolved {} to {}", raw, expanded.display());
+    }
+    Ok(expanded)
+}
+
+/// Expand `path` and anchor it at `base` when it is relative, e.g. a path
+/// in the config file relative to the file's directory.
+pub fn resolve_relative_to(path: &Path, base: &Path) -> Result<PathBuf> {
+    let expanded = expand_path(path)?;
+    Ok(if expanded.is_relative() { base.join(expanded) } else { expanded })
+}
+
+fn home_dir() -> Result<PathBuf> {
+    dirs::home_dir().context(t!("path-no-home"))
+}
+
+/// Replace a leading `~` (the current user's home) or `~user`.
+fn expand_home(path: &str) -> Result<PathBuf> {
+    let Some(rest) = path.strip_prefix('~') else {
+        return Ok(PathBuf::from(path));
+    };
+    let (user, tail) = match rest.find(['/', '\\']) {
+        Some(end) => (&rest[..end], rest[end + 1..].trim_start_matches(['/', '\\'])),
+        None => (rest, ""),
+    };
+    let home = if user.is_empty() { home_dir()? } else { user_home(user)? };
+    Ok(if tail.is_empty() { home } else { home.join(tail) })
+}
+
+/// Home directory of `user`, from the user database (`getpwnam`, which
+/// also covers LDAP and other NSS sources) on Unix, else next to the current
+/// user's home as on Windows.
+fn user_home(user: &str) -> Result<PathBuf> {
+    #[cfg(unix)]
+    if let Ok(Some(entry)) = nix::unistd::User::from_name(user) {
+        return Ok(entry.dir);
+    }
+    let sibling = home_dir()?.parent().map(|parent| parent.join(user));
+    match sibling {
+        Some(home) if home.is_dir() => Ok(home),
+        _ => anyhow::bail!(t!("path-unknown-user", user = user)),
+    }
+}
+
+/// Replace `${VAR}` with the value `var` gives the variable. Anything else
+/// that looks like a variable, e.g. `$Recycle.Bin` or `100%`, and references
+/// to unset variables are left as they are.
+fn expand_vars(path: &str, var: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after
+            .find('}')
+            .map(|end| &after[..end])
+            .filter(|name| is_var_name(name))
+            .and_then(|name| var(name).map(|value| (value, name.len() + 1)));
+        match value {
+            Some((value, consumed)) => {
+                out.push_str(&value);
+                rest = &after[consumed..];
+            }
+            None => {
+                out.push_str("${");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '(' || c == ')')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expands_home_and_variables() {
+        let var = |name: &str| (name == "WASMEDGEUP_ROOT").then(|| "/srv".to_string());
+        let expand = |path: &str| expand_path_with(Path::new(path), &var).unwrap();
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand("~/.wasmedge"), home.join(".wasmedge"));
+        assert_eq!(expand("/opt/wasmedge"), PathBuf::from("/opt/wasmedge"));
+        assert_eq!(expand("${WASMEDGEUP_ROOT}/wasmedge"), PathBuf::from("/srv/wasmedge"));
+
+        // Only `${VAR}` of a set variable expands; everything else is kept
+        for path in ["$WASMEDGEUP_ROOT/x", "%WASMEDGEUP_ROOT%/x", "${UNSET}/x", "${WASMEDGEUP_ROOT/x", "/tmp/100%/a$", r"C:\$Recycle.Bin\x", "/a/~/b"] {
+            assert_eq!(expand(path), PathBuf::from(path));
+        }
+        assert!(expand_path_with(Path::new("~wasmedgeup-no-such-user/x"), &var).is_err());
+
+        assert_eq!(resolve_relative_to(Path::new("cache"), Path::new("/etc/wasmedgeup")).unwrap(),
+            PathBuf::from("/etc/wasmedgeup/cache"));
+        #[cfg(target_os = "linux")]
+        assert_eq!(expand("~root/x"), PathBuf::from("/root/x"));
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use crate::{
+    installer::{self, RuntimeComponent},
+    version,
+};
+
+/// File naming the WasmEdge version a directory and everything below it uses.
+pub const PIN_FILE: &str = ".wasmedge-version";
+
+/// Project file naming the runtime version, plugins and components a
+/// project uses, as written by `wasmedgeup init`.
+pub const TOOLCHAIN_FILE: &str = "wasmedge-toolchain.toml";
+
+/// File at a monorepo root listing the directories that carry their own pin.
+pub const WORKSPACE_FILE: &str = "wasmedgeup-workspace.json";
+
+/// Members of a workspace, relative to the directory holding [`WORKSPACE_FILE`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    pub members: Vec<PathBuf>,
+}
+
+/// A version pinned by the pin or toolchain file at `path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub path: PathBuf,
+    pub version: String,
+    /// Plugins to install along with the runtime
+    pub plugins: Vec<String>,
+    /// Parts of the runtime to install; empty for all
+    pub components: Vec<RuntimeComponent>,
+}
+
+/// Contents of a [`TOOLCHAIN_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Toolchain {
+    pub version: String,
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    #[serde(default)]
+    pub components: Vec<RuntimeComponent>,
+}
+
+/// The [`TOOLCHAIN_FILE`] keeps its keys under a `[toolchain]` table.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToolchainFile {
+    toolchain: Toolchain,
+}
+
+impl Toolchain {
+    /// The file as committed: fixed key order, one line per key, so changes
+    /// show up as small diffs.
+    pub fn to_toml(&self) -> Result<String> {
+        let table = toml::to_string(&ToolchainFile { toolchain: self.clone() })
+            .with_context(|| format!("Failed to serialize {}", TOOLCHAIN_FILE))?;
+        Ok(format!(
+            "# WasmEdge toolchain of this project. Commit this file; `wasmedgeup sync`\n\
+             # installs what it names.\n\
+             {}",
+            table
+        ))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let ToolchainFile { mut toolchain } =
+            toml::from_str(content).with_context(|| format!("Invalid {}", TOOLCHAIN_FILE))?;
+        toolchain.version = version::normalize(&toolchain.version);
+        Ok(toolchain)
+    }
+}
+
+/// Read the pin in `dir`, if it has one: the [`TOOLCHAIN_FILE`], or else the
+/// [`PIN_FILE`], whose first line that is not blank or a `#` comment is the
+/// version.
+pub fn read_pin(dir: &Path) -> Result<Option<Pin>> {
+    let path = dir.join(TOOLCHAIN_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            let toolchain = Toolchain::parse(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+            return Ok(Some(Pin {
+                path,
+                version: toolchain.version,
+                plugins: toolchain.plugins,
+                components: toolchain.components,
+            }));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+
+    let path = dir.join(PIN_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let version = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(version::normalize)
+        .with_context(|| format!("{} names no version", path.display()))?;
+    Ok(Some(Pin { path, version, plugins: Vec::new(), components: Vec::new() }))
+}
+
+/// The pin closest to `start`, looking in `start` and then its ancestors.
+pub fn nearest_pin(start: &Path) -> Result<Option<Pin>> {
+    for dir in start.ancestors() {
+        if let Some(pin) = read_pin(dir)? {
+            return Ok(Some(pin));
+        }
+    }
+    Ok(None)
+}
+
+/// The workspace `start` belongs to and its root directory.
+pub fn find_workspace(start: &Path) -> Result<Option<(PathBuf, Workspace)>> {
+    for dir in start.ancestors() {
+        let path = dir.join(WORKSPACE_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let workspace: Workspace = serde_json::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                // Members are read and installed for, so none may lead out
+                // of the repository the workspace file came with
+                let inside = |member: &Path| member.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+                if let Some(member) = workspace.members.iter().find(|member| !inside(member)) {
+                    anyhow::bail!(t!("workspace-member-outside", member = member.display(), file = path.display()));
+                }
+                return Ok(Some((dir.to_path_buf(), workspace)));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+    Ok(None)
+}
+
+/// Pins of the workspace root and of every member. Members without a pin of
+/// their own are skipped with a warning.
+pub fn workspace_pins(root: &Path, workspace: &Workspace) -> Result<Vec<Pin>> {
+    let mut pins: Vec<Pin> = read_pin(root)?.into_iter().collect();
+    for member in &workspace.members {
+        match read_pin(&root.join(member))? {
+            Some(pin) => pins.push(pin),
+            None => warn!("Workspace member {} has no {}", member.display(), PIN_FILE),
+        }
+    }
+    Ok(pins)
+}
+
+/// Distinct versions required by `pins`.
+pub fn required_versions(pins: &[Pin]) -> BTreeSet<String> {
+    pins.iter().map(|pin| pin.version.clone()).collect()
+}
+
+/// Installation prefix of a pinned `version`. Pinned versions are installed
+/// side by side under the `versions` directory of `install_root`.
+pub fn version_prefix(install_root: &Path, version: &str) -> PathBuf {
+    installer::versioned_prefix(install_root, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_pin_and_workspace_members() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let api = root.join("services/api");
+        let worker = root.join("services/worker/src");
+        std::fs::create_dir_all(&api).unwrap();
+        std::fs::create_dir_all(&worker).unwrap();
+        std::fs::write(root.join(PIN_FILE), "0.14.1\n").unwrap();
+        std::fs::write(api.join(PIN_FILE), "# api needs the old runtime\n0.13.5\n").unwrap();
+        std::fs::write(root.join(WORKSPACE_FILE), r#"{"members": ["services/api", "services/worker"]}"#).unwrap();
+
+        assert_eq!(nearest_pin(&api).unwrap().unwrap().version, "0.13.5");
+        // The worker has no pin of its own and inherits the root's
+        assert_eq!(nearest_pin(&worker).unwrap().unwrap().version, "0.14.1");
+
+        let (found, workspace) = find_workspace(&worker).unwrap().unwrap();
+        assert_eq!(found, root);
+        let pins = workspace_pins(&found, &workspace).unwrap();
+        assert_eq!(pins.len(), 2);
+        assert_eq!(required_versions(&pins).into_iter().collect::<Vec<_>>(), vec!["0.13.5", "0.14.1"]);
+
+        for escape in ["../elsewhere", "services/../../elsewhere", "/etc"] {
+            std::fs::write(root.join(WORKSPACE_FILE), serde_json::json!({ "members": [escape] }).to_string()).unwrap();
+            let error = find_workspace(&worker).unwrap_err().to_string();
+            assert!(error.contains("must be a path inside the workspace"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_toolchain_file_round_trips_and_pins() {
+        let toolchain = Toolchain {
+            version: "0.14.1".into(),
+            plugins: vec!["wasi_logging".into(), "wasi_nn-ggml".into()],
+            components: vec![RuntimeComponent::Bin, RuntimeComponent::Lib],
+        };
+        let toml = toolchain.to_toml().unwrap();
+        assert!(toml.contains("plugins = [\"wasi_logging\", \"wasi_nn-ggml\"]\ncomponents = [\"bin\", \"lib\"]\n"), "{}", toml);
+        assert_eq!(Toolchain::parse(&toml).unwrap(), toolchain);
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(PIN_FILE), "0.13.5\n").unwrap();
+        std::fs::write(dir.path().join(TOOLCHAIN_FILE), toml).unwrap();
+        let pin = read_pin(dir.path()).unwrap().unwrap();
+        assert_eq!((pin.version.as_str(), pin.plugins.len()), ("0.14.1", 2));
+        assert!(Toolchain::parse("[toolchain]\ncomponents = [\"docs\"]\nversion = \"0.14.1\"\n").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::sync::OnceLock;
 use thiserror::Error;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -6,6 +7,10 @@ pub enum OS {
     Linux(LinuxDistro),
     Darwin,
     Windows,
+    /// No official binaries; installs build from source
+    FreeBsd,
+    /// No official binaries; installs build from source
+    OpenBsd,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,7 +23,10 @@ pub enum LinuxDistro {
 pub enum Architecture {
     X86_64,
     Aarch64,
+    #[allow(dead_code)]
     Arm64,
+    /// 32-bit ARM, e.g. Raspberry Pi OS. Only some releases publish archives
+    Armv7,
 }
 
 #[derive(Debug, Error)]
@@ -27,8 +35,10 @@ pub enum PlatformError {
     UnsupportedOS(String),
     #[error("Unsupported architecture: {0}")]
     UnsupportedArch(String),
-    #[error("Failed to detect system information: {0}")]
-    DetectionError(String),
+    #[error("{0} has no {1} builds")]
+    UnsupportedCombination(OS, Architecture),
+    #[error("Invalid platform {0}: expected <os>/<arch>, e.g. ubuntu20.04/aarch64 (see `wasmedgeup platforms`)")]
+    InvalidPlatform(String),
 }
 
 impl fmt::Display for OS {
@@ -40,6 +50,8 @@ impl fmt::Display for OS {
             },
             OS::Darwin => write!(f, "Darwin"),
             OS::Windows => write!(f, "Windows"),
+            OS::FreeBsd => write!(f, "FreeBSD"),
+            OS::OpenBsd => write!(f, "OpenBSD"),
         }
     }
 }
@@ -49,16 +61,48 @@ impl fmt::Display for Architecture {
         match self {
             Architecture::X86_64 => write!(f, "x86_64"),
             Architecture::Aarch64 | Architecture::Arm64 => write!(f, "arm64"),
+            Architecture::Armv7 => write!(f, "armv7l"),
         }
     }
 }
 
+/// Whether this process is an x86_64 build translated by Rosetta 2, e.g.
+/// one started from an x86_64 terminal on Apple silicon.
+pub fn under_rosetta() -> bool {
+    static TRANSLATED: OnceLock<bool> = OnceLock::new();
+    *TRANSLATED.get_or_init(|| proc_translated() == Some(1))
+}
+
+/// The `sysctl.proc_translated` of this process: 1 under Rosetta, 0 when
+/// native, and missing on Intel Macs and on releases before Rosetta 2.
+#[cfg(target_os = "macos")]
+fn proc_translated() -> Option<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    // SAFETY: the name is NUL-terminated, and value and size describe a
+    // c_int the kernel writes into
+    let status = unsafe {
+        libc::sysctlbyname(c"sysctl.proc_translated".as_ptr(), (&raw mut value).cast(), &mut size, std::ptr::null_mut(), 0)
+    };
+    (status == 0).then_some(value)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn proc_translated() -> Option<i32> {
+    None
+}
+
 impl Architecture {
+    /// Architecture of the hardware. The architecture wasmedgeup was built
+    /// for, except under Rosetta, which reports x86_64 on arm64 hardware.
     pub fn detect() -> Result<Self, PlatformError> {
         let arch = std::env::consts::ARCH;
         match arch {
+            "x86_64" if under_rosetta() => Ok(Architecture::Aarch64),
             "x86_64" => Ok(Architecture::X86_64),
             "aarch64" | "arm64" => Ok(Architecture::Aarch64),
+            // Rust reports 32-bit ARM builds, hard float or not, as "arm"
+            "arm" => Ok(Architecture::Armv7),
             arch => Err(PlatformError::UnsupportedArch(arch.to_string())),
         }
     }
@@ -67,6 +111,7 @@ impl Architecture {
         match arch.to_lowercase().as_str() {
             "x86_64" | "amd64" => Ok(Architecture::X86_64),
             "aarch64" | "arm64" => Ok(Architecture::Aarch64),
+            "armv7" | "armv7l" | "armhf" | "arm" => Ok(Architecture::Armv7),
             arch => Err(PlatformError::UnsupportedArch(arch.to_string())),
         }
     }
@@ -88,21 +133,51 @@ impl OS {
             }
             "macos" => Ok(OS::Darwin),
             "windows" => Ok(OS::Windows),
+            "freebsd" => Ok(OS::FreeBsd),
+            "openbsd" => Ok(OS::OpenBsd),
             os => Err(PlatformError::UnsupportedOS(os.to_string())),
         }
     }
 
+    /// Name of the variable the dynamic loader searches for shared libraries.
+    pub fn library_path_var(&self) -> &'static str {
+        match self {
+            OS::Linux(_) | OS::FreeBsd | OS::OpenBsd => "LD_LIBRARY_PATH",
+            OS::Darwin => "DYLD_LIBRARY_PATH",
+            OS::Windows => "PATH",
+        }
+    }
+
+    /// Whether upstream publishes release binaries for this OS.
+    pub fn has_release_binaries(&self) -> bool {
+        !matches!(self, OS::FreeBsd | OS::OpenBsd)
+    }
+
+    /// Parse an OS name. Names as release archives spell them, with a
+    /// version (`ubuntu20.04`, `manylinux2014`), are accepted too.
     pub fn from_str(os: &str) -> Result<Self, PlatformError> {
-        match os.to_lowercase().as_str() {
-            "linux" => Ok(OS::Linux(LinuxDistro::Generic)),
+        let lower = os.to_lowercase();
+        match lower.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '_') {
+            "linux" | "manylinux" => Ok(OS::Linux(LinuxDistro::Generic)),
             "ubuntu" => Ok(OS::Linux(LinuxDistro::Ubuntu)),
-            "darwin" => Ok(OS::Darwin),
+            "darwin" | "macos" => Ok(OS::Darwin),
             "windows" => Ok(OS::Windows),
-            os => Err(PlatformError::UnsupportedOS(os.to_string())),
+            "freebsd" => Ok(OS::FreeBsd),
+            "openbsd" => Ok(OS::OpenBsd),
+            _ => Err(PlatformError::UnsupportedOS(os.to_string())),
         }
     }
 }
 
+/// A runtime archive to try for a platform, in order of preference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageCandidate {
+    pub package: String,
+    /// Whether the user has to agree before this archive is used, because
+    /// it only runs under emulation
+    pub needs_confirmation: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Platform {
     pub os: OS,
@@ -111,24 +186,202 @@ pub struct Platform {
 
 impl Platform {
     pub fn detect() -> Result<Self, PlatformError> {
-        Ok(Self {
-            os: OS::detect()?,
-            arch: Architecture::detect()?,
-        })
+        Self::checked(OS::detect()?, Architecture::detect()?)
     }
 
     pub fn new(os: OS, arch: Architecture) -> Self {
         Self { os, arch }
     }
 
-    pub fn get_release_package_name(&self, _version: &str) -> String {
+    /// `os` on `arch`, refusing combinations nothing is built for: 32-bit
+    /// ARM is Linux only.
+    pub fn checked(os: OS, arch: Architecture) -> Result<Self, PlatformError> {
+        if arch == Architecture::Armv7 && matches!(os, OS::Darwin | OS::Windows) {
+            return Err(PlatformError::UnsupportedCombination(os, arch));
+        }
+        Ok(Self::new(os, arch))
+    }
+
+    /// Parse `<os>/<arch>` as `--platform` takes it, e.g. `ubuntu20.04/aarch64`
+    /// or `darwin/arm64`.
+    pub fn parse(platform: &str) -> Result<Self, PlatformError> {
+        let (os, arch) = platform
+            .split_once('/')
+            .ok_or_else(|| PlatformError::InvalidPlatform(platform.to_string()))?;
+        Self::checked(OS::from_str(os)?, Architecture::from_str(arch)?)
+    }
+
+    /// Platforms upstream publishes release archives for, in the order
+    /// `wasmedgeup platforms` lists them. 32-bit ARM is left out: no release
+    /// publishes archives for it under the name it would install.
+    pub fn known() -> Vec<Self> {
+        let ubuntu = OS::Linux(LinuxDistro::Ubuntu);
+        let generic = OS::Linux(LinuxDistro::Generic);
+        vec![
+            Self::new(ubuntu.clone(), Architecture::X86_64),
+            Self::new(ubuntu, Architecture::Aarch64),
+            Self::new(generic.clone(), Architecture::X86_64),
+            Self::new(generic, Architecture::Aarch64),
+            Self::new(OS::Darwin, Architecture::X86_64),
+            Self::new(OS::Darwin, Architecture::Aarch64),
+            Self::new(OS::Windows, Architecture::X86_64),
+        ]
+    }
+
+    /// The platform installing the archives named with `suffix`, e.g.
+    /// `ubuntu20.04/x86_64` for `ubuntu20.04_x86_64`. `None` when no
+    /// `--platform` value selects them, e.g. for `manylinux_2_28_x86_64`.
+    pub fn from_asset_suffix(suffix: &str) -> Option<Self> {
+        suffix.match_indices('_').find_map(|(i, _)| {
+            let platform = Self::new(OS::from_str(&suffix[..i]).ok()?, Architecture::from_str(&suffix[i + 1..]).ok()?);
+            (platform.asset_suffix() == suffix).then_some(platform)
+        })
+    }
+
+    /// `<os>/<arch>` as [`parse`](Self::parse) takes it.
+    pub fn shorthand(&self) -> String {
+        let suffix = self.asset_suffix();
+        match suffix.split_once('_') {
+            Some((os, arch)) => format!("{}/{}", os, arch),
+            None => suffix,
+        }
+    }
+
+    /// Platform part of release asset names, e.g. `ubuntu20.04_x86_64`.
+    pub fn asset_suffix(&self) -> String {
         match &self.os {
-            OS::Linux(distro) => match distro {
-                LinuxDistro::Ubuntu => format!("ubuntu20.04_{}.tar.gz", self.arch),
-                LinuxDistro::Generic => format!("manylinux2014_{}.tar.gz", self.arch),
-            },
-            OS::Darwin => format!("darwin_{}.tar.gz", self.arch),
-            OS::Windows => format!("windows_{}.tar.gz", self.arch),
+            OS::Linux(distro) => {
+                let arch = self.linux_arch();
+                match distro {
+                    LinuxDistro::Ubuntu => format!("ubuntu20.04_{}", arch),
+                    LinuxDistro::Generic => format!("manylinux2014_{}", arch),
+                }
+            }
+            OS::Darwin => format!("darwin_{}", self.arch),
+            OS::Windows => format!("windows_{}", self.arch),
+            // Never published; see `OS::has_release_binaries`
+            OS::FreeBsd | OS::OpenBsd => format!("{}_{}", self.os.to_string().to_lowercase(), self.arch),
+        }
+    }
+
+    /// Architecture part of Linux asset names. Linux archives call 64-bit
+    /// ARM `aarch64`, macOS ones `arm64`.
+    fn linux_arch(&self) -> String {
+        match self.arch {
+            Architecture::Aarch64 | Architecture::Arm64 => "aarch64".to_string(),
+            ref arch => arch.to_string(),
+        }
+    }
+
+    pub fn get_release_package_name(&self, _version: &str) -> String {
+        format!("{}.tar.gz", self.asset_suffix())
+    }
+
+    /// Runtime archives that work on this platform, best first. Ubuntu falls
+    /// back to the manylinux build; Apple silicon falls back to the x86_64
+    /// build under Rosetta, which old releases only published.
+    pub fn release_package_candidates(&self, version: &str) -> Vec<PackageCandidate> {
+        let candidate = |package: String, needs_confirmation| PackageCandidate { package, needs_confirmation };
+        let mut candidates = vec![candidate(self.get_release_package_name(version), false)];
+        match (&self.os, &self.arch) {
+            (OS::Linux(LinuxDistro::Ubuntu), _) => {
+                let generic = Platform::new(OS::Linux(LinuxDistro::Generic), self.arch.clone());
+                candidates.push(candidate(generic.get_release_package_name(version), false));
+            }
+            (OS::Darwin, Architecture::Aarch64 | Architecture::Arm64) => {
+                let intel = Platform::new(OS::Darwin, Architecture::X86_64);
+                candidates.push(candidate(intel.get_release_package_name(version), true));
+            }
+            _ => {}
         }
+        candidates
+    }
+
+    /// The statically linked CLI archive among `packages`, the runtime
+    /// archives of a release without their `WasmEdge-<version>-` prefix.
+    /// Upstream only publishes these for Linux and names their platform
+    /// independently of the regular archives (e.g.
+    /// `manylinux_2_28_x86_64_static.tar.gz`), so any glibc build for this
+    /// architecture qualifies, this platform's own first.
+    pub fn find_static_package<'a>(&self, packages: &[&'a str]) -> Option<&'a str> {
+        let OS::Linux(distro) = &self.os else {
+            return None;
+        };
+        let own = self.asset_suffix();
+        let arch = format!("_{}", self.linux_arch());
+        packages
+            .iter()
+            .filter_map(|package| Some((*package, package.strip_suffix("_static.tar.gz")?)))
+            .filter(|(_, platform)| {
+                platform.strip_suffix(arch.as_str()).is_some_and(|os| {
+                    os.starts_with("manylinux") || (os.starts_with("ubuntu") && *distro == LinuxDistro::Ubuntu)
+                })
+            })
+            .min_by_key(|(package, platform)| (*platform != own, *package))
+            .map(|(package, _)| package)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_package_is_any_glibc_build_for_the_architecture() {
+        let packages = [
+            "manylinux2014_x86_64.tar.gz",
+            "manylinux_2_28_x86_64_static.tar.gz",
+            "manylinux2014_x86_64_static.tar.gz",
+            "ubuntu20.04_x86_64_static.tar.gz",
+            "manylinux2014_aarch64_static.tar.gz",
+            "darwin_x86_64_static.tar.gz",
+        ];
+        let generic = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::X86_64);
+        assert_eq!(generic.find_static_package(&packages), Some("manylinux2014_x86_64_static.tar.gz"));
+        assert_eq!(generic.find_static_package(&packages[..2]), Some("manylinux_2_28_x86_64_static.tar.gz"));
+        assert_eq!(generic.find_static_package(&["ubuntu20.04_x86_64_static.tar.gz"]), None);
+        let ubuntu = Platform::new(OS::Linux(LinuxDistro::Ubuntu), Architecture::X86_64);
+        assert_eq!(ubuntu.find_static_package(&packages), Some("ubuntu20.04_x86_64_static.tar.gz"));
+        let arm = Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::Arm64);
+        assert_eq!(arm.find_static_package(&packages), Some("manylinux2014_aarch64_static.tar.gz"));
+        assert_eq!(Platform::new(OS::Darwin, Architecture::X86_64).find_static_package(&packages), None);
+        assert_eq!(Platform::new(OS::Linux(LinuxDistro::Generic), Architecture::Armv7).find_static_package(&packages), None);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_platform_shorthand_round_trips() {
+        let arm = Platform::parse("ubuntu20.04/aarch64").unwrap();
+        assert_eq!((arm.os, arm.arch), (OS::Linux(LinuxDistro::Ubuntu), Architecture::Aarch64));
+        assert_eq!(Platform::parse("macos/arm64").unwrap().os, OS::Darwin);
+        assert_eq!(Platform::parse("manylinux2014/x86_64").unwrap().os, OS::Linux(LinuxDistro::Generic));
+        assert!(matches!(Platform::parse("ubuntu20.04_aarch64"), Err(PlatformError::InvalidPlatform(_))));
+        assert!(matches!(Platform::parse("solaris/x86_64"), Err(PlatformError::UnsupportedOS(_))));
+        assert!(matches!(Platform::parse("darwin/armv7l"), Err(PlatformError::UnsupportedCombination(..))));
+        assert!(matches!(Platform::parse("windows/armhf"), Err(PlatformError::UnsupportedCombination(..))));
+        assert_eq!(Platform::parse("linux/armv7").unwrap().arch, Architecture::Armv7);
+        for platform in Platform::known() {
+            let parsed = Platform::from_asset_suffix(&platform.asset_suffix()).unwrap();
+            assert_eq!(parsed.shorthand(), platform.shorthand());
+            let parsed = Platform::parse(&platform.shorthand()).unwrap();
+            assert_eq!((parsed.os, parsed.arch), (platform.os, platform.arch));
+        }
+        assert_eq!(Platform::from_asset_suffix("darwin_arm64").unwrap().shorthand(), "darwin/arm64");
+        assert!(Platform::from_asset_suffix("manylinux_2_28_x86_64").is_none());
+        assert!(Platform::from_asset_suffix("android_aarch64").is_none());
+        assert!(Platform::from_asset_suffix("windows").is_none());
+    }
+
+    #[test]
+    fn test_rosetta_is_read_from_sysctl_on_macos() {
+        if cfg!(target_os = "macos") {
+            assert_eq!(under_rosetta(), proc_translated() == Some(1));
+            // Native arm64 processes see 0; the key only exists on Apple silicon
+            if cfg!(target_arch = "aarch64") {
+                assert_eq!(proc_translated(), Some(0));
+            }
+        } else {
+            assert_eq!(proc_translated(), None);
+            assert!(!under_rosetta());
+        }
+    }
+}
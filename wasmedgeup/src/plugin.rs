@@ -1,277 +1,1777 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use crate::{
+    asset::PluginAsset,
+    bundle::PluginBundle,
+    cache,
+    checksum::{self, ChecksumFile},
+    complete,
+    deps::{self, DepNode},
+    download::{self, ArchiveSize, Downloaded},
     platform::Platform,
-    downloader::Downloader,
+    downloader::{Downloader, Fetcher},
+    events::{Event, Events},
+    extract::{self, ExtractLimits},
+    fsutil::{self, FileSystem, RealFs},
+    installer,
+    licenses::{self, LICENSES_DIR},
+    release::{self, Release},
+    rpath,
+    shadow,
+    source::{self, SourceBuild},
+    version::WasmEdgeVersion,
 };
-use log::{info, warn, error};
-use flate2::read::GzDecoder;
-use tar::Archive;
-use tokio::fs;
+use log::{debug, info, warn};
 
-const KNOWN_PLUGINS: &[&str] = &[
-    "wasi-nn-ggml",
-    "wasi-nn-pytorch",
-    "wasi-nn-tensorflow",
-    "wasi-crypto",
-    "wasmedge-tensorflow",
-    "wasmedge-tensorflowlite",
-    "wasmedge-image",
-];
+/// File, next to the plugin directory, recording what each installed plugin
+/// put on disk.
+const RECEIPTS_FILE: &str = "plugins.json";
 
-#[derive(Debug, Deserialize)]
-pub struct PluginVersionInfo {
-    pub deps: Vec<String>,
-    pub platform: Vec<String>,
+/// A plugin archive published for a release, as exported by
+/// `plugin catalog export`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    pub url: String,
+    pub size: u64,
+    pub sha256: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct PluginManifest {
-    #[serde(flatten)]
-    pub plugins: HashMap<String, HashMap<String, PluginVersionInfo>>,
+/// Plugins [`PluginManager::reinstall_all`] moved to a new runtime version.
+#[derive(Debug, Default, Serialize)]
+pub struct Reinstalled {
+    pub reinstalled: Vec<String>,
+    /// No longer published for the new version
+    pub removed: Vec<String>,
+    /// Published, but could not be installed
+    pub failed: Vec<String>,
+    /// Left at their pinned version, which the new runtime will not load
+    pub pinned: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct VersionManifest {
-    pub maintained: Vec<String>,
-    pub deprecated: Vec<String>,
+/// Whether plugins built for `plugin_version` load in runtime `runtime_version`:
+/// the runtime only loads plugins of its own `major.minor` release line.
+pub fn is_compatible(runtime_version: &WasmEdgeVersion, plugin_version: &str) -> bool {
+    match plugin_version.parse::<WasmEdgeVersion>() {
+        Ok(plugin) => runtime_version.release_line() == plugin.release_line(),
+        Err(_) => runtime_version.tag() == plugin_version,
+    }
+}
+
+/// Version a source build is recorded as: the release its ref names, or the
+/// ref itself for branches and commits.
+fn built_version(build: &SourceBuild) -> String {
+    build.git_ref().parse::<WasmEdgeVersion>().map_or_else(|_| build.git_ref().to_string(), |version| version.tag())
+}
+
+/// Where a source build of `build` at `commit` is recorded as coming from:
+/// `<ref>@<commit>`, with the `+feature`s it enabled.
+fn built_source(build: &SourceBuild, commit: &str) -> String {
+    let mut source = format!("{}@{}", build.git_ref(), commit);
+    for feature in build.features() {
+        source.push_str(&format!("+{}", feature));
+    }
+    source
+}
+
+/// Install the plugins `build` built along with the runtime, which it left
+/// in `built_dir`, into the plugin directory of `install_path` and record a
+/// receipt for each. A library belongs to every requested plugin it is
+/// named after, so `wasi_nn-ggml` and `wasi_nn-openvino` both list the one
+/// WASI-NN library.
+pub fn install_built_plugins(fs: &dyn FileSystem, install_path: &Path, platform: &Platform, build: &SourceBuild, built_dir: &Path, commit: &str) -> Result<()> {
+    let published: Vec<&str> = build
+        .plugins()
+        .iter()
+        .map(|name| source::plugin_name(name).with_context(|| t!("source-unknown-plugin", name = name)))
+        .collect::<Result<_>>()?;
+    let mut libraries = Vec::new();
+    if built_dir.exists() {
+        fsutil::collect_files(built_dir, Path::new(""), &mut libraries)?;
+    }
+    libraries.retain(|file| is_library(file));
+
+    let plugin_dir = plugin_dir(install_path);
+    fs.create_dir_all(&plugin_dir)?;
+    let mut installed = Vec::new();
+    for library in libraries {
+        let staged = built_dir.join(&library);
+        let dest = plugin_dir.join(library.file_name().unwrap_or(library.as_os_str()));
+        let digest = checksum::sha256_file(&staged)?;
+        fs.rename(&staged, &dest)?;
+        info!("Installed plugin file: {}", dest.display());
+        installed.push((dest, digest));
+    }
+
+    let receipts_path = plugin_home(install_path).join(RECEIPTS_FILE);
+    let mut receipts = PluginReceipts::load(&receipts_path)?;
+    for name in published {
+        let wanted = library_key(name);
+        let files: Vec<(PathBuf, String)> = installed
+            .iter()
+            .filter(|(path, _)| {
+                let key = library_key(&path.file_name().unwrap_or_default().to_string_lossy());
+                !key.is_empty() && wanted.starts_with(&key)
+            })
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            anyhow::bail!(t!("plugin-build-no-library", name = name));
+        }
+        receipts.plugins.insert(
+            name.to_string(),
+            PluginReceipt {
+                version: built_version(build),
+                platform: platform.asset_suffix(),
+                files: files.iter().map(|(path, _)| path.clone()).collect(),
+                sha256: files.into_iter().collect(),
+                source: Some(built_source(build, commit)),
+                licenses: Vec::new(),
+            },
+        );
+    }
+    receipts.save(fs, &receipts_path)
+}
+
+/// Directory plugins of the installation at `install_path` are installed
+/// into, where its runtime looks for them.
+pub fn plugin_dir(install_path: &Path) -> PathBuf {
+    plugin_home(install_path).join("plugin")
+}
+
+/// Receipts of the plugins installed in the [`plugin_dir`] of `install_path`.
+pub fn installed_plugins(install_path: &Path) -> Result<PluginReceipts> {
+    PluginReceipts::load(&plugin_home(install_path).join(RECEIPTS_FILE))
+}
+
+/// Prefix whose plugins the installation at `install_path` uses: the
+/// version `wasmedgeup default` linked into it, whose plugin directory the
+/// prefix links to, or else the installation itself.
+fn plugin_home(install_path: &Path) -> PathBuf {
+    match installer::default_version(install_path) {
+        Some(version) => installer::versioned_prefix(install_path, &version),
+        None => install_path.to_path_buf(),
+    }
+}
+
+/// Plugin archives of the `version` release as last fetched, without going
+/// to the network or resolving digests. `None` when it was never fetched.
+pub fn cached_catalog(version: &WasmEdgeVersion) -> Option<Vec<CatalogEntry>> {
+    release::cached_release(version).map(|release| catalog_entries(&release, version, None))
+}
+
+/// Plugin archives `release` publishes for `version`, with their digests
+/// from `checksums`.
+fn catalog_entries(release: &Release, version: &WasmEdgeVersion, checksums: Option<&ChecksumFile>) -> Vec<CatalogEntry> {
+    let mut entries = Vec::new();
+    for asset in &release.assets {
+        let Some(parsed) = PluginAsset::parse(&asset.name) else {
+            continue;
+        };
+        if parsed.version != *version {
+            debug!("Skipping {}, which is not built for {}", asset.name, version);
+            continue;
+        }
+        entries.push(CatalogEntry {
+            name: parsed.name,
+            version: version.to_string(),
+            platform: parsed.platform,
+            url: asset.browser_download_url.clone(),
+            size: asset.size,
+            sha256: checksums
+                .and_then(|c| c.get(&asset.name))
+                .map(str::to_string),
+        });
+    }
+    entries
+}
+
+/// Plugin names are written both as published (`wasi_nn-ggml`) and in the
+/// dashed form users tend to type (`wasi-nn-ggml`); compare them loosely.
+pub fn normalize_plugin_name(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+/// Short description of a plugin family, for interactive listings.
+pub fn describe_plugin(name: &str) -> &'static str {
+    let name = normalize_plugin_name(name);
+    const DESCRIPTIONS: &[(&str, &str)] = &[
+        ("wasi_nn", "WASI-NN machine learning inference backend"),
+        ("wasi_crypto", "WASI-Crypto cryptography APIs"),
+        ("wasi_logging", "WASI-Logging structured logging"),
+        ("wasi_ocr", "WASI-OCR text recognition"),
+        ("wasmedge_rustls", "TLS support via rustls"),
+        ("wasmedge_image", "Image decoding helpers"),
+        ("wasmedge_tensorflow", "TensorFlow inference"),
+        ("wasmedge_opencvmini", "OpenCV subset for image processing"),
+        ("wasmedge_zlib", "zlib compression"),
+        ("wasmedge_ffmpeg", "FFmpeg media processing"),
+        ("wasmedge_stablediffusion", "Stable Diffusion image generation"),
+        ("wasm_bpf", "eBPF program loading"),
+    ];
+    DESCRIPTIONS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, description)| *description)
+        .unwrap_or("")
+}
+
+/// What an installed plugin put on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginReceipt {
+    pub version: String,
+    pub platform: String,
+    pub files: Vec<PathBuf>,
+    /// SHA-256 of each file when it was installed. Receipts written by older
+    /// versions have none.
+    #[serde(default)]
+    pub sha256: BTreeMap<PathBuf, String>,
+    /// `<ref>@<commit>` the plugin was built from, with `+feature`s, when
+    /// it was built from source rather than downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// License and notice files from the plugin archive, gathered into
+    /// `licenses/<plugin>` next to the plugin directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<PathBuf>,
+}
+
+/// Outcome of `plugin verify` for one installed plugin.
+#[derive(Debug, Serialize)]
+pub struct PluginCheck {
+    pub name: String,
+    pub problems: Vec<String>,
+}
+
+/// A plugin the runtime reported as loaded, with the plugin's own version.
+#[derive(Debug, PartialEq)]
+pub struct LoadedPlugin {
+    pub name: String,
+    pub version: String,
+}
+
+/// Plugins the runtime reported as loaded, and the lines of its load errors.
+#[derive(Debug, Default, PartialEq)]
+pub struct LoadReport {
+    pub loaded: Vec<LoadedPlugin>,
+    pub errors: Vec<String>,
+}
+
+/// One row of `plugin active`.
+#[derive(Debug, Serialize)]
+pub struct ActivePlugin {
+    pub name: String,
+    pub version: Option<String>,
+    pub status: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ReleaseAsset {
-    name: String,
-    browser_download_url: String,
+impl LoadReport {
+    /// Parse the output of `wasmedge --version`, which lists each loaded
+    /// plugin as `(plugin "name") version x.y.z` and reports libraries that
+    /// failed to load on stderr.
+    pub fn parse(stdout: &str, stderr: &str) -> Self {
+        let loaded = stdout
+            .lines()
+            .filter_map(|line| line.split_once("(plugin \"")?.1.split_once('"'))
+            .map(|(name, rest)| LoadedPlugin {
+                name: name.to_string(),
+                version: rest.trim_start_matches(')').trim().trim_start_matches("version").trim().to_string(),
+            })
+            .collect();
+        let errors = stderr
+            .lines()
+            .filter(|line| line.contains("error"))
+            .map(|line| line.trim().to_string())
+            .collect();
+        Self { loaded, errors }
+    }
+
+    /// Load errors that mention one of `files`.
+    fn errors_for(&self, files: &[PathBuf]) -> Vec<&String> {
+        let file_names: Vec<String> = files
+            .iter()
+            .filter_map(|f| f.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .collect();
+        self.errors
+            .iter()
+            .filter(|line| file_names.iter().any(|f| line.contains(f.as_str())))
+            .collect()
+    }
+
+    /// Whether the runtime loaded the plugin published as `name`.
+    fn has_loaded(&self, name: &str) -> bool {
+        self.loaded.iter().any(|l| loads_as(name, &l.name))
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct Release {
-    assets: Vec<ReleaseAsset>,
+/// Installed plugins keyed by their published name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PluginReceipts {
+    pub plugins: BTreeMap<String, PluginReceipt>,
+    /// Versions set with `plugin pin`, by normalized plugin name. Installs
+    /// and syncs use these instead of the runtime version
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pins: BTreeMap<String, String>,
+}
+
+impl PluginReceipts {
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, fs: &dyn FileSystem, path: &Path) -> Result<()> {
+        fs.write(path, serde_json::to_string_pretty(self)?.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Published name of an installed plugin matching `name` loosely.
+    pub fn find(&self, name: &str) -> Option<String> {
+        let wanted = normalize_plugin_name(name);
+        self.plugins
+            .keys()
+            .find(|k| normalize_plugin_name(k) == wanted)
+            .cloned()
+    }
+
+    /// Version `name` is pinned to, if any.
+    pub fn pinned(&self, name: &str) -> Option<&String> {
+        self.pins.get(&normalize_plugin_name(name))
+    }
+
+    /// Plugin libraries in `plugin_dir` that no receipt records, e.g. copied
+    /// in by hand or installed before receipts were kept.
+    pub fn orphans(&self, plugin_dir: &Path) -> Vec<PathBuf> {
+        let recorded: Vec<&Path> = self
+            .plugins
+            .values()
+            .flat_map(|receipt| &receipt.files)
+            .filter_map(|file| file.strip_prefix(plugin_dir).ok())
+            .collect();
+        let Ok(entries) = std::fs::read_dir(plugin_dir) else {
+            return Vec::new();
+        };
+        let mut orphans: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_library(path) && path.is_file())
+            .filter(|path| path.strip_prefix(plugin_dir).is_ok_and(|name| !recorded.contains(&name)))
+            .collect();
+        orphans.sort();
+        orphans
+    }
+
+    /// All receipts, or those of the plugins matching `names`.
+    pub fn select(&self, names: &[String]) -> Result<Vec<(&String, &PluginReceipt)>> {
+        if names.is_empty() {
+            return Ok(self.plugins.iter().collect());
+        }
+        names
+            .iter()
+            .map(|name| {
+                let published = self
+                    .find(name)
+                    .with_context(|| t!("plugin-not-installed", name = name))?;
+                Ok(self.plugins.get_key_value(&published).unwrap())
+            })
+            .collect()
+    }
 }
 
-pub struct PluginManager {
-    runtime_version: String,
+pub struct PluginManager<F: Fetcher = Downloader> {
+    install_path: PathBuf,
+    runtime_version: WasmEdgeVersion,
     platform: Platform,
-    downloader: Downloader,
+    temp_dir: PathBuf,
+    downloader: F,
+    checksums: Option<ChecksumFile>,
+    events: Events,
+    strict: bool,
+    extract_limits: ExtractLimits,
+    fs: Arc<dyn FileSystem>,
 }
 
-impl PluginManager {
-    pub fn new(runtime_version: String, platform: Platform) -> Self {
+impl<F: Fetcher> PluginManager<F> {
+    /// Create a plugin manager for the installation at `install_path` that
+    /// downloads through `fetcher` instead of the default HTTP client.
+    pub fn with_fetcher(install_path: PathBuf, runtime_version: WasmEdgeVersion, platform: Platform, fetcher: F) -> Self {
         Self {
+            install_path,
             runtime_version,
             platform,
-            downloader: Downloader::new(),
+            temp_dir: std::env::temp_dir(),
+            downloader: fetcher,
+            checksums: None,
+            events: Events::default(),
+            strict: false,
+            extract_limits: ExtractLimits::default(),
+            fs: Arc::new(RealFs),
         }
     }
 
-    fn get_platform_string(&self) -> String {
-        match &self.platform.os {
-            crate::platform::OS::Linux(distro) => match distro {
-                crate::platform::LinuxDistro::Ubuntu => format!("ubuntu20.04_{}", self.platform.arch),
-                _ => format!("manylinux2014_{}", self.platform.arch),
-            },
-            crate::platform::OS::Darwin => format!("darwin_{}", self.platform.arch),
-            crate::platform::OS::Windows => format!("windows_{}", self.platform.arch),
+    /// Stage downloads and builds under `temp_dir`, e.g. from `--tmpdir`.
+    pub fn with_temp_dir(mut self, temp_dir: PathBuf) -> Self {
+        self.temp_dir = temp_dir;
+        self
+    }
+
+    /// Require every downloaded plugin archive to match a pinned digest.
+    pub fn with_checksums(mut self, checksums: Option<ChecksumFile>) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// Make changes to the plugin directory through `fs`.
+    pub fn with_filesystem(mut self, fs: Arc<dyn FileSystem>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Send progress [`Event`]s of plugin installs and builds to `events`.
+    pub fn with_events(mut self, events: Events) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Refuse plugin archives without a pinned digest and guessed
+    /// download URLs.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Abort unpacking plugin archives and bundles that exceed `limits`.
+    pub fn with_extract_limits(mut self, limits: ExtractLimits) -> Self {
+        self.extract_limits = limits;
+        self
+    }
+
+    /// Check a downloaded archive against the pins, if any.
+    fn verify_pinned(&self, url: &str, archive_path: &Path) -> Result<()> {
+        let name = url.rsplit('/').next().unwrap_or(url);
+        self.events.emit(Event::Verifying { artifact: name.to_string() });
+        self.require_pin(url)?;
+        match &self.checksums {
+            Some(pins) => pins.verify(name, archive_path),
+            None => Ok(()),
         }
     }
 
-    async fn fetch_version_manifest(&self, repo: &str) -> Result<VersionManifest> {
-        let url = format!(
-            "https://raw.githubusercontent.com/WasmEdge/WasmEdge/master/plugins/{}/version.json",
-            repo
-        );
-        info!("Fetching version manifest from: {}", url);
-        self.downloader.download_json(&url).await
+    /// In `--strict` mode, refuse an archive at `url` the pin file does not
+    /// cover.
+    fn require_pin(&self, url: &str) -> Result<()> {
+        let name = url.rsplit('/').next().unwrap_or(url);
+        let pinned = self.checksums.as_ref().is_some_and(|pins| pins.get(name).is_some());
+        if self.strict && !pinned {
+            anyhow::bail!(t!("strict-no-checksum", name = name));
+        }
+        Ok(())
     }
 
-    async fn fetch_plugin_manifest(&self, repo: &str, version: &str) -> Result<PluginManifest> {
-        let url = format!(
-            "https://raw.githubusercontent.com/WasmEdge/WasmEdge/master/plugins/{}/manifest.json",
-            repo
-        );
-        info!("Fetching plugin manifest from: {}", url);
-        self.downloader.download_json(&url).await
+    fn plugin_dir(&self) -> PathBuf {
+        plugin_dir(&self.install_path)
+    }
+
+    fn receipts_path(&self) -> PathBuf {
+        plugin_home(&self.install_path).join(RECEIPTS_FILE)
+    }
+
+    /// Give plugin files the owner, group and mode the runtime was installed
+    /// with, so plugins of a `--system` install are not left to whoever ran
+    /// `plugin install`.
+    fn apply_ownership(&self) -> Result<()> {
+        let home = plugin_home(&self.install_path);
+        let Some(ownership) = installer::installed_ownership(&home) else {
+            return Ok(());
+        };
+        let plugin_dir = self.plugin_dir();
+        for path in [&plugin_dir, &share_root(&plugin_dir), &home.join(LICENSES_DIR), &self.receipts_path()] {
+            ownership.apply(self.fs.as_ref(), path)?;
+        }
+        Ok(())
+    }
+
+    fn get_platform_string(&self) -> String {
+        self.platform.asset_suffix()
+    }
+
+    /// Catalog entries installable on this platform.
+    pub async fn compatible_plugins(&self) -> Result<Vec<CatalogEntry>> {
+        let platform_string = self.get_platform_string();
+        Ok(self
+            .catalog()
+            .await?
+            .into_iter()
+            .filter(|e| e.platform == platform_string)
+            .collect())
     }
 
     pub async fn list_available_plugins(&self) -> Result<Vec<(String, String, bool)>> {
-        let mut available_plugins = Vec::new();
         let platform_string = self.get_platform_string();
+        let mut available_plugins: Vec<(String, String, bool)> = Vec::new();
 
-        // Fetch release information from GitHub API
-        let url = format!(
-            "https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/{}",
-            self.runtime_version
-        );
-        info!("Fetching release information from: {}", url);
-        
-        let release: Release = self.downloader.download_json(&url).await?;
-        let mut seen_plugins = HashSet::new();
-
-        // Process plugin assets
-        for asset in release.assets {
-            if asset.name.starts_with("WasmEdge-plugin-") && asset.name.ends_with(".tar.gz") {
-                // Extract plugin name and check platform compatibility
-                let parts: Vec<&str> = asset.name.split('-').collect();
-                if parts.len() >= 4 {
-                    let plugin_name = parts[2..parts.len()-2].join("-");
-                    let version = self.runtime_version.clone();
-                    let is_compatible = asset.name.contains(&platform_string);
-
-                    // Only add each plugin once
-                    if !seen_plugins.contains(&plugin_name) {
-                        seen_plugins.insert(plugin_name.clone());
-                        available_plugins.push((plugin_name, version, is_compatible));
-                    }
-                }
+        for entry in self.catalog().await? {
+            let is_compatible = entry.platform == platform_string;
+            match available_plugins.iter_mut().find(|(name, _, _)| *name == entry.name) {
+                Some(existing) => existing.2 |= is_compatible,
+                None => available_plugins.push((entry.name, entry.version, is_compatible)),
             }
         }
 
         Ok(available_plugins)
     }
 
-    async fn extract_plugin(&self, archive_path: &Path, plugin_dir: &Path) -> Result<()> {
+    /// Resolve every plugin archive published for the runtime version,
+    /// together with upstream digests when the release provides them.
+    pub async fn catalog(&self) -> Result<Vec<CatalogEntry>> {
+        self.catalog_for(&self.runtime_version).await
+    }
+
+    /// Plugin archives published with the `version` release.
+    async fn catalog_for(&self, version: &WasmEdgeVersion) -> Result<Vec<CatalogEntry>> {
+        let release = release::fetch_release(&self.downloader, version).await?;
+        let checksums = release::fetch_release_checksums(&self.downloader, &release).await?;
+        let entries = catalog_entries(&release, version, checksums.as_ref());
+        complete::remember_plugins(entries.iter().map(|e| e.name.as_str()));
+        Ok(entries)
+    }
+
+    /// Unpack the plugin archive of `published_name` at `archive_path` into
+    /// `staging`, then move its license files into `licenses` and the rest
+    /// into place. Returns every installed plugin file with its digest, and
+    /// the license files.
+    async fn extract_plugin(&self, archive_path: &Path, staging: &Path, plugin_dir: &Path, published_name: &str, licenses: &Path) -> Result<(Vec<(PathBuf, String)>, Vec<PathBuf>)> {
+        let unpacked = staging.join("unpacked");
         let file = std::fs::File::open(archive_path)?;
-        let gz = GzDecoder::new(file);
-        let mut archive = Archive::new(gz);
-
-        // Extract all .so files from the archive
-        for entry in archive.entries()? {
-            let mut entry = entry?;
-            let path = entry.path()?;
-            if let Some(ext) = path.extension() {
-                if ext == "so" || ext == "dll" || ext == "dylib" {
-                    let file_name = path.file_name().unwrap();
-                    let dest_path = plugin_dir.join(file_name);
-                    entry.unpack(&dest_path)?;
-                    info!("Extracted plugin file: {}", dest_path.display());
-                }
+        if archive_path.extension().is_some_and(|extension| extension == "zip") {
+            extract::unpack_zip(file, &unpacked, &self.extract_limits, |_| {})
+        } else {
+            extract::unpack_tar_gz(file, &unpacked, &self.extract_limits, |_| {})
+        }
+        .context("Failed to extract plugin archive")?;
+        let licenses = licenses::gather(self.fs.as_ref(), &unpacked, licenses)?;
+        Ok((self.place_files(&unpacked, plugin_dir, published_name)?, licenses))
+    }
+
+    /// Move the files of plugin `published_name` under `unpacked` into place
+    /// as laid out by [`plugin_layout`]: libraries into `plugin_dir`, and
+    /// auxiliary files into the plugin's own [`share_dir`], so no two plugins
+    /// write the same README. Returns every installed file with its digest.
+    fn place_files(&self, unpacked: &Path, plugin_dir: &Path, published_name: &str) -> Result<Vec<(PathBuf, String)>> {
+        let mut files = Vec::new();
+        fsutil::collect_files(unpacked, Path::new(""), &mut files)?;
+        files.retain(|file| !licenses::is_license(file));
+        if !files.iter().any(|f| is_library(f)) {
+            anyhow::bail!(t!("plugin-archive-no-library"));
+        }
+
+        let mut extracted = Vec::new();
+        let share = share_dir(plugin_dir, published_name);
+        // Plugins of a runtime installed with --patch-rpath load libwasmedge
+        // the same way; patched before hashing, so verify sees the result
+        let patch_rpath = installer::is_rpath_patched(&self.install_path);
+        for (source, dest) in plugin_layout(&files) {
+            let staged = unpacked.join(&source);
+            let dest_path = if is_library(&source) { plugin_dir.join(dest) } else { share.join(dest) };
+            if let Some(parent) = dest_path.parent() {
+                self.fs.create_dir_all(parent)?;
+            }
+            if patch_rpath && is_library(&source) {
+                rpath::patch_plugin(&staged, &self.install_path, &self.platform.os)?;
             }
+            self.events.emit(Event::Extracting { file: dest_path.clone() });
+            let digest = checksum::sha256_file(&staged)?;
+            self.fs.rename(&staged, &dest_path)?;
+            info!("Extracted plugin file: {}", dest_path.display());
+            extracted.push((dest_path, digest));
         }
 
-        Ok(())
+        Ok(extracted)
     }
 
-    pub async fn install_plugin(&self, plugin_name: &str, version: Option<String>) -> Result<()> {
-        info!("Installing plugin {} (version: {:?})", plugin_name, version);
-
+    /// The catalog entry of `plugin_name` built for this platform.
+    fn find_entry(&self, catalog: &[CatalogEntry], plugin_name: &str, version: &WasmEdgeVersion) -> Result<CatalogEntry> {
         let platform_string = self.get_platform_string();
-        let mut installed = false;
-
-        let plugin_dir = dirs::home_dir()
-            .context("Could not determine home directory")?
-            .join(".wasmedge")
-            .join("plugin");
+        let wanted = normalize_plugin_name(plugin_name);
+        let matching: Vec<&CatalogEntry> = catalog
+            .iter()
+            .filter(|e| normalize_plugin_name(&e.name) == wanted)
+            .collect();
+        if matching.is_empty() {
+            let mut names: Vec<&str> = catalog.iter().map(|e| e.name.as_str()).collect();
+            names.sort_unstable();
+            names.dedup();
+            anyhow::bail!(t!("plugin-unknown", name = plugin_name, version = version, available = names.join(", ")));
+        }
+        match matching.iter().find(|e| e.platform == platform_string) {
+            Some(entry) => Ok((*entry).clone()),
+            None => anyhow::bail!(t!(
+                "plugin-platform-unpublished",
+                name = plugin_name,
+                os = self.platform.os,
+                arch = self.platform.arch,
+                platforms = matching.iter().map(|e| e.platform.as_str()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
 
-        std::fs::create_dir_all(&plugin_dir)?;
+    /// Published name, candidate download URLs and, when the catalog lists
+    /// it, archive size of `plugin_name` for `version`.
+    async fn resolve_plugin(&self, plugin_name: &str, version: &WasmEdgeVersion) -> Result<(String, Vec<String>, Option<u64>)> {
+        match self.catalog_for(version).await {
+            Ok(catalog) => {
+                let entry = self.find_entry(&catalog, plugin_name, version)?;
+                Ok((entry.name, vec![entry.url], Some(entry.size)))
+            }
+            Err(e) if self.strict => Err(e.context(t!("strict-guessed-url", name = plugin_name))),
+            Err(e) => {
+                // Without the release listing (e.g. API rate limits), guess the asset URL
+                warn!("{}", t!("plugin-catalog-unavailable", error = e));
+                let (name, urls) = self.guessed_archive(plugin_name, version);
+                Ok((name, urls, None))
+            }
+        }
+    }
 
+    /// Published name and candidate download URLs of `plugin_name` for
+    /// `version`, guessed from the usual asset names.
+    fn guessed_archive(&self, plugin_name: &str, version: &WasmEdgeVersion) -> (String, Vec<String>) {
+        let platform_string = self.get_platform_string();
         let url_plugin_name = if let Some(pos) = plugin_name.find('-') {
-            format!("{}_{}", 
-                &plugin_name[..pos],
-                &plugin_name[pos + 1..]
-            )
+            format!("{}_{}", &plugin_name[..pos], &plugin_name[pos + 1..])
         } else {
             plugin_name.to_string()
         };
-
-        let url = format!(
-            "https://github.com/WasmEdge/WasmEdge/releases/download/{}/WasmEdge-plugin-{}-{}-{}.tar.gz",
-            self.runtime_version, url_plugin_name, self.runtime_version, platform_string
+        let base = format!(
+            "https://github.com/WasmEdge/WasmEdge/releases/download/{}",
+            version
         );
+        let urls = vec![
+            format!("{}/{}", base, version.plugin_asset_name(&url_plugin_name, &platform_string)),
+            format!("{}/WasmEdge-plugin-{}-{}.tar.gz", base, url_plugin_name, platform_string),
+        ];
+        (url_plugin_name, urls)
+    }
 
-        info!("Attempting to download plugin from: {}", url);
-
-        let temp_dir = tempfile::tempdir()?;
-        let archive_path = temp_dir.path().join("plugin.tar.gz");
-
-        match self.downloader.download_file(&url, &archive_path).await {
-            Ok(_) => {
-                info!("Successfully downloaded plugin archive");
-                if let Err(e) = self.extract_plugin(&archive_path, &plugin_dir).await {
-                    anyhow::bail!("Failed to extract plugin: {}", e);
-                }
-                installed = true;
-            }
-            Err(e) => {
-                warn!("Failed to download plugin: {}", e);
-
-                let alt_url = format!(
-                    "https://github.com/WasmEdge/WasmEdge/releases/download/{}/WasmEdge-plugin-{}-{}.tar.gz",
-                    self.runtime_version, url_plugin_name, platform_string
-                );
-                
-                info!("Attempting alternative URL: {}", alt_url);
-                
-                match self.downloader.download_file(&alt_url, &archive_path).await {
-                    Ok(_) => {
-                        info!("Successfully downloaded plugin archive from alternative URL");
-                        if let Err(e) = self.extract_plugin(&archive_path, &plugin_dir).await {
-                            anyhow::bail!("Failed to extract plugin: {}", e);
-                        }
-                        installed = true;
-                    }
+    /// Size of the archive `install_plugin` would download for each of
+    /// `plugins`, by name and version (this manager's runtime version by
+    /// default), where known. Each release's catalog is fetched once.
+    pub async fn download_sizes(&self, plugins: &[(String, Option<WasmEdgeVersion>)]) -> Result<Vec<Option<ArchiveSize>>> {
+        let mut catalogs: HashMap<String, Option<Vec<CatalogEntry>>> = HashMap::new();
+        let mut sizes = Vec::new();
+        for (plugin_name, version) in plugins {
+            let version = version.clone().unwrap_or_else(|| self.runtime_version.clone());
+            if let std::collections::hash_map::Entry::Vacant(slot) = catalogs.entry(version.tag()) {
+                let catalog = match self.catalog_for(&version).await {
+                    Ok(catalog) => Some(catalog),
+                    Err(e) if self.strict => return Err(e.context(t!("strict-guessed-url", name = plugin_name))),
                     Err(e) => {
-                        warn!("Failed to download plugin from alternative URL: {}", e);
+                        debug!("Could not fetch the plugin catalog, guessing download URLs: {}", e);
+                        None
+                    }
+                };
+                slot.insert(catalog);
+            }
+            let (urls, known) = match &catalogs[&version.tag()] {
+                Some(catalog) => {
+                    let entry = self.find_entry(catalog, plugin_name, &version)?;
+                    (vec![entry.url], Some(entry.size))
+                }
+                None => (self.guessed_archive(plugin_name, &version).1, None),
+            };
+            let mut size = None;
+            for url in &urls {
+                match download::archive_size(&self.downloader, url, known).await {
+                    Ok(found) => {
+                        size = found;
+                        break;
                     }
+                    Err(e) => debug!("Could not get the size of {}: {}", url, e),
                 }
             }
+            sizes.push(size);
         }
+        Ok(sizes)
+    }
 
-        if !installed {
-            anyhow::bail!(
-                "Failed to install plugin '{}'. The plugin may not be available for your platform ({} {}) or the specified version. Available plugins for your platform can be found at: https://github.com/WasmEdge/WasmEdge/releases/tag/{}",
-                plugin_name,
-                self.platform.os,
-                self.platform.arch,
-                self.runtime_version
-            );
+    pub async fn install_plugin(&self, plugin_name: &str, version: Option<WasmEdgeVersion>) -> Result<()> {
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        let pinned: Option<WasmEdgeVersion> = receipts.pinned(plugin_name).map(|pinned| pinned.parse()).transpose()?;
+        let version = match (version, pinned) {
+            (Some(version), Some(pinned)) if version != pinned => {
+                anyhow::bail!(t!("plugin-pinned", name = plugin_name, version = pinned, requested = version));
+            }
+            (version, pinned) => version.or(pinned).unwrap_or_else(|| self.runtime_version.clone()),
+        };
+        let result = self.install_plugin_files(plugin_name, version.clone()).await;
+        self.events.finish(plugin_name, &version.tag(), &result);
+        result
+    }
+
+    async fn install_plugin_files(&self, plugin_name: &str, version: WasmEdgeVersion) -> Result<()> {
+        info!("Installing plugin {} (version: {})", plugin_name, version);
+        if !self.platform.os.has_release_binaries() {
+            anyhow::bail!(t!("no-release-binaries", os = self.platform.os));
+        }
+        if !is_compatible(&self.runtime_version, &version.tag()) {
+            anyhow::bail!(t!(
+                "plugin-incompatible-runtime",
+                name = plugin_name,
+                version = version,
+                runtime = self.runtime_version,
+                reason = t!("plugin-line-mismatch", runtime = &self.runtime_version, plugin = &version)
+            ));
         }
 
+        let plugin_dir = self.plugin_dir();
+        self.fs.create_dir_all(&plugin_dir)?;
+
+        let (published_name, urls, _) = self.resolve_plugin(plugin_name, &version).await?;
+        self.events.emit(Event::Resolved {
+            name: published_name.clone(),
+            version: version.tag(),
+            artifact: urls.first().map(|url| url.rsplit('/').next().unwrap_or(url).to_string()).unwrap_or_default(),
+        });
+        // A dry run names the archive rather than downloading it
+        if self.fs.is_dry_run() {
+            let url = urls.first().with_context(|| t!("plugin-archive-unpublished", name = plugin_name, version = version))?;
+            self.require_pin(url)?;
+            return self.fs.skip(format!("download {} and install plugin {} into {}", url, published_name, plugin_dir.display()));
+        }
+
+        let temp_dir = cache::temp_dir_in(&self.temp_dir)?;
+        let licenses_dir = plugin_dir.with_file_name(LICENSES_DIR).join(&published_name);
+
+        let mut files = None;
+        for url in &urls {
+            // Windows plugins are published as zip archives
+            let archive_path = temp_dir.path().join(if url.ends_with(".zip") { "plugin.zip" } else { "plugin.tar.gz" });
+            info!("Attempting to download plugin from: {}", url);
+            let progress = |bytes, total| self.events.emit(Event::Downloading { url: url.clone(), bytes, total });
+            match self.downloader.download_file_with_progress(url, &archive_path, &progress).await {
+                Ok(_) => {
+                    info!("Successfully downloaded plugin archive");
+                    self.verify_pinned(url, &archive_path)?;
+                    match self.extract_plugin(&archive_path, temp_dir.path(), &plugin_dir, &published_name, &licenses_dir).await {
+                        Ok(extracted) => files = Some(extracted),
+                        Err(e) => anyhow::bail!(t!("plugin-extract-failed", error = e)),
+                    }
+                    break;
+                }
+                Err(e) => warn!("{}", t!("plugin-download-failed", error = e)),
+            }
+        }
+
+        let Some((files, licenses)) = files else {
+            anyhow::bail!(t!("plugin-install-failed", name = plugin_name, os = self.platform.os, arch = self.platform.arch, version = version));
+        };
+
+        self.record(published_name, version.tag(), files, licenses, None)?;
         info!("Successfully installed plugin {}", plugin_name);
         Ok(())
     }
 
-    pub fn remove_plugin(&self, plugin_name: &str, version: Option<String>) -> Result<()> {
-        info!("Removing plugin {} (version: {:?})", plugin_name, version);
+    /// Build `plugin_name` from the WasmEdge sources, for platforms or
+    /// features (e.g. CUDA on arm64) no published archive covers, and install
+    /// it like a downloaded one.
+    pub async fn build_plugin(&self, plugin_name: &str, build: SourceBuild) -> Result<()> {
+        let version = built_version(&build);
+        let result = self.build_plugin_files(plugin_name, build).await;
+        self.events.finish(plugin_name, &version, &result);
+        result
+    }
 
-        let plugin_dir = dirs::home_dir()
-            .context("Could not determine home directory")?
-            .join(".wasmedge")
-            .join("plugin");
-
-        let plugin_lib_name = if plugin_name.starts_with("wasi-nn-") {
-            format!("libwasmedgePluginWasiNN.dylib")
-        } else if plugin_name.starts_with("wasi-crypto") {
-            format!("libwasmedgePluginWasiCrypto.dylib")
-        } else if plugin_name.starts_with("wasmedge-") {
-            format!("libwasmedgePluginWasmEdge{}.dylib",
-                plugin_name[9..].split('-')
-                    .map(|s| s.chars().next().unwrap().to_uppercase().chain(s[1..].chars()).collect::<String>())
-                    .collect::<String>())
-        } else {
-            format!("libwasmedgePlugin{}.dylib", plugin_name)
+    async fn build_plugin_files(&self, plugin_name: &str, build: SourceBuild) -> Result<()> {
+        if self.strict {
+            anyhow::bail!(t!("strict-source-build"));
+        }
+        let published_name = source::plugin_name(plugin_name)
+            .with_context(|| t!("source-unknown-plugin", name = plugin_name))?;
+        let version = built_version(&build);
+        self.events.emit(Event::Resolved {
+            name: published_name.to_string(),
+            version: version.clone(),
+            artifact: format!("source:{}", build.git_ref()),
+        });
+        let plugin_dir = self.plugin_dir();
+        if self.fs.is_dry_run() {
+            return self.fs.skip(format!("build plugin {} from WasmEdge {} into {}", published_name, build.git_ref(), plugin_dir.display()));
+        }
+        info!("Building plugin {} from WasmEdge {}", published_name, build.git_ref());
+        let build = build.with_plugins(vec![published_name.to_string()]);
+
+        self.fs.create_dir_all(&plugin_dir)?;
+        let staging = cache::temp_dir_in(&self.temp_dir)?;
+        let built = build.build(staging.path()).await?;
+        let files = self
+            .place_files(&built.plugin_dir(), &plugin_dir, published_name)
+            .with_context(|| t!("plugin-build-no-library", name = published_name))?;
+
+        self.record(published_name.to_string(), version, files, Vec::new(), Some(built_source(&build, &built.commit)))?;
+        info!("Successfully built plugin {}", published_name);
+        Ok(())
+    }
+
+    /// Record what installing `published_name` put on disk.
+    fn record(&self, published_name: String, version: String, files: Vec<(PathBuf, String)>, licenses: Vec<PathBuf>, source: Option<String>) -> Result<()> {
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        receipts.plugins.insert(
+            published_name,
+            PluginReceipt {
+                version,
+                platform: self.get_platform_string(),
+                files: files.iter().map(|(path, _)| path.clone()).collect(),
+                sha256: files.into_iter().collect(),
+                source,
+                licenses,
+            },
+        );
+        receipts.save(self.fs.as_ref(), &receipts_path)?;
+        self.apply_ownership()
+    }
+
+    /// Download the archive of `plugin_name` (from the `version` release, by
+    /// default this manager's runtime version) into `output_dir` without
+    /// installing it, verified against the published digest and any pins.
+    pub async fn download_plugin(&self, plugin_name: &str, version: Option<WasmEdgeVersion>, output_dir: &Path) -> Result<Downloaded> {
+        let entry = self.resolve_archive(plugin_name, version).await?;
+        let file_name = entry.url.rsplit('/').next().unwrap_or(&entry.url).to_string();
+
+        let staging = cache::temp_dir_in(output_dir)?;
+        let staged = staging.path().join(&file_name);
+        self.downloader.download_file(&entry.url, &staged).await?;
+        self.verify_pinned(&entry.url, &staged)?;
+        download::keep(&staged, output_dir, &file_name, entry.sha256.as_deref())
+    }
+
+    /// Catalog entry of the archive `plugin_name` installs from, with the
+    /// digest of the pin file or the built-in list when upstream has none.
+    pub async fn resolve_archive(&self, plugin_name: &str, version: Option<WasmEdgeVersion>) -> Result<CatalogEntry> {
+        let version = version.unwrap_or_else(|| self.runtime_version.clone());
+        let catalog = self.catalog_for(&version).await?;
+        let mut entry = self.find_entry(&catalog, plugin_name, &version)?;
+        if entry.sha256.is_none() {
+            let file_name = entry.url.rsplit('/').next().unwrap_or(&entry.url);
+            let pinned = self.checksums.as_ref().and_then(|pins| pins.get(file_name));
+            entry.sha256 = pinned.map(str::to_string);
+        }
+        Ok(entry)
+    }
+
+    /// Reinstall every recorded plugin built for another `major.minor` than
+    /// this manager's runtime version, so the plugin directory matches the
+    /// runtime ABI. Plugins the release no longer publishes are removed:
+    /// their old builds would not load anyway. Plugins that fail to install
+    /// keep their old build, so nothing is lost. Pinned plugins are left alone.
+    pub async fn reinstall_all(&self) -> Result<Reinstalled> {
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        let stale: Vec<String> = receipts
+            .plugins
+            .iter()
+            .filter(|(_, receipt)| !is_compatible(&self.runtime_version, &receipt.version))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let mut outcome = Reinstalled::default();
+        let (pinned, stale): (Vec<String>, Vec<String>) =
+            stale.into_iter().partition(|name| receipts.pinned(name).is_some());
+        outcome.pinned = pinned;
+        if stale.is_empty() {
+            return Ok(outcome);
+        }
+
+        let platform = self.get_platform_string();
+        let catalog = self.catalog().await?;
+        for name in stale {
+            if !catalog.iter().any(|entry| entry.name == name && entry.platform == platform) {
+                self.remove_plugin(&name, None)?;
+                outcome.removed.push(name);
+                continue;
+            }
+            info!("Reinstalling plugin {} for WasmEdge {}", name, self.runtime_version);
+            // The old build stays on the same file system until the new one is in
+            let aside = cache::temp_dir_in(&plugin_home(&self.install_path))?;
+            let old = self.set_aside(&name, aside.path())?;
+            match self.install_plugin(&name, None).await {
+                Ok(()) => outcome.reinstalled.push(name),
+                Err(e) => {
+                    warn!("{}", t!("plugin-reinstall-one-failed", name = name, error = e));
+                    self.restore(&name, old, aside.path())?;
+                    outcome.failed.push(name);
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Move the files of the installed plugin `published_name` into `aside`
+    /// and drop its receipt, so it can be installed afresh and, should that
+    /// fail, [`restore`](Self::restore)d. Files other plugins share stay.
+    fn set_aside(&self, published_name: &str, aside: &Path) -> Result<PluginReceipt> {
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        let receipt = receipts.plugins.remove(published_name).with_context(|| t!("plugin-not-installed", name = published_name))?;
+        let shared: Vec<&PathBuf> = receipts.plugins.values().flat_map(|r| &r.files).collect();
+        for (i, file) in receipt.files.iter().chain(&receipt.licenses).enumerate() {
+            if !shared.contains(&file) && file.exists() {
+                self.fs.rename(file, &aside.join(i.to_string()))?;
+            }
+        }
+        receipts.save(self.fs.as_ref(), &receipts_path)?;
+        Ok(receipt)
+    }
+
+    /// Put back the plugin [`set_aside`](Self::set_aside) moved into `aside`,
+    /// replacing whatever a failed install left of it.
+    fn restore(&self, published_name: &str, receipt: PluginReceipt, aside: &Path) -> Result<()> {
+        if PluginReceipts::load(&self.receipts_path())?.plugins.contains_key(published_name) {
+            self.remove_plugin(published_name, None)?;
+        }
+        for (i, file) in receipt.files.iter().chain(&receipt.licenses).enumerate() {
+            let saved = aside.join(i.to_string());
+            if saved.exists() {
+                if let Some(parent) = file.parent() {
+                    self.fs.create_dir_all(parent)?;
+                }
+                self.fs.rename(&saved, file)?;
+            }
+        }
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        receipts.plugins.insert(published_name.to_string(), receipt);
+        receipts.save(self.fs.as_ref(), &receipts_path)
+    }
+
+    /// Pin `plugin_name` to `version`, or to the installed version when none
+    /// is given, so later installs and syncs keep it there. Returns the
+    /// pinned version.
+    pub fn pin_plugin(&self, plugin_name: &str, version: Option<WasmEdgeVersion>) -> Result<String> {
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        let version = match version {
+            Some(version) => version.tag(),
+            None => receipts
+                .find(plugin_name)
+                .map(|published| receipts.plugins[&published].version.clone())
+                .with_context(|| t!("plugin-pin-needs-version", name = plugin_name))?,
         };
+        receipts.pins.insert(normalize_plugin_name(plugin_name), version.clone());
+        self.fs.create_dir_all(receipts_path.parent().unwrap())?;
+        receipts.save(self.fs.as_ref(), &receipts_path)?;
+        Ok(version)
+    }
+
+    /// Release a pin set with [`Self::pin_plugin`]. Returns the version it
+    /// was pinned to.
+    pub fn unpin_plugin(&self, plugin_name: &str) -> Result<String> {
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        let version = receipts
+            .pins
+            .remove(&normalize_plugin_name(plugin_name))
+            .with_context(|| t!("plugin-not-pinned", name = plugin_name))?;
+        receipts.save(self.fs.as_ref(), &receipts_path)?;
+        Ok(version)
+    }
+
+    pub fn remove_plugin(&self, plugin_name: &str, version: Option<WasmEdgeVersion>) -> Result<()> {
+        info!("Removing plugin {} (version: {:?})", plugin_name, version);
+
+        let plugin_dir = self.plugin_dir();
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
 
         let mut found = false;
-        // Remove matching plugin files
-        if let Ok(entries) = std::fs::read_dir(&plugin_dir) {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let file_name = entry.file_name();
-                    let file_name = file_name.to_string_lossy();
-                    if file_name == plugin_lib_name {
-                        std::fs::remove_file(entry.path())?;
-                        info!("Removed plugin file: {}", file_name);
-                        found = true;
+        if let Some(published_name) = receipts.find(plugin_name) {
+            let receipt = receipts.plugins.remove(&published_name).unwrap();
+            let share_root = share_root(&plugin_dir);
+            // Other plugins (e.g. several wasi-nn backends) may share a library
+            let still_used: Vec<&PathBuf> = receipts.plugins.values().flat_map(|r| &r.files).collect();
+            for file in receipt.files.iter().filter(|f| !still_used.contains(f)) {
+                if file.exists() {
+                    self.fs.remove_file(file)?;
+                    info!("Removed plugin file: {}", file.display());
+                }
+                // Drop directories that only held this plugin's auxiliary files
+                let mut dir = file.parent();
+                while let Some(d) = dir.filter(|d| [&plugin_dir, &share_root].iter().any(|root| d != root && d.starts_with(root))) {
+                    if std::fs::read_dir(d).map_or(true, |mut entries| entries.next().is_some()) {
+                        break;
                     }
+                    self.fs.remove_dir_all(d)?;
+                    dir = d.parent();
+                }
+            }
+            self.remove_licenses(&plugin_dir, &receipt.licenses)?;
+            receipts.save(self.fs.as_ref(), &receipts_path)?;
+            found = true;
+        } else if let Ok(entries) = std::fs::read_dir(&plugin_dir) {
+            // Plugins installed before receipts were recorded: match library
+            // names exactly, so a library several backends share (e.g. the
+            // wasi-nn one) stays, and never touch a file a receipt records
+            let wanted = library_key(plugin_name);
+            let recorded: Vec<&PathBuf> = receipts.plugins.values().flat_map(|r| &r.files).collect();
+            for entry in entries.flatten() {
+                let file_name = entry.file_name();
+                let file_name = file_name.to_string_lossy();
+                let key = library_key(&file_name);
+                if !key.is_empty() && key == wanted && !recorded.contains(&&entry.path()) {
+                    self.fs.remove_file(&entry.path())?;
+                    info!("Removed plugin file: {}", file_name);
+                    found = true;
                 }
             }
         }
 
         if !found {
-            anyhow::bail!("No matching plugin files found for {} (version: {:?})", plugin_name, version);
+            anyhow::bail!(match &version {
+                Some(version) => t!("plugin-remove-version-not-found", name = plugin_name, version = version),
+                None => t!("plugin-remove-not-found", name = plugin_name),
+            });
         }
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    /// Remove the gathered license files of a removed plugin, and the
+    /// license directories they leave empty.
+    fn remove_licenses(&self, plugin_dir: &Path, files: &[PathBuf]) -> Result<()> {
+        let licenses_dir = plugin_dir.with_file_name(LICENSES_DIR);
+        for file in files.iter().filter(|f| f.starts_with(&licenses_dir)) {
+            if file.exists() {
+                self.fs.remove_file(file)?;
+            }
+        }
+        let dirs = files.iter().filter_map(|f| f.parent()).chain([licenses_dir.as_path()]);
+        for dir in dirs.filter(|d| d.starts_with(&licenses_dir)) {
+            if std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none()) {
+                self.fs.remove_dir_all(dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove one file from the plugin directory, given by name or path, e.g.
+    /// a stray library no receipt names, and drop it from the receipts. A
+    /// plugin whose library goes is no longer recorded as installed. Returns
+    /// the removed path and those plugins.
+    pub fn remove_plugin_file(&self, file: &str) -> Result<(PathBuf, Vec<String>)> {
+        let plugin_dir = self.plugin_dir();
+        let path = resolve_plugin_file(&plugin_dir, file)?;
+        self.fs.remove_file(&path)?;
+        info!("Removed plugin file: {}", path.display());
+
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        // Receipts record paths under the uncanonicalized plugin directory
+        let relative = path.strip_prefix(plugin_dir.canonicalize()?)?.to_path_buf();
+        let is_removed = |recorded: &PathBuf| recorded.strip_prefix(&plugin_dir).is_ok_and(|r| r == relative);
+        let mut owners = Vec::new();
+        for (name, receipt) in receipts.plugins.iter_mut() {
+            if receipt.files.iter().any(is_removed) {
+                receipt.files.retain(|f| !is_removed(f));
+                receipt.sha256.retain(|f, _| !is_removed(f));
+                owners.push(name.clone());
+            }
+        }
+        if !owners.is_empty() {
+            receipts.plugins.retain(|_, receipt| receipt.files.iter().any(|f| is_library(f)));
+            owners.retain(|name| !receipts.plugins.contains_key(name));
+            receipts.save(self.fs.as_ref(), &receipts_path)?;
+        }
+        Ok((path, owners))
+    }
+
+    /// Record a library in the plugin directory that no receipt names as an
+    /// installed plugin of this manager's runtime version, under `name` or
+    /// the library name. Returns the recorded name and the library path.
+    pub fn adopt_plugin_file(&self, file: &str, name: Option<&str>) -> Result<(String, PathBuf)> {
+        let plugin_dir = self.plugin_dir();
+        let resolved = resolve_plugin_file(&plugin_dir, file)?;
+        if !is_library(&resolved) {
+            anyhow::bail!(t!("plugin-adopt-not-library", path = resolved.display()));
+        }
+        // Receipts record paths under the uncanonicalized plugin directory
+        let path = plugin_dir.join(resolved.strip_prefix(plugin_dir.canonicalize()?)?);
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        if let Some((owner, _)) = receipts.plugins.iter().find(|(_, receipt)| receipt.files.contains(&path)) {
+            anyhow::bail!(t!("plugin-adopt-tracked", path = path.display(), name = owner));
+        }
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => adopted_name(&path),
+        };
+        if let Some(existing) = receipts.find(&name) {
+            anyhow::bail!(t!("plugin-adopt-exists", name = existing));
+        }
+
+        let digest = checksum::sha256_file(&path)?;
+        self.record(name.clone(), self.runtime_version.tag(), vec![(path.clone(), digest)], Vec::new(), None)?;
+        info!("Adopted plugin file {} as {}", path.display(), name);
+        Ok((name, path))
+    }
+
+    /// Remove every plugin recorded in the receipts, then the receipts and
+    /// pins themselves and the plugin directory if nothing else is left in
+    /// it. Returns the names of the removed plugins.
+    pub fn remove_all(&self) -> Result<Vec<String>> {
+        let receipts_path = self.receipts_path();
+        let names: Vec<String> = PluginReceipts::load(&receipts_path)?.plugins.into_keys().collect();
+        for name in &names {
+            self.remove_plugin(name, None)?;
+        }
+        if receipts_path.exists() {
+            self.fs.remove_file(&receipts_path)?;
+        }
+        let plugin_dir = self.plugin_dir();
+        if std::fs::read_dir(&plugin_dir).is_ok_and(|mut entries| entries.next().is_none()) {
+            self.fs.remove_dir_all(&plugin_dir)?;
+        }
+        Ok(names)
+    }
+
+    /// Pack the installed plugins (all, or those matching `names`) and their
+    /// receipts into a bundle at `output`. Returns the number of plugins.
+    pub fn export(&self, names: &[String], output: &Path) -> Result<usize> {
+        let plugin_dir = self.plugin_dir();
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        let selected = receipts.select(names)?;
+        let bundle = PluginBundle::from_receipts(&plugin_dir, &self.runtime_version.tag(), &self.get_platform_string(), selected)?;
+        bundle.write(&plugin_dir, output)?;
+        Ok(bundle.plugins.len())
+    }
+
+    /// Install the plugins from a bundle made by [`export`](Self::export).
+    /// Nothing is installed unless the bundle matches this platform and
+    /// runtime and every file matches its recorded digest. Returns the names
+    /// of the imported plugins.
+    pub fn import(&self, path: &Path) -> Result<Vec<String>> {
+        let staging = cache::temp_dir_in(&self.temp_dir)?;
+        let (bundle, files) = PluginBundle::unpack(path, staging.path(), &self.extract_limits)?;
+        bundle.check_target(&self.get_platform_string(), &self.runtime_version)?;
+
+        let plugin_dir = self.plugin_dir();
+        let receipts_path = self.receipts_path();
+        let mut receipts = PluginReceipts::load(&receipts_path)?;
+        let mut imported = Vec::new();
+        for (name, receipt) in bundle.plugins {
+            let mut installed = PluginReceipt {
+                files: Vec::new(),
+                sha256: BTreeMap::new(),
+                ..receipt.clone()
+            };
+            for file in &receipt.files {
+                let dest = plugin_dir.join(file);
+                if let Some(parent) = dest.parent() {
+                    self.fs.create_dir_all(parent)?;
+                }
+                self.fs.rename(&files.join(file), &dest)?;
+                if let Some(digest) = receipt.sha256.get(file) {
+                    installed.sha256.insert(dest.clone(), digest.clone());
+                }
+                installed.files.push(dest);
+            }
+            info!("Imported plugin {}", name);
+            receipts.plugins.insert(name.clone(), installed);
+            imported.push(name);
+        }
+        receipts.save(self.fs.as_ref(), &receipts_path)?;
+        self.apply_ownership()?;
+        Ok(imported)
+    }
+
+    /// Check installed plugins (all, or those matching `names`) against the
+    /// digests recorded at install time, then ask the runtime in
+    /// `install_path` to load them and collect what it reports.
+    pub fn verify(&self, install_path: &Path, names: &[String]) -> Result<Vec<PluginCheck>> {
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        let selected = receipts.select(names)?;
+
+        let report = self.load_report(install_path, true);
+        // Plugin libraries run to hundreds of MB, so they are hashed in parallel
+        let hashed: Vec<&Path> = selected
+            .iter()
+            .flat_map(|(_, receipt)| receipt.files.iter().filter(|f| receipt.sha256.contains_key(*f) && f.exists()))
+            .map(PathBuf::as_path)
+            .collect();
+        let mut digests = HashMap::new();
+        for (file, digest) in hashed.iter().zip(checksum::sha256_files(&hashed)) {
+            digests.insert(*file, digest?);
+        }
+
+        let mut checks = Vec::new();
+        for (name, receipt) in selected {
+            let mut problems = Vec::new();
+            for file in &receipt.files {
+                if !file.exists() {
+                    problems.push(t!("plugin-verify-missing", path = file.display()));
+                    continue;
+                }
+                match receipt.sha256.get(file) {
+                    Some(expected) if digests.get(file.as_path()).is_some_and(|d| !d.eq_ignore_ascii_case(expected)) => {
+                        problems.push(t!("plugin-verify-modified", path = file.display()));
+                    }
+                    Some(_) => {}
+                    None => debug!("No digest recorded for {}", file.display()),
+                }
+            }
+
+            if !is_compatible(&self.runtime_version, &receipt.version) {
+                problems.push(t!("plugin-verify-skew", version = receipt.version, runtime = self.runtime_version));
+            }
+
+            if let Some(report) = &report {
+                let errors = report.errors_for(&receipt.files);
+                if !errors.is_empty() {
+                    problems.extend(errors.iter().map(|e| t!("plugin-verify-load-error", error = e)));
+                } else if !report.has_loaded(name) && !report.loaded.is_empty() {
+                    // Runtimes that list no plugins at all predate the listing
+                    problems.push(t!("plugin-verify-not-loaded"));
+                }
+            }
+
+            checks.push(PluginCheck {
+                name: name.clone(),
+                problems,
+            });
+        }
+        Ok(checks)
+    }
+
+    /// Compare the plugins the runtime in `install_path` discovers on its own
+    /// (default directories and the caller's WASMEDGE_PLUGIN_PATH) with the
+    /// plugins wasmedgeup installed.
+    pub fn active(&self, install_path: &Path) -> Result<Vec<ActivePlugin>> {
+        let report = self
+            .load_report(install_path, false)
+            .with_context(|| t!("plugin-active-no-runtime", path = install_path.display()))?;
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        let mut rows = Vec::new();
+
+        for loaded in &report.loaded {
+            let managed = receipts.plugins.keys().any(|name| loads_as(name, &loaded.name));
+            rows.push(ActivePlugin {
+                name: loaded.name.clone(),
+                version: Some(loaded.version.clone()),
+                status: if managed {
+                    t!("plugin-active-managed")
+                } else {
+                    t!("plugin-active-unmanaged")
+                },
+            });
+        }
+
+        for (name, receipt) in &receipts.plugins {
+            if report.has_loaded(name) {
+                if !is_compatible(&self.runtime_version, &receipt.version) {
+                    rows.push(ActivePlugin {
+                        name: name.clone(),
+                        version: None,
+                        status: t!("plugin-active-skew", version = receipt.version),
+                    });
+                }
+                continue;
+            }
+            let errors = report.errors_for(&receipt.files);
+            let status = match errors.first() {
+                Some(e) if e.contains("undefined symbol") || e.contains("cannot open shared object") => {
+                    t!("plugin-active-missing-dependency", error = e)
+                }
+                Some(e) => t!("plugin-active-load-error", error = e),
+                None if !is_compatible(&self.runtime_version, &receipt.version) => {
+                    t!("plugin-active-not-loaded-skew", version = receipt.version)
+                }
+                None => t!(
+                    "plugin-active-not-searched",
+                    path = receipt.files.first().and_then(|f| f.parent()).unwrap_or(install_path).display()
+                ),
+            };
+            rows.push(ActivePlugin {
+                name: name.clone(),
+                version: None,
+                status,
+            });
+        }
+        Ok(rows)
+    }
+
+    /// Dependency tree of the installed plugin `plugin_name`: each installed
+    /// library with the shared libraries it links against, resolved with the
+    /// runtime's library directory on the loader path.
+    pub fn deps_tree(&self, install_path: &Path, plugin_name: &str) -> Result<DepNode> {
+        let receipts = PluginReceipts::load(&self.receipts_path())?;
+        let name = receipts
+            .find(plugin_name)
+            .with_context(|| t!("plugin-not-installed", name = plugin_name))?;
+        let receipt = &receipts.plugins[&name];
+        let mut root = DepNode::new(&name, "plugin", true).with_detail(&receipt.version);
+
+        let env = crate::exec::managed_env(install_path, &self.platform.os, true)?;
+        for file in receipt.files.iter().filter(|f| is_library(f)) {
+            let label = file.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if !file.exists() {
+                root.children.push(DepNode::new(label, "file", false).with_detail("missing"));
+                continue;
+            }
+            let mut node = DepNode::new(label, "file", true);
+            node.children = deps::shared_libraries(file, &self.platform.os, &env, &install_path.join("lib"))
+                .unwrap_or_default();
+            root.children.push(node);
+        }
+        Ok(root)
+    }
+
+    /// Run `wasmedge --version` from `install_path`, pointing it at the managed
+    /// plugin directory when `managed_plugins` is set. `None` when there is no
+    /// runtime to ask.
+    fn load_report(&self, install_path: &Path, managed_plugins: bool) -> Option<LoadReport> {
+        let wasmedge = install_path.join("bin").join(shadow::executable_name());
+        let env = crate::exec::managed_env(install_path, &self.platform.os, managed_plugins).ok()?;
+        match std::process::Command::new(&wasmedge).arg("--version").envs(env).output() {
+            Ok(output) => Some(LoadReport::parse(
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            )),
+            Err(e) => {
+                warn!("{}", t!("plugin-verify-no-runtime", path = wasmedge.display(), error = e));
+                None
+            }
+        }
+    }
+}
+
+/// Resolve `file`, a bare file name in `plugin_dir` or a path, to a file
+/// inside the plugin directory. Only the parent is canonicalized, so a
+/// symlink is itself removed and may point anywhere, but neither `..` nor a
+/// symlinked directory can reach outside.
+fn resolve_plugin_file(plugin_dir: &Path, file: &str) -> Result<PathBuf> {
+    let given = Path::new(file);
+    let candidate = if given.components().count() == 1 { plugin_dir.join(given) } else { given.to_path_buf() };
+    let missing = || t!("plugin-file-missing", path = candidate.display());
+    let root = plugin_dir.canonicalize().with_context(missing)?;
+    let parent = match candidate.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => anyhow::bail!(missing()),
+    };
+    let name = candidate.file_name().filter(|n| *n != "..").with_context(missing)?;
+    let path = parent.canonicalize().with_context(missing)?.join(name);
+    if !path.starts_with(&root) || path == root {
+        anyhow::bail!(t!("plugin-file-outside", path = candidate.display(), dir = plugin_dir.display()));
+    }
+    let metadata = path.symlink_metadata().with_context(missing)?;
+    if metadata.is_dir() {
+        anyhow::bail!(t!("plugin-file-directory", path = candidate.display()));
+    }
+    Ok(path)
+}
+
+/// Name an adopted library is recorded under: its file name without the
+/// `lib` prefix and extension, e.g. `wasmedgePluginWasiNN`.
+fn adopted_name(path: &Path) -> String {
+    let stem = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let stem = stem.split('.').next().unwrap_or(&stem);
+    stem.strip_prefix("lib").unwrap_or(stem).to_string()
+}
+
+/// Whether the plugin published as `published` registers with the runtime
+/// as `loaded`: under the same name, or under its name without the backend
+/// suffix (`wasi_nn-ggml` loads as `wasi_nn`).
+fn loads_as(published: &str, loaded: &str) -> bool {
+    let loaded = normalize_plugin_name(loaded);
+    normalize_plugin_name(published) == loaded
+        || published.split_once('-').is_some_and(|(family, _)| normalize_plugin_name(family) == loaded)
+}
+
+/// Whether `path` is a shared library the runtime would load as a plugin.
+fn is_library(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "so" || ext == "dll" || ext == "dylib")
+}
+
+/// Directory, next to the plugin directory, holding the auxiliary files of
+/// every plugin.
+fn share_root(plugin_dir: &Path) -> PathBuf {
+    plugin_dir.with_file_name("share").join("wasmedge")
+}
+
+/// Directory holding the auxiliary files of plugin `published_name`:
+/// `share/wasmedge/<plugin>`.
+fn share_dir(plugin_dir: &Path, published_name: &str) -> PathBuf {
+    share_root(plugin_dir).join(published_name)
+}
+
+/// Decide where each file of an unpacked plugin archive goes. Libraries are
+/// flattened into the plugin directory, where the runtime looks for them.
+/// Auxiliary files (models, configs, READMEs) go below the plugin's
+/// [`share_dir`], keeping their position relative to the libraries, or to
+/// the archive root when they live elsewhere.
+fn plugin_layout(files: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let library_root = files
+        .iter()
+        .find(|f| is_library(f))
+        .and_then(|f| f.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    // Archives usually wrap everything in one top-level directory
+    let first_components: Vec<_> = files.iter().filter_map(|f| f.components().next()).collect();
+    let archive_root = match first_components.first() {
+        Some(first)
+            if first_components.iter().all(|c| c == first)
+                && files.iter().all(|f| f.components().count() > 1) =>
+        {
+            PathBuf::from(first.as_os_str())
+        }
+        _ => PathBuf::new(),
+    };
+
+    files
+        .iter()
+        .map(|file| {
+            let dest = if is_library(file) {
+                PathBuf::from(file.file_name().unwrap_or_default())
+            } else if let Ok(rest) = file.strip_prefix(&library_root) {
+                rest.to_path_buf()
+            } else {
+                file.strip_prefix(&archive_root).unwrap_or(file).to_path_buf()
+            };
+            (file.clone(), dest)
+        })
+        .collect()
+}
+
+/// Reduce a plugin or library name to a comparable key, e.g. both
+/// `libwasmedgePluginWasiNN.so` and `wasi-nn-ggml` start with `wasinn`.
+fn library_key(name: &str) -> String {
+    let stem = name.split('.').next().unwrap_or(name).to_lowercase();
+    let stem = stem.strip_prefix("lib").unwrap_or(&stem);
+    let key: String = stem.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    let key = key.strip_prefix("wasmedgeplugin").unwrap_or(&key);
+    key.strip_prefix("wasmedge").unwrap_or(key).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_key_matches_published_names() {
+        assert_ne!(library_key("wasi-nn-ggml"), library_key("libwasmedgePluginWasiNN.so"));
+        assert_eq!(library_key("wasi_logging"), library_key("libwasmedgePluginWasiLogging.dylib"));
+        assert_eq!(library_key("wasmedge_rustls"), library_key("libwasmedge_rustls.so"));
+        assert_eq!(library_key("wasmedge-image"), library_key("libwasmedgePluginWasmEdgeImage.so"));
+        assert_eq!(normalize_plugin_name("wasi-nn-ggml"), normalize_plugin_name("wasi_nn-ggml"));
+    }
+
+    #[test]
+    fn test_remove_keeps_libraries_other_plugins_use() {
+        let prefix = tempfile::tempdir().unwrap();
+        let plugin_dir = prefix.path().join("plugin");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        let shared = plugin_dir.join("libwasmedgePluginWasiNN.so");
+        let logging = plugin_dir.join("libwasmedgePluginWasiLogging.so");
+        let stray = plugin_dir.join("libwasmedgePluginWasiCrypto.so");
+        for file in [&shared, &logging, &stray] {
+            std::fs::write(file, "").unwrap();
+        }
+        let receipts = serde_json::json!({ "plugins": {
+            "wasi_nn-ggml": { "version": "0.14.1", "platform": "manylinux2014_x86_64", "files": [shared] },
+            "wasi_nn-openvino": { "version": "0.14.1", "platform": "manylinux2014_x86_64", "files": [shared] },
+            "wasi_logging": { "version": "0.14.1", "platform": "manylinux2014_x86_64", "files": [logging] },
+        } });
+        std::fs::write(prefix.path().join(RECEIPTS_FILE), receipts.to_string()).unwrap();
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let manager = PluginManager::with_fetcher(prefix.path().to_path_buf(), "0.14.1".parse().unwrap(), platform, crate::downloader::fake::FakeFetcher::default());
+
+        manager.remove_plugin("wasi_nn-ggml", None).unwrap();
+        assert!(shared.exists());
+        manager.remove_plugin("wasi_nn-openvino", None).unwrap();
+        assert!(!shared.exists());
+
+        // Without a receipt, only a library of exactly that name goes
+        assert!(manager.remove_plugin("wasi_crypto-extra", None).is_err());
+        manager.remove_plugin("wasi_crypto", None).unwrap();
+        assert!(!stray.exists());
+        assert!(logging.exists());
+    }
+
+    #[test]
+    fn test_plugins_get_the_ownership_of_the_installation() {
+        let prefix = tempfile::tempdir().unwrap();
+        let plugin_dir = prefix.path().join("plugin");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join("libwasmedgePluginWasiLogging.so"), "").unwrap();
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let dry_run = Arc::new(crate::fsutil::DryRunFs::default());
+        let manager = PluginManager::with_fetcher(prefix.path().to_path_buf(), "0.14.1".parse().unwrap(), platform, crate::downloader::fake::FakeFetcher::default())
+            .with_filesystem(dry_run.clone());
+
+        // A user install records no ownership, so nothing changes
+        std::fs::write(prefix.path().join("VERSION"), "version=0.14.1\n").unwrap();
+        manager.apply_ownership().unwrap();
+        assert!(dry_run.operations().is_empty());
+
+        std::fs::write(prefix.path().join("VERSION"), "version=0.14.1\nowner=0\ngroup=0\nmode=755\n").unwrap();
+        manager.apply_ownership().unwrap();
+        let operations = dry_run.operations();
+        assert!(operations.contains(&format!("set owner of {} to 0:0", plugin_dir.display())));
+        assert!(operations.contains(&format!("set mode of {} to 644", plugin_dir.join("libwasmedgePluginWasiLogging.so").display())));
+    }
+
+    #[tokio::test]
+    async fn test_failed_reinstall_keeps_the_old_build() {
+        let asset = "WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz";
+        let release = serde_json::json!({ "tag_name": "0.14.1", "assets": [{
+            "name": asset,
+            "browser_download_url": format!("https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/{}", asset),
+            "size": 1000,
+        }] });
+        // The archive itself is missing, so the download fails
+        let fetcher = crate::downloader::fake::FakeFetcher::default()
+            .with("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1", release.to_string());
+        let prefix = tempfile::tempdir().unwrap();
+        let plugin_dir = prefix.path().join("plugin");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        let library = plugin_dir.join("libwasmedgePluginWasiLogging.so");
+        std::fs::write(&library, "0.13.5 build").unwrap();
+        let receipts = serde_json::json!({ "plugins": { "wasi_logging": {
+            "version": "0.13.5",
+            "platform": "manylinux2014_x86_64",
+            "files": [library],
+        } } });
+        std::fs::write(prefix.path().join(RECEIPTS_FILE), receipts.to_string()).unwrap();
+
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let manager = PluginManager::with_fetcher(prefix.path().to_path_buf(), "0.14.1".parse().unwrap(), platform, fetcher);
+        let outcome = manager.reinstall_all().await.unwrap();
+        assert_eq!(outcome.failed, ["wasi_logging"]);
+        assert_eq!(std::fs::read_to_string(&library).unwrap(), "0.13.5 build");
+        let receipts = PluginReceipts::load(&manager.receipts_path()).unwrap();
+        assert_eq!(receipts.plugins["wasi_logging"].version, "0.13.5");
+        let leftovers = std::fs::read_dir(prefix.path()).unwrap().filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().starts_with(cache::TEMP_DIR_PREFIX)).count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test]
+    async fn test_catalog_lists_the_plugin_archives_of_the_release_with_digests() {
+        let download = |name: &str| format!("https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/{}", name);
+        let assets: Vec<serde_json::Value> = [
+            "WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz",
+            "WasmEdge-plugin-wasi_nn-ggml-0.14.1-darwin_arm64.tar.gz",
+            "WasmEdge-plugin-wasi_crypto-0.14.0-manylinux2014_x86_64.tar.gz",
+            "WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz",
+            "SHA256SUM",
+        ]
+        .iter()
+        .map(|name| serde_json::json!({ "name": name, "browser_download_url": download(name), "size": 1000 }))
+        .collect();
+        let fetcher = crate::downloader::fake::FakeFetcher::default()
+            .with("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1", serde_json::json!({ "tag_name": "0.14.1", "assets": assets }).to_string())
+            .with(&download("SHA256SUM"), "abc123  WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz\n");
+        let prefix = tempfile::tempdir().unwrap();
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let manager = PluginManager::with_fetcher(prefix.path().to_path_buf(), "0.14.1".parse().unwrap(), platform, fetcher);
+
+        let catalog = manager.catalog().await.unwrap();
+        let listed: Vec<(&str, &str, Option<&str>)> = catalog.iter().map(|e| (e.name.as_str(), e.platform.as_str(), e.sha256.as_deref())).collect();
+        assert_eq!(listed, [("wasi_logging", "manylinux2014_x86_64", Some("abc123")), ("wasi_nn-ggml", "darwin_arm64", None)]);
+        assert_eq!(catalog[0].url, download("WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz"));
+        assert_eq!((catalog[0].version.as_str(), catalog[0].size), ("0.14.1", 1000));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_names_the_archive_instead_of_downloading_it() {
+        let asset = "WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz";
+        let url = format!("https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/{}", asset);
+        let release = serde_json::json!({ "tag_name": "0.14.1", "assets": [{ "name": asset, "browser_download_url": url, "size": 1000 }] });
+        // Only the release is served, so a download would fail the install
+        let fetcher = crate::downloader::fake::FakeFetcher::default()
+            .with("https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1", release.to_string());
+        let prefix = tempfile::tempdir().unwrap();
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let dry_run = Arc::new(crate::fsutil::DryRunFs::default());
+        let manager = PluginManager::with_fetcher(prefix.path().to_path_buf(), "0.14.1".parse().unwrap(), platform, fetcher)
+            .with_filesystem(dry_run.clone());
+
+        manager.install_plugin("wasi_logging", None).await.unwrap();
+        let plugin_dir = prefix.path().join("plugin");
+        assert!(dry_run.operations().contains(&format!("download {} and install plugin wasi_logging into {}", url, plugin_dir.display())));
+        assert!(!plugin_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_records_a_source_build_instead_of_running_it() {
+        let prefix = tempfile::tempdir().unwrap();
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let dry_run = Arc::new(crate::fsutil::DryRunFs::default());
+        let manager = PluginManager::with_fetcher(prefix.path().to_path_buf(), "0.14.1".parse().unwrap(), platform, crate::downloader::fake::FakeFetcher::default())
+            .with_filesystem(dry_run.clone());
+
+        manager.build_plugin("wasi-logging", SourceBuild::new("0.13.5")).await.unwrap();
+        let operations = dry_run.operations();
+        assert_eq!(operations.len(), 1);
+        assert!(operations[0].starts_with("build plugin wasi_logging from WasmEdge 0.13.5 into "));
+        assert!(!prefix.path().join("plugin").exists());
+
+        // Receipts name what was built, not the runtime it is built for
+        assert_eq!(built_version(&SourceBuild::new("0.13.5")), "0.13.5");
+        assert_eq!(built_version(&SourceBuild::new("master")), "master");
+    }
+
+    #[test]
+    fn test_plugins_built_with_the_runtime_get_receipts() {
+        let prefix = tempfile::tempdir().unwrap();
+        let built = tempfile::tempdir().unwrap();
+        std::fs::write(built.path().join("libwasmedgePluginWasiNN.so"), "nn").unwrap();
+        std::fs::write(built.path().join("libwasmedgePluginWasiLogging.so"), "logging").unwrap();
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let build = SourceBuild::new("0.14.1")
+            .with_plugins(vec!["wasi_nn-ggml".into(), "wasi-logging".into()])
+            .with_features(vec!["cuda".into()]);
+
+        install_built_plugins(&RealFs, prefix.path(), &platform, &build, built.path(), "abc123").unwrap();
+        let plugin_dir = prefix.path().join("plugin");
+        assert_eq!(std::fs::read_to_string(plugin_dir.join("libwasmedgePluginWasiNN.so")).unwrap(), "nn");
+        let receipts = installed_plugins(prefix.path()).unwrap();
+        let nn = &receipts.plugins["wasi_nn-ggml"];
+        assert_eq!(nn.files, [plugin_dir.join("libwasmedgePluginWasiNN.so")]);
+        assert_eq!(nn.version, "0.14.1");
+        assert_eq!(nn.source.as_deref(), Some("0.14.1@abc123+cuda"));
+        assert_eq!(receipts.plugins["wasi_logging"].files, [plugin_dir.join("libwasmedgePluginWasiLogging.so")]);
+
+        // A requested plugin the build left no library for is an error
+        let build = SourceBuild::new("0.14.1").with_plugins(vec!["wasi_crypto".into()]);
+        assert!(install_built_plugins(&RealFs, prefix.path(), &platform, &build, built.path(), "abc123").is_err());
+    }
+
+    #[test]
+    fn test_pins_match_loosely_and_older_receipts_have_none() {
+        let receipts: PluginReceipts = serde_json::from_str(r#"{"plugins": {}}"#).unwrap();
+        assert!(receipts.pins.is_empty());
+        assert!(!serde_json::to_string(&receipts).unwrap().contains("pins"));
+
+        let receipts: PluginReceipts = serde_json::from_str(r#"{"plugins": {}, "pins": {"wasi_nn_ggml": "0.14.0"}}"#).unwrap();
+        assert_eq!(receipts.pinned("wasi-nn-ggml").map(String::as_str), Some("0.14.0"));
+        assert_eq!(receipts.pinned("wasi_crypto"), None);
+    }
+
+    #[test]
+    fn test_load_report_lists_loaded_plugins_and_errors() {
+        let report = LoadReport::parse(
+            "wasmedge version 0.14.1\n (plugin \"wasi_logging\") version 0.1.0.0\n",
+            "[2024-10-01 12:00:00.000] [error] loading failed: undefined symbol, path:libwasmedgePluginWasiNN.so\n",
+        );
+        assert_eq!(report.loaded, vec![LoadedPlugin { name: "wasi_logging".to_string(), version: "0.1.0.0".to_string() }]);
+        assert!(report.has_loaded("wasi_logging"));
+        assert!(!report.has_loaded("wasi_logging_extra"));
+        assert!(loads_as("wasi_nn-ggml", "wasi_nn") && !loads_as("wasi_nn-ggml", "wasi"));
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("libwasmedgePluginWasiNN.so"));
+    }
+
+    #[test]
+    fn test_plugin_layout_keeps_auxiliary_files_next_to_libraries() {
+        let files: Vec<PathBuf> = [
+            "WasmEdge-plugin-wasi_nn/lib/libwasmedgePluginWasiNN.so",
+            "WasmEdge-plugin-wasi_nn/lib/models/config.json",
+            "WasmEdge-plugin-wasi_nn/share/vocab.txt",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        let dests: Vec<PathBuf> = plugin_layout(&files).into_iter().map(|(_, dest)| dest).collect();
+        assert_eq!(
+            dests,
+            vec![
+                PathBuf::from("libwasmedgePluginWasiNN.so"),
+                PathBuf::from("models/config.json"),
+                PathBuf::from("share/vocab.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auxiliary_files_go_to_a_directory_per_plugin() {
+        let scratch = tempfile::tempdir().unwrap();
+        let prefix = scratch.path().join("wasmedge");
+        let plugin_dir = prefix.join("plugin");
+        let platform = Platform::new(crate::platform::OS::Linux(crate::platform::LinuxDistro::Generic), crate::platform::Architecture::X86_64);
+        let manager = PluginManager::with_fetcher(prefix.clone(), "0.14.1".parse().unwrap(), platform, crate::downloader::fake::FakeFetcher::default());
+        for (name, library) in [("wasi_logging", "libwasmedgePluginWasiLogging.so"), ("wasi_crypto", "libwasmedgePluginWasiCrypto.so")] {
+            let unpacked = scratch.path().join(name);
+            std::fs::create_dir_all(unpacked.join("lib")).unwrap();
+            std::fs::write(unpacked.join("lib").join(library), "").unwrap();
+            std::fs::write(unpacked.join("README.md"), name).unwrap();
+            let files = manager.place_files(&unpacked, &plugin_dir, name).unwrap();
+            assert!(files.iter().any(|(file, _)| *file == plugin_dir.join(library)));
+        }
+        for name in ["wasi_logging", "wasi_crypto"] {
+            let readme = prefix.join("share").join("wasmedge").join(name).join("README.md");
+            assert_eq!(std::fs::read_to_string(readme).unwrap(), name);
+        }
+        assert!(!plugin_dir.join("README.md").exists());
+    }
+
+    #[test]
+    fn test_plugin_files_resolve_only_inside_the_plugin_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let plugin_dir = home.path().join("plugin");
+        std::fs::create_dir_all(plugin_dir.join("models")).unwrap();
+        std::fs::write(plugin_dir.join("libwasmedgePluginWasiNN.so"), "").unwrap();
+        std::fs::write(home.path().join("libwasmedge.so"), "").unwrap();
+        let root = plugin_dir.canonicalize().unwrap();
+
+        let by_name = resolve_plugin_file(&plugin_dir, "libwasmedgePluginWasiNN.so").unwrap();
+        assert_eq!(by_name, root.join("libwasmedgePluginWasiNN.so"));
+        let by_path = plugin_dir.join("models/../libwasmedgePluginWasiNN.so");
+        assert_eq!(resolve_plugin_file(&plugin_dir, &by_path.to_string_lossy()).unwrap(), by_name);
+
+        let escape = plugin_dir.join("../libwasmedge.so");
+        let outside = resolve_plugin_file(&plugin_dir, &escape.to_string_lossy()).unwrap_err();
+        assert!(outside.to_string().contains("outside the plugin directory"), "{}", outside);
+        assert!(resolve_plugin_file(&plugin_dir, "models").is_err());
+        assert!(resolve_plugin_file(&plugin_dir, "..").is_err());
+        assert!(resolve_plugin_file(&plugin_dir, "libwasmedgePluginWasiCrypto.so").is_err());
+    }
+
+    #[test]
+    fn test_orphans_are_libraries_no_receipt_records() {
+        let home = tempfile::tempdir().unwrap();
+        let plugin_dir = home.path().join("plugin");
+        std::fs::create_dir_all(plugin_dir.join("models")).unwrap();
+        for file in ["libwasmedgePluginWasiNN.so", "libwasmedgePluginWasiLogging.so", "models/config.json"] {
+            std::fs::write(plugin_dir.join(file), "").unwrap();
+        }
+        let mut receipts = PluginReceipts::default();
+        receipts.plugins.insert("wasi_nn-ggml".to_string(), PluginReceipt {
+            version: "0.14.1".to_string(),
+            platform: "ubuntu20.04_x86_64".to_string(),
+            files: vec![plugin_dir.join("libwasmedgePluginWasiNN.so"), plugin_dir.join("models/config.json")],
+            sha256: BTreeMap::new(),
+            source: None,
+            licenses: Vec::new(),
+        });
+
+        assert_eq!(receipts.orphans(&plugin_dir), vec![plugin_dir.join("libwasmedgePluginWasiLogging.so")]);
+        assert_eq!(adopted_name(&plugin_dir.join("libwasmedgePluginWasiLogging.so")), "wasmedgePluginWasiLogging");
+        assert!(PluginReceipts::default().orphans(&home.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_plugin_versions_compatible_within_minor_release() {
+        let runtime: WasmEdgeVersion = "0.14.1".parse().unwrap();
+        assert!(is_compatible(&runtime, "0.14.0"));
+        assert!(is_compatible(&runtime, "v0.14.1"));
+        assert!(!is_compatible(&runtime, "0.13.5"));
+        assert!(!is_compatible(&runtime, "nightly"));
+    }
+}
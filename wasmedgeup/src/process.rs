@@ -0,0 +1,81 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// A running process whose executable is a file from a managed installation,
+/// such as `wasmedge` or a host linked against `libwasmedge` shipped with it.
+#[derive(Debug, Clone)]
+pub struct RunningProcess {
+    pub pid: u32,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for RunningProcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pid {} ({})", self.pid, self.path.display())
+    }
+}
+
+/// Find processes running an executable under `prefix`. Detection is best
+/// effort: processes we are not permitted to inspect are skipped, and
+/// programs outside the prefix that only load its libraries are not seen.
+pub fn find_using(prefix: &Path) -> Vec<RunningProcess> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing().with_exe(UpdateKind::Always));
+    let processes = system.processes().iter().filter_map(|(pid, process)| {
+        Some(RunningProcess { pid: pid.as_u32(), path: process.exe()?.to_path_buf() })
+    });
+    under(prefix, processes, std::process::id())
+}
+
+/// The `processes` other than `own_pid` running from under `prefix`, by pid.
+fn under(prefix: &Path, processes: impl IntoIterator<Item = RunningProcess>, own_pid: u32) -> Vec<RunningProcess> {
+    let prefix = prefix.canonicalize().unwrap_or_else(|_| prefix.to_path_buf());
+    let mut found: Vec<RunningProcess> = processes
+        .into_iter()
+        .filter(|p| p.pid != own_pid && p.path.starts_with(&prefix))
+        .collect();
+    found.sort_by_key(|p| p.pid);
+    found.dedup_by_key(|p| p.pid);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_other_processes_under_the_prefix_count() {
+        let process = |pid, path: &str| RunningProcess { pid, path: PathBuf::from(path) };
+        let found = under(
+            Path::new("/opt/wasmedge"),
+            [
+                process(7, "/opt/wasmedge/bin/wasmedge"),
+                process(3, "/opt/wasmedge/bin/wasmedgec"),
+                process(7, "/opt/wasmedge/bin/wasmedge"),
+                process(1, "/opt/wasmedge"),
+                process(5, "/opt/wasmedge-old/bin/wasmedge"),
+                process(9, "/opt/wasmedge/bin/wasmedge"),
+            ],
+            9,
+        );
+        assert_eq!(found.iter().map(|p| p.pid).collect::<Vec<_>>(), [1, 3, 7]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_a_running_executable_from_the_prefix_is_found() {
+        let prefix = tempfile::tempdir().unwrap();
+        let bin = prefix.path().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let sleep = bin.join("sleep");
+        std::fs::copy("/bin/sleep", &sleep).unwrap();
+
+        let mut child = std::process::Command::new(&sleep).arg("30").spawn().unwrap();
+        let found = find_using(prefix.path());
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(found.iter().any(|p| p.pid == child.id()), "{:?}", found);
+        assert!(find_using(&bin.join("missing")).is_empty());
+    }
+}
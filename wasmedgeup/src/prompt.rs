@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, IsTerminal, Write};
+
+/// Whether we can ask the user questions.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+fn read_answer(question: &str) -> Result<String> {
+    let mut stderr = std::io::stderr();
+    write!(stderr, "{} ", question)?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    // Hand the worker's other tasks off while waiting, so Ctrl-C is still
+    // noticed by the interrupt handler
+    tokio::task::block_in_place(|| std::io::stdin().lock().read_line(&mut answer))
+        .context("Failed to read answer")?;
+    Ok(answer.trim().to_string())
+}
+
+/// Ask a yes/no question; an empty answer picks `default`.
+pub fn confirm(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        let answer = read_answer(&format!("{} {}", question, hint))?;
+        match answer.to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => continue,
+        }
+    }
+}
+
+/// Let the user pick any number of `items` by number. Returns the chosen
+/// indices in the order listed; an empty answer selects nothing.
+pub fn select_many(title: &str, items: &[String]) -> Result<Vec<usize>> {
+    eprintln!("{}", title);
+    for (i, item) in items.iter().enumerate() {
+        eprintln!("  {:>3}) {}", i + 1, item);
+    }
+
+    loop {
+        let answer = read_answer(&t!("prompt-select-many"))?;
+        if answer.eq_ignore_ascii_case("all") {
+            return Ok((0..items.len()).collect());
+        }
+        match parse_selection(&answer, items.len()) {
+            Some(selection) => return Ok(selection),
+            None => eprintln!("{}", t!("prompt-invalid-selection", max = items.len())),
+        }
+    }
+}
+
+/// Parse answers like `1, 3 4-6` into zero-based indices.
+fn parse_selection(answer: &str, len: usize) -> Option<Vec<usize>> {
+    let mut selection = Vec::new();
+    for part in answer.split([',', ' ']).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((a, b)) => (a.trim().parse::<usize>().ok()?, b.trim().parse::<usize>().ok()?),
+            None => {
+                let n = part.parse::<usize>().ok()?;
+                (n, n)
+            }
+        };
+        if start == 0 || end > len || start > end {
+            return None;
+        }
+        selection.extend((start - 1)..end);
+    }
+    selection.sort_unstable();
+    selection.dedup();
+    Some(selection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1, 3 4-5", 5), Some(vec![0, 2, 3, 4]));
+        assert_eq!(parse_selection("", 5), Some(vec![]));
+        assert_eq!(parse_selection("6", 5), None);
+        assert_eq!(parse_selection("0", 5), None);
+    }
+}
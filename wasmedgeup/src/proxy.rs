@@ -0,0 +1,57 @@
+//! Proxy selection. reqwest already honours the `*_PROXY` variables, with
+//! `NO_PROXY`, and when none is set the static proxy of the Windows registry
+//! or macOS system settings. `--proxy` takes precedence over all of them.
+//! Which proxy a run goes through is logged.
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+
+/// Variables reqwest reads a proxy from, in the order it prefers them.
+const PROXY_ENV: &[&str] = &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy", "HTTP_PROXY", "http_proxy"];
+
+/// How the HTTP client picks its proxy.
+#[derive(Debug, Clone, Default)]
+pub enum ProxyConfig {
+    /// reqwest's own lookup: `*_PROXY` variables, then the system proxy
+    #[default]
+    Auto,
+    /// Send every request through this proxy, except hosts `NO_PROXY` lists
+    Explicit(Proxy),
+}
+
+impl ProxyConfig {
+    /// Decide on the proxy configuration: `proxy` if given, otherwise the
+    /// proxy variables, then the proxy of the system.
+    pub fn resolve(proxy: Option<&str>) -> Result<Self> {
+        if let Some(url) = proxy {
+            let proxy = Proxy::all(url).with_context(|| format!("Invalid proxy URL {}", url))?;
+            info!("Using proxy {}", url);
+            return Ok(Self::Explicit(proxy.no_proxy(NoProxy::from_env())));
+        }
+        match PROXY_ENV.iter().find_map(|name| Some((name, std::env::var(name).ok()?))) {
+            Some((name, value)) => info!("Using proxy {} from {}", value, name),
+            None => debug!("No proxy variable set; using the system proxy, if any"),
+        }
+        Ok(Self::Auto)
+    }
+
+    /// Apply to the client being built.
+    pub fn apply(&self, builder: ClientBuilder) -> ClientBuilder {
+        match self {
+            Self::Auto => builder,
+            Self::Explicit(proxy) => builder.no_proxy().proxy(proxy.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_proxy_must_be_a_url() {
+        assert!(matches!(ProxyConfig::resolve(Some("http://proxy.example.com:3128")), Ok(ProxyConfig::Explicit(_))));
+        assert!(ProxyConfig::resolve(Some("not a url")).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::{cache, checksum::ChecksumFile, complete, downloader::Fetcher, version::WasmEdgeVersion};
+
+const RELEASES_API: &str = "https://api.github.com/repos/WasmEdge/WasmEdge/releases";
+/// Releases per page of the releases API, the most it allows.
+const RELEASES_PER_PAGE: usize = 100;
+/// Pages of releases fetched at most, a bound in case the API keeps
+/// linking to another page.
+const MAX_RELEASE_PAGES: usize = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    #[serde(default)]
+    pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// Support status of each release line, from the manifest `--support-manifest`
+/// points to. Upstream publishes none, so without one nothing is known.
+#[derive(Debug, Default, Deserialize)]
+pub struct VersionManifest {
+    pub maintained: Vec<String>,
+    pub deprecated: Vec<String>,
+}
+
+/// Whether a release line is still supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SupportStatus {
+    Maintained,
+    Deprecated,
+    /// Older than every maintained line, without being listed
+    Unmaintained,
+}
+
+impl SupportStatus {
+    /// Whether the line no longer gets fixes.
+    pub fn is_eol(self) -> bool {
+        matches!(self, SupportStatus::Deprecated | SupportStatus::Unmaintained)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SupportStatus::Maintained => "maintained",
+            SupportStatus::Deprecated => "deprecated",
+            SupportStatus::Unmaintained => "unmaintained",
+        }
+    }
+}
+
+impl fmt::Display for SupportStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl VersionManifest {
+    /// Support status of `version`, matched either exactly or by release
+    /// line (an entry `0.13` covers `0.13.5`).
+    pub fn status(&self, version: &str) -> Option<SupportStatus> {
+        let matches = |entry: &String| {
+            version == entry || version.starts_with(&format!("{}.", entry))
+        };
+        if self.deprecated.iter().any(matches) {
+            Some(SupportStatus::Deprecated)
+        } else if self.maintained.iter().any(matches) {
+            Some(SupportStatus::Maintained)
+        } else {
+            None
+        }
+    }
+}
+
+impl Release {
+    /// Names of the runtime (non-plugin) archives published for `version`.
+    pub fn runtime_asset_names(&self, version: &str) -> Vec<&str> {
+        let prefix = format!("WasmEdge-{}-", version);
+        self.assets
+            .iter()
+            .map(|a| a.name.as_str())
+            .filter(|name| name.starts_with(&prefix) && !name.ends_with("-src.tar.gz"))
+            .collect()
+    }
+
+    /// Find a runtime component archive such as `debugsymbols` for the given
+    /// platform key (e.g. `manylinux2014_x86_64`).
+    pub fn find_component_asset(&self, version: &str, component: &str, platform_key: &str) -> Option<&ReleaseAsset> {
+        let prefix = format!("WasmEdge-{}-{}", version, component);
+        self.assets
+            .iter()
+            .find(|a| a.name.starts_with(&prefix) && a.name.contains(platform_key))
+    }
+}
+
+/// Name of the digest list upstream attaches to each release.
+const CHECKSUM_ASSET: &str = "SHA256SUM";
+
+/// Fetch the digests published alongside `release`, if it has any.
+pub async fn fetch_release_checksums(downloader: &impl Fetcher, release: &Release) -> Result<Option<ChecksumFile>> {
+    let Some(asset) = release.assets.iter().find(|a| a.name == CHECKSUM_ASSET) else {
+        return Ok(None);
+    };
+    let content = downloader.download_text(&asset.browser_download_url).await?;
+    ChecksumFile::parse(&content, Path::new(&asset.browser_download_url)).map(Some)
+}
+
+/// Fetch every release, most recently published first, following the
+/// `rel="next"` links of the releases API to the last page.
+pub async fn fetch_releases(downloader: &impl Fetcher) -> Result<Vec<Release>> {
+    let mut releases = Vec::new();
+    let mut url = Some(format!("{}?per_page={}", RELEASES_API, RELEASES_PER_PAGE));
+    for _ in 0..MAX_RELEASE_PAGES {
+        let Some(page_url) = url.take() else {
+            break;
+        };
+        info!("Fetching releases from: {}", page_url);
+        let page = downloader.download_page(&page_url).await?;
+        let batch: Vec<Release> = serde_json::from_str(&page.body)
+            .with_context(|| format!("Invalid JSON from {}", page_url))?;
+        releases.extend(batch);
+        url = page.next;
+    }
+    if url.is_some() {
+        warn!("{}", t!("releases-truncated", pages = MAX_RELEASE_PAGES, count = releases.len()));
+    }
+    complete::remember_versions(releases.iter().map(|r| r.tag_name.as_str()));
+    Ok(releases)
+}
+
+/// Newest stable release by version precedence, the one `latest` means.
+/// GitHub's own "latest" is by publication date, which can be a patch
+/// release of an older line.
+pub async fn latest_version(downloader: &impl Fetcher) -> Result<WasmEdgeVersion> {
+    newest(&fetch_releases(downloader).await?, false).context(t!("latest-not-found"))
+}
+
+/// Newest release by version precedence including pre-releases, the one
+/// `latest` means with `--pre`.
+pub async fn latest_prerelease(downloader: &impl Fetcher) -> Result<WasmEdgeVersion> {
+    newest(&fetch_releases(downloader).await?, true).context(t!("latest-not-found"))
+}
+
+/// [`latest_version`] among the releases fetched last, without going to the
+/// network. `None` when none were fetched yet.
+pub fn cached_latest_version() -> Option<WasmEdgeVersion> {
+    let url = format!("{}?per_page={}", RELEASES_API, RELEASES_PER_PAGE);
+    let (releases, _) = cache::cached_metadata(&url)?;
+    newest(&serde_json::from_str::<Vec<Release>>(&releases).ok()?, false)
+}
+
+fn newest(releases: &[Release], include_prerelease: bool) -> Option<WasmEdgeVersion> {
+    releases
+        .iter()
+        .filter(|r| include_prerelease || !r.prerelease)
+        .filter_map(|r| r.tag_name.parse::<WasmEdgeVersion>().ok())
+        .filter(|v| include_prerelease || !v.is_prerelease())
+        .max()
+}
+
+/// Fetch the list of maintained and deprecated runtime versions at `url`.
+/// Without a URL nothing is listed.
+pub async fn fetch_version_manifest(downloader: &impl Fetcher, url: Option<&str>) -> Result<VersionManifest> {
+    let Some(url) = url else {
+        return Ok(VersionManifest::default());
+    };
+    info!("Fetching version manifest from: {}", url);
+    downloader.download_json(url).await
+}
+
+/// Fetch the release tagged `version` from the GitHub releases API.
+pub async fn fetch_release(downloader: &impl Fetcher, version: &WasmEdgeVersion) -> Result<Release> {
+    let url = format!("{}/tags/{}", RELEASES_API, version);
+    info!("Fetching release information from: {}", url);
+    downloader.download_json(&url).await
+}
+
+/// The release tagged `version` as last fetched by [`fetch_release`], if
+/// it ever was.
+pub fn cached_release(version: &WasmEdgeVersion) -> Option<Release> {
+    let (release, _) = cache::cached_metadata(&format!("{}/tags/{}", RELEASES_API, version))?;
+    serde_json::from_str(&release).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::fake::FakeFetcher;
+
+    #[tokio::test]
+    async fn test_fetch_release_checksums_reads_sha256sum_asset() {
+        let fetcher = FakeFetcher::default()
+            .with(
+                &format!("{}/tags/0.14.1", RELEASES_API),
+                r#"{"tag_name": "0.14.1", "assets": [
+                    {"name": "SHA256SUM", "browser_download_url": "https://example.com/SHA256SUM"}
+                ]}"#,
+            )
+            .with("https://example.com/SHA256SUM", "abc123  WasmEdge-0.14.1-darwin_arm64.tar.gz\n");
+
+        let release = fetch_release(&fetcher, &"0.14.1".parse().unwrap()).await.unwrap();
+        let checksums = fetch_release_checksums(&fetcher, &release).await.unwrap().unwrap();
+        assert_eq!(checksums.get("WasmEdge-0.14.1-darwin_arm64.tar.gz"), Some("abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_releases_follows_pages() {
+        let page = |versions: std::ops::Range<usize>| {
+            let releases: Vec<String> = versions.map(|patch| format!(r#"{{"tag_name": "0.9.{}", "assets": []}}"#, patch)).collect();
+            format!("[{}]", releases.join(","))
+        };
+        let first = format!("{}?per_page=100", RELEASES_API);
+        let second = "https://api.github.com/repositories/1/releases?per_page=100&page=2";
+        let fetcher = FakeFetcher::default()
+            .with(&first, page(0..100))
+            .with_next(&first, second)
+            .with(second, page(100..101));
+
+        let releases = fetch_releases(&fetcher).await.unwrap();
+        assert_eq!(releases.len(), 101);
+        assert_eq!(releases.last().unwrap().tag_name, "0.9.100");
+
+        // A full page without a next link is the last one
+        let fetcher = FakeFetcher::default().with(&first, page(0..100));
+        assert_eq!(fetch_releases(&fetcher).await.unwrap().len(), 100);
+
+        // Pages that keep linking on stop at the bound
+        let mut fetcher = FakeFetcher::default();
+        for n in 0..=MAX_RELEASE_PAGES {
+            let url = |n: usize| format!("{}&page={}", first, n);
+            fetcher = fetcher.with(&url(n), page(n..n + 1)).with_next(&url(n), &url(n + 1));
+        }
+        let fetcher = fetcher.with_next(&first, &format!("{}&page=0", first)).with(&first, "[]");
+        assert_eq!(fetch_releases(&fetcher).await.unwrap().len(), MAX_RELEASE_PAGES - 1);
+
+        // The newest stable version, not the last published release
+        let fetcher = FakeFetcher::default().with(
+            &format!("{}?per_page=100", RELEASES_API),
+            r#"[{"tag_name": "0.13.6", "assets": []}, {"tag_name": "0.15.0-alpha.1", "prerelease": true, "assets": []},
+                {"tag_name": "0.14.1", "assets": []}, {"tag_name": "0.14.0", "assets": []}]"#,
+        );
+        assert_eq!(latest_version(&fetcher).await.unwrap().to_string(), "0.14.1");
+        assert_eq!(latest_prerelease(&fetcher).await.unwrap().to_string(), "0.15.0-alpha.1");
+    }
+}
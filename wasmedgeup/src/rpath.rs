@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::platform::OS;
+
+/// Point the runtime search path of every installed binary at the managed
+/// `lib` directory, so `wasmedge` runs without LD_LIBRARY_PATH/DYLD_LIBRARY_PATH.
+pub fn patch(install_path: &Path, os: &OS) -> Result<()> {
+    if !patches(os) {
+        info!("Skipping RPATH patching: Windows resolves DLLs next to the executable");
+        return Ok(());
+    }
+
+    let lib_dir = install_path.join("lib");
+    let bin_dir = install_path.join("bin");
+    for path in binaries(&bin_dir)? {
+        patch_file(os, &lib_dir, &path, false)?;
+    }
+
+    info!("Binaries in {} now load libraries from {}", bin_dir.display(), lib_dir.display());
+    Ok(())
+}
+
+/// Point the runtime search path of the plugin `library` at the managed
+/// `lib` directory of `install_path`, so it finds `libwasmedge` the way the
+/// patched binaries do. Libraries shipped next to the plugin keep resolving.
+pub fn patch_plugin(library: &Path, install_path: &Path, os: &OS) -> Result<()> {
+    if patches(os) {
+        patch_file(os, &install_path.join("lib"), library, true)?;
+    }
+    Ok(())
+}
+
+fn patches(os: &OS) -> bool {
+    !matches!(os, OS::Windows)
+}
+
+/// Regular files in `bin_dir`, by name.
+fn binaries(bin_dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(bin_dir)
+        .with_context(|| format!("Failed to read {}", bin_dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()).collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// The command adding `lib_dir` to the search path of `path`. A plugin also
+/// keeps searching its own directory on Linux, where the path is replaced;
+/// install_name_tool only adds to it.
+fn command(os: &OS, lib_dir: &Path, path: &Path, plugin: bool) -> Command {
+    match os {
+        OS::Darwin => {
+            let mut cmd = Command::new("install_name_tool");
+            cmd.arg("-add_rpath").arg(lib_dir).arg(path);
+            cmd
+        }
+        _ => {
+            let mut search_path = lib_dir.as_os_str().to_os_string();
+            if plugin {
+                search_path.push(":$ORIGIN");
+            }
+            let mut cmd = Command::new("patchelf");
+            cmd.arg("--set-rpath").arg(search_path).arg(path);
+            cmd
+        }
+    }
+}
+
+fn patch_file(os: &OS, lib_dir: &Path, path: &Path, plugin: bool) -> Result<()> {
+    let mut cmd = command(os, lib_dir, path, plugin);
+    let tool = cmd.get_program().to_string_lossy().into_owned();
+    let output = cmd.output().with_context(|| {
+        format!("Failed to run {}. Install it or omit --patch-rpath", tool)
+    })?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // install_name_tool refuses to add an rpath that is already present
+    if !output.status.success() && !stderr.contains("would duplicate path") {
+        // Scripts in bin/ are not ELF/Mach-O files; leave them alone
+        if stderr.contains("not an ELF") || stderr.contains("is not a Mach-O file") {
+            debug!("Skipping {}: {}", path.display(), stderr.trim());
+            return Ok(());
+        }
+        anyhow::bail!("{} failed on {}: {}", tool, path.display(), stderr.trim());
+    }
+    debug!("Patched runtime search path of {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::LinuxDistro;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        std::iter::once(cmd.get_program()).chain(cmd.get_args()).map(|arg| arg.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_plugins_keep_their_own_directory_on_the_search_path() {
+        let lib = Path::new("/opt/wasmedge/lib");
+        let linux = OS::Linux(LinuxDistro::Generic);
+        assert_eq!(
+            args(&command(&linux, lib, Path::new("/opt/wasmedge/bin/wasmedge"), false)),
+            ["patchelf", "--set-rpath", "/opt/wasmedge/lib", "/opt/wasmedge/bin/wasmedge"]
+        );
+        assert_eq!(
+            args(&command(&linux, lib, Path::new("/opt/wasmedge/plugin/libwasmedgePluginWasiNN.so"), true)),
+            ["patchelf", "--set-rpath", "/opt/wasmedge/lib:$ORIGIN", "/opt/wasmedge/plugin/libwasmedgePluginWasiNN.so"]
+        );
+        assert_eq!(
+            args(&command(&OS::Darwin, lib, Path::new("/opt/wasmedge/plugin/libwasmedgePluginWasiNN.dylib"), true)),
+            ["install_name_tool", "-add_rpath", "/opt/wasmedge/lib", "/opt/wasmedge/plugin/libwasmedgePluginWasiNN.dylib"]
+        );
+    }
+
+    #[test]
+    fn test_only_files_in_bin_are_patched_and_windows_is_skipped() {
+        let prefix = tempfile::tempdir().unwrap();
+        let bin = prefix.path().join("bin");
+        std::fs::create_dir_all(bin.join("nested")).unwrap();
+        std::fs::write(bin.join("wasmedgec"), "").unwrap();
+        std::fs::write(bin.join("wasmedge"), "").unwrap();
+        assert_eq!(binaries(&bin).unwrap(), [bin.join("wasmedge"), bin.join("wasmedgec")]);
+
+        // Nothing is run, so no tool needs to be installed
+        patch(prefix.path(), &OS::Windows).unwrap();
+        patch_plugin(&prefix.path().join("plugin").join("wasmedgePluginWasiNN.dll"), prefix.path(), &OS::Windows).unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+//! SPDX software bill of materials of an installation: the runtime and each
+//! plugin as packages, with the license and notice files gathered from
+//! their archives, for legal review of images that bake WasmEdge in.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use crate::{checksum, installer, manifest::Manifest, plugin, version::WasmEdgeVersion};
+
+/// SPDX version of the documents written here.
+const SPDX_VERSION: &str = "SPDX-2.3";
+
+/// SPDX identifiers allow letters, digits, `.` and `-` only.
+fn spdx_id(kind: &str, name: &str) -> String {
+    let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' }).collect();
+    format!("SPDXRef-{}-{}", kind, name)
+}
+
+/// Everything installed at `install_path`, as an SPDX 2.3 JSON document
+/// created at `created` (RFC 3339).
+pub fn document(install_path: &Path, created: &str) -> Result<Value> {
+    let manifest = Manifest::read(install_path)?.with_context(|| t!("sbom-not-installed", path = install_path.display()))?;
+    let receipts = plugin::installed_plugins(install_path)?;
+
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+    let mut relationships = Vec::new();
+    let mut add_package = |name: &str, package: Value, licenses: Vec<(String, PathBuf)>| -> Result<()> {
+        let id = package["SPDXID"].as_str().unwrap_or_default().to_string();
+        relationships.push(json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": id,
+        }));
+        for (file_name, path) in licenses {
+            let file_id = spdx_id("File", &format!("{}-{}", name, file_name));
+            files.push(json!({
+                "fileName": format!("./{}", file_name),
+                "SPDXID": file_id,
+                "fileTypes": ["TEXT"],
+                "checksums": [{ "algorithm": "SHA256", "checksumValue": checksum::sha256_file(&path)? }],
+                "licenseConcluded": "NOASSERTION",
+                "copyrightText": "NOASSERTION",
+            }));
+            relationships.push(json!({ "spdxElementId": id, "relationshipType": "CONTAINS", "relatedSpdxElement": file_id }));
+        }
+        packages.push(package);
+        Ok(())
+    };
+
+    let runtime_url = manifest
+        .version
+        .parse::<WasmEdgeVersion>()
+        .map(|version| installer::release_download_url(&version, &manifest.archive))
+        .unwrap_or_else(|_| "NOASSERTION".to_string());
+    let mut runtime = package("wasmedge", &manifest.version, &runtime_url);
+    if let Some(sha256) = manifest.checksums.get(&manifest.archive) {
+        runtime["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": sha256 }]);
+    }
+    let licenses = manifest.licenses.iter().map(|file| (file.clone(), install_path.join(file))).collect();
+    add_package("wasmedge", runtime, licenses)?;
+
+    for (name, receipt) in &receipts.plugins {
+        let download = match &receipt.source {
+            Some(source) => format!("git+https://github.com/WasmEdge/WasmEdge@{}", source),
+            None => "NOASSERTION".to_string(),
+        };
+        let licenses = receipt
+            .licenses
+            .iter()
+            .map(|path| {
+                let relative = path.strip_prefix(install_path).unwrap_or(path);
+                (relative.to_string_lossy().replace('\\', "/"), path.clone())
+            })
+            .collect();
+        add_package(name, package(name, &receipt.version, &download), licenses)?;
+    }
+
+    // Unique per install and time, as SPDX asks of the namespace
+    let namespace = hex::encode(Sha256::digest(format!("{}\n{}", install_path.display(), created).as_bytes()));
+    Ok(json!({
+        "spdxVersion": SPDX_VERSION,
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("wasmedge-{}", manifest.version),
+        "documentNamespace": format!("https://wasmedge.org/spdxdocs/wasmedgeup/{}", namespace),
+        "creationInfo": {
+            "created": created,
+            "creators": [format!("Tool: wasmedgeup-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": packages,
+        "files": files,
+        "relationships": relationships,
+    }))
+}
+
+fn package(name: &str, version: &str, download: &str) -> Value {
+    json!({
+        "name": name,
+        "SPDXID": spdx_id("Package", name),
+        "versionInfo": version,
+        "downloadLocation": download,
+        "filesAnalyzed": false,
+        "licenseConcluded": "NOASSERTION",
+        "licenseDeclared": "NOASSERTION",
+        "copyrightText": "NOASSERTION",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_document_lists_the_runtime_plugins_and_their_licenses() {
+        let prefix = tempfile::tempdir().unwrap();
+        let prefix = prefix.path();
+        let archive = "WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz";
+        let manifest = Manifest {
+            version: "0.14.1".into(),
+            os: "linux".into(),
+            arch: "x86_64".into(),
+            archive: archive.into(),
+            components: vec!["bin".into(), "lib".into()],
+            installed_at: "2026-10-15T12:00:00Z".into(),
+            wasmedgeup_version: "0.1.0".into(),
+            checksums: BTreeMap::from([(archive.into(), "ab".repeat(32))]),
+            files: vec!["bin/wasmedge".into(), "licenses/wasmedge/LICENSE".into()],
+            licenses: vec!["licenses/wasmedge/LICENSE".into()],
+        };
+        std::fs::write(prefix.join(crate::manifest::MANIFEST_FILE), manifest.to_toml().unwrap()).unwrap();
+        std::fs::create_dir_all(prefix.join("licenses/wasmedge")).unwrap();
+        std::fs::create_dir_all(prefix.join("licenses/wasi_logging")).unwrap();
+        std::fs::write(prefix.join("licenses/wasmedge/LICENSE"), "Apache-2.0\n").unwrap();
+        let plugin_license = prefix.join("licenses/wasi_logging/NOTICE");
+        std::fs::write(&plugin_license, "notice\n").unwrap();
+        let receipts = json!({ "plugins": { "wasi_logging": {
+            "version": "0.14.1",
+            "platform": "manylinux2014_x86_64",
+            "files": [],
+            "licenses": [plugin_license],
+        } } });
+        std::fs::write(prefix.join("plugins.json"), receipts.to_string()).unwrap();
+
+        let document = document(prefix, "2026-10-15T12:00:00Z").unwrap();
+        assert_eq!(document["spdxVersion"], SPDX_VERSION);
+        let packages = document["packages"].as_array().unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0]["versionInfo"], "0.14.1");
+        assert_eq!(packages[0]["checksums"][0]["checksumValue"], "ab".repeat(32));
+        assert!(packages[0]["downloadLocation"].as_str().unwrap().ends_with(archive));
+        assert_eq!(packages[1]["SPDXID"], "SPDXRef-Package-wasi-logging");
+        let files: Vec<&str> = document["files"].as_array().unwrap().iter().map(|f| f["fileName"].as_str().unwrap()).collect();
+        assert_eq!(files, ["./licenses/wasmedge/LICENSE", "./licenses/wasi_logging/NOTICE"]);
+        let contains = document["relationships"].as_array().unwrap().iter().filter(|r| r["relationshipType"] == "CONTAINS").count();
+        assert_eq!(contains, 2);
+
+        assert!(super::document(&prefix.join("missing"), "2026-10-15T12:00:00Z").is_err());
+    }
+}
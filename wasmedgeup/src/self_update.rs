@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use ed25519_dalek::{Signature, VerifyingKey};
+use std::path::Path;
+use crate::{
+    cache,
+    downloader::Fetcher,
+    fsutil::{self, FileSystem},
+    output,
+    release::Release,
+    updates::{self, Outdated},
+    version::{self, WasmEdgeVersion},
+};
+
+pub const LATEST_RELEASE_API: &str = "https://api.github.com/repos/Ytemiloluwa/Wasmedgeup/releases/latest";
+
+/// Hex encoded Ed25519 public key release assets are signed with. Release
+/// builds pin it through `WASMEDGEUP_PUBLISHER_KEY` at compile time; a build
+/// without one cannot verify updates and needs `--allow-unsigned`.
+pub const PUBLISHER_KEY: Option<&str> = option_env!("WASMEDGEUP_PUBLISHER_KEY");
+
+/// Published name of the wasmedgeup binary for the target this build was
+/// compiled for, as the release workflow uploads it, e.g.
+/// `wasmedgeup-x86_64-unknown-linux-gnu`.
+fn binary_asset_name() -> String {
+    format!("wasmedgeup-{}", env!("WASMEDGEUP_BUILD_TARGET"))
+}
+
+/// Check `data` against a hex encoded Ed25519 `signature` made with the
+/// private half of the hex encoded `public_key`.
+pub fn verify_signature(public_key: &str, data: &[u8], signature: &str) -> Result<()> {
+    let key: [u8; 32] = hex::decode(public_key.trim())
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .context("Publisher key is not a hex encoded Ed25519 public key")?;
+    let key = VerifyingKey::from_bytes(&key).context("Publisher key is not an Ed25519 public key")?;
+    let signature: [u8; 64] = hex::decode(signature.trim())
+        .ok()
+        .and_then(|signature| signature.try_into().ok())
+        .context("Signature is not a hex encoded Ed25519 signature")?;
+    key.verify_strict(data, &Signature::from_bytes(&signature))
+        .map_err(|_| anyhow::anyhow!("Signature does not match the pinned publisher key"))
+}
+
+/// The running wasmedgeup and the latest published release, when that is
+/// newer.
+pub async fn check(fetcher: &impl Fetcher) -> Result<Option<Outdated>> {
+    let release: Release = fetcher.download_json(LATEST_RELEASE_API).await?;
+    let latest: WasmEdgeVersion = release.tag_name.parse()?;
+    Ok(updates::outdated("wasmedgeup", &env!("CARGO_PKG_VERSION").parse()?, &latest))
+}
+
+/// Replace the running executable with the latest published release. The
+/// new binary must carry a valid signature by the pinned publisher key
+/// unless `allow_unsigned` is set.
+pub async fn update(fetcher: &impl Fetcher, fs: &dyn FileSystem, allow_unsigned: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release: Release = fetcher.download_json(LATEST_RELEASE_API).await?;
+    let latest = version::normalize(&release.tag_name);
+    if latest.parse::<WasmEdgeVersion>()? <= current.parse::<WasmEdgeVersion>()? {
+        output::say(t!("self-update-current", version = current));
+        return Ok(());
+    }
+
+    let name = binary_asset_name();
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == name)
+        .with_context(|| format!("Release {} has no binary for this platform ({})", release.tag_name, name))?;
+    let signature_name = format!("{}.sig", name);
+    let signature_asset = release.assets.iter().find(|a| a.name == signature_name);
+    if fs.is_dry_run() {
+        output::say(t!("self-update-dry-run", from = current, to = latest, asset = name));
+        return Ok(());
+    }
+
+    let staging = cache::temp_dir_in(&std::env::temp_dir())?;
+    let downloaded = staging.path().join(&name);
+    fetcher.download_file(&asset.browser_download_url, &downloaded).await?;
+    let binary = std::fs::read(&downloaded)
+        .with_context(|| format!("Failed to read {}", downloaded.display()))?;
+
+    match (PUBLISHER_KEY, signature_asset) {
+        (Some(key), Some(signature_asset)) => {
+            let signature = fetcher.download_text(&signature_asset.browser_download_url).await?;
+            verify_signature(key, &binary, &signature)
+                .with_context(|| format!("Refusing to install {} {}", name, release.tag_name))?;
+            info!("Verified signature of {}", name);
+        }
+        (key, _) if allow_unsigned => {
+            let reason = if key.is_none() { "this build pins no publisher key" } else { "the release is not signed" };
+            warn!("Installing {} {} without signature verification: {}", name, release.tag_name, reason);
+        }
+        (None, _) => anyhow::bail!(t!("self-update-no-key", version = release.tag_name)),
+        (Some(_), None) => anyhow::bail!(t!("self-update-unsigned", version = release.tag_name)),
+    }
+
+    let exe = std::env::current_exe().context("Could not locate the running executable")?;
+    replace_executable(fs, &binary, &exe)?;
+    output::say(t!("self-update-success", from = current, to = latest));
+    Ok(())
+}
+
+/// Stage `binary` next to `exe`, so the final rename stays on one file
+/// system, then move it over `exe`.
+fn replace_executable(fs: &dyn FileSystem, binary: &[u8], exe: &Path) -> Result<()> {
+    // Windows leaves the previous binary behind while it is still running
+    fsutil::remove_retired_copies(fs, exe)?;
+    let mut staged_name = exe.file_name().unwrap_or_default().to_os_string();
+    staged_name.push(".update");
+    let staged = exe.with_file_name(staged_name);
+    fs.write(&staged, binary)?;
+    fs.set_executable(&staged)?;
+    fs.rename(&staged, exe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsutil::RealFs;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_signature_must_match_pinned_key() {
+        let private = SigningKey::from_bytes(&[7; 32]);
+        let public = hex::encode(private.verifying_key().as_bytes());
+        let binary = b"wasmedgeup binary";
+        let signature = hex::encode(private.sign(binary).to_bytes());
+
+        assert!(verify_signature(&public, binary, &signature).is_ok());
+        assert!(verify_signature(&public, b"tampered binary", &signature).is_err());
+        assert!(verify_signature(&public, binary, &signature[2..]).is_err());
+
+        let other = hex::encode(SigningKey::from_bytes(&[8; 32]).verifying_key().as_bytes());
+        assert!(verify_signature(&other, binary, &signature).is_err());
+    }
+
+    #[test]
+    fn test_replacing_the_executable_only_removes_its_retired_copies() {
+        assert!(binary_asset_name().starts_with("wasmedgeup-") && binary_asset_name().contains(std::env::consts::ARCH));
+
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("wasmedgeup");
+        std::fs::write(&exe, "old").unwrap();
+        let others = ["wasmedge.1234.wasmedgeup-old", "wasmedgeup.x.wasmedgeup-old", "tool/wasmedgeup.1.wasmedgeup-old"];
+        std::fs::write(dir.path().join("wasmedgeup.99.wasmedgeup-old"), "retired").unwrap();
+        std::fs::create_dir(dir.path().join("tool")).unwrap();
+        for other in others {
+            std::fs::write(dir.path().join(other), "someone else's").unwrap();
+        }
+
+        replace_executable(&RealFs, b"new", &exe).unwrap();
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "new");
+        assert!(!dir.path().join("wasmedgeup.99.wasmedgeup-old").exists());
+        assert!(others.iter().all(|other| dir.path().join(other).exists()));
+    }
+}
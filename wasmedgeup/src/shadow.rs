@@ -0,0 +1,177 @@
+//! Detection of another `wasmedge` shadowing the managed one on PATH, e.g.
+//! a distribution package or a copy left by the old install.sh.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Login shells that take longer than this to start are not waited for.
+const LOGIN_SHELL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A `wasmedge` that PATH resolves before the managed one.
+#[derive(Debug, PartialEq)]
+pub struct Shadow {
+    /// The binary that wins
+    pub binary: PathBuf,
+    /// PATH entry it was found in
+    pub entry: PathBuf,
+    /// Whether the managed `bin` directory is on PATH at all, after `entry`
+    pub managed_on_path: bool,
+}
+
+impl Shadow {
+    /// What shadows the managed install at `install_path` and how to fix it.
+    pub fn advice(&self, install_path: &Path) -> String {
+        let bin = install_path.join("bin");
+        if self.managed_on_path {
+            t!("path-shadowed", binary = self.binary.display(), entry = self.entry.display(),
+                bin = bin.display(), env = install_path.join("env").display())
+        } else {
+            t!("path-shadowed-missing", binary = self.binary.display(), entry = self.entry.display(),
+                bin = bin.display(), env = install_path.join("env").display())
+        }
+    }
+}
+
+/// PATH as a fresh login shell sets it up, which is what users get in a new
+/// terminal, falling back to the current PATH where that cannot be asked.
+/// Starting the shell can take seconds, so only `doctor` asks.
+pub fn login_path() -> Option<OsString> {
+    login_shell_path().or_else(|| std::env::var_os("PATH"))
+}
+
+#[cfg(unix)]
+fn login_shell_path() -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    let shell = std::env::var_os("SHELL").unwrap_or_else(|| "/bin/sh".into());
+    // Start from a clean environment so the login files build PATH from scratch
+    let keep = ["HOME", "USER", "LOGNAME", "SHELL", "LANG"];
+    let mut child = Command::new(&shell)
+        .args(["-l", "-c", print_path_command(Path::new(&shell))])
+        .env_clear()
+        .envs(keep.iter().filter_map(|k| std::env::var_os(k).map(|v| (k, v))))
+        .env("TERM", "dumb")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(None) if started.elapsed() < LOGIN_SHELL_TIMEOUT => std::thread::sleep(Duration::from_millis(20)),
+            _ => {
+                log::debug!("Could not read PATH from login shell {}", Path::new(&shell).display());
+                let _ = child.kill();
+                return None;
+            }
+        }
+    }
+    let output = child.wait_with_output().ok()?;
+    Some(OsString::from_vec(output.stdout)).filter(|path| !path.is_empty())
+}
+
+#[cfg(not(unix))]
+fn login_shell_path() -> Option<OsString> {
+    None
+}
+
+/// Command that makes `shell` print PATH separated by colons. fish keeps
+/// PATH as a list, which it joins with spaces in older releases.
+#[cfg(unix)]
+fn print_path_command(shell: &Path) -> &'static str {
+    if shell.file_name().is_some_and(|name| name == "fish") {
+        "string join : $PATH"
+    } else {
+        "printf '%s' \"$PATH\""
+    }
+}
+
+/// File name of the `wasmedge` CLI on this host.
+pub fn executable_name() -> &'static str {
+    if cfg!(windows) { "wasmedge.exe" } else { "wasmedge" }
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Whether `dir` is one of the entries of `path`.
+pub fn on_path(dir: &Path, path: &OsStr) -> bool {
+    std::env::split_paths(path).any(|entry| same_file(&entry, dir))
+}
+
+/// The `wasmedge` that `path` resolves to, when it is not the one in
+/// `install_path`. `None` when the managed binary wins or none is found.
+pub fn check(install_path: &Path, path: &OsStr) -> Option<Shadow> {
+    let managed_bin = install_path.join("bin");
+    let managed = managed_bin.join(executable_name());
+    let entries: Vec<PathBuf> = std::env::split_paths(path).filter(|e| !e.as_os_str().is_empty()).collect();
+    let (index, entry) = entries
+        .iter()
+        .enumerate()
+        .find(|(_, entry)| is_executable(&entry.join(executable_name())))?;
+    let binary = entry.join(executable_name());
+    if same_file(&binary, &managed) {
+        return None;
+    }
+    Some(Shadow {
+        binary,
+        entry: entry.clone(),
+        managed_on_path: entries[index + 1..].iter().any(|e| same_file(e, &managed_bin)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_earlier_path_entry_shadows_managed_binary() {
+        let root = tempfile::tempdir().unwrap();
+        let system = root.path().join("usr/bin");
+        let managed = root.path().join("wasmedge");
+        for dir in [&system, &managed.join("bin")] {
+            std::fs::create_dir_all(dir).unwrap();
+            let binary = dir.join(executable_name());
+            std::fs::write(&binary, "").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+            }
+        }
+        let path = |dirs: &[&Path]| std::env::join_paths(dirs).unwrap();
+
+        let shadow = check(&managed, &path(&[&system, &managed.join("bin")])).unwrap();
+        assert_eq!(shadow.entry, system);
+        assert!(shadow.managed_on_path);
+        assert!(!check(&managed, &path(&[&system])).unwrap().managed_on_path);
+        assert_eq!(check(&managed, &path(&[&managed.join("bin"), &system])), None);
+        assert_eq!(check(&managed, &path(&[root.path()])), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fish_prints_path_as_a_joined_list() {
+        assert_eq!(print_path_command(Path::new("/usr/bin/fish")), "string join : $PATH");
+        assert_eq!(print_path_command(Path::new("/bin/bash")), "printf '%s' \"$PATH\"");
+    }
+}
@@ -0,0 +1,210 @@
+//! Artifact cache shared by many users and machines, e.g. on a build farm
+//! NFS mount, so concurrent CI agents download each archive once.
+//!
+//! Entries are keyed by the SHA-256 of their download URL, so two releases
+//! publishing an archive of the same name never share an entry. One process
+//! at a time downloads an artifact, under `<key>.lock`; others wait for it.
+//! A finished download is renamed in next to a `<key>.sha256` digest, and
+//! every read is checked against that digest, so a truncated or corrupted
+//! entry is dropped and fetched again instead of installed.
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use crate::checksum;
+
+/// A lock whose download has not written anything for this long belongs to
+/// a process that died.
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Shared cache rooted at a group-writable directory.
+#[derive(Debug, Clone)]
+pub struct SharedCache {
+    dir: PathBuf,
+}
+
+/// Claim on downloading one artifact into the cache. Released, together with
+/// an unfinished download, on drop.
+#[derive(Debug)]
+pub struct FillLock {
+    lock: PathBuf,
+    partial: PathBuf,
+    artifact: PathBuf,
+    digest: PathBuf,
+}
+
+impl SharedCache {
+    pub fn new(dir: &Path) -> Result<Self> {
+        create_shared_dir(dir)?;
+        Ok(Self { dir: dir.to_path_buf() })
+    }
+
+    fn path(&self, url: &str, suffix: &str) -> PathBuf {
+        self.dir.join(format!("{}{}", hex::encode(Sha256::digest(url.as_bytes())), suffix))
+    }
+
+    /// Copy the artifact cached for `url` to `dest` if the cache has a valid
+    /// copy. An entry that does not match its digest is removed, but only
+    /// with `held`, the lock on it: without it, the entry may just be being
+    /// replaced, and removing it would throw away the new copy.
+    pub fn get(&self, url: &str, dest: &Path, held: Option<&FillLock>) -> Result<bool> {
+        let Ok(expected) = std::fs::read_to_string(self.path(url, ".sha256")) else {
+            return Ok(false);
+        };
+        let artifact = self.path(url, "");
+        match std::fs::copy(&artifact, dest) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e).with_context(|| format!("Failed to copy {}", artifact.display())),
+        }
+        // The copy is what gets installed, so that is what is checked
+        if let Err(e) = checksum::verify_checksum(dest, expected.trim()) {
+            match held {
+                Some(lock) => {
+                    warn!("{}", t!("shared-cache-corrupt", path = artifact.display(), error = e));
+                    let _ = std::fs::remove_file(&lock.artifact);
+                    let _ = std::fs::remove_file(&lock.digest);
+                }
+                None => debug!("{} does not match its digest yet: {}", artifact.display(), e),
+            }
+            return Ok(false);
+        }
+        debug!("Took {} from the shared cache", artifact.display());
+        Ok(true)
+    }
+
+    /// Take the lock for downloading `url`, or `None` while another process
+    /// holds it. A lock left by a dead process is taken over.
+    pub fn try_lock(&self, url: &str) -> Result<Option<FillLock>> {
+        let lock = FillLock {
+            lock: self.path(url, ".lock"),
+            partial: self.path(url, ".partial"),
+            artifact: self.path(url, ""),
+            digest: self.path(url, ".sha256"),
+        };
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&lock.lock) {
+            Ok(mut file) => {
+                let host = sys_info::hostname().unwrap_or_default();
+                write!(file, "{}:{}", host, std::process::id())?;
+                make_group_writable(&lock.lock);
+                Ok(Some(lock))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if is_stale(&lock.lock, &lock.partial) {
+                    warn!("{}", t!("shared-cache-stale-lock", path = lock.lock.display()));
+                    let _ = std::fs::remove_file(&lock.partial);
+                    let _ = std::fs::remove_file(&lock.lock);
+                }
+                // Never return the lock here: dropping it would remove a
+                // lock file another process may have just created
+                Ok(None)
+            }
+            Err(e) => Err(e).with_context(|| format!("Failed to create {}", lock.lock.display())),
+        }
+    }
+}
+
+impl FillLock {
+    /// Where the download goes until it is complete.
+    pub fn partial(&self) -> &Path {
+        &self.partial
+    }
+
+    /// Move the finished download into the cache. The digest is renamed in
+    /// first, so any reader that sees the artifact can validate it.
+    pub fn commit(self) -> Result<()> {
+        let digest = checksum::sha256_file(&self.partial)?;
+        let digest_tmp = self.partial.with_extension("sha256.partial");
+        std::fs::write(&digest_tmp, &digest)
+            .with_context(|| format!("Failed to write {}", digest_tmp.display()))?;
+        make_group_writable(&digest_tmp);
+        make_group_writable(&self.partial);
+        std::fs::rename(&digest_tmp, &self.digest)
+            .with_context(|| format!("Failed to write {}", self.digest.display()))?;
+        std::fs::rename(&self.partial, &self.artifact)
+            .with_context(|| format!("Failed to add {} to the shared cache", self.artifact.display()))?;
+        debug!("Added {} to the shared cache", self.artifact.display());
+        Ok(())
+    }
+}
+
+impl Drop for FillLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.partial);
+        if let Err(e) = std::fs::remove_file(&self.lock) {
+            debug!("Could not remove {}: {}", self.lock.display(), e);
+        }
+    }
+}
+
+/// Whether neither the lock nor the download it guards has changed within
+/// [`STALE_AFTER`]. A live download keeps its partial file fresh.
+fn is_stale(lock: &Path, partial: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let Some(last) = [modified(lock), modified(partial)].into_iter().flatten().max() else {
+        return false;
+    };
+    SystemTime::now().duration_since(last).is_ok_and(|age| age > STALE_AFTER)
+}
+
+/// Create `dir` so every member of its group can add to it; on Unix the
+/// setgid bit keeps new entries in the directory's group.
+fn create_shared_dir(dir: &Path) -> Result<()> {
+    if !dir.exists() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o2775));
+        }
+    }
+    Ok(())
+}
+
+fn make_group_writable(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o664));
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/a.tar.gz";
+
+    #[test]
+    fn test_fill_is_exclusive_and_reads_are_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("dest.tar.gz");
+        let cache = SharedCache::new(&dir.path().join("shared")).unwrap();
+        assert!(!cache.get(URL, &dest, None).unwrap());
+
+        let lock = cache.try_lock(URL).unwrap().unwrap();
+        assert!(cache.try_lock(URL).unwrap().is_none());
+        std::fs::write(lock.partial(), b"archive").unwrap();
+        lock.commit().unwrap();
+        assert!(cache.get(URL, &dest, None).unwrap());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"archive");
+
+        // An archive of the same name from another release is another entry
+        let other = URL.replace("0.14.1", "0.13.5");
+        assert!(!cache.get(&other, &dest, None).unwrap());
+
+        // A corrupted entry is only dropped by the holder of its lock
+        let entry = cache.path(URL, "");
+        std::fs::write(&entry, b"truncat").unwrap();
+        assert!(!cache.get(URL, &dest, None).unwrap());
+        assert!(entry.exists());
+        let lock = cache.try_lock(URL).unwrap().unwrap();
+        assert!(!cache.get(URL, &dest, Some(&lock)).unwrap());
+        assert!(!entry.exists());
+    }
+}
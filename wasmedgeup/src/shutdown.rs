@@ -0,0 +1,50 @@
+use std::time::Duration;
+use tokio::sync::{RwLock, RwLockReadGuard};
+use tokio::task::JoinHandle;
+
+/// Held while files are being moved into an installation prefix. An interrupt
+/// waits for every holder to finish, so a prefix is never left half replaced.
+static COMMIT: RwLock<()> = RwLock::const_new(());
+
+/// How long an interrupted command gets to unwind before the process exits.
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Exit code for a run stopped by a signal, following the shell convention.
+pub const INTERRUPTED_EXIT_CODE: u8 = 130;
+
+/// Resolve once the user presses Ctrl-C or, on unix, the process receives SIGTERM.
+pub async fn interrupted() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+                return;
+            }
+            Err(e) => log::debug!("Could not listen for SIGTERM: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Enter a section that must not be cut short by an interrupt. The section
+/// ends when the returned guard is dropped.
+pub async fn commit_section() -> RwLockReadGuard<'static, ()> {
+    COMMIT.read().await
+}
+
+/// Stop `task` after an interrupt. Running commit sections are allowed to
+/// finish first; the task is then dropped at its next await point, which
+/// runs the destructors that delete staging directories and release locks.
+pub async fn cancel<T>(task: JoinHandle<T>) {
+    let _commits = COMMIT.write().await;
+    task.abort();
+    // A task stuck in blocking I/O, such as a prompt, cannot be aborted
+    if tokio::time::timeout(GRACE_PERIOD, task).await.is_err() {
+        log::debug!("Interrupted command did not stop within {:?}", GRACE_PERIOD);
+    }
+}
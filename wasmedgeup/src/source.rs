@@ -0,0 +1,274 @@
+//! Building the runtime from the WasmEdge sources, for patched builds and
+//! platforms upstream publishes no binaries for. The build installs into a
+//! staging prefix laid out like a release archive, which the installer then
+//! moves into place.
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use crate::plugin::normalize_plugin_name;
+
+/// Repository the sources are cloned from.
+pub const WASMEDGE_GIT_URL: &str = "https://github.com/WasmEdge/WasmEdge.git";
+
+/// Tools a build needs, with the package that usually provides them.
+const REQUIRED_TOOLS: &[(&str, &str)] = &[("git", "git"), ("cmake", "cmake"), ("ninja", "ninja"), ("c++", "a C++ compiler (clang or gcc)")];
+
+/// CMake option enabling each plugin. WASI-NN backends share one option
+/// taking a list.
+const PLUGIN_OPTIONS: &[(&str, &str, &str)] = &[
+    ("wasi_crypto", "WASMEDGE_PLUGIN_WASI_CRYPTO", "ON"),
+    ("wasi_logging", "WASMEDGE_PLUGIN_WASI_LOGGING", "ON"),
+    ("wasi_nn-ggml", "WASMEDGE_PLUGIN_WASI_NN_BACKEND", "GGML"),
+    ("wasi_nn-openvino", "WASMEDGE_PLUGIN_WASI_NN_BACKEND", "OpenVINO"),
+    ("wasi_nn-pytorch", "WASMEDGE_PLUGIN_WASI_NN_BACKEND", "PyTorch"),
+    ("wasi_nn-tensorflowlite", "WASMEDGE_PLUGIN_WASI_NN_BACKEND", "TensorFlowLite"),
+    ("wasm_bpf", "WASMEDGE_PLUGIN_WASM_BPF", "ON"),
+    ("wasmedge_ffmpeg", "WASMEDGE_PLUGIN_FFMPEG", "ON"),
+    ("wasmedge_image", "WASMEDGE_PLUGIN_IMAGE", "ON"),
+    ("wasmedge_ocr", "WASMEDGE_PLUGIN_OCR", "ON"),
+    ("wasmedge_process", "WASMEDGE_PLUGIN_PROCESS", "ON"),
+    ("wasmedge_stablediffusion", "WASMEDGE_PLUGIN_STABLEDIFFUSION", "ON"),
+    ("wasmedge_tensorflow", "WASMEDGE_PLUGIN_TENSORFLOW", "ON"),
+    ("wasmedge_tensorflowlite", "WASMEDGE_PLUGIN_TENSORFLOWLITE", "ON"),
+    ("wasmedge_zlib", "WASMEDGE_PLUGIN_ZLIB", "ON"),
+];
+
+/// Optional features of a plugin's build, with the CMake option each sets.
+const PLUGIN_FEATURES: &[(&str, &str, &str, &str)] = &[
+    ("wasi_nn-ggml", "blas", "WASMEDGE_PLUGIN_WASI_NN_GGML_LLAMA_BLAS", "ON"),
+    ("wasi_nn-ggml", "cuda", "WASMEDGE_PLUGIN_WASI_NN_GGML_LLAMA_CUBLAS", "ON"),
+    ("wasi_nn-ggml", "metal", "WASMEDGE_PLUGIN_WASI_NN_GGML_LLAMA_METAL", "ON"),
+    ("wasi_nn-ggml", "noavx", "WASMEDGE_PLUGIN_WASI_NN_GGML_LLAMA_NATIVE", "OFF"),
+    ("wasmedge_stablediffusion", "cuda", "WASMEDGE_PLUGIN_STABLEDIFFUSION_CUBLAS", "ON"),
+    ("wasmedge_stablediffusion", "metal", "WASMEDGE_PLUGIN_STABLEDIFFUSION_METAL", "ON"),
+];
+
+/// Published name of a plugin a source build knows how to enable, matched
+/// loosely like other plugin names.
+pub fn plugin_name(name: &str) -> Option<&'static str> {
+    let wanted = normalize_plugin_name(name);
+    PLUGIN_OPTIONS
+        .iter()
+        .map(|(published, _, _)| *published)
+        .find(|published| normalize_plugin_name(published) == wanted)
+}
+
+/// A finished build.
+#[derive(Debug)]
+pub struct Built {
+    /// Prefix the build installed into, holding `bin`, `lib` and `include`
+    pub prefix: PathBuf,
+    /// Commit the sources were checked out at
+    pub commit: String,
+}
+
+impl Built {
+    /// Directory the build installed its plugins into.
+    pub fn plugin_dir(&self) -> PathBuf {
+        let lib64 = self.prefix.join("lib64").join("wasmedge");
+        if lib64.exists() {
+            lib64
+        } else {
+            self.prefix.join("lib").join("wasmedge")
+        }
+    }
+}
+
+/// A source build of WasmEdge at a tag, branch or commit.
+#[derive(Debug, Clone)]
+pub struct SourceBuild {
+    git_ref: String,
+    defines: Vec<String>,
+    plugins: Vec<String>,
+    features: Vec<String>,
+}
+
+impl SourceBuild {
+    pub fn new(git_ref: impl Into<String>) -> Self {
+        Self { git_ref: git_ref.into(), defines: Vec::new(), plugins: Vec::new(), features: Vec::new() }
+    }
+
+    /// Extra CMake cache entries, as `NAME=VALUE`.
+    pub fn with_defines(mut self, defines: Vec<String>) -> Self {
+        self.defines = defines;
+        self
+    }
+
+    /// Plugins to build along with the runtime, by release name.
+    pub fn with_plugins(mut self, plugins: Vec<String>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Optional plugin features, e.g. `cuda` for `wasi_nn-ggml`.
+    pub fn with_features(mut self, features: Vec<String>) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn git_ref(&self) -> &str {
+        &self.git_ref
+    }
+
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Plugins built along with the runtime, as requested.
+    pub fn plugins(&self) -> &[String] {
+        &self.plugins
+    }
+
+    /// `-D` arguments for the configure step.
+    fn cmake_defines(&self) -> Result<Vec<String>> {
+        let mut options: Vec<(String, String)> = vec![
+            ("CMAKE_BUILD_TYPE".into(), "Release".into()),
+            ("WASMEDGE_BUILD_TESTS".into(), "OFF".into()),
+        ];
+        for plugin in &self.plugins {
+            let published = plugin_name(plugin).with_context(|| t!("source-unknown-plugin", name = plugin))?;
+            let (_, option, value) = PLUGIN_OPTIONS.iter().find(|(name, _, _)| *name == published).unwrap();
+            match options.iter_mut().find(|(name, _)| name == option) {
+                Some((_, existing)) => *existing = format!("{};{}", existing, value),
+                None => options.push((option.to_string(), value.to_string())),
+            }
+        }
+        for feature in &self.features {
+            let (_, _, option, value) = PLUGIN_FEATURES
+                .iter()
+                .find(|(plugin, name, _, _)| {
+                    name.eq_ignore_ascii_case(feature) && self.plugins.iter().any(|p| plugin_name(p) == Some(*plugin))
+                })
+                .with_context(|| {
+                    let known: Vec<String> = PLUGIN_FEATURES
+                        .iter()
+                        .filter(|(plugin, ..)| self.plugins.iter().any(|p| plugin_name(p) == Some(*plugin)))
+                        .map(|(plugin, name, ..)| format!("{} ({})", name, plugin))
+                        .collect();
+                    t!("source-unknown-feature", feature = feature, known = if known.is_empty() { "none".to_string() } else { known.join(", ") })
+                })?;
+            options.push((option.to_string(), value.to_string()));
+        }
+        for define in &self.defines {
+            let (name, value) = define
+                .split_once('=')
+                .with_context(|| format!("Invalid CMake option {}: expected NAME=VALUE", define))?;
+            options.retain(|(existing, _)| existing != name);
+            options.push((name.to_string(), value.to_string()));
+        }
+        Ok(options.into_iter().map(|(name, value)| format!("-D{}={}", name, value)).collect())
+    }
+
+    /// Fetch the sources at the requested ref into `work_dir`, build them
+    /// and install into a prefix under `work_dir`.
+    pub async fn build(&self, work_dir: &Path) -> Result<Built> {
+        let defines = self.cmake_defines()?;
+        check_tools().await?;
+        let src = work_dir.join("src");
+        let build = work_dir.join("build");
+        let prefix = work_dir.join("prefix");
+        let path = |p: &Path| p.to_string_lossy().into_owned();
+
+        // Fetching a single ref works alike for tags, branches and commits
+        info!("Fetching {} at {}", WASMEDGE_GIT_URL, self.git_ref);
+        run("git", &["init", "--quiet", &path(&src)], work_dir).await?;
+        run("git", &["fetch", "--depth", "1", WASMEDGE_GIT_URL, &self.git_ref], &src).await?;
+        run("git", &["checkout", "--quiet", "FETCH_HEAD"], &src).await?;
+        let commit = output("git", &["rev-parse", "HEAD"], &src).await?;
+
+        info!("Building WasmEdge {} ({}) from source", self.git_ref, commit);
+        let install_prefix = format!("-DCMAKE_INSTALL_PREFIX={}", path(&prefix));
+        let mut configure = vec!["-S".to_string(), path(&src), "-B".into(), path(&build), "-G".into(), "Ninja".into(), install_prefix];
+        configure.extend(defines);
+        let configure: Vec<&str> = configure.iter().map(String::as_str).collect();
+        run("cmake", &configure, work_dir).await?;
+        run("cmake", &["--build", &path(&build)], work_dir).await?;
+        run("cmake", &["--install", &path(&build)], work_dir).await?;
+        Ok(Built { prefix, commit })
+    }
+}
+
+/// Fail naming every required tool that cannot be run.
+pub async fn check_tools() -> Result<()> {
+    let mut missing = Vec::new();
+    for (tool, package) in REQUIRED_TOOLS {
+        let found = Command::new(tool)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .is_ok_and(|status| status.success());
+        if !found {
+            missing.push(format!("{} ({})", tool, package));
+        }
+    }
+    if !missing.is_empty() {
+        anyhow::bail!(t!("source-tools-missing", tools = missing.join(", ")));
+    }
+    Ok(())
+}
+
+/// Run one build step, passing its output through to stderr so stdout keeps
+/// only results.
+async fn run(program: &str, args: &[&str], cwd: &Path) -> Result<()> {
+    debug!("Running {} {}", program, args.join(" "));
+    let status = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .stdout(Stdio::from(std::io::stderr()))
+        .kill_on_drop(true)
+        .status()
+        .await
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !status.success() {
+        anyhow::bail!("{} {} failed with {}", program, args.join(" "), status);
+    }
+    Ok(())
+}
+
+/// Run a command and return its trimmed stdout.
+async fn output(program: &str, args: &[&str], cwd: &Path) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        anyhow::bail!("{} {} failed: {}", program, args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmake_defines_combine_plugins_and_overrides() {
+        let build = SourceBuild::new("0.14.1")
+            .with_plugins(vec!["wasi_nn-ggml".into(), "wasi_logging".into(), "wasi_nn-openvino".into()])
+            .with_defines(vec!["CMAKE_BUILD_TYPE=RelWithDebInfo".into(), "WASMEDGE_USE_LLVM=OFF".into()]);
+        assert_eq!(
+            build.cmake_defines().unwrap(),
+            [
+                "-DWASMEDGE_BUILD_TESTS=OFF",
+                "-DWASMEDGE_PLUGIN_WASI_NN_BACKEND=GGML;OpenVINO",
+                "-DWASMEDGE_PLUGIN_WASI_LOGGING=ON",
+                "-DCMAKE_BUILD_TYPE=RelWithDebInfo",
+                "-DWASMEDGE_USE_LLVM=OFF",
+            ]
+        );
+        assert!(SourceBuild::new("master").with_plugins(vec!["nope".into()]).cmake_defines().is_err());
+
+        let cuda = SourceBuild::new("0.14.1").with_plugins(vec!["wasi-nn-ggml".into()]).with_features(vec!["cuda".into()]);
+        assert_eq!(cuda.cmake_defines().unwrap()[2..], ["-DWASMEDGE_PLUGIN_WASI_NN_BACKEND=GGML", "-DWASMEDGE_PLUGIN_WASI_NN_GGML_LLAMA_CUBLAS=ON"]);
+        let misplaced = SourceBuild::new("0.14.1").with_plugins(vec!["wasi_logging".into()]).with_features(vec!["cuda".into()]);
+        assert!(misplaced.cmake_defines().is_err());
+        assert!(SourceBuild::new("master").with_defines(vec!["NOVALUE".into()]).cmake_defines().is_err());
+    }
+}
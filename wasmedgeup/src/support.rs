@@ -0,0 +1,198 @@
+//! Upstream support status of an installed runtime: whether its release line
+//! is still maintained, what to upgrade to, and published security
+//! advisories that affect it.
+
+use anyhow::{Context, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use crate::{
+    cache,
+    downloader::Fetcher,
+    release::{SupportStatus, VersionManifest},
+};
+
+const ADVISORIES_API: &str = "https://api.github.com/repos/WasmEdge/WasmEdge/security-advisories?state=published";
+
+/// A security advisory as listed by the GitHub API.
+#[derive(Debug, Deserialize)]
+struct Advisory {
+    ghsa_id: String,
+    summary: String,
+    #[serde(default)]
+    vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Vulnerability {
+    /// Comparators such as `>= 0.13.0, < 0.13.5`
+    vulnerable_version_range: Option<String>,
+    patched_versions: Option<String>,
+}
+
+/// An advisory affecting the checked version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdvisoryMatch {
+    pub id: String,
+    pub summary: String,
+    pub patched: Option<String>,
+}
+
+/// How upstream supports one runtime version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Support {
+    pub version: String,
+    /// `None` when the support manifest does not list the version, or
+    /// there is none
+    pub status: Option<SupportStatus>,
+    /// Newest maintained release line, e.g. `0.14.x`
+    pub upgrade_to: Option<String>,
+    pub advisories: Vec<AdvisoryMatch>,
+}
+
+impl Support {
+    /// Support of `version` according to `manifest` and `advisories`.
+    fn new(version: &str, manifest: &VersionManifest, advisories: &[Advisory]) -> Self {
+        let parse = |v: &str| semver::Version::parse(&pad(v)).ok();
+        let newest = manifest.maintained.iter().filter_map(|line| Some((parse(line)?, line))).max();
+        let oldest = manifest.maintained.iter().filter_map(|line| parse(line)).min();
+        let status = manifest.status(version).or_else(|| {
+            let version = parse(version)?;
+            oldest.filter(|oldest| version < *oldest).map(|_| SupportStatus::Unmaintained)
+        });
+        let upgrade_to = newest.map(|(_, line)| match line.matches('.').count() {
+            1 => format!("{}.x", line),
+            _ => line.clone(),
+        });
+        Self {
+            version: version.to_string(),
+            status,
+            upgrade_to,
+            advisories: matching_advisories(version, advisories),
+        }
+    }
+
+    /// Support of `version` when upstream could not be asked.
+    pub fn unknown(version: &str) -> Self {
+        Self { version: version.to_string(), status: None, upgrade_to: None, advisories: Vec::new() }
+    }
+
+    /// Whether upstream no longer supports the version.
+    pub fn is_eol(&self) -> bool {
+        self.status.is_some_and(SupportStatus::is_eol)
+    }
+
+    /// Warnings to show for the version, if any.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.is_eol() {
+            warnings.push(match &self.upgrade_to {
+                Some(upgrade) => t!("support-eol", version = &self.version, upgrade = upgrade),
+                None => t!("support-eol-no-upgrade", version = &self.version),
+            });
+        }
+        for advisory in &self.advisories {
+            warnings.push(t!("support-advisory", version = &self.version, id = &advisory.id,
+                summary = &advisory.summary, patched = advisory.patched.as_deref().unwrap_or("-")));
+        }
+        warnings
+    }
+}
+
+/// `0.13` as `0.13.0`, so release lines compare as versions.
+fn pad(version: &str) -> String {
+    let version = version.trim_start_matches('v');
+    match version.matches('.').count() {
+        0 => format!("{}.0.0", version),
+        1 => format!("{}.0", version),
+        _ => version.to_string(),
+    }
+}
+
+fn matching_advisories(version: &str, advisories: &[Advisory]) -> Vec<AdvisoryMatch> {
+    let Ok(version) = semver::Version::parse(&pad(version)) else {
+        return Vec::new();
+    };
+    advisories
+        .iter()
+        .filter_map(|advisory| {
+            let vulnerability = advisory.vulnerabilities.iter().find(|v| {
+                v.vulnerable_version_range
+                    .as_deref()
+                    .and_then(|range| semver::VersionReq::parse(range).ok())
+                    .is_some_and(|range| range.matches(&version))
+            })?;
+            Some(AdvisoryMatch {
+                id: advisory.ghsa_id.clone(),
+                summary: advisory.summary.clone(),
+                patched: vulnerability.patched_versions.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Check `version` against the support manifest at `manifest_url`, if any,
+/// and, when GitHub serves them, upstream's security advisories. Both are
+/// kept for [`check_cached`].
+pub async fn check(fetcher: &impl Fetcher, manifest_url: Option<&str>, version: &str) -> Result<Support> {
+    let manifest: VersionManifest = match manifest_url {
+        Some(url) => serde_json::from_str(&fetch_and_keep(fetcher, url).await?)
+            .with_context(|| format!("Invalid JSON from {}", url))?,
+        None => VersionManifest::default(),
+    };
+    let advisories = match fetch_and_keep(fetcher, ADVISORIES_API).await {
+        Ok(advisories) => serde_json::from_str(&advisories).unwrap_or_default(),
+        Err(e) => {
+            debug!("Could not fetch security advisories: {:#}", e);
+            Vec::new()
+        }
+    };
+    Ok(Support::new(version, &manifest, &advisories))
+}
+
+async fn fetch_and_keep(fetcher: &impl Fetcher, url: &str) -> Result<String> {
+    let body = fetcher.download_text(url).await?;
+    cache::store_metadata(url, &body);
+    Ok(body)
+}
+
+/// Check `version` against the responses of earlier checks only, for hints
+/// after commands that should not wait on the network.
+pub fn check_cached(manifest_url: Option<&str>, version: &str) -> Option<Support> {
+    let manifest: VersionManifest = match manifest_url {
+        Some(url) => serde_json::from_str(&cache::cached_metadata(url)?.0).ok()?,
+        None => VersionManifest::default(),
+    };
+    let advisories: Vec<Advisory> = cache::cached_metadata(ADVISORIES_API)
+        .and_then(|(advisories, _)| serde_json::from_str(&advisories).ok())
+        .unwrap_or_default();
+    Some(Support::new(version, &manifest, &advisories))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_support_reports_eol_lines_and_advisories() {
+        let manifest = VersionManifest {
+            maintained: vec!["0.14".into(), "0.13.5".into()],
+            deprecated: vec!["0.12".into()],
+        };
+        let advisories: Vec<Advisory> = serde_json::from_str(r#"[{
+            "ghsa_id": "GHSA-xxxx-yyyy-zzzz",
+            "summary": "Out-of-bounds read in the loader",
+            "vulnerabilities": [{"vulnerable_version_range": ">= 0.11.0, < 0.13.5", "patched_versions": "0.13.5"}]
+        }]"#).unwrap();
+
+        let old = Support::new("0.12.1", &manifest, &advisories);
+        assert_eq!((old.status, old.upgrade_to.as_deref()), (Some(SupportStatus::Deprecated), Some("0.14.x")));
+        assert_eq!(old.advisories[0].id, "GHSA-xxxx-yyyy-zzzz");
+        assert_eq!(old.warnings().len(), 2);
+
+        assert_eq!(Support::new("0.11.2", &manifest, &[]).status, Some(SupportStatus::Unmaintained));
+        let current = Support::new("0.14.1", &manifest, &advisories);
+        assert!(!current.is_eol() && current.warnings().is_empty());
+        assert_eq!(serde_json::to_value(&old).unwrap()["status"], "deprecated");
+        assert_eq!(Support::new("0.14.1", &VersionManifest::default(), &[]).status, None);
+    }
+}
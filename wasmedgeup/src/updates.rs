@@ -0,0 +1,157 @@
+//! What `update --check` and `outdated` report: newer releases of the
+//! installed runtime, of its plugins and of wasmedgeup itself.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use crate::{
+    plugin::{self, CatalogEntry, PluginReceipts},
+    release,
+    version::WasmEdgeVersion,
+};
+
+/// Exit code of `update --check` when anything can be updated, so scheduled
+/// jobs can tell it apart from success (0) and failure (1).
+pub const UPDATES_AVAILABLE_EXIT_CODE: u8 = 10;
+
+/// Something installed with a newer release available.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Outdated {
+    pub name: String,
+    pub installed: String,
+    pub available: String,
+}
+
+impl fmt::Display for Outdated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} -> {}", self.name, self.installed, self.available)
+    }
+}
+
+/// Updates available for an installation.
+#[derive(Debug, Default, Serialize)]
+pub struct Updates {
+    pub runtime: Option<Outdated>,
+    pub plugins: Vec<Outdated>,
+    pub wasmedgeup: Option<Outdated>,
+}
+
+impl Updates {
+    /// Whether everything is up to date.
+    pub fn is_empty(&self) -> bool {
+        self.runtime.is_none() && self.plugins.is_empty() && self.wasmedgeup.is_none()
+    }
+
+    /// Everything outdated, runtime first.
+    pub fn all(&self) -> impl Iterator<Item = &Outdated> {
+        self.runtime.iter().chain(&self.plugins).chain(&self.wasmedgeup)
+    }
+}
+
+/// `installed` and `latest` as an [`Outdated`] entry for `name` when
+/// `latest` is newer.
+pub fn outdated(name: &str, installed: &WasmEdgeVersion, latest: &WasmEdgeVersion) -> Option<Outdated> {
+    (latest > installed).then(|| Outdated {
+        name: name.to_string(),
+        installed: installed.to_string(),
+        available: latest.to_string(),
+    })
+}
+
+/// Installed plugins with a newer build for `runtime` published in
+/// `catalog` for `platform`. Pinned plugins stay where they are and are
+/// never outdated.
+pub fn outdated_plugins(receipts: &PluginReceipts, runtime: &WasmEdgeVersion, catalog: &[CatalogEntry], platform: &str) -> Vec<Outdated> {
+    receipts
+        .plugins
+        .iter()
+        .filter(|(name, _)| receipts.pinned(name).is_none())
+        .filter(|(name, _)| catalog.iter().any(|entry| entry.name == **name && entry.platform == platform))
+        .filter_map(|(name, receipt)| outdated(name, &receipt.version.parse().ok()?, runtime))
+        .collect()
+}
+
+/// Updates of the runtime and its plugins known from release information
+/// fetched by earlier commands, without going to the network, so login
+/// hooks never wait on it. `None` when no releases were fetched yet.
+pub fn cached(install_path: &Path, installed: &WasmEdgeVersion, platform: &str) -> Result<Option<Updates>> {
+    let Some(latest) = release::cached_latest_version() else {
+        return Ok(None);
+    };
+    let receipts = plugin::installed_plugins(install_path)?;
+    let plugins = plugin::cached_catalog(installed)
+        .map(|catalog| outdated_plugins(&receipts, installed, &catalog, platform))
+        .unwrap_or_default();
+    Ok(Some(Updates { runtime: outdated("wasmedge", installed, &latest), plugins, wasmedgeup: None }))
+}
+
+/// One line for a MOTD or login hook, e.g. "WasmEdge 0.14.1 installed;
+/// 0.15.0 available; 2 plugins outdated". `updates` is `None` when they
+/// are unknown.
+pub fn brief(installed: &WasmEdgeVersion, updates: Option<&Updates>) -> String {
+    let mut parts = vec![t!("outdated-brief-installed", version = installed)];
+    match updates {
+        None => {}
+        Some(updates) if updates.is_empty() => parts.push(t!("outdated-brief-current")),
+        Some(updates) => {
+            if let Some(runtime) = &updates.runtime {
+                parts.push(t!("outdated-brief-available", version = runtime.available));
+            }
+            match updates.plugins.len() {
+                0 => {}
+                1 => parts.push(t!("outdated-brief-plugin")),
+                count => parts.push(t!("outdated-brief-plugins", count = count)),
+            }
+        }
+    }
+    parts.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_published_unpinned_plugins_are_outdated() {
+        let receipts: PluginReceipts = serde_json::from_str(r#"{
+            "plugins": {
+                "wasi_logging": {"version": "0.14.0", "platform": "manylinux2014_x86_64", "files": []},
+                "wasi_nn-ggml": {"version": "0.14.0", "platform": "manylinux2014_x86_64", "files": []},
+                "wasi_crypto": {"version": "0.14.0", "platform": "manylinux2014_x86_64", "files": []},
+                "wasmedge_image": {"version": "0.14.1", "platform": "manylinux2014_x86_64", "files": []}
+            },
+            "pins": {"wasi_nn_ggml": "0.14.0"}
+        }"#).unwrap();
+        let entry = |name: &str| CatalogEntry {
+            name: name.to_string(),
+            version: "0.14.1".to_string(),
+            platform: "manylinux2014_x86_64".to_string(),
+            url: String::new(),
+            size: 0,
+            sha256: None,
+        };
+        let catalog = [entry("wasi_logging"), entry("wasi_nn-ggml"), entry("wasmedge_image")];
+        let runtime: WasmEdgeVersion = "0.14.1".parse().unwrap();
+
+        let outdated = outdated_plugins(&receipts, &runtime, &catalog, "manylinux2014_x86_64");
+        assert_eq!(outdated.iter().map(ToString::to_string).collect::<Vec<_>>(), ["wasi_logging 0.14.0 -> 0.14.1"]);
+        assert!(outdated_plugins(&receipts, &runtime, &catalog, "darwin_arm64").is_empty());
+    }
+
+    #[test]
+    fn test_brief_summary() {
+        let installed: WasmEdgeVersion = "0.14.1".parse().unwrap();
+        let plugin = |name: &str| Outdated { name: name.to_string(), installed: "0.14.0".into(), available: "0.14.1".into() };
+        let updates = Updates {
+            runtime: outdated("wasmedge", &installed, &"0.15.0".parse().unwrap()),
+            plugins: vec![plugin("wasi_logging"), plugin("wasi_crypto")],
+            wasmedgeup: None,
+        };
+        assert_eq!(brief(&installed, Some(&updates)), "WasmEdge 0.14.1 installed; 0.15.0 available; 2 plugins outdated");
+        let plugins = Updates { plugins: vec![plugin("wasi_logging")], ..Default::default() };
+        assert_eq!(brief(&installed, Some(&plugins)), "WasmEdge 0.14.1 installed; 1 plugin outdated");
+        assert_eq!(brief(&installed, Some(&Updates::default())), "WasmEdge 0.14.1 installed; up to date");
+        assert_eq!(brief(&installed, None), "WasmEdge 0.14.1 installed");
+    }
+}
@@ -0,0 +1,111 @@
+//! WasmEdge release versions. Upstream tags carry no `v` (`0.14.1`), but
+//! users and other tools often write one (`v0.14.1`). Both are accepted
+//! wherever a version is read, and the bare form is what ends up in URLs,
+//! directory names and receipts.
+
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A WasmEdge release version, parsed with or without a leading `v` and
+/// ordered by semver precedence, so `0.15.0-alpha.1` sorts between `0.14.1`
+/// and `0.15.0`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WasmEdgeVersion(semver::Version);
+
+impl WasmEdgeVersion {
+    /// The release tag, which is also the version in download URLs.
+    pub fn tag(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// File name of the runtime archive for `package`, e.g.
+    /// `WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz`.
+    pub fn asset_name(&self, package: &str) -> String {
+        format!("WasmEdge-{}-{}", self, package)
+    }
+
+    /// File name of the archive of `plugin` for `platform`, e.g.
+    /// `WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz`.
+    pub fn plugin_asset_name(&self, plugin: &str, platform: &str) -> String {
+        format!("WasmEdge-plugin-{}-{}-{}.tar.gz", plugin, self, platform)
+    }
+
+    /// `(major, minor)`, the release line plugins are built against.
+    pub fn release_line(&self) -> (u64, u64) {
+        (self.0.major, self.0.minor)
+    }
+
+    /// Alphas, betas and release candidates.
+    pub fn is_prerelease(&self) -> bool {
+        !self.0.pre.is_empty()
+    }
+}
+
+impl FromStr for WasmEdgeVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(version: &str) -> anyhow::Result<Self> {
+        semver::Version::parse(&normalize(version))
+            .map(Self)
+            .map_err(|e| anyhow::anyhow!(t!("version-invalid", version = version, error = e)))
+    }
+}
+
+impl fmt::Display for WasmEdgeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for WasmEdgeVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// `version` without a leading `v` or `V` in front of a number. Keywords
+/// like `latest` and partial versions like `v0.14` are otherwise left as
+/// they are.
+pub fn normalize(version: &str) -> String {
+    let trimmed = version.trim();
+    match trimmed.strip_prefix(['v', 'V']) {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest.to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Clap value parser for version arguments.
+pub fn parse_arg(version: &str) -> Result<String, String> {
+    Ok(normalize(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leading_v_is_optional_and_versions_sort_by_precedence() {
+        assert_eq!(normalize("v0.14.1"), "0.14.1");
+        assert_eq!(normalize("V0.15.0-alpha.1"), "0.15.0-alpha.1");
+        assert_eq!(normalize("0.14.1"), "0.14.1");
+        assert_eq!(normalize("v0.14"), "0.14");
+        assert_eq!(normalize("latest"), "latest");
+        assert_eq!(normalize("vlatest"), "vlatest");
+
+        let tagged: WasmEdgeVersion = "v0.14.1".parse().unwrap();
+        assert_eq!(tagged, "0.14.1".parse().unwrap());
+        assert_eq!(tagged.tag(), "0.14.1");
+        assert_eq!(tagged.asset_name("darwin_arm64.tar.gz"), "WasmEdge-0.14.1-darwin_arm64.tar.gz");
+        assert_eq!(tagged.release_line(), (0, 14));
+        assert!("latest".parse::<WasmEdgeVersion>().is_err());
+
+        let mut versions: Vec<WasmEdgeVersion> = ["0.15.0", "0.9.1", "0.15.0-alpha.1", "0.14.1"]
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        versions.sort();
+        assert_eq!(versions.iter().map(WasmEdgeVersion::tag).collect::<Vec<_>>(), ["0.9.1", "0.14.1", "0.15.0-alpha.1", "0.15.0"]);
+        assert!(versions[2].is_prerelease() && !versions[3].is_prerelease());
+    }
+}
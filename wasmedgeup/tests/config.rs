@@ -0,0 +1,69 @@
+//! Settings resolve as command line flag, then `WASMEDGEUP_*` variable, then
+//! config file, then built-in default.
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+
+fn plugin_dir(home: &Path, env: &[(&str, &Path)], args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_wasmedgeup"))
+        .args(["show", "plugin-dir"])
+        .args(args)
+        .env_clear()
+        .env("HOME", home)
+        .env("WASMEDGEUP_CONFIG", home.join("config.toml"))
+        .envs(env.iter().copied())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_flag_beats_env_beats_config_file() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let expected = |prefix: &str| home.join(prefix).join("plugin").display().to_string();
+
+    assert_eq!(plugin_dir(home, &[], &[]), expected(".wasmedge"));
+
+    std::fs::write(home.join("config.toml"), "path = \"from-config\"\n").unwrap();
+    assert_eq!(plugin_dir(home, &[], &[]), expected("from-config"));
+
+    let env = home.join("from-env");
+    assert_eq!(plugin_dir(home, &[("WASMEDGEUP_PATH", &env)], &[]), expected("from-env"));
+
+    let flag = home.join("from-flag").display().to_string();
+    assert_eq!(plugin_dir(home, &[("WASMEDGEUP_PATH", &env)], &["-p", &flag]), expected("from-flag"));
+}
+
+#[test]
+fn test_profile_settings_replace_top_level_ones() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let expected = |prefix: &str| home.join(prefix).join("plugin").display().to_string();
+    std::fs::write(home.join("config.toml"), "path = \"from-config\"\n\n[profiles.gpu-dev]\npath = \"from-profile\"\n").unwrap();
+
+    assert_eq!(plugin_dir(home, &[], &["--profile", "gpu-dev"]), expected("from-profile"));
+    let profile = Path::new("gpu-dev");
+    assert_eq!(plugin_dir(home, &[("WASMEDGEUP_PROFILE", profile)], &[]), expected("from-profile"));
+}
+
+#[test]
+fn test_system_install_ignores_the_prefix_from_the_environment() {
+    let home = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_wasmedgeup"))
+        .args(["install", "0.14.1", "--system", "--dry-run"])
+        .env_clear()
+        .env("HOME", home.path())
+        .env("WASMEDGEUP_CONFIG", home.path().join("config.toml"))
+        .env("WASMEDGEUP_PATH", home.path().join("from-env"))
+        .env("HTTPS_PROXY", "http://127.0.0.1:9")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    // The dry-run plan installs into the system prefix only
+    let plan = String::from_utf8_lossy(&output.stdout);
+    assert!(plan.contains("install it into /opt/wasmedge"), "{}", plan);
+    assert!(!plan.contains("from-env"), "{}", plan);
+}
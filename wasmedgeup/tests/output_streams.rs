@@ -0,0 +1,504 @@
+//! Every subcommand must keep diagnostics off stdout, so its output can be
+//! piped or parsed. The binary runs against a seeded metadata cache with all
+//! network access failing, which also exercises the stale cache warning.
+#![cfg(target_os = "linux")]
+
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::{Command, Output};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/WasmEdge/WasmEdge/releases?per_page=100";
+const RELEASE_URL: &str = "https://api.github.com/repos/WasmEdge/WasmEdge/releases/tags/0.14.1";
+const RELEASE: &str = r#"{"tag_name": "0.14.1", "prerelease": false, "assets": [
+    {"name": "WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz",
+     "browser_download_url": "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz",
+     "size": 1000},
+    {"name": "WasmEdge-0.14.1-debugsymbols_manylinux2014_x86_64.tar.gz",
+     "browser_download_url": "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/WasmEdge-0.14.1-debugsymbols_manylinux2014_x86_64.tar.gz",
+     "size": 1000},
+    {"name": "WasmEdge-0.14.1-debugsymbols_ubuntu20.04_x86_64.tar.gz",
+     "browser_download_url": "https://github.com/WasmEdge/WasmEdge/releases/download/0.14.1/WasmEdge-0.14.1-debugsymbols_ubuntu20.04_x86_64.tar.gz",
+     "size": 1000}
+]}"#;
+
+/// Archives `download` takes from the artifact store instead of the
+/// network, for whichever distribution the tests run on.
+const ARCHIVES: &[&str] = &[
+    "WasmEdge-0.14.1-manylinux2014_x86_64.tar.gz",
+    "WasmEdge-0.14.1-ubuntu20.04_x86_64.tar.gz",
+    "WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz",
+];
+
+/// Commands that cannot succeed offline: checking wasmedgeup's own release
+/// needs the network, and `plugin active` a runtime to ask.
+const OFFLINE_FAILURES: &[&[&str]] = &[&["update", "--check"], &["self", "update"], &["plugin", "active"]];
+
+/// Commands whose stdout is not a wasmedgeup result, so `--json` leaves it
+/// alone: `exec` hands it to the program it runs.
+const FOREIGN_STDOUT: &[&[&str]] = &[&["exec"]];
+
+/// Lines only diagnostics produce.
+const DIAGNOSTIC_MARKERS: &[&str] = &[" WARN ", " INFO ", " ERROR ", "Downloading", "Extracting", "stale as of"];
+
+fn seed(cache: &Path, url: &str, body: &str) {
+    let dir = cache.join("wasmedgeup").join("metadata");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(hex::encode(Sha256::digest(url.as_bytes()))), body).unwrap();
+}
+
+fn run(home: &Path, args: &[&str]) -> Output {
+    command(home).args(args).output().unwrap()
+}
+
+/// wasmedgeup run from `home` with nothing but `home` to work with.
+fn command(home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_wasmedgeup"));
+    command
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", home)
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        // Nothing listens on the discard port, so every request fails at once
+        .env("HTTPS_PROXY", "http://127.0.0.1:9")
+        .env("HTTP_PROXY", "http://127.0.0.1:9")
+        .env("WASMEDGEUP_TMPDIR", home.join("tmp"))
+        .current_dir(home);
+    command
+}
+
+/// An installed runtime, a prefix with a version installed side by side, a
+/// plugin in the default prefix, archives to "download" and a workspace, so
+/// every command has work to do.
+fn provision(home: &Path) {
+    seed(&home.join("cache"), RELEASES_URL, &format!("[{}]", RELEASE));
+    seed(&home.join("cache"), RELEASE_URL, RELEASE);
+
+    let prefix = home.join("wasmedge");
+    std::fs::create_dir_all(prefix.join("bin")).unwrap();
+    std::fs::write(prefix.join("VERSION"), "version=0.14.1\n").unwrap();
+    // Only a side-by-side version, which `default` can link
+    let versioned = home.join("side").join("versions").join("0.13.5");
+    std::fs::create_dir_all(versioned.join("bin")).unwrap();
+    std::fs::write(versioned.join("bin").join("wasmedge"), b"").unwrap();
+    std::fs::write(versioned.join("VERSION"), "version=0.13.5\n").unwrap();
+    std::fs::write(
+        versioned.join("manifest.toml"),
+        r#"version = "0.13.5"
+os = "Linux"
+arch = "x86_64"
+archive = "WasmEdge-0.13.5-manylinux2014_x86_64.tar.gz"
+components = ["bin"]
+installed_at = "2026-01-01T00:00:00Z"
+wasmedgeup_version = "0.1.0"
+files = ["bin/wasmedge"]
+
+[checksums]
+"#,
+    )
+    .unwrap();
+    let plugin_dir = home.join(".wasmedge").join("plugin");
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    std::fs::write(plugin_dir.join("libwasmedgePluginWasiLogging.so"), b"").unwrap();
+    // A recorded plugin beside the runtime, and a library no plugin is
+    // recorded for to adopt
+    let library = prefix.join("plugin").join("libwasmedgePluginWasiLogging.so");
+    std::fs::create_dir_all(prefix.join("plugin")).unwrap();
+    std::fs::write(&library, b"").unwrap();
+    std::fs::write(prefix.join("plugin").join("libwasmedgePluginWasiCrypto.so"), b"").unwrap();
+    let receipts = serde_json::json!({
+        "plugins": {
+            "wasi_logging": {
+                "version": "0.14.1",
+                "platform": "manylinux2014_x86_64",
+                "files": [library],
+                "sha256": { library.display().to_string(): hex::encode(Sha256::digest(b"")) }
+            }
+        }
+    });
+    std::fs::write(prefix.join("plugins.json"), receipts.to_string()).unwrap();
+
+    let store = home.join("store");
+    std::fs::create_dir_all(&store).unwrap();
+    let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(0);
+    archive.append_data(&mut header, "README", &b""[..]).unwrap();
+    let archive = archive.into_inner().unwrap().finish().unwrap();
+    for name in ARCHIVES {
+        std::fs::write(store.join(name), &archive).unwrap();
+    }
+    std::fs::create_dir_all(home.join("app")).unwrap();
+    std::fs::write(home.join("app").join(".wasmedge-version"), "0.14.1\n").unwrap();
+    std::fs::write(home.join("wasmedgeup-workspace.json"), r#"{"members": ["app"]}"#).unwrap();
+}
+
+/// Every subcommand, each run twice, with and without `--json`, so those
+/// that change anything are dry runs or leave the same state behind. Left
+/// out is the hidden `__complete`, which the completion scripts call and
+/// whose stdout is a list of candidates for the shell.
+fn invocations(home: &Path) -> Vec<Vec<String>> {
+    let prefix = home.join("wasmedge").display().to_string();
+    let side = home.join("side").display().to_string();
+    let side_version = home.join("side").join("versions").join("0.13.5").display().to_string();
+    let artifacts = home.join("artifacts").display().to_string();
+    let store = home.join("store").display().to_string();
+    let asdf_install = home.join("asdf").join("installs").join("0.14.1").display().to_string();
+    let asdf_download = home.join("asdf").join("downloads").join("0.14.1").display().to_string();
+    let asdf_plugin = home.join("asdf").join("plugins").join("wasmedge").display().to_string();
+    let bundle = home.join("plugins.tar.gz").display().to_string();
+    [
+        vec!["list"],
+        vec!["list", "--verify"],
+        vec!["download", "0.14.1", "--plugins", "wasi_logging", "-o", &artifacts, "--artifact-store", &store],
+        vec!["install", "0.14.1", "--dry-run", "-p", &prefix],
+        vec!["remove", "--dry-run", "-p", &prefix],
+        vec!["plugin", "list"],
+        vec!["plugin", "install", "wasi_logging", "--dry-run"],
+        vec!["plugin", "remove", "wasi_logging", "--dry-run"],
+        vec!["plugin", "verify"],
+        vec!["plugin", "active"],
+        vec!["plugin", "catalog", "export", "--version", "0.14.1"],
+        vec!["component", "add", "debug", "--version", "0.14.1", "-p", &prefix, "--dry-run"],
+        vec!["show", "plugin-dir"],
+        vec!["compat", "0.14.1", "0.14.0"],
+        vec!["doctor", "-p", &prefix],
+        vec!["update", "--check", "-p", &prefix],
+        vec!["outdated", "--format", "brief", "-p", &prefix],
+        vec!["cache", "clean", "--dry-run"],
+        vec!["self", "update"],
+        vec!["sync", "--workspace", "--dry-run"],
+        vec!["generate", "ci", "--provider", "github", "--version", "0.14.1"],
+        vec!["asdf", "list-all"],
+        vec!["asdf", "latest-stable"],
+        vec!["asdf", "list-bin-paths"],
+        vec!["asdf", "exec-env", "--path", &prefix],
+        vec!["generate", "nix", "--version", "0.14.1", "--plugins", "wasi_logging", "--artifact-store", &store],
+        vec!["default", "0.13.5", "-p", &side, "--dry-run"],
+        vec!["platforms"],
+        vec!["platforms", "0.14.1"],
+        vec!["init", "--version", "0.14.1", "--plugins", "wasi_logging", "--force"],
+        vec!["env", "-p", &prefix],
+        vec!["exec", "-p", &prefix, "true"],
+        vec!["completions", "bash"],
+        vec!["generate", "sbom", "-p", &side_version],
+        vec!["update", "-p", &prefix, "--dry-run"],
+        vec!["cache", "clean", "--tmp", "--dry-run"],
+        vec!["plugin", "adopt", "libwasmedgePluginWasiCrypto.so", "-p", &prefix, "--dry-run"],
+        vec!["plugin", "deps", "tree", "wasi_logging", "-p", &prefix],
+        vec!["plugin", "pin", "wasi_logging", "-p", &prefix],
+        vec!["plugin", "pin", "-p", &prefix],
+        vec!["plugin", "unpin", "wasi_logging", "-p", &prefix, "--dry-run"],
+        vec!["plugin", "export", "wasi_logging", "-o", &bundle, "-p", &prefix],
+        vec!["plugin", "import", &bundle, "-p", &prefix, "--dry-run"],
+        vec!["asdf", "plugin", &asdf_plugin],
+        vec!["asdf", "download", "--version", "0.14.1", "--path", &asdf_install, "--download-path", &asdf_download, "--artifact-store", &store],
+        vec!["asdf", "install", "--version", "0.14.1", "--path", &asdf_install, "--download-path", &asdf_download, "--dry-run"],
+    ]
+    .into_iter()
+    .map(|args| args.into_iter().map(str::to_string).collect())
+    .collect()
+}
+
+#[test]
+fn test_diagnostics_never_reach_stdout() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+
+    for args in invocations(home.path()) {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let succeeds = !OFFLINE_FAILURES.iter().any(|failing| args.starts_with(failing));
+
+        let output = run(home.path(), &args);
+        assert_eq!(output.status.success(), succeeds, "`{}` exited with {}:\n{}", args.join(" "), output.status, String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for marker in DIAGNOSTIC_MARKERS {
+            assert!(!stdout.contains(marker), "`{}` printed {:?} to stdout:\n{}", args.join(" "), marker, stdout);
+        }
+
+        // Ahead of the command, where it cannot be taken for an argument of
+        // the program `exec` runs
+        let json_args: Vec<&str> = std::iter::once("--json").chain(args.iter().copied()).collect();
+        let output = run(home.path(), &json_args);
+        assert_eq!(output.status.success(), succeeds, "`{}` exited with {}:\n{}", json_args.join(" "), output.status, String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // A failed command prints nothing, unless it has partial results
+        if !succeeds && stdout.trim().is_empty() || FOREIGN_STDOUT.iter().any(|foreign| args.starts_with(foreign)) {
+            continue;
+        }
+        let document: serde_json::Value = serde_json::from_str(&stdout)
+            .unwrap_or_else(|e| panic!("`{}` printed invalid JSON ({}):\n{}", json_args.join(" "), e, stdout));
+        assert!(document["warnings"].is_array(), "`{}` has no warnings array", json_args.join(" "));
+        assert!(document.get("result").is_some(), "`{}` has no result", json_args.join(" "));
+    }
+}
+
+#[test]
+fn test_stale_metadata_warning_goes_to_stderr_or_json_warnings() {
+    let home = tempfile::tempdir().unwrap();
+    seed(&home.path().join("cache"), RELEASES_URL, &format!("[{}]", RELEASE));
+
+    let output = run(home.path(), &["list"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0.14.1"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("releases as cached at"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("releases as cached at"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stale as of"));
+
+    let output = run(home.path(), &["list", "--json"]);
+    assert!(output.status.success());
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["result"][0]["version"], "0.14.1");
+    assert_eq!(document["result"][0]["latest"], true);
+    let warnings = document["warnings"].as_array().unwrap();
+    assert!(warnings.iter().any(|w| w.as_str().unwrap().contains("stale as of")));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("stale as of"));
+}
+
+#[test]
+fn test_show_plugin_dir_follows_the_prefix() {
+    let home = tempfile::tempdir().unwrap();
+    let prefix = home.path().join("opt").join("wasmedge");
+
+    let output = run(home.path(), &["show", "plugin-dir", "-p", &prefix.display().to_string()]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), prefix.join("plugin").display().to_string());
+
+    let output = run(home.path(), &["show", "plugin-dir"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), home.path().join(".wasmedge").join("plugin").display().to_string());
+}
+
+#[test]
+fn test_commands_that_download_nothing_ignore_a_broken_checksum_file() {
+    let home = tempfile::tempdir().unwrap();
+    let missing = home.path().join("missing.sums").display().to_string();
+
+    let output = run(home.path(), &["--checksum-file", &missing, "show", "plugin-dir"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let prefix = home.path().join("wasmedge").display().to_string();
+    let output = run(home.path(), &["--checksum-file", &missing, "install", "0.14.1", "--dry-run", "-p", &prefix]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.sums"));
+}
+
+#[test]
+fn test_cache_clean_only_removes_the_selected_parts() {
+    let home = tempfile::tempdir().unwrap();
+    let cache = home.path().join("cache");
+    seed(&cache, RELEASES_URL, &format!("[{}]", RELEASE));
+    let archives = cache.join("wasmedgeup").join("archives");
+    std::fs::create_dir_all(&archives).unwrap();
+
+    let output = run(home.path(), &["cache", "clean", "--only", "metadata"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!cache.join("wasmedgeup").join("metadata").exists());
+    assert!(archives.exists());
+
+    let output = run(home.path(), &["cache", "clean"]);
+    assert!(output.status.success());
+    assert!(!cache.join("wasmedgeup").exists());
+}
+
+#[test]
+fn test_installed_plugins_follow_the_prefix() {
+    let home = tempfile::tempdir().unwrap();
+    let prefix = home.path().join("opt").join("wasmedge");
+    std::fs::create_dir_all(prefix.join("plugin")).unwrap();
+    std::fs::write(prefix.join("plugin").join("libwasmedgePluginWasiLogging.so"), b"").unwrap();
+
+    let output = run(home.path(), &["plugin", "list", "--installed", "-p", &prefix.display().to_string()]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("libwasmedgePluginWasiLogging.so"));
+
+    let output = run(home.path(), &["plugin", "list", "--installed"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("libwasmedgePluginWasiLogging.so"));
+}
+
+#[test]
+fn test_generate_ci_reads_a_lockfile() {
+    let home = tempfile::tempdir().unwrap();
+    let lockfile = home.path().join("wasmedge-toolchain.toml");
+    std::fs::write(&lockfile, "[toolchain]\nversion = \"0.13.5\"\nplugins = [\"wasi_logging@0.13.5\"]\ncomponents = []\n").unwrap();
+
+    let output = run(home.path(), &["generate", "ci", "--provider", "github", "--lockfile", &lockfile.display().to_string()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let snippet = String::from_utf8_lossy(&output.stdout);
+    assert!(snippet.contains("install 0.13.5"));
+    assert!(snippet.contains("plugin install wasi_logging@0.13.5"));
+}
+
+#[test]
+fn test_update_check_fails_when_a_check_fails() {
+    let home = tempfile::tempdir().unwrap();
+    seed(&home.path().join("cache"), RELEASES_URL, &format!("[{}]", RELEASE));
+    let prefix = home.path().join("opt").join("wasmedge");
+    std::fs::create_dir_all(&prefix).unwrap();
+    std::fs::write(prefix.join("VERSION"), "version=0.14.1\n").unwrap();
+
+    // The runtime is current, but wasmedgeup's own release cannot be looked up
+    let output = run(home.path(), &["update", "--check", "-p", &prefix.display().to_string(), "--json"]);
+    assert_eq!(output.status.code(), Some(1), "{}", String::from_utf8_lossy(&output.stderr));
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(document["result"]["runtime"].is_null());
+}
+
+#[test]
+fn test_plugin_bundles_need_an_installed_runtime() {
+    let home = tempfile::tempdir().unwrap();
+    let prefix = home.path().join("opt").join("wasmedge").display().to_string();
+    let bundle = home.path().join("plugins.tar.gz").display().to_string();
+
+    // No latest release is looked up in its place, which would need the network
+    for args in [["plugin", "export", "-o", &bundle, "-p", &prefix], ["plugin", "import", &bundle, "-p", &prefix, "--dry-run"]] {
+        let output = run(home.path(), &args);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("No WasmEdge runtime is installed"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_first_run_without_a_command_installs_latest() {
+    let home = tempfile::tempdir().unwrap();
+    seed(&home.path().join("cache"), RELEASES_URL, &format!("[{}]", RELEASE));
+    seed(&home.path().join("cache"), RELEASE_URL, RELEASE);
+    let prefix = home.path().join(".wasmedge");
+
+    // Nobody can be asked without a terminal, so nothing is installed
+    let output = run(home.path(), &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("wasmedgeup install latest"));
+
+    // Global flags given with the bare command still apply to the install
+    let output = run(home.path(), &["--yes", "--dry-run", "--json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let changes = document["result"]["changes"].as_array().unwrap();
+    assert!(changes.iter().any(|c| c.as_str().unwrap().contains("0.14.1/WasmEdge-0.14.1-") && c.as_str().unwrap().ends_with(&format!("install it into {}", prefix.display()))), "{:?}", changes);
+    assert!(!prefix.exists());
+
+    std::fs::create_dir_all(&prefix).unwrap();
+    std::fs::write(prefix.join("VERSION"), "version=0.14.1\n").unwrap();
+    let output = run(home.path(), &["--yes"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Usage:"));
+}
+
+#[test]
+fn test_generate_nix_pins_the_archives_by_digest() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+    let store = home.path().join("store");
+
+    let output = run(home.path(), &["generate", "nix", "-p", &home.path().join("wasmedge").display().to_string(), "--plugins", "wasi_logging", "--artifact-store", &store.display().to_string()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let derivation = String::from_utf8_lossy(&output.stdout);
+    let digest = hex::encode(Sha256::digest(std::fs::read(store.join(ARCHIVES[0])).unwrap()));
+    assert!(derivation.contains("0.14.1/WasmEdge-plugin-wasi_logging-0.14.1-manylinux2014_x86_64.tar.gz"), "{}", derivation);
+    assert!(derivation.contains(&digest), "{}", derivation);
+    assert!(derivation.ends_with("}\n"), "{}", derivation);
+}
+
+#[test]
+fn test_asdf_callbacks_print_what_asdf_reads() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+    let prefix = home.path().join("wasmedge").display().to_string();
+
+    let stdout = |args: &[&str]| {
+        let output = run(home.path(), args);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(stdout(&["asdf", "list-all"]), "0.14.1\n");
+    assert_eq!(stdout(&["asdf", "latest-stable"]), "0.14.1\n");
+    assert_eq!(stdout(&["asdf", "list-bin-paths"]), "bin\n");
+    assert_eq!(stdout(&["asdf", "exec-env", "--path", &prefix]), format!("export LD_LIBRARY_PATH=\"{}/lib${{LD_LIBRARY_PATH:+:$LD_LIBRARY_PATH}}\"\n", prefix));
+
+    let document: serde_json::Value = serde_json::from_str(&stdout(&["asdf", "list-all", "--json"])).unwrap();
+    assert_eq!(document["result"]["versions"], serde_json::json!(["0.14.1"]));
+}
+
+#[test]
+fn test_exec_runs_with_the_selected_version() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+    let prefix = home.path().join("wasmedge");
+    let versioned = prefix.join("versions").join("0.13.5");
+    std::fs::create_dir_all(prefix.join("bin")).unwrap();
+    std::fs::create_dir_all(versioned.join("bin")).unwrap();
+    std::fs::write(versioned.join("VERSION"), "version=0.13.5\n").unwrap();
+
+    let plugin_path = |version: &str| {
+        let output = run(home.path(), &["exec", "-p", prefix.to_str().unwrap(), "--version", version, "--with-plugins", "printenv", "WASMEDGE_PLUGIN_PATH"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(plugin_path("0.13.5"), format!("{}\n", versioned.join("plugin").display()));
+    assert_eq!(plugin_path("v0.14.1"), format!("{}\n", prefix.join("plugin").display()));
+
+    let output = run(home.path(), &["exec", "-p", prefix.to_str().unwrap(), "--version", "0.12.0", "true"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("0.12.0 is not installed"));
+}
+
+#[test]
+fn test_exec_exits_with_the_status_of_the_program() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+    let prefix = home.path().join("wasmedge").display().to_string();
+
+    let output = run(home.path(), &["exec", "-p", &prefix, "sh", "-c", "echo ran; exit 3"]);
+    assert_eq!(output.status.code(), Some(3), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ran\n");
+
+    // Under --json too, stdout is the program's alone
+    let output = run(home.path(), &["--json", "exec", "-p", &prefix, "sh", "-c", "echo ran; exit 3"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ran\n");
+}
+
+#[test]
+fn test_exec_and_env_find_pins_under_the_configured_path() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+    let prefix = home.path().join("custom");
+    let versioned = prefix.join("versions").join("0.14.1");
+    std::fs::create_dir_all(versioned.join("bin")).unwrap();
+    std::fs::write(versioned.join("VERSION"), "version=0.14.1\n").unwrap();
+
+    let run_in_app = |args: &[&str]| {
+        let output = command(home.path())
+            .env("WASMEDGEUP_PATH", &prefix)
+            .current_dir(home.path().join("app"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(run_in_app(&["exec", "--with-plugins", "printenv", "WASMEDGE_PLUGIN_PATH"]), format!("{}\n", versioned.join("plugin").display()));
+    assert!(run_in_app(&["env"]).contains(&versioned.join("bin").display().to_string()));
+}
+
+#[test]
+fn test_plugin_catalog_export_writes_the_catalog() {
+    let home = tempfile::tempdir().unwrap();
+    provision(home.path());
+
+    let output = run(home.path(), &["plugin", "catalog", "export", "--version", "0.14.1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let catalog: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(catalog[0]["name"], "wasi_logging");
+    assert_eq!(catalog[0]["platform"], "manylinux2014_x86_64");
+    assert_eq!(catalog.as_array().unwrap().len(), 1);
+
+    let path = home.path().join("catalog.json");
+    let output = run(home.path(), &["plugin", "catalog", "export", "--version", "0.14.1", "-o", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written, catalog);
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&path.display().to_string()));
+}